@@ -25,10 +25,15 @@ fn main() {
     // serialised payload of `OsmNetwork`.
     let files = [
         "src/osm/graph.rs",
+        "src/osm/hierarchy.rs",
+        "src/osm/mod.rs",
+        "src/osm/weighting.rs",
         "src/osm/element/variants/mod.rs",
         "src/osm/element/variants/way.rs",
         "src/osm/element/variants/node.rs",
         "src/osm/element/variants/relation.rs",
+        "src/osm/parsers/turn_restriction.rs",
+        "src/osm/parsers/vehicle_limit.rs",
     ];
 
     let mut h: u64 = HASH_SEED;
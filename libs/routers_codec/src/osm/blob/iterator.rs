@@ -1,55 +1,43 @@
-//! The file blob iterator
-//! Supports `mmap` reading through the optional feature
+//! The file blob iterator, reading from a memory-mapped [`Source`].
 
 use crate::osm::BlobHeader;
 use crate::osm::BlockItem;
 use crate::osm::blob::item::BlobItem;
+use crate::osm::blob::source::Source;
 
 use buffa::Message;
 
 use alloc::sync::Arc;
-use std::fs::File;
 use std::io;
-use std::io::{BufReader, Read};
 use std::path::PathBuf;
 
 const HEADER_LEN_SIZE: usize = 4;
 
 pub struct BlobIterator {
-    pub(crate) buf: Arc<Vec<u8>>,
+    pub(crate) buf: Arc<Source>,
 
     pub(crate) index: u64,
     offset: u64,
 }
 
 impl BlobIterator {
+    /// Iterate over the blobs of the `.osm.pbf` at `path`, memory-mapping
+    /// the file rather than reading it in.
     pub fn new(path: PathBuf) -> Result<BlobIterator, io::Error> {
-        let file = File::open(path)?;
-
-        let mut buf = Vec::new(); // vec![0; file.metadata()?.size() as usize];
-        let mut reader = BufReader::new(file);
-        reader.read_to_end(&mut buf)?;
-        let buf = Arc::new(buf);
-
-        Ok(BlobIterator {
-            buf,
-            offset: 0,
-            index: 0,
-        })
+        Ok(BlobIterator::from_source(Arc::new(Source::open(&path)?)))
     }
 
     pub fn with_existing(buf: Arc<Vec<u8>>) -> Result<BlobIterator, io::Error> {
-        // let file = File::open(path)?;
-
-        // let mut buf = Vec::new(); // vec![0; file.metadata()?.size() as usize];
-        // let mut reader = BufReader::new(file);
-        // reader.read_to_end(&mut buf)?;
+        Ok(BlobIterator::from_source(Arc::new(Source::from(buf))))
+    }
 
-        Ok(BlobIterator {
+    /// Iterate over the blobs of an already opened [`Source`].
+    pub fn from_source(buf: Arc<Source>) -> BlobIterator {
+        BlobIterator {
             buf,
             offset: 0,
             index: 0,
-        })
+        }
     }
 
     pub fn make_block(&self, blob: &BlobItem) -> Option<BlockItem> {
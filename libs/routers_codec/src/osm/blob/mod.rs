@@ -2,8 +2,11 @@
 
 pub mod item;
 pub mod iterator;
+pub mod source;
 
 #[doc(inline)]
 pub use item::BlobItem;
 #[doc(inline)]
 pub use iterator::BlobIterator;
+#[doc(inline)]
+pub use source::Source;
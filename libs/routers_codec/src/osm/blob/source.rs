@@ -0,0 +1,99 @@
+//! The bytes an `.osm.pbf` file is read from.
+//!
+//! Files are memory-mapped wherever the platform allows, so a planet-sized
+//! extract is paged in by the OS as its blobs are decoded rather than held
+//! on the heap all at once. Pages already read are clean, and the OS may
+//! drop them again under pressure, so peak resident memory follows what is
+//! being decoded rather than the size of the file.
+
+use alloc::sync::Arc;
+use core::ops::Deref;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+/// The contents of an `.osm.pbf` file, shared between the iterators which
+/// index and decode it.
+pub enum Source {
+    /// A read-only memory map of the file.
+    #[cfg(not(target_arch = "wasm32"))]
+    Mapped(memmap2::Mmap),
+
+    /// The file, read into memory in full.
+    Buffered(Arc<Vec<u8>>),
+}
+
+impl Source {
+    /// Memory-map the file at `path`, or read it in full where mapping is
+    /// unavailable.
+    pub fn open(path: &Path) -> Result<Source, io::Error> {
+        let file = File::open(path)?;
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            // The map is read-only, and lives no longer than the iterators
+            // borrowing it. As with any mapped reader, the file must not be
+            // truncated while it is being read.
+            #[allow(unsafe_code)]
+            let map = unsafe { memmap2::Mmap::map(&file)? };
+            Ok(Source::Mapped(map))
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            use std::io::Read;
+
+            let mut buf = Vec::new();
+            io::BufReader::new(file).read_to_end(&mut buf)?;
+            Ok(Source::Buffered(Arc::new(buf)))
+        }
+    }
+}
+
+impl Deref for Source {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        match self {
+            #[cfg(not(target_arch = "wasm32"))]
+            Source::Mapped(map) => map,
+            Source::Buffered(buf) => buf,
+        }
+    }
+}
+
+impl From<Vec<u8>> for Source {
+    fn from(buf: Vec<u8>) -> Self {
+        Source::Buffered(Arc::new(buf))
+    }
+}
+
+impl From<Arc<Vec<u8>>> for Source {
+    fn from(buf: Arc<Vec<u8>>) -> Self {
+        Source::Buffered(buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_the_file_contents() {
+        let path = std::env::temp_dir().join(format!("routers-source-{}", std::process::id()));
+        std::fs::write(&path, b"\x00\x00\x00\x0dOSMHeader").expect("must write");
+
+        let source = Source::open(&path).expect("must open");
+        assert_eq!(&*source, b"\x00\x00\x00\x0dOSMHeader");
+
+        drop(source);
+        std::fs::remove_file(path).expect("must remove");
+    }
+
+    #[test]
+    fn buffers_dereference_to_their_bytes() {
+        let source = Source::from(vec![1, 2, 3]);
+        assert_eq!(&*source, &[1, 2, 3]);
+    }
+}
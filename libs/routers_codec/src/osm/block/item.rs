@@ -36,14 +36,42 @@ impl BlockItem {
         BlockItem::from_data(data.as_slice(), blob_item)
     }
 
+    /// The blob's uncompressed contents. Zlib and uncompressed blobs are
+    /// always decoded; zstd, lz4 and lzma each need their cargo feature. A blob
+    /// which cannot be decoded is skipped with a warning.
     #[inline]
     fn from_blob(blob: Blob) -> Option<Vec<u8>> {
-        if let Some(Data::ZlibData(data)) = blob.data {
-            return BlockItem::zlib_decode(data, blob.raw_size.unwrap_or(0) as usize);
+        let raw_size = blob.raw_size.and_then(|size| usize::try_from(size).ok());
+
+        match blob.data? {
+            Data::Raw(data) => Some(data),
+            Data::ZlibData(data) => BlockItem::zlib_decode(data, raw_size.unwrap_or(0)),
+            #[cfg(feature = "zstd")]
+            Data::ZstdData(data) => BlockItem::zstd_decode(&data, raw_size),
+            #[cfg(not(feature = "zstd"))]
+            Data::ZstdData(_) => {
+                warn!("Skipping a zstd-compressed blob; enable the `zstd` feature to decode it.");
+                None
+            }
+            #[cfg(feature = "lz4")]
+            Data::Lz4Data(data) => BlockItem::lz4_decode(&data, raw_size),
+            #[cfg(not(feature = "lz4"))]
+            Data::Lz4Data(_) => {
+                warn!("Skipping an lz4-compressed blob; enable the `lz4` feature to decode it.");
+                None
+            }
+            #[cfg(feature = "lzma")]
+            Data::LzmaData(data) => BlockItem::lzma_decode(&data, raw_size),
+            #[cfg(not(feature = "lzma"))]
+            Data::LzmaData(_) => {
+                warn!("Skipping an lzma-compressed blob; enable the `lzma` feature to decode it.");
+                None
+            }
+            Data::OBSOLETEBzip2Data(_) => {
+                warn!("Skipping a bzip2-compressed blob; bzip2 has been obsolete since 2010.");
+                None
+            }
         }
-
-        warn!("Type {:?} not yet supported.", blob.data);
-        None
     }
 
     #[inline]
@@ -69,6 +97,62 @@ impl BlockItem {
         Some(decoded)
     }
 
+    /// Zstd blobs hold a single zstd frame, decoded into the blob's
+    /// `raw_size` where it is given, and streamed to its end otherwise.
+    #[cfg(feature = "zstd")]
+    #[inline]
+    fn zstd_decode(data: &[u8], raw_size: Option<usize>) -> Option<Vec<u8>> {
+        let decoded = match raw_size {
+            Some(raw_size) => zstd::bulk::decompress(data, raw_size),
+            None => zstd::stream::decode_all(data),
+        };
+
+        decoded
+            .inspect_err(|error| {
+                warn!("Skipping a zstd-compressed blob which failed to decode: {error}")
+            })
+            .ok()
+    }
+
+    /// Lz4 blobs hold a single lz4 block, without the frame format's
+    /// header; the blob's `raw_size` gives its decoded length, so one
+    /// without it cannot be decoded.
+    #[cfg(feature = "lz4")]
+    #[inline]
+    fn lz4_decode(data: &[u8], raw_size: Option<usize>) -> Option<Vec<u8>> {
+        let Some(raw_size) = raw_size.and_then(|size| i32::try_from(size).ok()) else {
+            warn!("Skipping an lz4-compressed blob without a `raw_size` to decode it into.");
+            return None;
+        };
+
+        lz4::block::decompress(data, Some(raw_size))
+            .inspect_err(|error| {
+                warn!("Skipping an lz4-compressed blob which failed to decode: {error}")
+            })
+            .ok()
+    }
+
+    /// Lzma blobs are decoded with liblzma's auto-detecting decoder, which
+    /// accepts both the `.xz` container and the legacy `.lzma` format. The
+    /// blob's `raw_size`, where given, only reserves room for the output.
+    #[cfg(feature = "lzma")]
+    #[inline]
+    fn lzma_decode(data: &[u8], raw_size: Option<usize>) -> Option<Vec<u8>> {
+        let stream = xz2::stream::Stream::new_auto_decoder(u64::MAX, 0)
+            .inspect_err(|error| warn!("Could not start an lzma decoder: {error}"))
+            .ok()?;
+
+        let mut decoded = Vec::with_capacity(raw_size.unwrap_or_default());
+        xz2::read::XzDecoder::new_stream(data, stream)
+            .read_to_end(&mut decoded)
+            .inspect_err(|error| {
+                warn!("Skipping an lzma-compressed blob which failed to decode: {error}")
+            })
+            .ok()?;
+
+        Some(decoded)
+    }
+
     pub fn r#type(&self) -> &str {
         match self {
             BlockItem::HeaderBlock(_) => "HeaderBlock",
@@ -2,34 +2,28 @@
 
 use crate::osm::BlobItem;
 use crate::osm::blob::iterator::BlobIterator;
+use crate::osm::blob::source::Source;
 use crate::osm::block::item::BlockItem;
 
 use alloc::sync::Arc;
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
-use std::fs::File;
 use std::io;
-use std::io::{BufReader, Read};
 use std::path::PathBuf;
 
 pub struct BlockIterator {
     blobs: Vec<BlobItem>,
-    buf: Arc<Vec<u8>>,
+    buf: Arc<Source>,
     index: usize,
 }
 
 impl BlockIterator {
+    /// Iterate over the blocks of the `.osm.pbf` at `path`. The file is
+    /// memory-mapped and indexed up front; each block is only decompressed
+    /// as it is reached.
     #[inline]
     pub fn new(path: PathBuf) -> Result<BlockIterator, io::Error> {
-        let file = File::open(path)?;
-
-        let mut buf = Vec::new();
-        let mut reader = BufReader::new(file);
-        reader.read_to_end(&mut buf)?;
-
-        let buf = Arc::new(buf);
-
-        let iter = BlobIterator::with_existing(Arc::clone(&buf))?;
-        let blobs = iter.collect::<Vec<_>>();
+        let buf = Arc::new(Source::open(&path)?);
+        let blobs = BlobIterator::from_source(Arc::clone(&buf)).collect::<Vec<_>>();
 
         Ok(BlockIterator {
             index: 0,
@@ -42,7 +36,7 @@ impl BlockIterator {
     pub fn par_iter(&mut self) -> impl ParallelIterator<Item = BlockItem> + '_ {
         self.blobs
             .par_iter()
-            .filter_map(|blob| BlockItem::from_blob_item(blob, self.buf.as_slice()))
+            .filter_map(|blob| BlockItem::from_blob_item(blob, &self.buf))
     }
 }
 
@@ -52,7 +46,7 @@ impl Iterator for BlockIterator {
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
         let blob = self.blobs.get(self.index)?;
-        let block = BlockItem::from_blob_item(blob, self.buf.as_slice());
+        let block = BlockItem::from_blob_item(blob, &self.buf);
         self.index += 1;
         block
     }
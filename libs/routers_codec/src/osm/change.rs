@@ -0,0 +1,454 @@
+//! OsmChange (`.osc`) files, and their application to an [`OsmNetwork`].
+//!
+//! A replication diff lists the nodes and ways created, modified or deleted
+//! since some earlier state of the map. Applying one to a network built from
+//! that state patches its graph, nodes and metadata in place, which costs a
+//! scan of the graph rather than a fresh ingest of the extract.
+//!
+//! Only nodes and ways are applied. Relations are skipped, so a turn
+//! restriction naming a way the change modifies or deletes may no longer
+//! describe it, and is dropped. The rest keep the state they were ingested
+//! with.
+
+use std::io::BufRead;
+
+use geo::Point;
+use log::debug;
+use petgraph::Direction;
+use routers_network::Node;
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::osm::graph::way_edges;
+use crate::osm::turn_restriction::TurnRestriction;
+use crate::osm::xml::{self, XmlElement, XmlError};
+use crate::osm::{OsmEntryId, OsmNetwork, Tags};
+
+/// What a change does to the element it lists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Create,
+    Modify,
+    Delete,
+}
+
+/// A node as of a change. Deletions need not carry a position.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeChange {
+    pub action: Action,
+    pub id: OsmEntryId,
+    pub position: Option<Point>,
+}
+
+/// A way as of a change, listing its nodes and tags in full.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WayChange {
+    pub action: Action,
+    pub id: OsmEntryId,
+    pub refs: Vec<OsmEntryId>,
+    pub tags: Vec<(String, String)>,
+}
+
+impl WayChange {
+    /// The way's tags, as ingest reads them.
+    pub fn tags(&self) -> Tags<'_> {
+        xml::tags(&self.tags)
+    }
+}
+
+/// The nodes and ways of an `<osmChange>` document, in document order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OsmChange {
+    pub nodes: Vec<NodeChange>,
+    pub ways: Vec<WayChange>,
+}
+
+impl OsmChange {
+    /// Parse an `<osmChange>` document.
+    pub fn parse(text: &str) -> Result<Self, XmlError> {
+        Self::read(text.as_bytes())
+    }
+
+    /// Read an `.osc` file, or a gzipped `.osc.gz` as replication serves
+    /// them. Not available on WASM.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_file(path: &std::path::Path) -> Result<Self, XmlError> {
+        Self::read(xml::open(path)?)
+    }
+
+    fn read(reader: impl BufRead) -> Result<Self, XmlError> {
+        let mut change = OsmChange::default();
+
+        xml::read(reader, |action, element| match (action, element) {
+            (Some(action), XmlElement::Node { id, position }) => change.nodes.push(NodeChange {
+                action,
+                id: OsmEntryId::node(id),
+                position,
+            }),
+            (Some(action), XmlElement::Way { id, refs, tags }) => change.ways.push(WayChange {
+                action,
+                id: OsmEntryId::way(id),
+                refs: refs.into_iter().map(OsmEntryId::node).collect(),
+                tags,
+            }),
+            _ => {}
+        })?;
+
+        Ok(change)
+    }
+}
+
+/// What applying an [`OsmChange`] did to a network.
+#[derive(Debug, Clone, Default)]
+pub struct ChangeReport {
+    /// Positions, before and after the change, of every node whose edges or
+    /// position it altered. Any shard holding one of them is out of date.
+    pub touched: Vec<Point>,
+    /// Routable ways left out of the network, since a node they pass through
+    /// is neither in the network nor given a position by the change.
+    pub unresolved: Vec<OsmEntryId>,
+    /// Turn restrictions dropped from the network, since a way they name was
+    /// modified or deleted.
+    pub dropped: Vec<TurnRestriction>,
+}
+
+/// Why a change could not be applied to a network.
+#[derive(Debug, thiserror::Error)]
+pub enum ChangeError {
+    #[error("the network has been simplified, so the nodes a change lists may no longer be in it")]
+    Simplified,
+}
+
+impl OsmNetwork {
+    /// Patch the network with `change`, then rebuild its indices.
+    ///
+    /// Where the change lists an element more than once, the last listing
+    /// wins. Every changed way's edges are lifted and laid again from its new
+    /// nodes and tags, nodes left without edges are dropped, and moved nodes
+    /// take their new positions. An attached contraction hierarchy no longer
+    /// describes the network, so it is detached.
+    ///
+    /// A [simplified](Self::simplify) network has lifted nodes its ways pass
+    /// through, so it refuses any change with [`ChangeError::Simplified`].
+    pub fn apply(&mut self, change: &OsmChange) -> Result<ChangeReport, ChangeError> {
+        if self.is_simplified() {
+            return Err(ChangeError::Simplified);
+        }
+
+        let mut report = ChangeReport::default();
+
+        let nodes = change
+            .nodes
+            .iter()
+            .map(|node| (node.id, node))
+            .collect::<FxHashMap<_, _>>();
+        let ways = change
+            .ways
+            .iter()
+            .map(|way| (way.id, way))
+            .collect::<FxHashMap<_, _>>();
+
+        // Lift every edge the changed ways laid.
+        let lifted = self
+            .graph
+            .all_edges()
+            .filter(|(_, _, (_, id))| ways.contains_key(&id.index()))
+            .map(|(source, target, _)| (source, target))
+            .collect::<Vec<_>>();
+
+        let mut loose = FxHashSet::default();
+        for (source, target) in lifted {
+            self.graph.remove_edge(source, target);
+            self.shapes.remove(&(source, target));
+            loose.extend([source, target]);
+        }
+
+        for id in ways.keys() {
+            self.meta.remove(id);
+        }
+
+        report.dropped = self.restrictions.remove_naming(|id| ways.contains_key(id));
+
+        // Move nodes the network holds; deleted ones go if nothing uses them.
+        for node in nodes.values() {
+            match (node.action, node.position) {
+                (Action::Delete, _) => {
+                    loose.insert(node.id);
+                }
+                (_, Some(position)) => {
+                    if let Some(existing) = self.hash.get_mut(&node.id)
+                        && existing.position != position
+                    {
+                        report.touched.extend([existing.position, position]);
+                        existing.position = position;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        report.touched.extend(
+            loose
+                .iter()
+                .filter_map(|id| self.hash.get(id).map(|node| node.position)),
+        );
+
+        // Lay the changed ways afresh.
+        for way in ways.values().filter(|way| way.action != Action::Delete) {
+            let tags = way.tags();
+            let Some((metadata, edges)) = way_edges(way.id, &tags, way.refs.iter().copied()) else {
+                continue;
+            };
+
+            let positions = way
+                .refs
+                .iter()
+                .map(|id| match nodes.get(id) {
+                    Some(node) if node.action == Action::Delete => None,
+                    Some(node) => node.position,
+                    None => self.hash.get(id).map(|node| node.position),
+                })
+                .collect::<Option<Vec<_>>>();
+
+            let Some(positions) = positions else {
+                report.unresolved.push(way.id);
+                continue;
+            };
+
+            for (&id, position) in way.refs.iter().zip(positions) {
+                self.hash.insert(id, Node::new(position, id));
+                loose.remove(&id);
+                report.touched.push(position);
+            }
+
+            for edge in edges {
+                self.graph
+                    .add_edge(edge.source, edge.target, (edge.weight, edge.id));
+            }
+            self.meta.insert(way.id, metadata);
+        }
+
+        // Drop the nodes nothing routes through any more.
+        for id in loose {
+            let connected = self
+                .graph
+                .neighbors_directed(id, Direction::Outgoing)
+                .chain(self.graph.neighbors_directed(id, Direction::Incoming))
+                .next()
+                .is_some();
+
+            if !connected {
+                self.graph.remove_node(id);
+                self.hash.remove(&id);
+            }
+        }
+
+        debug!(
+            "OsmNetwork::apply: {} nodes and {} ways changed, {} positions touched, {} ways unresolved, {} restrictions dropped",
+            nodes.len(),
+            ways.len(),
+            report.touched.len(),
+            report.unresolved.len(),
+            report.dropped.len()
+        );
+
+        self.hierarchy = None;
+        self.rebuild_indices();
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::osm::turn_restriction::TurnKind;
+    use geo::point;
+
+    /// A residential street from node 1 to node 3 through node 2, and a
+    /// footpath on from node 3 to node 4.
+    const CREATE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<osmChange version="0.6" generator="test">
+  <create>
+    <node id="1" version="1" lat="0.0" lon="0.0"/>
+    <node id="2" version="1" lat="0.0" lon="0.001">
+      <tag k="highway" v="crossing"/>
+    </node>
+    <node id="3" version="1" lat="0.0" lon="0.002"/>
+    <node id="4" version="1" lat="0.001" lon="0.002"/>
+    <way id="10" version="1">
+      <nd ref="1"/>
+      <nd ref="2"/>
+      <nd ref="3"/>
+      <tag k="highway" v="residential"/>
+      <tag k="name" v="Main &amp; High"/>
+    </way>
+    <way id="20" version="1">
+      <nd ref="3"/>
+      <nd ref="4"/>
+      <tag k="highway" v="footway"/>
+    </way>
+    <relation id="30" version="1">
+      <member type="way" ref="10" role="from"/>
+      <tag k="type" v="restriction"/>
+    </relation>
+  </create>
+</osmChange>"#;
+
+    fn network() -> OsmNetwork {
+        let mut network = OsmNetwork::default();
+        network
+            .apply(&OsmChange::parse(CREATE).expect("must parse"))
+            .expect("must apply");
+        network
+    }
+
+    #[test]
+    fn parses_nodes_and_ways() {
+        let change = OsmChange::parse(CREATE).expect("must parse");
+
+        assert_eq!(change.nodes.len(), 4);
+        assert_eq!(change.nodes[1].id, OsmEntryId::node(2));
+        assert_eq!(change.nodes[1].position, Some(point!(x: 0.001, y: 0.0)));
+
+        let ids: Vec<_> = change.ways.iter().map(|way| way.id.identifier).collect();
+        assert_eq!(ids, vec![10, 20]);
+        assert_eq!(change.ways[0].action, Action::Create);
+        assert_eq!(change.ways[0].refs.len(), 3);
+        assert_eq!(change.ways[0].tags()["name"], "Main & High");
+    }
+
+    #[test]
+    fn rejects_malformed_elements() {
+        assert!(matches!(
+            OsmChange::parse(r#"<osmChange><create><way version="1"/></create></osmChange>"#),
+            Err(XmlError::MissingAttribute {
+                element: "way",
+                attribute: "id"
+            })
+        ));
+        assert!(matches!(
+            OsmChange::parse(r#"<osmChange><delete><node id="n1"/></delete></osmChange>"#),
+            Err(XmlError::Invalid {
+                attribute: "id",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn creates_routable_ways() {
+        let network = network();
+
+        // The footpath is not routable, so node 4 is never kept.
+        assert_eq!(network.graph.edge_count(), 4);
+        assert_eq!(network.hash.len(), 3);
+        assert!(network.meta.contains_key(&OsmEntryId::way(10)));
+        assert_eq!(network.index.len(), 3);
+    }
+
+    #[test]
+    fn moves_nodes() {
+        let mut network = network();
+        let report = network.apply(
+            &OsmChange::parse(
+                r#"<osmChange><modify><node id="2" lat="0.0005" lon="0.001"/></modify></osmChange>"#,
+            )
+            .expect("must parse"),
+        )
+        .expect("must apply");
+
+        let moved = point!(x: 0.001, y: 0.0005);
+        assert_eq!(network.hash[&OsmEntryId::node(2)].position, moved);
+        assert_eq!(report.touched, vec![point!(x: 0.001, y: 0.0), moved]);
+    }
+
+    #[test]
+    fn relays_modified_ways_and_drops_orphaned_nodes() {
+        let mut network = network();
+        let report = network.apply(
+            &OsmChange::parse(
+                r#"<osmChange>
+                  <modify>
+                    <way id="10"><nd ref="1"/><nd ref="2"/><tag k="highway" v="primary"/><tag k="oneway" v="yes"/></way>
+                  </modify>
+                  <delete><node id="3"/></delete>
+                </osmChange>"#,
+            )
+            .expect("must parse"),
+        )
+        .expect("must apply");
+
+        assert_eq!(network.graph.edge_count(), 1);
+        assert!(!network.hash.contains_key(&OsmEntryId::node(3)));
+        assert_eq!(network.index.len(), 2);
+        assert!(report.touched.contains(&point!(x: 0.002, y: 0.0)));
+    }
+
+    #[test]
+    fn reports_ways_through_unknown_nodes() {
+        let mut network = network();
+        let report = network
+            .apply(
+                &OsmChange::parse(
+                    r#"<osmChange><modify>
+                  <way id="20"><nd ref="3"/><nd ref="4"/><tag k="highway" v="service"/></way>
+                </modify></osmChange>"#,
+                )
+                .expect("must parse"),
+            )
+            .expect("must apply");
+
+        assert_eq!(report.unresolved, vec![OsmEntryId::way(20)]);
+        assert_eq!(network.graph.edge_count(), 4);
+    }
+
+    #[test]
+    fn drops_restrictions_naming_changed_ways() {
+        let restriction = |from: i64, to: i64| TurnRestriction {
+            kind: TurnKind::Prohibitory,
+            from: OsmEntryId::way(from),
+            through: Vec::new(),
+            via: OsmEntryId::node(3),
+            to: OsmEntryId::way(to),
+            mode: None,
+            except: Vec::new(),
+            condition: None,
+        };
+
+        let mut network = network();
+        network.restrictions.insert(restriction(10, 10));
+        network.restrictions.insert(restriction(40, 50));
+
+        let report = network
+            .apply(
+                &OsmChange::parse(
+                    r#"<osmChange><modify>
+                      <way id="10"><nd ref="1"/><nd ref="3"/><tag k="highway" v="residential"/></way>
+                    </modify></osmChange>"#,
+                )
+                .expect("must parse"),
+            )
+            .expect("must apply");
+
+        assert_eq!(report.dropped, vec![restriction(10, 10)]);
+        assert_eq!(network.restrictions.len(), 1);
+        assert_eq!(
+            network.restrictions.at(&OsmEntryId::node(3)),
+            &[restriction(40, 50)]
+        );
+    }
+
+    #[test]
+    fn deletes_ways() {
+        let mut network = network();
+        network
+            .apply(
+                &OsmChange::parse(r#"<osmChange><delete><way id="10"/></delete></osmChange>"#)
+                    .expect("must parse"),
+            )
+            .expect("must apply");
+
+        assert_eq!(network.graph.edge_count(), 0);
+        assert!(network.hash.is_empty());
+        assert!(network.meta.is_empty());
+    }
+}
@@ -48,11 +48,10 @@ impl<'a> ProcessedElement<'a> {
                 .collect(),
             Element::Node(_node) => vec![ProcessedElement::Node(node(_node))],
             Element::Way(way) => vec![ProcessedElement::Way(Way::from_raw(way, block))],
-            Element::Relation(_relation) => {
-                vec![]
-                // ProcessedElement::Relation(Relation::from_raw(
-                //     relation, block,
-                // ))
+            Element::Relation(relation) => {
+                vec![ProcessedElement::Relation(Relation::from_raw(
+                    relation, block,
+                ))]
             }
         }
     }
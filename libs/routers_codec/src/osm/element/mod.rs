@@ -3,6 +3,7 @@
 pub mod item;
 pub mod iterator;
 pub mod processed_iterator;
+pub mod xml_iterator;
 
 #[doc(hidden)]
 pub mod variants;
@@ -19,3 +20,5 @@ pub use processed_iterator::ProcessedElementIterator;
 pub use variants::OsmEntryId;
 #[doc(inline)]
 pub use variants::common::*;
+#[doc(inline)]
+pub use xml_iterator::XmlElementIterator;
@@ -1,12 +1,17 @@
-use super::common::{ReferenceKey, References, Referential, Taggable, Tags};
+use super::common::{Reference, ReferenceKey, References, Referential, Taggable, Tags};
 use crate::osm;
 use crate::osm::element::variants::Intermediate;
+use crate::osm::relation::MemberType;
 
 #[derive(Clone, Debug)]
 pub struct Relation<'a> {
     pub id: i64,
     pub tags: Tags<'a>,
     pub refs: References,
+    /// The member type of each of `refs`, in order. Kept alongside rather
+    /// than on the [`OsmEntryId`](super::OsmEntryId), which only records it
+    /// in debug builds.
+    pub types: Vec<MemberType>,
 }
 
 impl<'a> Relation<'a> {
@@ -15,8 +20,14 @@ impl<'a> Relation<'a> {
             id: relation.id,
             tags: relation.tags(block),
             refs: relation.references(block),
+            types: relation.types.clone(),
         }
     }
+
+    /// The relation's members alongside their member type.
+    pub fn members(&self) -> impl Iterator<Item = (&Reference, MemberType)> {
+        self.refs.iter().zip(self.types.iter().copied())
+    }
 }
 
 impl Taggable for osm::Relation {
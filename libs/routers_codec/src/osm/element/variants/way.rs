@@ -18,6 +18,10 @@ pub struct Way<'a> {
 }
 
 impl<'a> Way<'a> {
+    pub fn new(id: OsmEntryId, refs: References, tags: Tags<'a>) -> Self {
+        Way { id, refs, tags }
+    }
+
     pub fn id(&self) -> OsmEntryId {
         self.id
     }
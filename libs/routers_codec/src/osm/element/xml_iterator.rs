@@ -0,0 +1,284 @@
+//! Iterator over the entities of an OSM XML (`.osm`) file, yielding the
+//! same processed elements as the PBF iterators do.
+
+use std::io::BufRead;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::PathBuf;
+
+use log::debug;
+use rayon::prelude::*;
+use routers_network::Node;
+
+use crate::osm::element::item::ProcessedElement;
+use crate::osm::element::variants::{OsmEntryId, Reference, References, Relation, Role, Way};
+use crate::osm::parallel::Parallel;
+use crate::osm::relation::MemberType;
+use crate::osm::xml::{self, Member, XmlElement, XmlError};
+
+/// The entities of an OSM XML document, read in full.
+///
+/// Unlike a PBF file, XML cannot be split into independently decodable
+/// blocks, so the document is read once up front and its elements are
+/// traversed in parallel from memory. It suits the small, hand-written or
+/// editor-exported files XML is used for, rather than whole extracts.
+pub struct XmlElementIterator {
+    nodes: Vec<Node<OsmEntryId>>,
+    ways: Vec<Listed<i64>>,
+    relations: Vec<Listed<Member>>,
+}
+
+/// A way or relation as listed: its id, its members and its tags.
+type Listed<T> = (i64, Vec<T>, Vec<(String, String)>);
+
+impl XmlElementIterator {
+    /// Read the `.osm` file at `path`, or a gzipped `.osm.gz`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn new(path: PathBuf) -> Result<XmlElementIterator, XmlError> {
+        Self::read(xml::open(&path)?)
+    }
+
+    /// Read an `<osm>` document.
+    pub fn parse(text: &str) -> Result<XmlElementIterator, XmlError> {
+        Self::read(text.as_bytes())
+    }
+
+    fn read(reader: impl BufRead) -> Result<XmlElementIterator, XmlError> {
+        let mut iterator = XmlElementIterator {
+            nodes: Vec::new(),
+            ways: Vec::new(),
+            relations: Vec::new(),
+        };
+
+        let mut unplaced = 0;
+        xml::read(reader, |_, element| match element {
+            XmlElement::Node {
+                id,
+                position: Some(position),
+            } => iterator
+                .nodes
+                .push(Node::new(position, OsmEntryId::node(id))),
+            XmlElement::Node { position: None, .. } => unplaced += 1,
+            XmlElement::Way { id, refs, tags } => iterator.ways.push((id, refs, tags)),
+            XmlElement::Relation { id, members, tags } => {
+                iterator.relations.push((id, members, tags))
+            }
+        })?;
+
+        if unplaced > 0 {
+            debug!("Skipped {unplaced} nodes without a position");
+        }
+
+        Ok(iterator)
+    }
+
+    /// Every element, borrowing its tags and roles from the document.
+    fn elements(&self) -> Vec<ProcessedElement<'_>> {
+        let nodes = self.nodes.iter().copied().map(ProcessedElement::Node);
+
+        let ways = self.ways.iter().map(|(id, refs, way_tags)| {
+            let refs = refs
+                .iter()
+                .map(|&id| Reference::without_role(OsmEntryId::node(id)))
+                .collect::<Vec<_>>();
+
+            ProcessedElement::Way(Way::new(
+                OsmEntryId::way(*id),
+                References::from(refs),
+                xml::tags(way_tags),
+            ))
+        });
+
+        let relations = self.relations.iter().map(|(id, members, relation_tags)| {
+            let refs = members
+                .iter()
+                .map(|member| {
+                    let id = OsmEntryId::new(
+                        member.id,
+                        #[cfg(debug_assertions)]
+                        member.kind,
+                    );
+                    Reference::with_role(id, Role(member.role.clone()))
+                })
+                .collect::<Vec<_>>();
+
+            ProcessedElement::Relation(Relation {
+                id: *id,
+                tags: xml::tags(relation_tags),
+                refs: References::from(refs),
+                types: members
+                    .iter()
+                    .map(|member| member.kind)
+                    .collect::<Vec<MemberType>>(),
+            })
+        });
+
+        nodes.chain(ways).chain(relations).collect()
+    }
+}
+
+impl Parallel for XmlElementIterator {
+    type Item<'a> = ProcessedElement<'a>;
+
+    fn for_each<F>(self, f: F)
+    where
+        F: for<'a> Fn(ProcessedElement<'a>) + Send + Sync,
+    {
+        self.elements().into_par_iter().for_each(f)
+    }
+
+    fn map_red<Map, Reduce, Identity, T>(self, map_op: Map, red_op: Reduce, ident: Identity) -> T
+    where
+        Map: for<'a> Fn(ProcessedElement<'a>) -> T + Send + Sync,
+        Reduce: Fn(T, T) -> T + Send + Sync,
+        Identity: Fn() -> T + Send + Sync,
+        T: Send,
+    {
+        self.elements()
+            .into_par_iter()
+            .map(map_op)
+            .reduce(ident, red_op)
+    }
+
+    fn par_red<Reduce, Identity, Combine, T>(
+        self,
+        fold_op: Reduce,
+        combine: Combine,
+        ident: Identity,
+    ) -> T
+    where
+        Reduce: for<'a> Fn(T, ProcessedElement<'a>) -> T + Send + Sync,
+        Identity: Fn() -> T + Send + Sync,
+        Combine: Fn(T, T) -> T + Send + Sync,
+        T: Send,
+    {
+        self.elements()
+            .into_par_iter()
+            .fold(&ident, fold_op)
+            .reduce(&ident, combine)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo::point;
+
+    const OSM: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<osm version="0.6" generator="JOSM">
+  <bounds minlat="0" minlon="0" maxlat="0.001" maxlon="0.002"/>
+  <node id="1" version="1" lat="0.0" lon="0.0"/>
+  <node id="2" version="1" lat="0.0" lon="0.001">
+    <tag k="highway" v="traffic_signals"/>
+  </node>
+  <node id="-3" action="modify" lat="0.001" lon="0.002"/>
+  <way id="10" version="1">
+    <nd ref="1"/>
+    <nd ref="2"/>
+    <nd ref="-3"/>
+    <tag k="highway" v="residential"/>
+  </way>
+  <relation id="20" version="1">
+    <member type="way" ref="10" role="from"/>
+    <member type="node" ref="2" role="via"/>
+    <member type="way" ref="10"/>
+    <tag k="type" v="restriction"/>
+    <tag k="restriction" v="no_u_turn"/>
+  </relation>
+</osm>"#;
+
+    #[test]
+    fn yields_every_element() {
+        let counts = XmlElementIterator::parse(OSM).expect("must parse").map_red(
+            |element| match element {
+                ProcessedElement::Node(_) => (1, 0, 0),
+                ProcessedElement::Way(_) => (0, 1, 0),
+                ProcessedElement::Relation(_) => (0, 0, 1),
+            },
+            |a, b| (a.0 + b.0, a.1 + b.1, a.2 + b.2),
+            || (0, 0, 0),
+        );
+
+        assert_eq!(counts, (3, 1, 1));
+    }
+
+    #[test]
+    fn reads_references_and_tags() {
+        let iterator = XmlElementIterator::parse(OSM).expect("must parse");
+        let elements = iterator.elements();
+
+        let Some(ProcessedElement::Node(node)) = elements.get(2) else {
+            panic!("expected a node");
+        };
+        assert_eq!(node.id, OsmEntryId::node(-3));
+        assert_eq!(node.position, point!(x: 0.002, y: 0.001));
+
+        let Some(ProcessedElement::Way(way)) = elements.get(3) else {
+            panic!("expected a way");
+        };
+        let refs: Vec<_> = way.refs().iter().map(|r| r.id.identifier).collect();
+        assert_eq!(refs, vec![1, 2, -3]);
+        assert_eq!(way.tags().get("highway"), Some(&"residential"));
+
+        let Some(ProcessedElement::Relation(relation)) = elements.get(4) else {
+            panic!("expected a relation");
+        };
+        let members: Vec<_> = relation
+            .members()
+            .map(|(reference, kind)| (reference.role.as_ref().unwrap().0.as_str(), kind))
+            .collect();
+        assert_eq!(
+            members,
+            vec![
+                ("from", MemberType::WAY),
+                ("via", MemberType::NODE),
+                ("", MemberType::WAY)
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_deleted_elements() {
+        let text = r#"<osm version="0.6">
+  <node id="1" lat="0.0" lon="0.0"/>
+  <node id="2" action="delete" lat="0.0" lon="0.001"/>
+  <node id="3" visible="false" lat="0.0" lon="0.002"/>
+  <way id="10" action="delete">
+    <nd ref="1"/>
+    <nd ref="2"/>
+    <tag k="highway" v="residential"/>
+  </way>
+  <way id="11" visible="true" action="modify">
+    <nd ref="1"/>
+    <nd ref="3"/>
+  </way>
+  <relation id="20" visible="false">
+    <member type="way" ref="11" role="from"/>
+  </relation>
+</osm>"#;
+
+        let iterator = XmlElementIterator::parse(text).expect("must parse");
+        let ids: Vec<_> = iterator
+            .elements()
+            .iter()
+            .map(|element| match element {
+                ProcessedElement::Node(node) => ("node", node.id.identifier),
+                ProcessedElement::Way(way) => ("way", way.id().identifier),
+                ProcessedElement::Relation(relation) => ("relation", relation.id),
+            })
+            .collect();
+
+        assert_eq!(ids, vec![("node", 1), ("way", 11)]);
+    }
+
+    #[test]
+    fn rejects_unknown_member_types() {
+        let text = r#"<osm><relation id="1"><member type="area" ref="1"/></relation></osm>"#;
+        assert!(matches!(
+            XmlElementIterator::parse(text),
+            Err(XmlError::Invalid {
+                attribute: "type",
+                ..
+            })
+        ));
+    }
+}
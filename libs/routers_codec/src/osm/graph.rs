@@ -1,10 +1,13 @@
 use petgraph::prelude::DiGraphMap;
 use routers_network::edge::Weight;
 use routers_network::network::GraphEdge;
+use routers_network::routing::Path as RoutePath;
 use routers_network::{
-    DirectionAwareEdgeId, Discovery, Edge, Node, Route, RowIndex, Scan, envelope_of,
+    DirectionAwareEdgeId, Discovery, Edge, Metadata, Node, Route, RowIndex, Scan, Turn, envelope_of,
 };
+use routers_network::{Routing, RoutingGraph, RoutingProvider};
 
+use itertools::Itertools;
 use log::debug;
 use rustc_hash::{FxHashMap, FxHasher};
 use serde::{Deserialize, Serialize};
@@ -17,14 +20,14 @@ use web_time::Instant;
 #[cfg(not(target_arch = "wasm32"))]
 use log::info;
 #[cfg(not(target_arch = "wasm32"))]
-use routers_network::Metadata;
-#[cfg(not(target_arch = "wasm32"))]
 use std::io::Write;
 #[cfg(not(target_arch = "wasm32"))]
 use std::path::{Path, PathBuf};
 
 #[cfg(not(target_arch = "wasm32"))]
 use crate::osm::element::ProcessedElement;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::osm::turn_restriction::RestrictionRelation;
 use crate::osm::*;
 
 pub type GraphStructure<E> =
@@ -42,6 +45,25 @@ pub struct OsmNetwork {
     pub graph: GraphStructure<OsmEntryId>,
     pub hash: FxHashMap<OsmEntryId, Node<OsmEntryId>>,
     pub meta: FxHashMap<OsmEntryId, OsmEdgeMetadata>,
+    /// The positions each edge left by [`simplify`](Self::simplify) bends
+    /// through, keyed by its `(source, target)`. Edges of an unsimplified
+    /// network are straight, and have none.
+    pub shapes: FxHashMap<(OsmEntryId, OsmEntryId), Vec<Point>>,
+    /// Turn restrictions, keyed by the node their turn is taken at.
+    pub restrictions: TurnRestrictions,
+    /// How routes over the network are priced, fixed when it is built.
+    pub weighting: Weighting,
+    /// The search point-to-point routes are found with.
+    #[serde(skip)]
+    pub routing: Routing,
+    /// The [`Weighting::cost_per_metre`] of the fastest way in the network,
+    /// refreshed alongside the indices.
+    #[serde(skip)]
+    pub(crate) cost_per_metre: f64,
+    /// A contraction hierarchy over the network, which answers
+    /// point-to-point routes in its place when attached.
+    #[serde(skip)]
+    pub hierarchy: Option<ContractionHierarchy>,
 
     #[serde(skip)]
     pub index: RowIndex<OsmEntryId>,
@@ -164,6 +186,7 @@ impl OsmNetwork {
             .collect();
 
         let hash = &self.hash;
+        let shapes = &self.shapes;
         let (node_index, edge_index) = rayon::join(
             || {
                 RowIndex::build(nodes, |id| {
@@ -171,10 +194,110 @@ impl OsmNetwork {
                     (p, p)
                 })
             },
-            || RowIndex::build(edges, |e| envelope_of(e.source.position, e.target.position)),
+            || {
+                RowIndex::build(edges, |e| {
+                    let ends = envelope_of(e.source.position, e.target.position);
+                    shapes
+                        .get(&(e.source.id, e.target.id))
+                        .into_iter()
+                        .flatten()
+                        .fold(ends, |(min, max), &p| {
+                            (envelope_of(min, p).0, envelope_of(max, p).1)
+                        })
+                })
+            },
         );
         self.index = node_index;
         self.index_edge = edge_index;
+
+        let top = self
+            .meta
+            .values()
+            .map(OsmEdgeMetadata::speed)
+            .fold(OsmEdgeMetadata::FALLBACK_SPEED, Ord::max);
+        self.cost_per_metre = self.weighting.cost_per_metre(top);
+    }
+
+    /// Route with the given search, in place of the default [`Routing`].
+    pub fn with_routing(self, routing: Routing) -> Self {
+        Self { routing, ..self }
+    }
+
+    /// Route point-to-point through `hierarchy`, which must have been built
+    /// over this network.
+    pub fn with_hierarchy(self, hierarchy: ContractionHierarchy) -> Result<Self, String> {
+        if !hierarchy.fits(&self) {
+            return Err("Hierarchy was built over another network, rebuild it.".to_string());
+        }
+
+        Ok(Self {
+            hierarchy: Some(hierarchy),
+            ..self
+        })
+    }
+
+    /// Attach a contraction hierarchy, read from beside the network's `.rt`
+    /// file at `saved_path` (fast path) or, if it is missing or stale, built
+    /// afresh and written there for next time (slow path).
+    ///
+    /// Filesystem-bound; not available on WASM targets — use
+    /// [`ContractionHierarchy::from_bytes`] with
+    /// [`with_hierarchy`](Self::with_hierarchy) instead.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn contract_and_save(self, saved_path: &Path) -> Result<Self, String> {
+        let path = ContractionHierarchy::alongside(saved_path);
+
+        if path.exists() {
+            match ContractionHierarchy::from_saved(&path) {
+                Ok(hierarchy) if hierarchy.fits(&self) => return self.with_hierarchy(hierarchy),
+                Ok(_) => log::warn!(
+                    "Contraction hierarchy at `{}` belongs to another network; rebuilding",
+                    path.display()
+                ),
+                Err(e) => log::warn!(
+                    "Contraction hierarchy at `{}` is unusable ({e}); rebuilding",
+                    path.display()
+                ),
+            }
+        }
+
+        let hierarchy = ContractionHierarchy::build(&self);
+        hierarchy.save_to_file(&path)?;
+        self.with_hierarchy(hierarchy)
+    }
+
+    /// Whether `trip` may travel `nodes` in order: every edge along them
+    /// accessible to it, and every turn between them permitted.
+    fn admits(&self, nodes: &[OsmEntryId], trip: &OsmTripConfiguration) -> bool {
+        let mut edges = Vec::with_capacity(nodes.len().saturating_sub(1));
+        for pair in nodes.windows(2) {
+            let Some(&data) = self.graph.edge_weight(pair[0], pair[1]) else {
+                return false;
+            };
+
+            let edge = (pair[0], pair[1], data);
+            if !self.traversable(&edge, trip) {
+                return false;
+            }
+            edges.push(edge);
+        }
+
+        (1..edges.len()).all(|at| {
+            let ((origin, via, (_, from)), (_, destination, (_, to))) = (edges[at - 1], edges[at]);
+            let turn = Turn {
+                origin,
+                from: from.index(),
+                via,
+                to: to.index(),
+                destination,
+            };
+
+            let mut approach = edges[..at - 1]
+                .iter()
+                .rev()
+                .map(|(_, _, (_, id))| id.index());
+            self.restrictions.permits(&turn, &mut approach, trip)
+        })
     }
 
     /// Persist this network to disk. Thin wrapper around
@@ -193,69 +316,107 @@ impl OsmNetwork {
         Ok(())
     }
 
-    /// Construct an `OsmNetwork` from a `.osm.pbf` file. Uses memory-mapped
-    /// IO, multithreaded parsing and rayon; not available on WASM.
+    /// Construct an `OsmNetwork` from a `.osm.pbf` file, routing by the
+    /// default [`Weighting`]. Uses memory-mapped IO, multithreaded parsing and
+    /// rayon; not available on WASM.
     #[cfg(not(target_arch = "wasm32"))]
     pub fn from_pbf(filename: &PathBuf) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::from_pbf_weighted(filename, Weighting::default())
+    }
+
+    /// Construct an `OsmNetwork` from a `.osm.pbf` file, as
+    /// [`from_pbf`](Self::from_pbf) does, routing by the given `weighting`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_pbf_weighted(
+        filename: &Path,
+        weighting: Weighting,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::from_elements(
+            || ProcessedElementIterator::new(filename.to_path_buf()),
+            weighting,
+        )
+    }
+
+    /// Construct an `OsmNetwork` from an OSM XML (`.osm` or `.osm.gz`) file,
+    /// such as an editor exports, routing by the default [`Weighting`]. Not
+    /// available on WASM.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_xml(filename: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::from_xml_weighted(filename, Weighting::default())
+    }
+
+    /// Construct an `OsmNetwork` from an OSM XML file, as
+    /// [`from_xml`](Self::from_xml) does, routing by the given `weighting`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_xml_weighted(
+        filename: &Path,
+        weighting: Weighting,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::from_elements(
+            || XmlElementIterator::new(filename.to_path_buf()),
+            weighting,
+        )
+    }
+
+    /// Construct an `OsmNetwork` from the elements `open` reads, whatever
+    /// their format, routing by the given `weighting`.
+    ///
+    /// The elements are read in two passes, each from a fresh `open`. The
+    /// first builds the graph from the routable ways, which fixes the nodes
+    /// the network needs; the second keeps only those nodes' coordinates.
+    /// Most nodes in an extract belong to buildings, landuse and the like, so
+    /// this bounds peak memory by the size of the routable graph rather than
+    /// of the file.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_elements<P, E>(
+        open: impl Fn() -> Result<P, E>,
+        weighting: Weighting,
+    ) -> Result<Self, Box<dyn std::error::Error>>
+    where
+        P: for<'a> Parallel<Item<'a> = ProcessedElement<'a>>,
+        E: Debug,
+    {
         let mut start_time = Instant::now();
         let fixed_start_time = Instant::now();
 
-        let reader =
-            ProcessedElementIterator::new(filename.clone()).map_err(|err| format!("{err:?}"))?;
+        let reader = open().map_err(|err| format!("{err:?}"))?;
 
         debug!("Iterator warming took: {:?}", start_time.elapsed());
         start_time = Instant::now();
 
-        info!("Ingesting...");
+        info!("Ingesting ways...");
 
-        let (nodes, edges, metadata): (
-            Vec<Node<OsmEntryId>>,
+        type Ingest = (
             Vec<Edge<OsmEntryId>>,
             Vec<(OsmEntryId, OsmEdgeMetadata)>,
-        ) = reader.par_red(
-            |mut trees: (
-                Vec<Node<OsmEntryId>>,
-                Vec<Edge<OsmEntryId>>,
-                Vec<(OsmEntryId, OsmEdgeMetadata)>,
-            ),
-             element: ProcessedElement| {
+            Vec<(OsmEntryId, (OsmEntryId, OsmEntryId))>,
+            Vec<RestrictionRelation>,
+        );
+
+        let (edges, metadata, endpoints, relations): Ingest = reader.par_red(
+            |mut trees: Ingest, element: ProcessedElement| {
                 match element {
                     ProcessedElement::Way(way) => {
-                        let metadata = OsmEdgeMetadata::pick(way.tags());
+                        let refs = way.refs().iter().map(|reference| reference.id);
                         // If way is not traversable (/ is not road)
-                        if metadata.road_class.is_none() {
+                        let Some((metadata, edges)) = way_edges(way.id(), way.tags(), refs) else {
                             return trees;
-                        }
+                        };
 
-                        // Get the weight from the weight table
-                        let weight = metadata.road_class.unwrap().weighting();
-
-                        let bidirectional = !way.tags().unidirectional();
-                        trees.2.push((way.id(), metadata));
-
-                        // Update with all adjacent nodes
-                        way.refs().windows(2).for_each(|edge| {
-                            if let [a, b] = edge {
-                                let direction_aware = DirectionAwareEdgeId::new(way.id());
-
-                                let w = (weight, direction_aware.forward());
-                                trees.1.push(Edge::from((a.id, b.id, &w)));
-
-                                // If way is bidi, add opposite edge with a DirAw backward.
-                                if bidirectional {
-                                    let w = (weight, direction_aware.backward());
-                                    trees.1.push(Edge::from((b.id, a.id, &w)));
-                                }
-                            } else {
-                                debug!("Edge windowing produced odd-sized entry: {edge:?}");
-                            }
-                        });
+                        trees.0.extend(edges);
+                        trees.1.push((way.id(), metadata));
+
+                        // Via-way restrictions are resolved against way endpoints
+                        if let (Some(first), Some(last)) = (way.refs().first(), way.refs().last()) {
+                            trees.2.push((way.id(), (first.id, last.id)));
+                        }
                     }
-                    ProcessedElement::Node(node) => {
-                        // Add the node to the graph
-                        trees.0.push(node);
+                    ProcessedElement::Relation(relation) => {
+                        trees.3.extend(RestrictionRelation::parse(&relation));
                     }
-                    _ => {}
+                    // Nodes are kept in the second pass, once it is known
+                    // which of them the graph needs.
+                    ProcessedElement::Node(_) => {}
                 }
 
                 trees
@@ -264,13 +425,14 @@ impl OsmNetwork {
                 a_tree.0.extend(b_tree.0);
                 a_tree.1.extend(b_tree.1);
                 a_tree.2.extend(b_tree.2);
+                a_tree.3.extend(b_tree.3);
                 a_tree
             },
-            || (Vec::new(), Vec::new(), Vec::new()),
+            || (Vec::new(), Vec::new(), Vec::new(), Vec::new()),
         );
 
         let mut graph = GraphStructure::new();
-        for edge in &edges {
+        for edge in edges {
             graph.add_edge(edge.source, edge.target, (edge.weight, edge.id));
         }
 
@@ -279,18 +441,65 @@ impl OsmNetwork {
 
         let meta = metadata.into_iter().collect::<FxHashMap<_, _>>();
 
-        let mut hash = FxHashMap::default();
-        for node in nodes.iter().filter(|node| graph.contains_node(node.id)) {
-            hash.insert(node.id, *node);
+        let endpoints = endpoints.into_iter().collect::<FxHashMap<_, _>>();
+        let mut restrictions = TurnRestrictions::default();
+        for relation in relations {
+            for restriction in relation.resolve(|way| endpoints.get(way).copied()) {
+                restrictions.insert(restriction);
+            }
         }
 
-        debug!("HashMap creation took: {:?}", start_time.elapsed());
+        debug!(
+            "Turn restriction table took: {:?}, holding {} restrictions",
+            start_time.elapsed(),
+            restrictions.len()
+        );
+        start_time = Instant::now();
+
+        info!("Ingesting nodes...");
+
+        let reader = open().map_err(|err| format!("{err:?}"))?;
+
+        // Only the graph's own nodes are kept, alongside a count of all
+        // nodes seen for reporting.
+        type Kept = (Vec<Node<OsmEntryId>>, usize);
+
+        let (nodes, total): Kept = reader.par_red(
+            |(mut nodes, total): Kept, element: ProcessedElement| match element {
+                ProcessedElement::Node(node) => {
+                    if graph.contains_node(node.id) {
+                        nodes.push(node);
+                    }
+
+                    (nodes, total + 1)
+                }
+                _ => (nodes, total),
+            },
+            |(mut a_nodes, a_total), (b_nodes, b_total)| {
+                a_nodes.extend(b_nodes);
+                (a_nodes, a_total + b_total)
+            },
+            || (Vec::new(), 0),
+        );
+
+        let hash = nodes
+            .into_iter()
+            .map(|node| (node.id, node))
+            .collect::<FxHashMap<_, _>>();
+
+        debug!("Node ingestion took: {:?}", start_time.elapsed());
         start_time = Instant::now();
 
         let mut network = OsmNetwork {
             graph,
             hash,
             meta,
+            shapes: FxHashMap::default(),
+            restrictions,
+            weighting,
+            routing: Routing::default(),
+            cost_per_metre: 0.0,
+            hierarchy: None,
             index: RowIndex::default(),
             index_edge: RowIndex::default(),
         };
@@ -300,16 +509,55 @@ impl OsmNetwork {
         info!(
             "Finished. Ingested {:?} nodes from {:?} nodes total in {}ms",
             network.index.len(),
-            nodes.len(),
+            total,
             fixed_start_time.elapsed().as_millis()
         );
 
         Ok(network)
     }
 
+    /// Reports the cost of a routed `path` in the network's units, and its
+    /// nodes in full.
+    fn resolve(&self, (cost, nodes): RoutePath<OsmEntryId>) -> (Weight, Vec<Node<OsmEntryId>>) {
+        let nodes = nodes
+            .iter()
+            .filter_map(|node| self.hash.get(node).copied())
+            .collect();
+
+        (self.weighting.report(cost), nodes)
+    }
+
     pub fn num_nodes(&self) -> usize {
         self.graph.node_count()
     }
+
+    /// The routing cost of the edge from `source` to `target`, of road-class
+    /// `weight`, under the network's [`Weighting`].
+    pub fn cost(
+        &self,
+        source: OsmEntryId,
+        target: OsmEntryId,
+        weight: Weight,
+        id: DirectionAwareEdgeId<OsmEntryId>,
+    ) -> Weight {
+        let (Some(start), Some(end)) = (self.hash.get(&source), self.hash.get(&target)) else {
+            return weight;
+        };
+
+        let meta = self.meta.get(&id.index());
+        match self.shapes.get(&(source, target)) {
+            // A shaped edge costs what the edges it replaced did.
+            Some(shape) => core::iter::once(start.position)
+                .chain(shape.iter().copied())
+                .chain(core::iter::once(end.position))
+                .tuple_windows()
+                .map(|(a, b)| self.weighting.cost(a, b, weight, meta))
+                .sum(),
+            None => self
+                .weighting
+                .cost(start.position, end.position, weight, meta),
+        }
+    }
 }
 
 impl Default for OsmNetwork {
@@ -318,6 +566,12 @@ impl Default for OsmNetwork {
             graph: GraphStructure::new(),
             hash: FxHashMap::default(),
             meta: FxHashMap::default(),
+            shapes: FxHashMap::default(),
+            restrictions: TurnRestrictions::default(),
+            weighting: Weighting::default(),
+            routing: Routing::default(),
+            cost_per_metre: 0.0,
+            hierarchy: None,
             index: RowIndex::default(),
             index_edge: RowIndex::default(),
         }
@@ -366,21 +620,52 @@ impl Route for OsmNetwork {
         &self,
         start_node: OsmEntryId,
         finish_node: OsmEntryId,
+        runtime: &OsmTripConfiguration,
     ) -> Option<(Weight, Vec<Node<OsmEntryId>>)> {
-        let (score, path) = petgraph::algo::astar(
-            &self.graph,
-            start_node,
-            |finish| finish == finish_node,
-            |(_, _, w)| w.0,
-            |_| 0 as Weight,
-        )?;
-
-        let route = path
-            .iter()
-            .filter_map(|v| self.hash.get(v).copied())
-            .collect();
+        // The hierarchy sees neither access nor turn restrictions, so a route
+        // the trip may not travel, or none at all, is searched for again. Its
+        // cost is a lower bound on any the trip may, so one it may travel is
+        // as cheap as can be.
+        if let Some(hierarchy) = &self.hierarchy
+            && let Some(path) = hierarchy.route(start_node, finish_node)
+            && self.admits(&path.1, runtime)
+        {
+            return Some(self.resolve(path));
+        }
+
+        self.routing
+            .route(self, start_node, finish_node, runtime)
+            .map(|path| self.resolve(path))
+    }
+
+    fn route_many(
+        &self,
+        start_node: OsmEntryId,
+        finish_nodes: &[OsmEntryId],
+        runtime: &OsmTripConfiguration,
+    ) -> Vec<Option<(Weight, Vec<Node<OsmEntryId>>)>> {
+        if self.hierarchy.is_some() {
+            return finish_nodes
+                .iter()
+                .map(|&finish| self.route_nodes(start_node, finish, runtime))
+                .collect();
+        }
+
+        self.routing
+            .route_many(self, start_node, finish_nodes, runtime)
+            .into_iter()
+            .map(|path| path.map(|path| self.resolve(path)))
+            .collect()
+    }
+}
+
+impl RoutingGraph for OsmNetwork {
+    fn cost(&self, &(source, target, (weight, id)): &GraphEdge<OsmEntryId>) -> Weight {
+        OsmNetwork::cost(self, source, target, weight, id)
+    }
 
-        Some((score, route))
+    fn cost_per_metre(&self) -> f64 {
+        self.cost_per_metre
     }
 }
 
@@ -403,6 +688,13 @@ impl routers_network::DataPlane for OsmNetwork {
         self.hash.get(id).map(|v| v.position)
     }
 
+    fn shape(&self, source: &OsmEntryId, target: &OsmEntryId) -> &[Point] {
+        self.shapes
+            .get(&(*source, *target))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
     fn edges_into<'a>(
         &'a self,
         id: OsmEntryId,
@@ -441,4 +733,434 @@ impl routers_network::DataPlane for OsmNetwork {
             weight: *weight,
         })
     }
+
+    fn turn_permitted(
+        &self,
+        turn: &Turn<OsmEntryId>,
+        approach: &mut dyn Iterator<Item = OsmEntryId>,
+        runtime: &OsmTripConfiguration,
+    ) -> bool {
+        self.restrictions.permits(turn, approach, runtime)
+    }
+}
+
+/// The metadata of the way `id`, and the edges between each consecutive pair
+/// of its `refs`, if its tags make it routable. A way is routable in both
+/// directions unless it is tagged one-way.
+pub(crate) fn way_edges(
+    id: OsmEntryId,
+    tags: &Tags,
+    refs: impl Iterator<Item = OsmEntryId>,
+) -> Option<(OsmEdgeMetadata, Vec<Edge<OsmEntryId>>)> {
+    let metadata = OsmEdgeMetadata::pick(tags);
+
+    // Get the rank from the weight table. Routing prices
+    // edges through the network's `Weighting` instead.
+    let weight = metadata.road_class?.weighting();
+
+    let bidirectional = !tags.unidirectional();
+    let direction_aware = DirectionAwareEdgeId::new(id);
+
+    let mut edges = Vec::new();
+    for (a, b) in refs.tuple_windows() {
+        let w = (weight, direction_aware.forward());
+        edges.push(Edge::from((a, b, &w)));
+
+        // If way is bidi, add opposite edge with a DirAw backward.
+        if bidirectional {
+            let w = (weight, direction_aware.backward());
+            edges.push(Edge::from((b, a, &w)));
+        }
+    }
+
+    Some((metadata, edges))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::osm::access_tag::AccessTag;
+    use crate::osm::primitives::condition::VehicleProperty;
+    use crate::osm::primitives::{RoadClass, TransportMode};
+    use crate::osm::turn_restriction::{TurnKind, TurnRestriction};
+    use crate::osm::vehicle_limit::{VehicleLimitEntry, VehicleLimits};
+
+    /// A junction at node 2, where turning from way 10 onto way 20 leads
+    /// straight to node 4, and the detour runs around by ways 30, 40 and 50.
+    ///
+    /// ```text
+    ///         4 ─── 6
+    ///         │     │
+    ///   1 ─── 2 ─── 3
+    /// ```
+    fn junction(restrictions: TurnRestrictions) -> OsmNetwork {
+        let node = |id: i64, x: f64, y: f64| Node::new(Point::new(x, y), OsmEntryId::node(id));
+        let way = |id: i64| (1, DirectionAwareEdgeId::new(OsmEntryId::way(id)));
+
+        let mut graph = GraphStructure::new();
+        for (a, b, id) in [(1, 2, 10), (2, 4, 20), (2, 3, 30), (3, 6, 40), (6, 4, 50)] {
+            graph.add_edge(OsmEntryId::node(a), OsmEntryId::node(b), way(id));
+        }
+
+        let hash = [
+            node(1, 0.0, 0.0),
+            node(2, 0.001, 0.0),
+            node(3, 0.002, 0.0),
+            node(4, 0.001, 0.001),
+            node(6, 0.002, 0.001),
+        ]
+        .into_iter()
+        .map(|node| (node.id, node))
+        .collect();
+
+        let mut network = OsmNetwork {
+            graph,
+            hash,
+            restrictions,
+            ..OsmNetwork::default()
+        };
+        network.rebuild_indices();
+        network
+    }
+
+    fn route(network: &OsmNetwork) -> Vec<i64> {
+        let (_, nodes) = network
+            .route_nodes(
+                OsmEntryId::node(1),
+                OsmEntryId::node(4),
+                &OsmTripConfiguration::default(),
+            )
+            .expect("a route exists");
+
+        nodes.iter().map(|node| node.id.identifier).collect()
+    }
+
+    #[test]
+    fn route_nodes_takes_the_direct_turn_when_unrestricted() {
+        let network = junction(TurnRestrictions::default());
+        assert_eq!(route(&network), vec![1, 2, 4]);
+    }
+
+    #[test]
+    fn route_nodes_detours_around_a_restricted_turn() {
+        let mut restrictions = TurnRestrictions::default();
+        restrictions.insert(TurnRestriction {
+            kind: TurnKind::Prohibitory,
+            from: OsmEntryId::way(10),
+            through: Vec::new(),
+            via: OsmEntryId::node(2),
+            to: OsmEntryId::way(20),
+            mode: None,
+            except: Vec::new(),
+            condition: None,
+        });
+
+        let network = junction(restrictions);
+        assert_eq!(route(&network), vec![1, 2, 3, 6, 4]);
+    }
+
+    #[test]
+    fn from_elements_ingests_osm_xml() {
+        let way = |id: i64, a: i64, b: i64, oneway: &str| {
+            format!(
+                r#"<way id="{id}"><nd ref="{a}"/><nd ref="{b}"/><tag k="highway" v="residential"/><tag k="oneway" v="{oneway}"/></way>"#
+            )
+        };
+
+        // The junction above, with the turn from way 10 onto way 20
+        // prohibited, a one-way detour, and a node no way passes through.
+        let text = format!(
+            r#"<osm version="0.6">
+              <node id="1" lat="0.0" lon="0.0"/>
+              <node id="2" lat="0.0" lon="0.001"/>
+              <node id="3" lat="0.0" lon="0.002"/>
+              <node id="4" lat="0.001" lon="0.001"/>
+              <node id="6" lat="0.001" lon="0.002"/>
+              <node id="9" lat="0.005" lon="0.005"/>
+              {}{}{}{}{}
+              <relation id="100">
+                <member type="way" ref="10" role="from"/>
+                <member type="node" ref="2" role="via"/>
+                <member type="way" ref="20" role="to"/>
+                <tag k="type" v="restriction"/>
+                <tag k="restriction" v="no_left_turn"/>
+              </relation>
+            </osm>"#,
+            way(10, 1, 2, "no"),
+            way(20, 2, 4, "no"),
+            way(30, 2, 3, "yes"),
+            way(40, 3, 6, "yes"),
+            way(50, 6, 4, "yes"),
+        );
+
+        let network =
+            OsmNetwork::from_elements(|| XmlElementIterator::parse(&text), Weighting::default())
+                .expect("must ingest");
+
+        assert_eq!(network.hash.len(), 5);
+        assert_eq!(network.graph.edge_count(), 7);
+        assert_eq!(network.restrictions.len(), 1);
+        assert_eq!(route(&network), vec![1, 2, 3, 6, 4]);
+    }
+
+    /// A residential street straight from node 1 to node 2, and a longer
+    /// motorway detour through node 3, every edge of equal rank.
+    ///
+    /// ```text
+    ///         3
+    ///       ╱   ╲
+    ///   1 ─────── 2
+    /// ```
+    fn bypass(weighting: Weighting) -> OsmNetwork {
+        let node = |id: i64, x: f64, y: f64| Node::new(Point::new(x, y), OsmEntryId::node(id));
+        let way = |id: i64| (1, DirectionAwareEdgeId::new(OsmEntryId::way(id)));
+        let class = |road_class| OsmEdgeMetadata {
+            road_class: Some(road_class),
+            ..OsmEdgeMetadata::default()
+        };
+
+        let mut graph = GraphStructure::new();
+        for (a, b, id) in [(1, 2, 10), (1, 3, 20), (3, 2, 20)] {
+            graph.add_edge(OsmEntryId::node(a), OsmEntryId::node(b), way(id));
+        }
+
+        let hash = [node(1, 0.0, 0.0), node(2, 0.01, 0.0), node(3, 0.005, 0.003)]
+            .into_iter()
+            .map(|node| (node.id, node))
+            .collect();
+
+        let meta = [
+            (OsmEntryId::way(10), class(RoadClass::Residential)),
+            (OsmEntryId::way(20), class(RoadClass::Motorway)),
+        ]
+        .into_iter()
+        .collect();
+
+        let mut network = OsmNetwork {
+            graph,
+            hash,
+            meta,
+            weighting,
+            ..OsmNetwork::default()
+        };
+        network.rebuild_indices();
+        network
+    }
+
+    #[test]
+    fn travel_time_takes_the_faster_detour() {
+        let (seconds, nodes) = bypass(Weighting::TravelTime)
+            .route_nodes(
+                OsmEntryId::node(1),
+                OsmEntryId::node(2),
+                &OsmTripConfiguration::default(),
+            )
+            .expect("a route exists");
+
+        let ids: Vec<i64> = nodes.iter().map(|node| node.id.identifier).collect();
+        assert_eq!(ids, vec![1, 3, 2]);
+
+        // ~1.3 km of motorway at 100 km/h.
+        assert_eq!(seconds, 47);
+    }
+
+    #[test]
+    fn every_routing_takes_the_same_detour() {
+        let restricted = |routing| {
+            let mut restrictions = TurnRestrictions::default();
+            restrictions.insert(TurnRestriction {
+                kind: TurnKind::Prohibitory,
+                from: OsmEntryId::way(10),
+                through: Vec::new(),
+                via: OsmEntryId::node(2),
+                to: OsmEntryId::way(20),
+                mode: None,
+                except: Vec::new(),
+                condition: None,
+            });
+
+            junction(restrictions).with_routing(routing)
+        };
+
+        for routing in [Routing::Dijkstra, Routing::AStar, Routing::Bidirectional] {
+            assert_eq!(
+                route(&restricted(routing)),
+                vec![1, 2, 3, 6, 4],
+                "{routing:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn every_routing_honours_via_way_restrictions() {
+        // From way 10, along way 30 (nodes 2 → 3), onto way 40 at node 3.
+        let restricted = |routing| {
+            let mut restrictions = TurnRestrictions::default();
+            restrictions.insert(TurnRestriction {
+                kind: TurnKind::Prohibitory,
+                from: OsmEntryId::way(10),
+                through: vec![OsmEntryId::way(30)],
+                via: OsmEntryId::node(3),
+                to: OsmEntryId::way(40),
+                mode: None,
+                except: Vec::new(),
+                condition: None,
+            });
+
+            let mut network = junction(restrictions).with_routing(routing);
+            network.graph.add_edge(
+                OsmEntryId::node(4),
+                OsmEntryId::node(6),
+                (1, DirectionAwareEdgeId::new(OsmEntryId::way(60))),
+            );
+            network.rebuild_indices();
+            network
+        };
+
+        for routing in [Routing::Dijkstra, Routing::AStar, Routing::Bidirectional] {
+            let (_, nodes) = restricted(routing)
+                .route_nodes(
+                    OsmEntryId::node(1),
+                    OsmEntryId::node(6),
+                    &OsmTripConfiguration::default(),
+                )
+                .expect("a route exists");
+
+            let ids: Vec<i64> = nodes.iter().map(|node| node.id.identifier).collect();
+            assert_eq!(ids, vec![1, 2, 4, 6], "{routing:?}");
+        }
+    }
+
+    /// A motorway straight from node 1 to node 2, and a detour of many
+    /// centimetre-long motorway edges bowing away from it, each far too short
+    /// to cost a whole millisecond.
+    fn centimetres() -> OsmNetwork {
+        let node = |id: i64, x: f64, y: f64| Node::new(Point::new(x, y), OsmEntryId::node(id));
+        let way = |id: i64| (1, DirectionAwareEdgeId::new(OsmEntryId::way(id)));
+
+        let (start, apex, finish) = ((0.0, 0.0), (0.0, 2e-6), (2e-6, 0.0));
+        let step = |(x0, y0): (f64, f64), (x1, y1): (f64, f64), t: f64| {
+            (x0 + (x1 - x0) * t, y0 + (y1 - y0) * t)
+        };
+
+        let detour = (1..20)
+            .map(|i| step(start, apex, f64::from(i) / 20.0))
+            .chain((0..30).map(|i| step(apex, finish, f64::from(i) / 30.0)));
+
+        let mut hash = FxHashMap::default();
+        hash.insert(OsmEntryId::node(1), node(1, start.0, start.1));
+        hash.insert(OsmEntryId::node(2), node(2, finish.0, finish.1));
+
+        let mut graph = GraphStructure::new();
+        graph.add_edge(OsmEntryId::node(1), OsmEntryId::node(2), way(10));
+
+        let mut previous = OsmEntryId::node(1);
+        for (id, (x, y)) in (100..).zip(detour) {
+            hash.insert(OsmEntryId::node(id), node(id, x, y));
+            graph.add_edge(previous, OsmEntryId::node(id), way(20));
+            previous = OsmEntryId::node(id);
+        }
+        graph.add_edge(previous, OsmEntryId::node(2), way(20));
+
+        let motorway = OsmEdgeMetadata {
+            road_class: Some(RoadClass::Motorway),
+            ..OsmEdgeMetadata::default()
+        };
+        let meta = [10, 20]
+            .map(|id| (OsmEntryId::way(id), motorway.clone()))
+            .into_iter()
+            .collect();
+
+        let mut network = OsmNetwork {
+            graph,
+            hash,
+            meta,
+            weighting: Weighting::TravelTime,
+            ..OsmNetwork::default()
+        };
+        network.rebuild_indices();
+        network
+    }
+
+    #[test]
+    fn astar_costs_as_dijkstra_over_short_edges() {
+        let network = centimetres();
+        let trip = OsmTripConfiguration::default();
+        let (start, finish) = (OsmEntryId::node(1), OsmEntryId::node(2));
+
+        let (dijkstra, _) = Routing::Dijkstra
+            .route(&network, start, finish, &trip)
+            .expect("a route exists");
+        let (astar, _) = Routing::AStar
+            .route(&network, start, finish, &trip)
+            .expect("a route exists");
+
+        assert_eq!(astar, dijkstra);
+    }
+
+    #[test]
+    fn road_class_weighting_takes_the_fewest_ranked_edges() {
+        let (rank, nodes) = bypass(Weighting::RoadClass)
+            .route_nodes(
+                OsmEntryId::node(1),
+                OsmEntryId::node(2),
+                &OsmTripConfiguration::default(),
+            )
+            .expect("a route exists");
+
+        let ids: Vec<i64> = nodes.iter().map(|node| node.id.identifier).collect();
+        assert_eq!(ids, vec![1, 2]);
+        assert_eq!(rank, 1);
+    }
+
+    #[test]
+    fn route_nodes_avoids_ways_closed_to_the_trip() {
+        let mut network = bypass(Weighting::RoadClass);
+        if let Some(meta) = network.meta.get_mut(&OsmEntryId::way(10)) {
+            meta.access = vec![AccessTag::from_key_value("hgv", "no").expect("a valid tag")];
+        }
+
+        let route = |trip: &OsmTripConfiguration| -> Vec<i64> {
+            let (_, nodes) = network
+                .route_nodes(OsmEntryId::node(1), OsmEntryId::node(2), trip)
+                .expect("a route exists");
+            nodes.iter().map(|node| node.id.identifier).collect()
+        };
+
+        let car = OsmTripConfiguration::default();
+        let truck = OsmTripConfiguration {
+            transport_mode: TransportMode::Hgv,
+            ..OsmTripConfiguration::default()
+        };
+
+        assert_eq!(route(&car), vec![1, 2]);
+        assert_eq!(route(&truck), vec![1, 3, 2]);
+    }
+
+    #[test]
+    fn route_nodes_avoids_ways_too_low_for_the_vehicle() {
+        let mut network = bypass(Weighting::RoadClass);
+        if let Some(meta) = network.meta.get_mut(&OsmEntryId::way(10)) {
+            meta.vehicle_limits = VehicleLimits(vec![VehicleLimitEntry {
+                property: VehicleProperty::Height,
+                value: Some(3.0),
+                condition: None,
+            }]);
+        }
+
+        let route = |height: f32| -> Vec<i64> {
+            let trip = OsmTripConfiguration {
+                vehicle_properties: Some(vec![(VehicleProperty::Height, height)]),
+                ..OsmTripConfiguration::default()
+            };
+
+            let (_, nodes) = network
+                .route_nodes(OsmEntryId::node(1), OsmEntryId::node(2), &trip)
+                .expect("a route exists");
+            nodes.iter().map(|node| node.id.identifier).collect()
+        };
+
+        assert_eq!(route(2.5), vec![1, 2]);
+        assert_eq!(route(4.0), vec![1, 3, 2]);
+    }
 }
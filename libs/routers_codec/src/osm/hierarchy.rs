@@ -0,0 +1,769 @@
+//! Contraction hierarchies over an [`OsmNetwork`], for fast point-to-point
+//! routing on large extracts.
+//!
+//! Preprocessing contracts the network's nodes one at a time, least
+//! important first. Wherever a contracted node lay on the only cheapest route
+//! between two of its neighbours, a *shortcut* between them takes its place.
+//! A query then runs a bidirectional Dijkstra which only ever climbs the
+//! hierarchy, settling a few hundred nodes where a plain search would settle
+//! most of the network, and unpacks the shortcuts it took back into the
+//! network's own nodes.
+//!
+//! The hierarchy is built over nodes, not edges, for no trip in particular,
+//! so it sees neither turn restrictions nor access rules. [`OsmNetwork`]
+//! checks each route it unpacks against the trip, and falls back to its
+//! plain search for the few the trip may not travel.
+
+use alloc::collections::BinaryHeap;
+use core::cmp::Reverse;
+use core::hash::{Hash, Hasher};
+
+use log::debug;
+use routers_network::edge::Weight;
+use routers_network::routing::Path as RoutePath;
+use rustc_hash::{FxHashMap, FxHasher};
+use serde::{Deserialize, Serialize};
+use web_time::Instant;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::io::Write;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::{Path, PathBuf};
+
+use crate::osm::{OsmEntryId, OsmNetwork, Weighting};
+
+/// Magic header stapled at the start of every `.ch` file.
+const SAVE_MAGIC: &[u8; 4] = b"OSCH";
+
+// Prevent files from being used across build revisions
+include!(concat!(env!("OUT_DIR"), "/format_hash.rs"));
+const SAVE_VERSION: u64 = FORMAT_HASH;
+
+/// The number of nodes a witness search settles before giving up, keeping
+/// the shortcut it was looking for an alternative to.
+const WITNESS_LIMIT: usize = 256;
+
+/// A directed arc of the hierarchy: an edge of the network, or a shortcut
+/// standing in for two arcs through the `middle` node.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+struct Link {
+    head: u32,
+    cost: Weight,
+    middle: Option<u32>,
+}
+
+/// The arcs leaving each node, in compressed-row form.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Adjacency {
+    offsets: Vec<u32>,
+    links: Vec<Link>,
+}
+
+impl Adjacency {
+    fn build(len: usize, mut links: Vec<(u32, Link)>) -> Self {
+        links.sort_unstable_by_key(|(tail, link)| (*tail, link.head));
+
+        let mut offsets = vec![0; len + 1];
+        for (tail, _) in &links {
+            offsets[*tail as usize + 1] += 1;
+        }
+        for node in 0..len {
+            offsets[node + 1] += offsets[node];
+        }
+
+        Self {
+            offsets,
+            links: links.into_iter().map(|(_, link)| link).collect(),
+        }
+    }
+
+    fn of(&self, node: u32) -> &[Link] {
+        let node = node as usize;
+        &self.links[self.offsets[node] as usize..self.offsets[node + 1] as usize]
+    }
+}
+
+/// A contraction hierarchy built over an [`OsmNetwork`], answering the same
+/// point-to-point queries as its plain search with the same routes.
+///
+/// Build one with [`build`](Self::build) and attach it with
+/// [`OsmNetwork::with_hierarchy`]. As building is slow on large networks, it
+/// persists alongside the network's `.rt` file, see
+/// [`OsmNetwork::contract_and_save`].
+#[derive(Serialize, Deserialize)]
+pub struct ContractionHierarchy {
+    /// The weighting the network priced its edges by when built.
+    weighting: Weighting,
+    /// The number of edges in the network when built.
+    edges: usize,
+    /// The [`fingerprint`] of the network's edges when built, which tells
+    /// apart a network whose costs or shapes have since changed.
+    fingerprint: u64,
+    /// The network's nodes, in the order they were contracted. A node's
+    /// position is its rank.
+    nodes: Vec<OsmEntryId>,
+    /// Arcs leading up the hierarchy, from each node.
+    up: Adjacency,
+    /// Arcs leading down the hierarchy, into each node. Their `head` is the
+    /// node they come from.
+    down: Adjacency,
+
+    #[serde(skip)]
+    rank: FxHashMap<OsmEntryId, u32>,
+}
+
+impl ContractionHierarchy {
+    /// Contract every node of `network`, pricing edges as its plain search
+    /// does.
+    pub fn build(network: &OsmNetwork) -> Self {
+        let start_time = Instant::now();
+
+        let nodes: Vec<OsmEntryId> = network.graph.nodes().collect();
+        let index: FxHashMap<OsmEntryId, u32> = nodes
+            .iter()
+            .enumerate()
+            .map(|(position, &node)| (node, position as u32))
+            .collect();
+
+        let mut contraction = Contraction::new(nodes.len());
+        for (source, target, &(weight, id)) in network.graph.all_edges() {
+            if source != target {
+                let cost = network.cost(source, target, weight, id);
+                contraction.insert(index[&source], index[&target], cost, None);
+            }
+        }
+
+        let order = contraction.run();
+
+        let mut rank = vec![0; nodes.len()];
+        for (position, &node) in order.iter().enumerate() {
+            rank[node as usize] = position as u32;
+        }
+
+        let mut up = Vec::new();
+        let mut down = Vec::new();
+        for (&(tail, head), &(cost, middle)) in &contraction.links {
+            let (tail, head) = (rank[tail as usize], rank[head as usize]);
+            let middle = middle.map(|node| rank[node as usize]);
+
+            match tail < head {
+                true => up.push((tail, Link { head, cost, middle })),
+                false => down.push((
+                    head,
+                    Link {
+                        head: tail,
+                        cost,
+                        middle,
+                    },
+                )),
+            }
+        }
+
+        let mut hierarchy = ContractionHierarchy {
+            weighting: network.weighting,
+            edges: network.graph.edge_count(),
+            fingerprint: fingerprint(network),
+            nodes: order.iter().map(|&node| nodes[node as usize]).collect(),
+            up: Adjacency::build(nodes.len(), up),
+            down: Adjacency::build(nodes.len(), down),
+            rank: FxHashMap::default(),
+        };
+        hierarchy.rebuild_ranks();
+
+        debug!(
+            "ContractionHierarchy::build contracted {} nodes, adding {} shortcuts, in {:?}",
+            hierarchy.nodes.len(),
+            hierarchy.shortcuts(),
+            start_time.elapsed()
+        );
+
+        hierarchy
+    }
+
+    /// The number of shortcuts added by contraction.
+    pub fn shortcuts(&self) -> usize {
+        self.up
+            .links
+            .iter()
+            .chain(&self.down.links)
+            .filter(|link| link.middle.is_some())
+            .count()
+    }
+
+    /// Whether the hierarchy was built over `network`, as it stands: the
+    /// same nodes, joined by edges which cost and bend as they did.
+    pub fn fits(&self, network: &OsmNetwork) -> bool {
+        self.weighting == network.weighting
+            && self.edges == network.graph.edge_count()
+            && self.nodes.len() == network.graph.node_count()
+            && self
+                .nodes
+                .iter()
+                .all(|&node| network.graph.contains_node(node))
+            && self.fingerprint == fingerprint(network)
+    }
+
+    fn rebuild_ranks(&mut self) {
+        self.rank = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(rank, &node)| (node, rank as u32))
+            .collect();
+    }
+
+    /// The cheapest route from `start` to `finish`, ignoring turn
+    /// restrictions and access rules, with its cost summed as the network's plain search
+    /// sums it. `None` if there is no route.
+    pub fn route(&self, start: OsmEntryId, finish: OsmEntryId) -> Option<RoutePath<OsmEntryId>> {
+        let (&start, &finish) = (self.rank.get(&start)?, self.rank.get(&finish)?);
+
+        // Each direction labels a node with its cost and the arc it was
+        // reached by: its tail and the link, both read forwards.
+        type Labels = FxHashMap<u32, (Weight, Option<(u32, Link)>)>;
+        let mut labels: [Labels; 2] = [
+            FxHashMap::from_iter([(start, (0, None))]),
+            FxHashMap::from_iter([(finish, (0, None))]),
+        ];
+        let mut queues = [
+            BinaryHeap::from([Reverse((0, start))]),
+            BinaryHeap::from([Reverse((0, finish))]),
+        ];
+
+        let mut best: Option<(Weight, u32)> = None;
+        loop {
+            let direction = match (queues[0].peek(), queues[1].peek()) {
+                (None, None) => break,
+                (Some(ahead), Some(behind)) if behind < ahead => 1,
+                (Some(_), _) => 0,
+                (None, Some(_)) => 1,
+            };
+
+            let Some(Reverse((cost, node))) = queues[direction].pop() else {
+                break;
+            };
+
+            // The cheaper of the two queues can no longer improve on it.
+            if best.is_some_and(|(known, _)| cost >= known) {
+                break;
+            }
+            if labels[direction][&node].0 < cost {
+                continue;
+            }
+
+            if let Some(&(other, _)) = labels[1 - direction].get(&node) {
+                let total = cost.saturating_add(other);
+                if best.is_none_or(|(known, _)| total < known) {
+                    best = Some((total, node));
+                }
+            }
+
+            let links = match direction {
+                0 => self.up.of(node),
+                _ => self.down.of(node),
+            };
+
+            for &link in links {
+                let next = cost.saturating_add(link.cost);
+                if labels[direction]
+                    .get(&link.head)
+                    .is_some_and(|&(known, _)| known <= next)
+                {
+                    continue;
+                }
+
+                let arc = match direction {
+                    0 => (node, link),
+                    _ => (link.head, Link { head: node, ..link }),
+                };
+
+                labels[direction].insert(link.head, (next, Some(arc)));
+                queues[direction].push(Reverse((next, link.head)));
+            }
+        }
+
+        let (cost, meeting) = best?;
+
+        let mut arcs = Vec::new();
+        let mut cursor = meeting;
+        while let Some((tail, link)) = labels[0][&cursor].1 {
+            arcs.push((tail, link));
+            cursor = tail;
+        }
+        arcs.reverse();
+
+        cursor = meeting;
+        while let Some((tail, link)) = labels[1][&cursor].1 {
+            arcs.push((tail, link));
+            cursor = link.head;
+        }
+
+        let mut ranks = vec![start];
+        for (tail, link) in arcs {
+            self.unpack(tail, link, &mut ranks)?;
+        }
+
+        let nodes = ranks
+            .iter()
+            .map(|&rank| self.nodes[rank as usize])
+            .collect();
+        Some((cost, nodes))
+    }
+
+    /// Push the nodes after `tail` along `link` onto `nodes`, expanding each
+    /// shortcut into the two arcs it stands in for. `None` if either arc is
+    /// missing, as the route through the shortcut cannot then be told.
+    fn unpack(&self, tail: u32, link: Link, nodes: &mut Vec<u32>) -> Option<()> {
+        let Some(middle) = link.middle else {
+            nodes.push(link.head);
+            return Some(());
+        };
+
+        // The middle node was contracted before either end, so the arc into
+        // it leads down the hierarchy and the arc out of it leads up.
+        let into = self.down.of(middle).iter().find(|arc| arc.head == tail);
+        let outof = self.up.of(middle).iter().find(|arc| arc.head == link.head);
+        let (Some(&into), Some(&outof)) = (into, outof) else {
+            return None;
+        };
+
+        self.unpack(
+            tail,
+            Link {
+                head: middle,
+                ..into
+            },
+            nodes,
+        )?;
+        self.unpack(middle, outof, nodes)
+    }
+
+    /// Decode a previously-encoded `ContractionHierarchy` from a byte slice.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        const HEADER_LEN: usize = SAVE_MAGIC.len() + 8;
+
+        if bytes.len() < HEADER_LEN || &bytes[..SAVE_MAGIC.len()] != SAVE_MAGIC {
+            return Err("Header bytes are missing, try rebuilding the hierarchy.".to_string());
+        }
+
+        let version = u64::from_le_bytes(
+            bytes[SAVE_MAGIC.len()..HEADER_LEN]
+                .try_into()
+                .expect("8 bytes"),
+        );
+
+        if version != SAVE_VERSION {
+            return Err(format!(
+                "Header expects {SAVE_VERSION:016x}, got format hash {version:016x}, rebuild the hierarchy."
+            ));
+        }
+
+        let mut hierarchy: Self =
+            postcard::from_bytes(&bytes[HEADER_LEN..]).map_err(|v| v.to_string())?;
+        hierarchy.rebuild_ranks();
+
+        Ok(hierarchy)
+    }
+
+    /// Encode `self` into a `Vec<u8>` with the format header prepended.
+    /// Counterpart to [`from_bytes`](Self::from_bytes).
+    pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        let payload: Vec<u8> =
+            postcard::to_allocvec(self).map_err(|e| format!("failed to serialise value: {e}"))?;
+        let mut out = Vec::with_capacity(SAVE_MAGIC.len() + 8 + payload.len());
+
+        out.extend_from_slice(SAVE_MAGIC);
+        out.extend_from_slice(&SAVE_VERSION.to_le_bytes());
+        out.extend_from_slice(&payload);
+
+        Ok(out)
+    }
+
+    /// Where the hierarchy of the network saved at `saved_path` is kept: the
+    /// same path, with a `.ch` extension in place of `.rt`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn alongside(saved_path: &Path) -> PathBuf {
+        saved_path.with_extension("ch")
+    }
+
+    /// Persist this hierarchy to disk. Thin wrapper around
+    /// [`to_bytes`](Self::to_bytes); not available on WASM.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save_to_file(&self, path: &Path) -> Result<(), String> {
+        let bytes = self.to_bytes()?;
+        let mut file = std::fs::File::create(path).map_err(|e| e.to_string())?;
+        file.write_all(&bytes).map_err(|e| e.to_string())?;
+        debug!(
+            "ContractionHierarchy::save_to_file wrote {} bytes (incl. 12-byte header, format {:016x}) to {}",
+            bytes.len(),
+            SAVE_VERSION,
+            path.display()
+        );
+        Ok(())
+    }
+
+    /// Read a saved `.ch` from disk. Thin wrapper around
+    /// [`from_bytes`](Self::from_bytes); not available on WASM.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_saved(filename: &Path) -> Result<Self, String> {
+        let bytes = std::fs::read(filename).map_err(|v| v.to_string())?;
+        Self::from_bytes(&bytes)
+            .map_err(|e| format!("hierarchy file `{}`: {e}", filename.display()))
+    }
+}
+
+/// A fingerprint of every edge of `network` as its plain search prices and
+/// draws it: its ends, its cost and its shape. Edges are summed rather than
+/// hashed in turn, so the order the graph keeps them in does not matter.
+fn fingerprint(network: &OsmNetwork) -> u64 {
+    network
+        .graph
+        .all_edges()
+        .map(|(source, target, &(weight, id))| {
+            let mut hasher = FxHasher::default();
+            (source, target, network.cost(source, target, weight, id)).hash(&mut hasher);
+            for point in network.shapes.get(&(source, target)).into_iter().flatten() {
+                (point.x().to_bits(), point.y().to_bits()).hash(&mut hasher);
+            }
+            hasher.finish()
+        })
+        .fold(0, u64::wrapping_add)
+}
+
+/// The working state of [`ContractionHierarchy::build`]: the arcs between
+/// the nodes yet to be contracted, and every arc added so far.
+struct Contraction {
+    outgoing: Vec<FxHashMap<u32, Weight>>,
+    incoming: Vec<FxHashMap<u32, Weight>>,
+    /// The cheapest arc between each pair of nodes, and the node it
+    /// shortcuts through, if any.
+    links: FxHashMap<(u32, u32), (Weight, Option<u32>)>,
+    /// The number of each node's neighbours already contracted, which
+    /// spreads contraction evenly across the network.
+    depth: Vec<i64>,
+}
+
+impl Contraction {
+    fn new(len: usize) -> Self {
+        Self {
+            outgoing: vec![FxHashMap::default(); len],
+            incoming: vec![FxHashMap::default(); len],
+            links: FxHashMap::default(),
+            depth: vec![0; len],
+        }
+    }
+
+    /// Add an arc from `tail` to `head`, unless there is already one as
+    /// cheap.
+    fn insert(&mut self, tail: u32, head: u32, cost: Weight, middle: Option<u32>) {
+        if self
+            .links
+            .get(&(tail, head))
+            .is_some_and(|&(known, _)| known <= cost)
+        {
+            return;
+        }
+
+        self.links.insert((tail, head), (cost, middle));
+        self.outgoing[tail as usize].insert(head, cost);
+        self.incoming[head as usize].insert(tail, cost);
+    }
+
+    /// Contract every node, returning the order they were contracted in.
+    fn run(&mut self) -> Vec<u32> {
+        let len = self.outgoing.len() as u32;
+        let mut queue: BinaryHeap<Reverse<(i64, u32)>> = (0..len)
+            .map(|node| Reverse((self.priority(node, &self.shortcuts(node)), node)))
+            .collect();
+
+        let mut order = Vec::with_capacity(len as usize);
+        while let Some(Reverse((_, node))) = queue.pop() {
+            // Priorities go stale as neighbours are contracted, so refresh
+            // this one before committing to it.
+            let shortcuts = self.shortcuts(node);
+            let priority = self.priority(node, &shortcuts);
+            if queue
+                .peek()
+                .is_some_and(|Reverse((next, _))| priority > *next)
+            {
+                queue.push(Reverse((priority, node)));
+                continue;
+            }
+
+            self.contract(node, shortcuts);
+            order.push(node);
+        }
+
+        order
+    }
+
+    /// Lower for nodes whose contraction adds fewer arcs than it removes.
+    fn priority(&self, node: u32, shortcuts: &[(u32, u32, Weight)]) -> i64 {
+        let degree = self.outgoing[node as usize].len() + self.incoming[node as usize].len();
+        shortcuts.len() as i64 - degree as i64 + self.depth[node as usize]
+    }
+
+    /// The shortcuts contracting `node` would add: one between each pair of
+    /// its neighbours with no route as cheap which avoids it.
+    fn shortcuts(&self, node: u32) -> Vec<(u32, u32, Weight)> {
+        let mut shortcuts = Vec::new();
+
+        for (&tail, &into) in &self.incoming[node as usize] {
+            let targets: Vec<(u32, Weight)> = self.outgoing[node as usize]
+                .iter()
+                .filter(|&(&head, _)| head != tail)
+                .map(|(&head, &outof)| (head, into.saturating_add(outof)))
+                .collect();
+
+            let Some(limit) = targets.iter().map(|&(_, cost)| cost).max() else {
+                continue;
+            };
+
+            let witnesses = self.witness(tail, node, limit);
+            for (head, cost) in targets {
+                if witnesses.get(&head).is_none_or(|&known| known > cost) {
+                    shortcuts.push((tail, head, cost));
+                }
+            }
+        }
+
+        shortcuts
+    }
+
+    /// The costs of routes from `source` which avoid `avoid`, searched up to
+    /// `limit` or until [`WITNESS_LIMIT`] nodes are settled.
+    fn witness(&self, source: u32, avoid: u32, limit: Weight) -> FxHashMap<u32, Weight> {
+        let mut costs = FxHashMap::from_iter([(source, 0)]);
+        let mut queue = BinaryHeap::from([Reverse((0, source))]);
+        let mut settled = 0;
+
+        while let Some(Reverse((cost, node))) = queue.pop() {
+            if cost > limit || settled == WITNESS_LIMIT {
+                break;
+            }
+            if costs[&node] < cost {
+                continue;
+            }
+            settled += 1;
+
+            for (&head, &step) in &self.outgoing[node as usize] {
+                let next = cost.saturating_add(step);
+                if head == avoid || costs.get(&head).is_some_and(|&known| known <= next) {
+                    continue;
+                }
+
+                costs.insert(head, next);
+                queue.push(Reverse((next, head)));
+            }
+        }
+
+        costs
+    }
+
+    /// Remove `node` from the remaining graph, bridging its neighbours with
+    /// `shortcuts`.
+    fn contract(&mut self, node: u32, shortcuts: Vec<(u32, u32, Weight)>) {
+        let outgoing = core::mem::take(&mut self.outgoing[node as usize]);
+        let incoming = core::mem::take(&mut self.incoming[node as usize]);
+
+        for tail in incoming.keys() {
+            self.outgoing[*tail as usize].remove(&node);
+            self.depth[*tail as usize] += 1;
+        }
+        for head in outgoing.keys() {
+            self.incoming[*head as usize].remove(&node);
+            self.depth[*head as usize] += 1;
+        }
+
+        for (tail, head, cost) in shortcuts {
+            self.insert(tail, head, cost, Some(node));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::osm::graph::GraphStructure;
+    use crate::osm::turn_restriction::{TurnKind, TurnRestriction};
+    use crate::osm::{OsmTripConfiguration, TurnRestrictions};
+    use geo::Point;
+    use routers_network::{DirectionAwareEdgeId, Node, Route};
+
+    const SIDE: i64 = 6;
+
+    /// A grid of `SIDE` by `SIDE` nodes, every edge two-way and on its own
+    /// way. Nodes are nudged off the lattice so that no two routes between
+    /// the same nodes cost the same.
+    fn grid(restrictions: TurnRestrictions) -> OsmNetwork {
+        let id = |x: i64, y: i64| OsmEntryId::node(y * SIDE + x + 1);
+        let way = |a: i64, b: i64| (1, DirectionAwareEdgeId::new(OsmEntryId::way(a * 100 + b)));
+
+        let mut graph = GraphStructure::new();
+        let mut hash = FxHashMap::default();
+        for y in 0..SIDE {
+            for x in 0..SIDE {
+                let nudge =
+                    |a: i64, b: i64| ((x * x * a + y * y * b + x * y) % 97) as f64 * 0.000005;
+                let position = Point::new(
+                    x as f64 * 0.001 + nudge(31, 17),
+                    y as f64 * 0.001 + nudge(13, 29),
+                );
+                hash.insert(id(x, y), Node::new(position, id(x, y)));
+
+                let neighbours = [(x + 1, y), (x, y + 1)];
+                for (nx, ny) in neighbours
+                    .into_iter()
+                    .filter(|&(nx, ny)| nx < SIDE && ny < SIDE)
+                {
+                    let (a, b) = (id(x, y), id(nx, ny));
+                    graph.add_edge(a, b, way(a.identifier, b.identifier));
+                    graph.add_edge(b, a, way(a.identifier, b.identifier));
+                }
+            }
+        }
+
+        let mut network = OsmNetwork {
+            graph,
+            hash,
+            restrictions,
+            ..OsmNetwork::default()
+        };
+        network.rebuild_indices();
+        network
+    }
+
+    #[test]
+    fn routes_match_the_plain_search() {
+        let trip = OsmTripConfiguration::default();
+        let network = grid(TurnRestrictions::default());
+        let hierarchy = ContractionHierarchy::build(&network);
+        assert!(hierarchy.shortcuts() > 0);
+
+        let contracted = grid(TurnRestrictions::default())
+            .with_hierarchy(hierarchy)
+            .expect("the hierarchy fits");
+
+        for start in network.graph.nodes() {
+            for finish in network.graph.nodes() {
+                let plain = network.route_nodes(start, finish, &trip);
+                let fast = contracted.route_nodes(start, finish, &trip);
+
+                assert_eq!(
+                    plain.map(|(cost, nodes)| (
+                        cost,
+                        nodes.iter().map(|n| n.id).collect::<Vec<_>>()
+                    )),
+                    fast.map(|(cost, nodes)| (
+                        cost,
+                        nodes.iter().map(|n| n.id).collect::<Vec<_>>()
+                    )),
+                    "{start:?} -> {finish:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn restricted_routes_fall_back_to_the_plain_search() {
+        let trip = OsmTripConfiguration::default();
+        // Forbid the first turn of the cheapest route from one corner to the
+        // other, whichever it is.
+        let network = grid(TurnRestrictions::default());
+        let (start, finish) = (OsmEntryId::node(1), OsmEntryId::node(SIDE * SIDE));
+        let (_, nodes) = network
+            .route_nodes(start, finish, &trip)
+            .expect("a route exists");
+
+        let way = |a: &Node<OsmEntryId>, b: &Node<OsmEntryId>| {
+            network
+                .graph
+                .edge_weight(a.id, b.id)
+                .expect("an edge")
+                .1
+                .index()
+        };
+
+        let mut restrictions = TurnRestrictions::default();
+        restrictions.insert(TurnRestriction {
+            kind: TurnKind::Prohibitory,
+            from: way(&nodes[0], &nodes[1]),
+            through: Vec::new(),
+            via: nodes[1].id,
+            to: way(&nodes[1], &nodes[2]),
+            mode: None,
+            except: Vec::new(),
+            condition: None,
+        });
+
+        let restricted = grid(restrictions.clone());
+        let plain = restricted
+            .route_nodes(start, finish, &trip)
+            .expect("a detour exists");
+
+        let contracted = grid(restrictions)
+            .with_hierarchy(ContractionHierarchy::build(&network))
+            .expect("the hierarchy fits");
+        let fast = contracted
+            .route_nodes(start, finish, &trip)
+            .expect("a detour exists");
+
+        assert_ne!(plain.1, nodes);
+        assert_eq!(plain, fast);
+    }
+
+    #[test]
+    fn round_trips_through_bytes() {
+        let network = grid(TurnRestrictions::default());
+        let hierarchy = ContractionHierarchy::build(&network);
+
+        let bytes = hierarchy.to_bytes().expect("serialises");
+        let decoded = ContractionHierarchy::from_bytes(&bytes).expect("deserialises");
+
+        let (start, finish) = (OsmEntryId::node(1), OsmEntryId::node(SIDE * SIDE));
+        assert_eq!(decoded.route(start, finish), hierarchy.route(start, finish));
+        assert!(decoded.fits(&network));
+    }
+
+    #[test]
+    fn rejects_a_hierarchy_of_another_network() {
+        let hierarchy = ContractionHierarchy::build(&grid(TurnRestrictions::default()));
+
+        let mut other = grid(TurnRestrictions::default());
+        other.graph.remove_node(OsmEntryId::node(1));
+
+        assert!(!hierarchy.fits(&other));
+        assert!(other.with_hierarchy(hierarchy).is_err());
+    }
+
+    #[test]
+    fn rejects_a_hierarchy_of_a_moved_network() {
+        let hierarchy = ContractionHierarchy::build(&grid(TurnRestrictions::default()));
+
+        // The same nodes and edges, but one node moved so its edges cost more.
+        let mut moved = grid(TurnRestrictions::default());
+        moved
+            .hash
+            .get_mut(&OsmEntryId::node(8))
+            .expect("a node")
+            .position = Point::new(0.01, 0.01);
+
+        assert!(!hierarchy.fits(&moved));
+        assert!(hierarchy.fits(&grid(TurnRestrictions::default())));
+    }
+
+    #[test]
+    fn refuses_to_unpack_a_shortcut_missing_an_arc() {
+        let network = grid(TurnRestrictions::default());
+        let mut hierarchy = ContractionHierarchy::build(&network);
+
+        let (tail, link) = (0..hierarchy.nodes.len() as u32)
+            .flat_map(|tail| hierarchy.up.of(tail).iter().map(move |&link| (tail, link)))
+            .find(|(_, link)| link.middle.is_some())
+            .expect("a shortcut");
+        hierarchy.down.links.clear();
+        hierarchy.down.offsets.fill(0);
+
+        assert!(hierarchy.unpack(tail, link, &mut Vec::new()).is_none());
+    }
+}
@@ -5,8 +5,13 @@ pub mod blob;
 pub mod block;
 pub mod element;
 
+pub mod change;
 pub mod graph;
+pub mod hierarchy;
 pub mod parsers;
+pub mod simplify;
+pub mod weighting;
+pub mod xml;
 
 // Hidden modules
 #[doc(hidden)]
@@ -25,6 +30,8 @@ pub use element::OsmEntryId;
 pub use element::iterator::ElementIterator;
 #[doc(inline)]
 pub use element::processed_iterator::ProcessedElementIterator;
+#[doc(inline)]
+pub use element::xml_iterator::XmlElementIterator;
 
 // Doc-Linking
 #[doc(inline)]
@@ -37,7 +44,10 @@ pub use model::*;
 #[doc(inline)]
 pub use parsers::*;
 
+pub use change::{ChangeError, ChangeReport, OsmChange};
 pub use graph::OsmNetwork;
+pub use hierarchy::ContractionHierarchy;
+pub use weighting::Weighting;
 
 #[doc(hidden)]
 pub use blob::item::BlobItem;
@@ -64,20 +74,61 @@ pub mod meta {
     use crate::osm::element::Tags;
     use crate::osm::primitives::condition::VehicleProperty;
     use crate::osm::primitives::*;
-    use crate::osm::speed_limit::SpeedLimitCollection;
-    use crate::osm::{Access, OsmTripConfiguration, SpeedLimit};
+    use crate::osm::speed_limit::{SpeedLimitCollection, SpeedLimitConditions, SpeedLimitExt};
+    use crate::osm::{Access, OsmTripConfiguration, SpeedLimit, VehicleLimit, VehicleLimits};
 
     use crate::primitive;
 
     use core::num::NonZeroU8;
     use routers_network::{Direction, Metadata};
 
-    #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+    /// The speed, in km/h, taken for a `maxspeed=walk` limit.
+    const WALKING_SPEED: u16 = 5;
+
+    #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
     pub struct OsmEdgeMetadata {
         pub lane_count: Option<NonZeroU8>,
         pub speed_limit: Option<SpeedLimitCollection>,
         pub access: Vec<AccessTag>,
         pub road_class: Option<RoadClass>,
+        /// The unconditional speed limit of the way in km/h, resolved from
+        /// `speed_limit` once at ingest so routing need not re-parse it.
+        pub max_speed: Option<Speed>,
+        /// The height, width, length and weight limits of the way, which
+        /// exclude it for any vehicle the trip describes as exceeding them.
+        pub vehicle_limits: VehicleLimits,
+    }
+
+    impl OsmEdgeMetadata {
+        /// The speed assumed on a way with neither a usable `maxspeed` nor a
+        /// road class.
+        pub const FALLBACK_SPEED: Speed = RoadClass::Road.default_speed();
+
+        /// The speed, in km/h, a vehicle is assumed to travel along the way:
+        /// its tagged limit where there is one, or else the default of its
+        /// road class.
+        pub fn speed(&self) -> Speed {
+            self.max_speed
+                .or_else(|| self.road_class.map(|class| class.default_speed()))
+                .unwrap_or(Self::FALLBACK_SPEED)
+        }
+
+        /// The lowest unconditional limit which applies to every vehicle of
+        /// the trip's transport mode, in either direction.
+        fn unconditional_speed(
+            limits: &SpeedLimitCollection,
+            runtime: &OsmTripConfiguration,
+        ) -> Option<Speed> {
+            limits
+                .relevant_limits(runtime, SpeedLimitConditions::default())
+                .into_iter()
+                .filter(|limit| limit.condition.is_none())
+                .filter_map(|limit| match limit.speed {
+                    SpeedValue::Walk => Speed::new(WALKING_SPEED),
+                    speed => speed.in_kmh(),
+                })
+                .min()
+        }
     }
 
     impl Metadata for OsmEdgeMetadata {
@@ -86,11 +137,17 @@ pub mod meta {
         type TripContext = primitive::context::TripContext;
 
         fn pick(raw: Self::Raw<'_>) -> Self {
+            let speed_limit = raw.speed_limit();
+
             Self {
                 road_class: raw.r#as::<RoadClass>(Tags::HIGHWAY),
                 lane_count: raw.r#as::<NonZeroU8>(Tags::LANES),
-                speed_limit: raw.speed_limit(),
+                max_speed: speed_limit.as_ref().and_then(|limits| {
+                    Self::unconditional_speed(limits, &OsmTripConfiguration::default())
+                }),
+                speed_limit,
                 access: raw.access(),
+                vehicle_limits: raw.vehicle_limits(),
             }
         }
 
@@ -106,7 +163,7 @@ pub mod meta {
                         default.transport_mode = TransportMode::MotorVehicle;
                         default.vehicle_properties = Some(vec![
                             (VehicleProperty::Height, car.height),
-                            (VehicleProperty::Weight, car.width),
+                            (VehicleProperty::Width, car.width),
                         ]);
                     }
                     Car(None) => {
@@ -116,7 +173,7 @@ pub mod meta {
                         default.transport_mode = TransportMode::Bus;
                         default.vehicle_properties = Some(vec![
                             (VehicleProperty::Height, bus.height),
-                            (VehicleProperty::Weight, bus.width),
+                            (VehicleProperty::Width, bus.width),
                         ]);
                     }
                     Bus(None) => {
@@ -126,7 +183,8 @@ pub mod meta {
                         default.transport_mode = TransportMode::Hgv;
                         default.vehicle_properties = Some(vec![
                             (VehicleProperty::Height, truck.vehicle_costing.height),
-                            (VehicleProperty::Weight, truck.vehicle_costing.width),
+                            (VehicleProperty::Width, truck.vehicle_costing.width),
+                            (VehicleProperty::Weight, truck.weight),
                             (VehicleProperty::Axleload, truck.axle_load),
                             (VehicleProperty::Length, truck.length),
                         ]);
@@ -198,6 +256,12 @@ pub mod meta {
 
         #[inline]
         fn accessible(&self, conditions: &Self::Runtime, direction: Direction) -> bool {
+            // A vehicle too large or heavy for the way may not use it,
+            // whatever its access tags permit.
+            if !self.vehicle_limits.admits(conditions) {
+                return false;
+            }
+
             // Computes the negative-filter access restriction, assuming accessible by default.
             // If any access conditions match the input, it will be rejected.
             self.access
@@ -233,6 +297,15 @@ pub mod meta {
                 })
                 .unwrap_or(true)
         }
+
+        #[inline]
+        fn permitted_speed(&self, runtime: &Self::Runtime) -> Option<f64> {
+            let limits = self.speed_limit.as_ref()?;
+            let speed = Self::unconditional_speed(limits, runtime)?;
+
+            // Tagged in km/h; reported in m/s.
+            Some(f64::from(speed.get()) / 3.6)
+        }
     }
 }
 
@@ -1,9 +1,13 @@
 pub mod access_tag;
 pub mod primitives;
 pub mod speed_limit;
+pub mod turn_restriction;
+pub mod vehicle_limit;
 
 pub use access_tag::Access;
 pub use speed_limit::SpeedLimit;
+pub use turn_restriction::TurnRestrictions;
+pub use vehicle_limit::{VehicleLimit, VehicleLimits};
 
 pub trait Parser: Sized {
     fn parse(tags: &crate::osm::Tags<'_>) -> Option<Self>;
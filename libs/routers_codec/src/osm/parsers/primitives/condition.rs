@@ -1,3 +1,4 @@
+use crate::osm::OsmTripConfiguration;
 use crate::osm::primitives::opening_hours::{OpeningHours, OpeningHoursParser};
 use alloc::fmt;
 use core::fmt::{Display, Formatter};
@@ -595,6 +596,53 @@ impl Condition {
     }
 }
 
+impl Condition {
+    /// Whether the condition holds for the given trip.
+    ///
+    /// Time and date conditions are read against the trip's `time_of_week`,
+    /// and vehicle properties against its `vehicle_properties`. A condition the
+    /// trip cannot speak to, such as the season or an unset time, does not
+    /// hold.
+    pub fn holds(&self, trip: &OsmTripConfiguration) -> bool {
+        self.condition_type.holds(trip)
+    }
+}
+
+impl ConditionType {
+    fn holds(&self, trip: &OsmTripConfiguration) -> bool {
+        match self {
+            ConditionType::TimeDate(td) => trip
+                .time_of_week
+                .as_ref()
+                .is_some_and(|time| td.opening_hours.is_open_at(time)),
+            ConditionType::VehicleProperty(vp) => trip
+                .vehicle_properties
+                .iter()
+                .flatten()
+                .find(|(property, _)| *property == vp.property)
+                .is_some_and(|(_, value)| vp.operator.compare(*value as f64, vp.value)),
+            ConditionType::Combined(combined) => match combined.operator {
+                LogicalOperator::And => combined.left.holds(trip) && combined.right.holds(trip),
+                LogicalOperator::Or => combined.left.holds(trip) || combined.right.holds(trip),
+            },
+            _ => false,
+        }
+    }
+}
+
+impl ComparisonOperator {
+    /// Whether `lhs` relates to `rhs` as the operator describes.
+    pub fn compare(&self, lhs: f64, rhs: f64) -> bool {
+        match self {
+            ComparisonOperator::LessThan => lhs < rhs,
+            ComparisonOperator::GreaterThan => lhs > rhs,
+            ComparisonOperator::Equal => lhs == rhs,
+            ComparisonOperator::LessThanOrEqual => lhs <= rhs,
+            ComparisonOperator::GreaterThanOrEqual => lhs >= rhs,
+        }
+    }
+}
+
 /// Errors that can occur during parsing
 #[derive(Debug, PartialEq)]
 pub enum ParseError {
@@ -708,4 +756,20 @@ mod tests {
         let regenerated = condition.to_string();
         assert_eq!(regenerated, "weight<7.5"); // Note: spaces might be normalized
     }
+
+    #[test]
+    fn test_time_date_holds_at_time_of_week() {
+        use crate::osm::primitives::opening_hours::TimeOfWeek;
+
+        let condition = Condition::parse("Mo-Fr 07:00-09:00").unwrap();
+        let at = |weekday, hour| OsmTripConfiguration {
+            time_of_week: Some(TimeOfWeek::new(weekday, Time::new(hour, 0).unwrap())),
+            ..OsmTripConfiguration::default()
+        };
+
+        assert!(condition.holds(&at(Weekday::Tuesday, 8)));
+        assert!(!condition.holds(&at(Weekday::Tuesday, 12)));
+        assert!(!condition.holds(&at(Weekday::Sunday, 8)));
+        assert!(!condition.holds(&OsmTripConfiguration::default()));
+    }
 }
@@ -36,6 +36,11 @@ impl Weekday {
             _ => None,
         }
     }
+
+    /// The day's position in the week, counting from Monday.
+    fn ordinal(&self) -> u8 {
+        self.clone() as u8
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -45,7 +50,7 @@ pub struct Time {
 }
 
 impl Time {
-    fn new(hour: u8, minute: u8) -> Result<Self, String> {
+    pub fn new(hour: u8, minute: u8) -> Result<Self, String> {
         if hour > 24 || minute > 59 {
             Err("Invalid time".to_string())
         } else {
@@ -78,6 +83,12 @@ pub struct TimeOfWeek {
     weekday: Weekday,
 }
 
+impl TimeOfWeek {
+    pub fn new(weekday: Weekday, time: Time) -> Self {
+        TimeOfWeek { time, weekday }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum WeekdayRange {
     Single(Weekday),
@@ -320,13 +331,24 @@ impl OpeningHours {
                 None => true, // No weekday restriction means all days
                 Some(WeekdayRange::Single(day)) => day == weekday,
                 Some(WeekdayRange::List(days)) => days.contains(weekday),
-                Some(WeekdayRange::Range(_start, _end)) => {
-                    // This is simplified - proper range checking would need day ordering
-                    true // For now, assume it matches
+                Some(WeekdayRange::Range(start, end)) => {
+                    let (start, end, day) = (start.ordinal(), end.ordinal(), weekday.ordinal());
+
+                    if start <= end {
+                        (start..=end).contains(&day)
+                    } else {
+                        // Ranges such as `Sa-Mo` wrap over the end of the week
+                        day >= start || day <= end
+                    }
                 }
             };
 
             if applies_to_weekday {
+                // A rule naming only days, such as `Sa-Su`, holds all day
+                if rule.times.is_empty() {
+                    return true;
+                }
+
                 for time_range in &rule.times {
                     if self.time_in_range(time, &time_range.start, &time_range.end) {
                         return true;
@@ -423,4 +445,16 @@ mod tests {
             time: monday_early
         }));
     }
+
+    #[test]
+    fn test_is_open_at_respects_weekday_ranges() {
+        let noon = Time::new(12, 0).unwrap();
+        let weekdays = OpeningHoursParser::parse("Mo-Fr 09:00-17:00").unwrap();
+        assert!(!weekdays.is_open_at(&TimeOfWeek::new(Weekday::Saturday, noon)));
+
+        let weekend = OpeningHoursParser::parse("Sa-Mo").unwrap();
+        assert!(weekend.is_open_at(&TimeOfWeek::new(Weekday::Sunday, noon)));
+        assert!(weekend.is_open_at(&TimeOfWeek::new(Weekday::Monday, noon)));
+        assert!(!weekend.is_open_at(&TimeOfWeek::new(Weekday::Wednesday, noon)));
+    }
 }
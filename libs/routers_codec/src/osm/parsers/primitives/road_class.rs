@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use strum::{AsRefStr, Display, EnumString};
 
+use crate::osm::primitives::Speed;
+
 #[derive(
     Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Display, EnumString, AsRefStr,
 )]
@@ -144,4 +146,45 @@ impl RoadClass {
             RoadClass::Pedestrian => 100,
         }
     }
+
+    /// The speed, in kilometers per hour, assumed on a road of this class
+    /// when it carries no usable `maxspeed` tag.
+    ///
+    /// These are deliberately conservative free-flow speeds rather than
+    /// legal limits, since an untagged road is most often a minor one.
+    #[inline]
+    pub const fn default_speed(&self) -> Speed {
+        let kmh = match self {
+            RoadClass::Motorway => 100,
+            RoadClass::MotorwayLink => 60,
+            RoadClass::Trunk => 80,
+            RoadClass::TrunkLink => 50,
+            RoadClass::Primary => 65,
+            RoadClass::PrimaryLink => 45,
+            RoadClass::Secondary => 55,
+            RoadClass::SecondaryLink => 40,
+            RoadClass::Tertiary => 45,
+            RoadClass::TertiaryLink => 35,
+
+            // Residential / Assoc.
+            RoadClass::Residential => 30,
+            RoadClass::Busway => 40,
+            RoadClass::BusGuideway => 40,
+            RoadClass::Unclassified => 40,
+
+            // Misc / Service.
+            RoadClass::LivingStreet => 10,
+            RoadClass::Service => 20,
+            RoadClass::Road => 30,
+            RoadClass::Raceway => 30,
+            RoadClass::Escape => 10,
+            RoadClass::Track => 15,
+            RoadClass::Pedestrian => 5,
+        };
+
+        match Speed::new(kmh) {
+            Some(speed) => speed,
+            None => unreachable!(),
+        }
+    }
 }
@@ -5,7 +5,7 @@ use serde::{Deserialize, Serialize};
 
 pub type Speed = NonZeroU16;
 
-#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum SpeedValue {
     /// Speed in kilometers per hour
     Kmh(Speed),
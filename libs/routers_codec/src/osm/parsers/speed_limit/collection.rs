@@ -8,7 +8,7 @@ use core::ops::Deref;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SpeedLimitCollection(pub(crate) Vec<SpeedLimitEntry>);
 
 impl Deref for SpeedLimitCollection {
@@ -19,7 +19,7 @@ static VALUE_RE: LazyLock<regex::Regex> =
 /// This represents the individual `number <unit> @ (...)` value.
 /// This may be spread across a lane representation in the SpeedLimit
 /// structure.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct PossiblyConditionalSpeedLimit {
     /// Represents the speed limit on a singular lane.
     ///
@@ -88,7 +88,7 @@ impl PossiblyConditionalSpeedLimit {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct PerLaneSpeedLimit(pub Vec<Option<PossiblyConditionalSpeedLimit>>);
 
 impl PerLaneSpeedLimit {
@@ -100,7 +100,7 @@ impl PerLaneSpeedLimit {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum SpeedLimitVariant {
     /// Applies to every lane within the way, and is
     /// therefore non-dependent on lanes.
@@ -113,7 +113,7 @@ pub enum SpeedLimitVariant {
     PerLane(PerLaneSpeedLimit),
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct SpeedLimitEntry {
     pub restriction: RestrictionOptionals,
     pub limit: SpeedLimitVariant,
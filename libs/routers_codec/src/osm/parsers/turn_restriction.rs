@@ -0,0 +1,606 @@
+//! Turn restrictions, from `type=restriction` relations.
+//!
+//! A restriction names the way a manoeuvre is approached `from`, the node (or
+//! ways) it passes `via`, and the way it leaves `to`. Prohibitory restrictions
+//! (`no_left_turn`, `no_u_turn`, ...) forbid exactly that manoeuvre, while
+//! mandatory ones (`only_straight_on`, ...) forbid every other departure.
+//!
+//! See: https://wiki.openstreetmap.org/wiki/Relation:restriction
+
+use core::str::FromStr;
+
+use routers_network::Turn;
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::osm::element::variants::{OsmEntryId, Relation};
+use crate::osm::primitives::{Condition, TransportMode};
+use crate::osm::relation::MemberType;
+use crate::osm::{OsmTripConfiguration, Tags};
+
+/// Whether a restriction forbids its manoeuvre, or forbids everything else.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TurnKind {
+    /// `no_*`: the manoeuvre is forbidden.
+    Prohibitory,
+    /// `only_*`: the manoeuvre is the only one permitted.
+    Mandatory,
+}
+
+impl FromStr for TurnKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.starts_with("no_") {
+            Ok(TurnKind::Prohibitory)
+        } else if s.starts_with("only_") {
+            Ok(TurnKind::Mandatory)
+        } else {
+            Err(())
+        }
+    }
+}
+
+/// What a restriction passes between its `from` and `to` ways.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Via {
+    /// The node the turn is taken at.
+    Node(OsmEntryId),
+    /// The ways travelled between `from` and `to`, in order.
+    Ways(Vec<OsmEntryId>),
+}
+
+/// A turn restriction as its relation describes it, before a via-way
+/// restriction's turning node is known.
+///
+/// Relations are read alongside the ways they reference, so a via-way
+/// restriction is only [resolved](Self::resolve) once every way's endpoints
+/// have been read.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RestrictionRelation {
+    pub kind: TurnKind,
+    pub from: Vec<OsmEntryId>,
+    pub via: Via,
+    pub to: Vec<OsmEntryId>,
+    pub mode: Option<TransportMode>,
+    pub except: Vec<TransportMode>,
+    pub condition: Option<Condition>,
+}
+
+impl RestrictionRelation {
+    const TYPE: &'static str = "type";
+    const RESTRICTION: &'static str = "restriction";
+    const CONDITIONAL: &'static str = "conditional";
+    const EXCEPT: &'static str = "except";
+
+    /// The restrictions a relation describes: one for each of its
+    /// `restriction`, `restriction:<mode>` and `restriction:conditional` tags.
+    ///
+    /// Empty when the relation is not a turn restriction, or its members do
+    /// not describe one.
+    pub fn parse(relation: &Relation) -> Vec<Self> {
+        if relation.tags.get(Self::TYPE) != Some(&Self::RESTRICTION) {
+            return Vec::new();
+        }
+
+        let Some((from, via, to)) = Self::members(relation) else {
+            return Vec::new();
+        };
+
+        let except: Vec<TransportMode> = relation
+            .tags
+            .get(Self::EXCEPT)
+            .map(|modes| {
+                modes
+                    .split(';')
+                    .filter_map(|mode| TransportMode::from_str(mode.trim()).ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self::kinds(&relation.tags)
+            .into_iter()
+            .map(|(kind, mode, condition)| RestrictionRelation {
+                kind,
+                from: from.clone(),
+                via: via.clone(),
+                to: to.clone(),
+                mode,
+                except: except.clone(),
+                condition,
+            })
+            .collect()
+    }
+
+    /// The kind, transport mode and condition of each restriction tag.
+    fn kinds(tags: &Tags) -> Vec<(TurnKind, Option<TransportMode>, Option<Condition>)> {
+        tags.iter()
+            .filter_map(|(&key, &value)| {
+                let qualifier = match key.split_once(':') {
+                    None if key == Self::RESTRICTION => None,
+                    Some((Self::RESTRICTION, qualifier)) => Some(qualifier),
+                    _ => return None,
+                };
+
+                match qualifier {
+                    None => Some((TurnKind::from_str(value).ok()?, None, None)),
+                    Some(Self::CONDITIONAL) => {
+                        // e.g. `no_left_turn @ (Mo-Fr 07:00-09:00)`
+                        let (value, condition) = value.split_once('@')?;
+                        let condition = Condition::parse(condition).ok()?;
+                        Some((
+                            TurnKind::from_str(value.trim()).ok()?,
+                            None,
+                            Some(condition),
+                        ))
+                    }
+                    Some(mode) => Some((
+                        TurnKind::from_str(value).ok()?,
+                        Some(TransportMode::from_str(mode).ok()?),
+                        None,
+                    )),
+                }
+            })
+            .collect()
+    }
+
+    fn members(relation: &Relation) -> Option<(Vec<OsmEntryId>, Via, Vec<OsmEntryId>)> {
+        let mut from = Vec::new();
+        let mut to = Vec::new();
+        let mut via_node = None;
+        let mut via_ways = Vec::new();
+
+        for (reference, member_type) in relation.members() {
+            let identifier = reference.id.identifier;
+            match (
+                reference.role.as_ref().map(|role| role.0.as_str()),
+                member_type,
+            ) {
+                (Some("from"), MemberType::WAY) => from.push(OsmEntryId::way(identifier)),
+                (Some("to"), MemberType::WAY) => to.push(OsmEntryId::way(identifier)),
+                (Some("via"), MemberType::NODE) => via_node = Some(OsmEntryId::node(identifier)),
+                (Some("via"), MemberType::WAY) => via_ways.push(OsmEntryId::way(identifier)),
+                _ => {}
+            }
+        }
+
+        let via = match (via_node, via_ways.is_empty()) {
+            (Some(node), true) => Via::Node(node),
+            (None, false) => Via::Ways(via_ways),
+            _ => return None,
+        };
+
+        if from.is_empty() || to.is_empty() {
+            return None;
+        }
+
+        Some((from, via, to))
+    }
+
+    /// Resolve into one [`TurnRestriction`] per `from` and `to` way pair.
+    ///
+    /// `endpoints` gives the first and last node of a way. A via-way
+    /// restriction turns at the end of its last via way which meets `to`; one
+    /// whose ways cannot be found or do not meet is dropped.
+    pub fn resolve(
+        self,
+        endpoints: impl Fn(&OsmEntryId) -> Option<(OsmEntryId, OsmEntryId)>,
+    ) -> Vec<TurnRestriction> {
+        let mut restrictions = Vec::new();
+
+        for &from in &self.from {
+            for &to in &self.to {
+                let (via, through) = match &self.via {
+                    Via::Node(node) => (*node, Vec::new()),
+                    Via::Ways(ways) => {
+                        let Some(node) = ways.last().and_then(|last| {
+                            let (first, end) = endpoints(last)?;
+                            let (to_first, to_end) = endpoints(&to)?;
+                            [end, first]
+                                .into_iter()
+                                .find(|node| *node == to_first || *node == to_end)
+                        }) else {
+                            continue;
+                        };
+                        (node, ways.clone())
+                    }
+                };
+
+                restrictions.push(TurnRestriction {
+                    kind: self.kind,
+                    from,
+                    through,
+                    via,
+                    to,
+                    mode: self.mode,
+                    except: self.except.clone(),
+                    condition: self.condition.clone(),
+                });
+            }
+        }
+
+        restrictions
+    }
+}
+
+/// A single turn restriction between two ways, resolved to the node the turn
+/// is taken at.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TurnRestriction {
+    pub kind: TurnKind,
+    /// The way the manoeuvre is approached along.
+    pub from: OsmEntryId,
+    /// The ways travelled between `from` and the turn, in order. Empty unless
+    /// the restriction is via-way.
+    pub through: Vec<OsmEntryId>,
+    /// The node the turn is taken at.
+    pub via: OsmEntryId,
+    /// The way the manoeuvre departs along.
+    pub to: OsmEntryId,
+    /// The transport mode the restriction is limited to, if any.
+    pub mode: Option<TransportMode>,
+    /// The transport modes exempt from the restriction.
+    pub except: Vec<TransportMode>,
+    /// The condition under which the restriction applies, if it is
+    /// conditional.
+    pub condition: Option<Condition>,
+}
+
+impl TurnRestriction {
+    /// Every way the restriction names, as its `from`, `to` or one of its
+    /// `through` ways.
+    pub fn ways(&self) -> impl Iterator<Item = OsmEntryId> + '_ {
+        [self.from, self.to]
+            .into_iter()
+            .chain(self.through.iter().copied())
+    }
+
+    /// Whether the restriction applies to the given trip at all.
+    pub fn applies(&self, trip: &OsmTripConfiguration) -> bool {
+        let mode = &trip.transport_mode;
+
+        self.mode
+            .is_none_or(|restricted| mode.is_restricted_by(restricted))
+            && !self
+                .except
+                .iter()
+                .any(|exempt| mode.is_restricted_by(*exempt))
+            && self
+                .condition
+                .as_ref()
+                .is_none_or(|condition| condition.holds(trip))
+    }
+
+    /// The way the turn itself is approached along: the last via way, or
+    /// `from` when the restriction is via-node.
+    fn approach(&self) -> OsmEntryId {
+        self.through.last().copied().unwrap_or(self.from)
+    }
+
+    /// Whether `history` — the ways travelled before the turn, most recent
+    /// first and without repeats — leads up through `through` from `from`.
+    fn preceded_by(&self, history: &[OsmEntryId]) -> bool {
+        let expected = self.through.iter().rev().skip(1).chain([&self.from]);
+        let mut history = history.iter();
+
+        expected.into_iter().all(|way| history.next() == Some(way))
+    }
+
+    /// Whether the restriction, once approached, forbids the turn.
+    fn forbids(&self, turn: &Turn<OsmEntryId>) -> bool {
+        // A restriction from a way onto itself (such as `no_u_turn`) speaks of
+        // doubling back, not of continuing along it.
+        let onto = turn.to == self.to && (self.from != self.to || turn.is_reversal());
+
+        match self.kind {
+            TurnKind::Prohibitory => onto,
+            TurnKind::Mandatory => !onto,
+        }
+    }
+}
+
+/// Every turn restriction of a network, keyed by the node its turn is taken
+/// at.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct TurnRestrictions(FxHashMap<OsmEntryId, Vec<TurnRestriction>>);
+
+impl TurnRestrictions {
+    pub fn insert(&mut self, restriction: TurnRestriction) {
+        self.0.entry(restriction.via).or_default().push(restriction);
+    }
+
+    /// The restrictions whose turn is taken at `via`.
+    pub fn at(&self, via: &OsmEntryId) -> &[TurnRestriction] {
+        self.0.get(via).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Remove every restriction naming a way `named` picks out, returning
+    /// them.
+    pub fn remove_naming(&mut self, named: impl Fn(&OsmEntryId) -> bool) -> Vec<TurnRestriction> {
+        let mut removed = Vec::new();
+        self.0.retain(|_, restrictions| {
+            let (naming, kept): (Vec<_>, Vec<_>) = core::mem::take(restrictions)
+                .into_iter()
+                .partition(|restriction| restriction.ways().any(|way| named(&way)));
+
+            removed.extend(naming);
+            *restrictions = kept;
+            !restrictions.is_empty()
+        });
+
+        removed
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Whether `turn` may be taken on `trip`. `approach` yields the ways
+    /// travelled before `turn.from`, most recent first, and is only read for
+    /// via-way restrictions.
+    pub fn permits(
+        &self,
+        turn: &Turn<OsmEntryId>,
+        approach: &mut dyn Iterator<Item = OsmEntryId>,
+        trip: &OsmTripConfiguration,
+    ) -> bool {
+        let restrictions = self.at(&turn.via);
+        let relevant = restrictions
+            .iter()
+            .filter(|restriction| restriction.approach() == turn.from)
+            .filter(|restriction| restriction.applies(trip));
+
+        // Only as much of the approach as the longest via-way restriction
+        // here could match is read, and only once.
+        let mut history: Option<Vec<OsmEntryId>> = None;
+        for restriction in relevant {
+            if !restriction.through.is_empty() {
+                let history = history.get_or_insert_with(|| {
+                    let depth = restrictions.iter().map(|r| r.through.len()).max();
+                    let mut ways: Vec<OsmEntryId> = Vec::new();
+                    while ways.len() < depth.unwrap_or_default() {
+                        let Some(way) = approach.next() else { break };
+                        if ways.last().copied().unwrap_or(turn.from) != way {
+                            ways.push(way);
+                        }
+                    }
+                    ways
+                });
+
+                if !restriction.preceded_by(history) {
+                    continue;
+                }
+            }
+
+            if restriction.forbids(turn) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::osm::element::variants::{Reference, Role};
+    use crate::osm::primitives::opening_hours::{Time, TimeOfWeek, Weekday};
+    use std::collections::HashMap;
+
+    fn relation<'a>(
+        tags: &[(&'a str, &'a str)],
+        members: &[(&str, MemberType, i64)],
+    ) -> Relation<'a> {
+        let refs = members
+            .iter()
+            .map(|&(role, _, id)| {
+                Reference::with_role(OsmEntryId::from(id), Role(role.to_string()))
+            })
+            .collect::<Vec<_>>();
+
+        Relation {
+            id: 1,
+            tags: Tags::new(HashMap::from_iter(tags.iter().copied())),
+            refs: refs.into(),
+            types: members.iter().map(|&(_, kind, _)| kind).collect(),
+        }
+    }
+
+    fn turn(from: (i64, i64), to: (i64, i64), via: i64) -> Turn<OsmEntryId> {
+        Turn {
+            origin: OsmEntryId::node(from.1),
+            from: OsmEntryId::way(from.0),
+            via: OsmEntryId::node(via),
+            to: OsmEntryId::way(to.0),
+            destination: OsmEntryId::node(to.1),
+        }
+    }
+
+    fn table(relation: &Relation) -> TurnRestrictions {
+        let mut table = TurnRestrictions::default();
+        RestrictionRelation::parse(relation)
+            .into_iter()
+            .flat_map(|parsed| parsed.resolve(|_| None))
+            .for_each(|restriction| table.insert(restriction));
+        table
+    }
+
+    fn permits(table: &TurnRestrictions, turn: &Turn<OsmEntryId>) -> bool {
+        table.permits(
+            turn,
+            &mut core::iter::empty(),
+            &OsmTripConfiguration::default(),
+        )
+    }
+
+    #[test]
+    fn prohibitory_restriction_forbids_its_turn() {
+        let table = table(&relation(
+            &[("type", "restriction"), ("restriction", "no_left_turn")],
+            &[
+                ("from", MemberType::WAY, 10),
+                ("via", MemberType::NODE, 2),
+                ("to", MemberType::WAY, 20),
+            ],
+        ));
+
+        assert_eq!(table.len(), 1);
+        assert!(!permits(&table, &turn((10, 1), (20, 3), 2)));
+        assert!(permits(&table, &turn((10, 1), (30, 4), 2)));
+        assert!(permits(&table, &turn((40, 5), (20, 3), 2)));
+    }
+
+    #[test]
+    fn mandatory_restriction_forbids_every_other_turn() {
+        let table = table(&relation(
+            &[("type", "restriction"), ("restriction", "only_straight_on")],
+            &[
+                ("from", MemberType::WAY, 10),
+                ("via", MemberType::NODE, 2),
+                ("to", MemberType::WAY, 20),
+            ],
+        ));
+
+        assert!(permits(&table, &turn((10, 1), (20, 3), 2)));
+        assert!(!permits(&table, &turn((10, 1), (30, 4), 2)));
+    }
+
+    #[test]
+    fn u_turn_restriction_only_forbids_doubling_back() {
+        let table = table(&relation(
+            &[("type", "restriction"), ("restriction", "no_u_turn")],
+            &[
+                ("from", MemberType::WAY, 10),
+                ("via", MemberType::NODE, 2),
+                ("to", MemberType::WAY, 10),
+            ],
+        ));
+
+        assert!(!permits(&table, &turn((10, 1), (10, 1), 2)));
+        assert!(permits(&table, &turn((10, 1), (10, 3), 2)));
+    }
+
+    #[test]
+    fn mode_specific_and_exempt_modes() {
+        let hgv = table(&relation(
+            &[
+                ("type", "restriction"),
+                ("restriction:hgv", "no_right_turn"),
+            ],
+            &[
+                ("from", MemberType::WAY, 10),
+                ("via", MemberType::NODE, 2),
+                ("to", MemberType::WAY, 20),
+            ],
+        ));
+        let except = table(&relation(
+            &[
+                ("type", "restriction"),
+                ("restriction", "no_right_turn"),
+                ("except", "bicycle;hgv"),
+            ],
+            &[
+                ("from", MemberType::WAY, 10),
+                ("via", MemberType::NODE, 2),
+                ("to", MemberType::WAY, 20),
+            ],
+        ));
+
+        let right = turn((10, 1), (20, 3), 2);
+        let truck = OsmTripConfiguration {
+            transport_mode: TransportMode::Hgv,
+            ..OsmTripConfiguration::default()
+        };
+
+        assert!(permits(&hgv, &right));
+        assert!(!hgv.permits(&right, &mut core::iter::empty(), &truck));
+
+        assert!(!permits(&except, &right));
+        assert!(except.permits(&right, &mut core::iter::empty(), &truck));
+    }
+
+    #[test]
+    fn conditional_restriction_follows_time_of_week() {
+        let table = table(&relation(
+            &[
+                ("type", "restriction"),
+                (
+                    "restriction:conditional",
+                    "no_left_turn @ (Mo-Fr 07:00-09:00)",
+                ),
+            ],
+            &[
+                ("from", MemberType::WAY, 10),
+                ("via", MemberType::NODE, 2),
+                ("to", MemberType::WAY, 20),
+            ],
+        ));
+
+        let left = turn((10, 1), (20, 3), 2);
+        let at = |weekday, hour| OsmTripConfiguration {
+            time_of_week: Some(TimeOfWeek::new(weekday, Time::new(hour, 0).unwrap())),
+            ..OsmTripConfiguration::default()
+        };
+
+        assert!(!table.permits(&left, &mut core::iter::empty(), &at(Weekday::Monday, 8)));
+        assert!(table.permits(&left, &mut core::iter::empty(), &at(Weekday::Monday, 18)));
+        assert!(table.permits(&left, &mut core::iter::empty(), &at(Weekday::Saturday, 8)));
+        // Without a time of week, a time-conditional restriction is not known to apply.
+        assert!(permits(&table, &left));
+    }
+
+    #[test]
+    fn via_way_restriction_needs_the_whole_approach() {
+        // From way 10, along via way 15 (nodes 2 → 3), onto way 20 at node 3.
+        let endpoints = |way: &OsmEntryId| match way.identifier {
+            15 => Some((OsmEntryId::node(2), OsmEntryId::node(3))),
+            20 => Some((OsmEntryId::node(3), OsmEntryId::node(4))),
+            _ => None,
+        };
+
+        let mut table = TurnRestrictions::default();
+        RestrictionRelation::parse(&relation(
+            &[("type", "restriction"), ("restriction", "no_left_turn")],
+            &[
+                ("from", MemberType::WAY, 10),
+                ("via", MemberType::WAY, 15),
+                ("to", MemberType::WAY, 20),
+            ],
+        ))
+        .into_iter()
+        .flat_map(|parsed| parsed.resolve(endpoints))
+        .for_each(|restriction| table.insert(restriction));
+
+        let left = turn((15, 2), (20, 4), 3);
+        let trip = OsmTripConfiguration::default();
+        let from_restricted = [OsmEntryId::way(15), OsmEntryId::way(10)];
+        let from_elsewhere = [OsmEntryId::way(30)];
+
+        assert!(!table.permits(&left, &mut from_restricted.into_iter(), &trip));
+        assert!(table.permits(&left, &mut from_elsewhere.into_iter(), &trip));
+    }
+
+    #[test]
+    fn non_restriction_relations_are_ignored() {
+        let route = relation(
+            &[("type", "route"), ("restriction", "no_left_turn")],
+            &[
+                ("from", MemberType::WAY, 10),
+                ("via", MemberType::NODE, 2),
+                ("to", MemberType::WAY, 20),
+            ],
+        );
+        let incomplete = relation(
+            &[("type", "restriction"), ("restriction", "no_left_turn")],
+            &[("from", MemberType::WAY, 10), ("to", MemberType::WAY, 20)],
+        );
+
+        assert!(RestrictionRelation::parse(&route).is_empty());
+        assert!(RestrictionRelation::parse(&incomplete).is_empty());
+    }
+}
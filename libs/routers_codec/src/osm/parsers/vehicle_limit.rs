@@ -0,0 +1,314 @@
+//! Legal and physical vehicle limits: `maxheight`, `maxwidth`, `maxlength`,
+//! `maxweight` and `maxaxleload`.
+//!
+//! Each limit is held in metric units, metres for dimensions and tonnes for
+//! weights, whatever unit the tag was written in. A `:conditional` variant
+//! (`maxweight:conditional=3.5 @ (Mo-Fr 07:00-19:00)`) replaces the plain
+//! limit while its condition holds, and may lift it with `none`.
+//!
+//! See: https://wiki.openstreetmap.org/wiki/Key:maxheight
+//! and https://wiki.openstreetmap.org/wiki/Key:maxweight
+
+use serde::{Deserialize, Serialize};
+
+use crate::osm::primitives::Condition;
+use crate::osm::primitives::condition::VehicleProperty;
+use crate::osm::{OsmTripConfiguration, Parser, Tags};
+
+const METRES_PER_FOOT: f64 = 0.3048;
+const METRES_PER_INCH: f64 = 0.0254;
+const TONNES_PER_POUND: f64 = 0.000_453_592_37;
+const TONNES_PER_SHORT_TON: f64 = 0.907_184_74;
+
+const CONDITIONAL: &str = ":conditional";
+
+/// The tags read for each property, the plain key first.
+const KEYS: [(&str, VehicleProperty); 7] = [
+    ("maxheight", VehicleProperty::Height),
+    ("maxheight:physical", VehicleProperty::Height),
+    ("maxwidth", VehicleProperty::Width),
+    ("maxwidth:physical", VehicleProperty::Width),
+    ("maxlength", VehicleProperty::Length),
+    ("maxweight", VehicleProperty::Weight),
+    ("maxaxleload", VehicleProperty::Axleload),
+];
+
+/// A single limit on a way.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct VehicleLimitEntry {
+    /// The property of the vehicle which is limited.
+    pub property: VehicleProperty,
+
+    /// The greatest permitted value, in metres or tonnes. `None` lifts
+    /// the limit, as in `maxweight:conditional=none @ (Sa-Su)`.
+    pub value: Option<f64>,
+
+    /// The condition under which the limit applies, if it is conditional.
+    pub condition: Option<Condition>,
+}
+
+/// Every vehicle limit tagged on a way.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct VehicleLimits(pub Vec<VehicleLimitEntry>);
+
+pub trait VehicleLimit {
+    fn vehicle_limits(&self) -> VehicleLimits;
+}
+
+impl VehicleLimit for Tags<'_> {
+    fn vehicle_limits(&self) -> VehicleLimits {
+        VehicleLimits::parse(self).unwrap_or_default()
+    }
+}
+
+impl Parser for VehicleLimits {
+    fn parse(tags: &Tags<'_>) -> Option<Self> {
+        let mut limits = Vec::new();
+
+        for (key, property) in KEYS {
+            if let Some(value) = tags
+                .get(key)
+                .and_then(|value| parse_value(value, &property))
+            {
+                limits.push(VehicleLimitEntry {
+                    property: property.clone(),
+                    value: Some(value),
+                    condition: None,
+                });
+            }
+
+            let conditional = tags.get(format!("{key}{CONDITIONAL}").as_str());
+            for rule in conditional.into_iter().flat_map(|value| split_rules(value)) {
+                let Some((value, condition)) = rule.split_once('@') else {
+                    continue;
+                };
+
+                let Ok(condition) = Condition::parse(condition) else {
+                    continue;
+                };
+
+                let value = match value.trim() {
+                    "none" => None,
+                    value => match parse_value(value, &property) {
+                        Some(value) => Some(value),
+                        None => continue,
+                    },
+                };
+
+                limits.push(VehicleLimitEntry {
+                    property: property.clone(),
+                    value,
+                    condition: Some(condition),
+                });
+            }
+        }
+
+        if limits.is_empty() {
+            None
+        } else {
+            Some(VehicleLimits(limits))
+        }
+    }
+}
+
+impl VehicleLimits {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The limit placed on the property during the trip, if any.
+    ///
+    /// The last conditional limit whose condition holds takes precedence,
+    /// otherwise the lowest of the unconditional limits applies.
+    pub fn limit(&self, property: &VehicleProperty, trip: &OsmTripConfiguration) -> Option<f64> {
+        let entries = self.0.iter().filter(|entry| entry.property == *property);
+
+        if let Some(entry) = entries.clone().rev().find(|entry| {
+            entry
+                .condition
+                .as_ref()
+                .is_some_and(|condition| condition.holds(trip))
+        }) {
+            return entry.value;
+        }
+
+        entries
+            .filter(|entry| entry.condition.is_none())
+            .filter_map(|entry| entry.value)
+            .reduce(f64::min)
+    }
+
+    /// Whether the trip's vehicle is within every limit on the way.
+    ///
+    /// Properties the trip does not describe, or describes as zero, are
+    /// taken to be within their limit.
+    pub fn admits(&self, trip: &OsmTripConfiguration) -> bool {
+        if self.is_empty() {
+            return true;
+        }
+
+        trip.vehicle_properties
+            .iter()
+            .flatten()
+            .filter(|(_, value)| *value > 0.0)
+            .all(|(property, value)| {
+                self.limit(property, trip)
+                    .is_none_or(|limit| f64::from(*value) <= limit)
+            })
+    }
+}
+
+/// Splits a conditional value into its `;`-separated rules, leaving any
+/// `;` within a bracketed condition in place.
+fn split_rules(value: &str) -> Vec<&str> {
+    let mut rules = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+
+    for (index, character) in value.char_indices() {
+        match character {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ';' if depth == 0 => {
+                rules.push(&value[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+
+    rules.push(&value[start..]);
+    rules
+        .into_iter()
+        .map(str::trim)
+        .filter(|rule| !rule.is_empty())
+        .collect()
+}
+
+/// Parses a limit value into metres or tonnes, depending on the property.
+fn parse_value(value: &str, property: &VehicleProperty) -> Option<f64> {
+    match property {
+        VehicleProperty::Weight | VehicleProperty::Axleload => parse_weight(value),
+        _ => parse_length(value),
+    }
+}
+
+/// Splits a value such as `7.5 t` or `3,5m` into its number and unit.
+fn number_and_unit(value: &str) -> Option<(f64, String)> {
+    let value = value.trim().replace(',', ".");
+    let split = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(value.len());
+
+    let number = value[..split].parse::<f64>().ok()?;
+    Some((number, value[split..].trim().to_lowercase()))
+}
+
+/// Parses a length in metres, feet and inches (`12'6"`), feet or inches.
+fn parse_length(value: &str) -> Option<f64> {
+    if let Some((feet, inches)) = value.split_once('\'') {
+        let feet = feet.trim().parse::<f64>().ok()?;
+        let inches = match inches.trim().trim_end_matches('"').trim() {
+            "" => 0.0,
+            inches => inches.parse::<f64>().ok()?,
+        };
+
+        return Some(feet * METRES_PER_FOOT + inches * METRES_PER_INCH);
+    }
+
+    let (number, unit) = number_and_unit(value)?;
+    match unit.as_str() {
+        "" | "m" => Some(number),
+        "cm" => Some(number / 100.0),
+        "ft" => Some(number * METRES_PER_FOOT),
+        "in" | "\"" => Some(number * METRES_PER_INCH),
+        _ => None,
+    }
+}
+
+/// Parses a weight in tonnes, kilograms, pounds or short tons.
+fn parse_weight(value: &str) -> Option<f64> {
+    let (number, unit) = number_and_unit(value)?;
+    match unit.as_str() {
+        "" | "t" => Some(number),
+        "kg" => Some(number / 1000.0),
+        "lb" | "lbs" => Some(number * TONNES_PER_POUND),
+        "st" => Some(number * TONNES_PER_SHORT_TON),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::osm::primitives::opening_hours::{Time, TimeOfWeek, Weekday};
+    use std::collections::HashMap;
+
+    fn limits(tags: &[(&'static str, &'static str)]) -> VehicleLimits {
+        Tags::new(tags.iter().copied().collect::<HashMap<_, _>>()).vehicle_limits()
+    }
+
+    fn vehicle(properties: &[(VehicleProperty, f32)]) -> OsmTripConfiguration {
+        OsmTripConfiguration {
+            vehicle_properties: Some(properties.to_vec()),
+            ..OsmTripConfiguration::default()
+        }
+    }
+
+    #[test]
+    fn parses_metric_and_imperial_values() {
+        let trip = OsmTripConfiguration::default();
+        let limits = limits(&[
+            ("maxheight", "12'6\""),
+            ("maxwidth", "2.5 m"),
+            ("maxlength", "40 ft"),
+            ("maxweight", "7500 kg"),
+            ("maxaxleload", "10 st"),
+        ]);
+
+        let limit = |property| limits.limit(&property, &trip).expect("a limit");
+        assert!((limit(VehicleProperty::Height) - 3.81).abs() < 1e-9);
+        assert!((limit(VehicleProperty::Width) - 2.5).abs() < 1e-9);
+        assert!((limit(VehicleProperty::Length) - 12.192).abs() < 1e-9);
+        assert!((limit(VehicleProperty::Weight) - 7.5).abs() < 1e-9);
+        assert!((limit(VehicleProperty::Axleload) - 9.0718474).abs() < 1e-9);
+    }
+
+    #[test]
+    fn ignores_values_without_a_limit() {
+        assert!(limits(&[("maxheight", "default"), ("maxweight", "none")]).is_empty());
+    }
+
+    #[test]
+    fn admits_only_vehicles_within_the_limit() {
+        let limits = limits(&[("maxheight", "3.5"), ("maxweight", "7.5")]);
+
+        assert!(limits.admits(&OsmTripConfiguration::default()));
+        assert!(limits.admits(&vehicle(&[(VehicleProperty::Height, 3.2)])));
+        assert!(!limits.admits(&vehicle(&[(VehicleProperty::Height, 4.0)])));
+        assert!(!limits.admits(&vehicle(&[
+            (VehicleProperty::Height, 3.0),
+            (VehicleProperty::Weight, 18.0),
+        ])));
+    }
+
+    #[test]
+    fn conditional_limits_apply_while_their_condition_holds() {
+        let limits = limits(&[
+            ("maxweight", "3.5"),
+            (
+                "maxweight:conditional",
+                "none @ (Sa-Su); 7.5 @ (Mo-Fr 18:00-22:00)",
+            ),
+        ]);
+
+        let lorry = |weekday, hour| OsmTripConfiguration {
+            time_of_week: Some(TimeOfWeek::new(weekday, Time::new(hour, 0).unwrap())),
+            ..vehicle(&[(VehicleProperty::Weight, 7.0)])
+        };
+
+        assert!(!limits.admits(&lorry(Weekday::Tuesday, 12)));
+        assert!(limits.admits(&lorry(Weekday::Tuesday, 20)));
+        assert!(limits.admits(&lorry(Weekday::Saturday, 12)));
+    }
+}
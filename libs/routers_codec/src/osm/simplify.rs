@@ -0,0 +1,363 @@
+//! Simplifying an [`OsmNetwork`] by merging runs of edges along a way.
+//!
+//! Ingest lays an edge between every consecutive pair of a way's nodes, so a
+//! long, curving road becomes a chain of short edges through nodes which do
+//! nothing but bend it. Each such node is a vertex of the graph and the edge
+//! index, and a step of every search over them.
+//!
+//! Simplification lifts those nodes out, joining the edges either side into
+//! one which keeps the lifted positions as its
+//! [shape](routers_network::DataPlane::shape). Candidates project onto, and
+//! matches are drawn along, the shape as they were the original edges, and
+//! each merged edge costs what the edges it replaced did.
+//!
+//! A simplified network no longer holds every node its ways were laid
+//! through, so it cannot take a change: [`apply`](OsmNetwork::apply) refuses
+//! one.
+
+use log::debug;
+use petgraph::Direction;
+use routers_network::DirectionAwareEdgeId;
+use routers_network::edge::Weight;
+
+use crate::osm::{OsmEntryId, OsmNetwork};
+
+/// An edge into or out of a node: the node at its other end, and its data.
+type Side = (OsmEntryId, (Weight, DirectionAwareEdgeId<OsmEntryId>));
+
+impl OsmNetwork {
+    /// Merge every chain of edges which runs in one direction through nodes
+    /// joining nothing else, and along a single way, into one edge carrying
+    /// the chain's shape. Returns the number of nodes lifted out of the
+    /// network.
+    ///
+    /// Nodes a turn restriction is taken at are kept, as are those which
+    /// would leave a way looping onto itself, or duplicate an edge the
+    /// network already has. Where ways meet end to end, the join is kept, as
+    /// a merged edge carries one way's id throughout.
+    ///
+    /// An attached contraction hierarchy no longer describes the network, so
+    /// it is detached. Changes must be [applied](Self::apply) before
+    /// simplifying, as the ways they list may pass through lifted nodes.
+    pub fn simplify(&mut self) -> usize {
+        let nodes = self.graph.nodes().collect::<Vec<_>>();
+        let mut lifted = 0;
+        for node in nodes {
+            let Some(passes) = self.passes(node) else {
+                continue;
+            };
+
+            for (before, after) in passes {
+                let Some(&data) = self.graph.edge_weight(before, node) else {
+                    continue;
+                };
+
+                let mut shape = self.shapes.remove(&(before, node)).unwrap_or_default();
+                shape.push(self.hash[&node].position);
+                shape.extend(self.shapes.remove(&(node, after)).unwrap_or_default());
+
+                self.graph.add_edge(before, after, data);
+                self.shapes.insert((before, after), shape);
+            }
+
+            self.graph.remove_node(node);
+            self.hash.remove(&node);
+            lifted += 1;
+        }
+
+        debug!(
+            "OsmNetwork::simplify: lifted {lifted} nodes, {} remain",
+            self.hash.len()
+        );
+
+        if lifted > 0 {
+            self.hierarchy = None;
+            self.rebuild_indices();
+        }
+
+        lifted
+    }
+
+    /// Whether [`simplify`](Self::simplify) has lifted nodes out of the
+    /// network. Only simplification lays shaped edges, so a network holding
+    /// any has been.
+    pub fn is_simplified(&self) -> bool {
+        !self.shapes.is_empty()
+    }
+
+    /// The `(before, after)` neighbours of each run through `node`, if
+    /// `node` only continues it and may be lifted out.
+    ///
+    /// A node on a one-way run has one edge in and one out; on a two-way run,
+    /// two of each to the same pair of neighbours.
+    fn passes(&self, node: OsmEntryId) -> Option<Vec<(OsmEntryId, OsmEntryId)>> {
+        if !self.restrictions.at(&node).is_empty() {
+            return None;
+        }
+
+        let into = self
+            .graph
+            .edges_directed(node, Direction::Incoming)
+            .map(|(before, _, &data)| (before, data))
+            .collect::<Vec<Side>>();
+        let outof = self
+            .graph
+            .edges_directed(node, Direction::Outgoing)
+            .map(|(_, after, &data)| (after, data))
+            .collect::<Vec<Side>>();
+
+        // Both edges travel one way along one way, and joining them neither
+        // loops nor lays an edge twice.
+        let continues = |&(before, into): &Side, &(after, outof): &Side| {
+            before != after
+                && before != node
+                && after != node
+                && self.alike(into, outof)
+                && !self.graph.contains_edge(before, after)
+        };
+
+        match (into.as_slice(), outof.as_slice()) {
+            ([a], [b]) => continues(a, b).then(|| vec![(a.0, b.0)]),
+            ([a, b], [c, d]) => {
+                let (ahead, back) = if c.0 == b.0 { (c, d) } else { (d, c) };
+                let neighbours =
+                    (ahead.0 == b.0 && back.0 == a.0) && continues(a, ahead) && continues(b, back);
+
+                neighbours.then(|| vec![(a.0, ahead.0), (b.0, back.0)])
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether an edge `into` a node may be merged with the edge `outof` it:
+    /// edges of one way, of equal weight, in one direction.
+    fn alike(
+        &self,
+        (weight, into): (Weight, DirectionAwareEdgeId<OsmEntryId>),
+        (other, outof): (Weight, DirectionAwareEdgeId<OsmEntryId>),
+    ) -> bool {
+        weight == other && into.direction() == outof.direction() && into.index() == outof.index()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::osm::turn_restriction::{TurnKind, TurnRestriction};
+    use crate::osm::{ContractionHierarchy, OsmChange, OsmTripConfiguration};
+    use geo::{Point, point};
+    use routers_network::{DataPlane, Route, Scan};
+
+    /// A residential street bending from node 1 round to node 6, crossed at
+    /// node 4 by a one-way service road from node 7 through node 8 to
+    /// node 9.
+    const BEND: &str = r#"<osmChange version="0.6"><create>
+  <node id="1" lat="0.0" lon="0.0"/>
+  <node id="2" lat="0.0005" lon="0.001"/>
+  <node id="3" lat="0.0015" lon="0.0012"/>
+  <node id="4" lat="0.002" lon="0.002"/>
+  <node id="5" lat="0.0025" lon="0.003"/>
+  <node id="6" lat="0.002" lon="0.004"/>
+  <node id="7" lat="0.003" lon="0.002"/>
+  <node id="8" lat="0.0025" lon="0.0021"/>
+  <node id="9" lat="0.001" lon="0.002"/>
+  <way id="10">
+    <nd ref="1"/><nd ref="2"/><nd ref="3"/><nd ref="4"/><nd ref="5"/><nd ref="6"/>
+    <tag k="highway" v="residential"/>
+  </way>
+  <way id="20">
+    <nd ref="7"/><nd ref="8"/><nd ref="4"/><nd ref="9"/>
+    <tag k="highway" v="service"/>
+    <tag k="oneway" v="yes"/>
+  </way>
+</create></osmChange>"#;
+
+    fn bend() -> OsmNetwork {
+        let mut network = OsmNetwork::default();
+        network
+            .apply(&OsmChange::parse(BEND).expect("must parse"))
+            .expect("must apply");
+        network
+    }
+
+    fn route(network: &OsmNetwork, from: i64, to: i64) -> (Weight, Vec<i64>) {
+        let (cost, nodes) = network
+            .route_nodes(
+                OsmEntryId::node(from),
+                OsmEntryId::node(to),
+                &OsmTripConfiguration::default(),
+            )
+            .expect("must route");
+
+        (cost, nodes.iter().map(|node| node.id.identifier).collect())
+    }
+
+    #[test]
+    fn lifts_nodes_which_only_continue_a_way() {
+        let mut network = bend();
+        assert_eq!(network.graph.edge_count(), 13);
+
+        assert_eq!(network.simplify(), 4);
+        assert_eq!(network.graph.edge_count(), 6);
+        assert_eq!(network.index.len(), 5);
+
+        let kept = [1, 4, 6, 7, 9].map(OsmEntryId::node);
+        assert!(kept.iter().all(|id| network.hash.contains_key(id)));
+
+        let positions = |ids: &[i64]| -> Vec<Point> {
+            let network = bend();
+            ids.iter()
+                .map(|&id| network.hash[&OsmEntryId::node(id)].position)
+                .collect()
+        };
+        let (one, four) = (OsmEntryId::node(1), OsmEntryId::node(4));
+        assert_eq!(network.shape(&one, &four), positions(&[2, 3]));
+        assert_eq!(network.shape(&four, &one), positions(&[3, 2]));
+        assert_eq!(network.shape(&OsmEntryId::node(7), &four), positions(&[8]));
+    }
+
+    #[test]
+    fn routes_cost_as_before() {
+        let plain = bend();
+        let mut simple = bend();
+        simple.simplify();
+
+        for (from, to) in [(1, 6), (6, 1), (7, 6), (1, 9)] {
+            let (before, _) = route(&plain, from, to);
+            let (after, _) = route(&simple, from, to);
+            assert_eq!(before, after, "route from {from} to {to}");
+        }
+
+        assert_eq!(route(&simple, 1, 6).1, vec![1, 4, 6]);
+    }
+
+    #[test]
+    fn matrix_legs_measure_along_the_shape() {
+        let plain = bend();
+        let mut simple = bend();
+        simple.simplify();
+
+        let origins = [1, 6, 7].map(OsmEntryId::node);
+        let destinations = [1, 6, 9].map(OsmEntryId::node);
+        let runtime = OsmTripConfiguration::default();
+        let before = plain.matrix(&origins, &destinations, &runtime);
+        let after = simple.matrix(&origins, &destinations, &runtime);
+
+        for (row, (before, after)) in before.rows().zip(after.rows()).enumerate() {
+            for (column, (before, after)) in before.iter().zip(after).enumerate() {
+                let (before, after) = (before.expect("must route"), after.expect("must route"));
+                assert_eq!(before.cost, after.cost, "cell ({row}, {column})");
+                assert!(
+                    (before.distance - after.distance).abs() < 1e-6,
+                    "cell ({row}, {column}): {} != {}",
+                    before.distance,
+                    after.distance
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn projects_onto_the_shape() {
+        let plain = bend();
+        let mut simple = bend();
+        simple.simplify();
+
+        // Beside the bend at node 3, a lifted node.
+        let point = point!(x: 0.0013, y: 0.0014);
+        let nearest = |network: &OsmNetwork| {
+            network
+                .nearest_nodes_projected(&point, 50.0)
+                .map(|(position, _)| position)
+                .min_by(|a, b| {
+                    use geo::{Distance, Haversine};
+                    Haversine
+                        .distance(*a, point)
+                        .total_cmp(&Haversine.distance(*b, point))
+                })
+                .expect("must project")
+        };
+
+        assert_eq!(nearest(&plain), nearest(&simple));
+    }
+
+    #[test]
+    fn keeps_restricted_junctions() {
+        let mut network = bend();
+        network.restrictions.insert(TurnRestriction {
+            kind: TurnKind::Prohibitory,
+            from: OsmEntryId::way(10),
+            through: Vec::new(),
+            via: OsmEntryId::node(2),
+            to: OsmEntryId::way(10),
+            mode: None,
+            except: Vec::new(),
+            condition: None,
+        });
+
+        assert_eq!(network.simplify(), 3);
+        assert!(network.hash.contains_key(&OsmEntryId::node(2)));
+    }
+
+    #[test]
+    fn hierarchies_route_over_the_shapes() {
+        let (expected, _) = route(&bend(), 1, 6);
+
+        let mut network = bend();
+        network.simplify();
+        let hierarchy = ContractionHierarchy::build(&network);
+        let network = network.with_hierarchy(hierarchy).expect("must fit");
+
+        assert_eq!(route(&network, 1, 6), (expected, vec![1, 4, 6]));
+    }
+
+    /// Two residential ways meeting end to end at node 3, running on from
+    /// node 1 to node 5.
+    const JOINED: &str = r#"<osmChange version="0.6"><create>
+  <node id="1" lat="0.0" lon="0.0"/>
+  <node id="2" lat="0.0005" lon="0.001"/>
+  <node id="3" lat="0.0" lon="0.002"/>
+  <node id="4" lat="0.0005" lon="0.003"/>
+  <node id="5" lat="0.0" lon="0.004"/>
+  <way id="10">
+    <nd ref="1"/><nd ref="2"/><nd ref="3"/>
+    <tag k="highway" v="residential"/>
+  </way>
+  <way id="11">
+    <nd ref="3"/><nd ref="4"/><nd ref="5"/>
+    <tag k="highway" v="residential"/>
+  </way>
+</create></osmChange>"#;
+
+    fn joined() -> OsmNetwork {
+        let mut network = OsmNetwork::default();
+        network
+            .apply(&OsmChange::parse(JOINED).expect("must parse"))
+            .expect("must apply");
+        network
+    }
+
+    #[test]
+    fn keeps_joins_of_alike_ways() {
+        let (expected, _) = route(&joined(), 1, 5);
+
+        let mut network = joined();
+        assert_eq!(network.simplify(), 2);
+        assert_eq!(route(&network, 1, 5), (expected, vec![1, 3, 5]));
+    }
+
+    #[test]
+    fn refuses_changes_once_simplified() {
+        let mut network = bend();
+        assert!(network.simplify() > 0);
+
+        let change = OsmChange::parse(
+            r#"<osmChange><modify><node id="6" lat="0.002" lon="0.005"/></modify></osmChange>"#,
+        )
+        .expect("must parse");
+        assert!(matches!(
+            network.apply(&change),
+            Err(crate::osm::ChangeError::Simplified)
+        ));
+    }
+}
@@ -0,0 +1,190 @@
+//! The cost model point-to-point routing minimises over an [`OsmNetwork`].
+//!
+//! Edges in the graph always carry their [`RoadClass::weighting`] rank, which
+//! the matcher folds into its own length-aware cost. Routing instead prices
+//! each edge through the [`Weighting`] the network was built with.
+//!
+//! [`OsmNetwork`]: crate::osm::OsmNetwork
+//! [`RoadClass::weighting`]: crate::osm::primitives::RoadClass::weighting
+
+use geo::{Distance, Haversine, Point};
+use routers_network::edge::Weight;
+use serde::{Deserialize, Serialize};
+
+use crate::osm::OsmEdgeMetadata;
+use crate::osm::primitives::Speed;
+
+/// Milliseconds in an hour, relating a speed in km/h to a travel time.
+const MILLIS_PER_HOUR: f64 = 3_600_000.0;
+
+/// How an [`OsmNetwork`](crate::osm::OsmNetwork) prices the edges it routes
+/// over, chosen when the network is built.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Weighting {
+    /// The time taken to drive each edge at its effective speed: the
+    /// unconditional `maxspeed` where tagged, or the road class's
+    /// [default speed](crate::osm::primitives::RoadClass::default_speed) otherwise.
+    ///
+    /// Route costs are reported in seconds.
+    #[default]
+    TravelTime,
+
+    /// The edge's road-class rank, regardless of its length. Route costs are
+    /// the sum of the ranks along the route, and carry no unit.
+    RoadClass,
+}
+
+impl Weighting {
+    /// The cost of the edge between `source` and `target`, of road-class
+    /// `rank`, along a way described by `metadata`.
+    ///
+    /// Travel times are given in milliseconds so that short edges do not
+    /// round away; sum them and [`report`](Self::report) the total. They are
+    /// rounded up, so no edge costs less than its length at
+    /// [`cost_per_metre`](Self::cost_per_metre), which A* relies on.
+    pub fn cost(
+        &self,
+        source: Point,
+        target: Point,
+        rank: Weight,
+        metadata: Option<&OsmEdgeMetadata>,
+    ) -> Weight {
+        match self {
+            Weighting::RoadClass => rank,
+            Weighting::TravelTime => {
+                let speed = metadata.map_or(OsmEdgeMetadata::FALLBACK_SPEED, |meta| meta.speed());
+                let kilometres = Haversine.distance(source, target) / 1_000.0;
+
+                (kilometres / f64::from(speed.get()) * MILLIS_PER_HOUR).ceil() as Weight
+            }
+        }
+    }
+
+    /// A lower bound on the [`cost`](Self::cost) of a metre of travel, for a
+    /// network whose fastest way is driven at `top` km/h.
+    ///
+    /// Under [`RoadClass`](Weighting::RoadClass) an edge costs the same
+    /// however long it is, so there is no bound but zero.
+    pub fn cost_per_metre(&self, top: Speed) -> f64 {
+        match self {
+            Weighting::RoadClass => 0.0,
+            Weighting::TravelTime => MILLIS_PER_HOUR / 1_000.0 / f64::from(top.get()),
+        }
+    }
+
+    /// The cost of a route, given the sum of its edges' [`cost`](Self::cost)s.
+    pub fn report(&self, total: Weight) -> Weight {
+        match self {
+            Weighting::RoadClass => total,
+            Weighting::TravelTime => (total + 500) / 1_000,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::osm::primitives::RoadClass;
+    use geo::point;
+
+    fn motorway() -> OsmEdgeMetadata {
+        OsmEdgeMetadata {
+            road_class: Some(RoadClass::Motorway),
+            ..OsmEdgeMetadata::default()
+        }
+    }
+
+    #[test]
+    fn travel_time_scales_with_length() {
+        let meta = motorway();
+        let origin = point!(x: 0.0, y: 0.0);
+
+        // 0.01° and 0.02° of longitude at the equator, ~1.1 km and ~2.2 km.
+        let short = Weighting::TravelTime.cost(origin, point!(x: 0.01, y: 0.0), 1, Some(&meta));
+        let long = Weighting::TravelTime.cost(origin, point!(x: 0.02, y: 0.0), 1, Some(&meta));
+
+        assert!(long.abs_diff(2 * short) <= 1, "{long} is not twice {short}");
+    }
+
+    #[test]
+    fn travel_time_follows_the_effective_speed() {
+        let origin = point!(x: 0.0, y: 0.0);
+        let destination = point!(x: 0.01, y: 0.0);
+
+        let fast = Weighting::TravelTime.cost(origin, destination, 1, Some(&motorway()));
+        let slow = Weighting::TravelTime.cost(
+            origin,
+            destination,
+            1,
+            Some(&OsmEdgeMetadata {
+                road_class: Some(RoadClass::Motorway),
+                max_speed: Speed::new(50),
+                ..OsmEdgeMetadata::default()
+            }),
+        );
+
+        assert!(slow > fast);
+        assert_eq!(Weighting::TravelTime.report(slow), 80);
+    }
+
+    #[test]
+    fn road_class_ignores_length() {
+        let origin = point!(x: 0.0, y: 0.0);
+        let meta = motorway();
+
+        let short = Weighting::RoadClass.cost(origin, point!(x: 0.01, y: 0.0), 7, Some(&meta));
+        let long = Weighting::RoadClass.cost(origin, point!(x: 0.02, y: 0.0), 7, Some(&meta));
+
+        assert_eq!((short, long), (7, 7));
+        assert_eq!(Weighting::RoadClass.report(14), 14);
+    }
+
+    #[test]
+    fn max_speed_is_the_lowest_unconditional_limit() {
+        use crate::osm::element::Tags;
+        use routers_network::Metadata;
+        use std::collections::HashMap;
+
+        let tags = Tags::new(HashMap::from([
+            ("highway", "primary"),
+            ("maxspeed", "30 mph"),
+            ("maxspeed:conditional", "20 @ (Mo-Fr 07:00-09:00)"),
+            ("maxspeed:hgv", "40"),
+        ]));
+        let meta = OsmEdgeMetadata::pick(&tags);
+
+        assert_eq!(meta.max_speed, Speed::new(48));
+        assert_eq!(meta.speed(), Speed::new(48).unwrap());
+
+        let untagged = OsmEdgeMetadata::pick(&Tags::new(HashMap::from([("highway", "primary")])));
+        assert_eq!(untagged.speed(), RoadClass::Primary.default_speed());
+    }
+
+    #[test]
+    fn permitted_speed_follows_the_transport_mode() {
+        use crate::osm::OsmTripConfiguration;
+        use crate::osm::element::Tags;
+        use crate::osm::primitives::TransportMode;
+        use routers_network::Metadata;
+        use std::collections::HashMap;
+
+        let tags = Tags::new(HashMap::from([
+            ("highway", "primary"),
+            ("maxspeed", "50"),
+            ("maxspeed:hgv", "40"),
+        ]));
+        let meta = OsmEdgeMetadata::pick(&tags);
+
+        let car = OsmTripConfiguration::default();
+        assert_eq!(meta.permitted_speed(&car), Some(50.0 / 3.6));
+
+        let hgv = OsmTripConfiguration {
+            transport_mode: TransportMode::Hgv,
+            ..OsmTripConfiguration::default()
+        };
+        assert_eq!(meta.permitted_speed(&hgv), Some(40.0 / 3.6));
+
+        let untagged = OsmEdgeMetadata::pick(&Tags::new(HashMap::from([("highway", "primary")])));
+        assert_eq!(untagged.permitted_speed(&car), None);
+    }
+}
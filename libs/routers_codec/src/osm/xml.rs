@@ -0,0 +1,247 @@
+//! Reading the elements of OSM XML.
+//!
+//! Plain `.osm` files and OsmChange (`.osc`) files share one element
+//! grammar; a change only wraps its elements in `<create>`, `<modify>` and
+//! `<delete>` blocks. Both are read here, element by element, so neither
+//! need be held in memory as text.
+
+use std::collections::HashMap;
+use std::io::BufRead;
+
+use geo::Point;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::{Reader, XmlVersion};
+
+use crate::osm::Tags;
+use crate::osm::change::Action;
+use crate::osm::relation::MemberType;
+
+#[derive(Debug, thiserror::Error)]
+pub enum XmlError {
+    #[error("could not read file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("malformed XML: {0}")]
+    Xml(#[from] quick_xml::Error),
+    #[error("<{element}> is missing its `{attribute}` attribute")]
+    MissingAttribute {
+        element: &'static str,
+        attribute: &'static str,
+    },
+    #[error("invalid `{attribute}`: {value:?}")]
+    Invalid {
+        attribute: &'static str,
+        value: String,
+    },
+}
+
+/// A relation member, as listed.
+pub(crate) struct Member {
+    pub kind: MemberType,
+    pub id: i64,
+    pub role: String,
+}
+
+/// An element as listed, owning its references and tags. A node's tags are
+/// not kept, as nothing routes by them.
+pub(crate) enum XmlElement {
+    Node {
+        id: i64,
+        /// Absent on nodes an OsmChange deletes.
+        position: Option<Point>,
+    },
+    Way {
+        id: i64,
+        refs: Vec<i64>,
+        tags: Vec<(String, String)>,
+    },
+    Relation {
+        id: i64,
+        members: Vec<Member>,
+        tags: Vec<(String, String)>,
+    },
+}
+
+/// Read every element from `reader`, in document order, passing each to
+/// `visit` with the OsmChange block it is listed in, if any.
+///
+/// Outside a block, elements a document marks as deleted are skipped: those
+/// an editor has deleted but not uploaded (`action="delete"`), and those a
+/// history or API download lists as no longer visible (`visible="false"`).
+pub(crate) fn read<R: BufRead>(
+    reader: R,
+    mut visit: impl FnMut(Option<Action>, XmlElement),
+) -> Result<(), XmlError> {
+    let mut reader = Reader::from_reader(reader);
+    reader.config_mut().trim_text(true);
+
+    let mut buf = Vec::new();
+    let mut action = None;
+    let mut open = None;
+
+    loop {
+        buf.clear();
+        let (tag, empty) = match reader.read_event_into(&mut buf)? {
+            Event::Start(tag) => (tag, false),
+            Event::Empty(tag) => (tag, true),
+            Event::End(tag) => {
+                match tag.local_name().as_ref() {
+                    b"create" | b"modify" | b"delete" => action = None,
+                    b"node" | b"way" | b"relation" => {
+                        if let Some(element) = open.take() {
+                            visit(action, element);
+                        }
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+            Event::Eof => break,
+            _ => continue,
+        };
+
+        match (tag.local_name().as_ref(), &mut open) {
+            (b"create", _) => action = Some(Action::Create),
+            (b"modify", _) => action = Some(Action::Modify),
+            (b"delete", _) => action = Some(Action::Delete),
+            // Left unopened, so neither its children nor its end are read
+            // into anything.
+            (b"node" | b"way" | b"relation", _) if action.is_none() && deleted(&tag)? => {}
+            (b"node", _) => {
+                open = Some(XmlElement::Node {
+                    id: attribute(&tag, "node", "id")?,
+                    position: position(&tag)?,
+                })
+            }
+            (b"way", _) => {
+                open = Some(XmlElement::Way {
+                    id: attribute(&tag, "way", "id")?,
+                    refs: Vec::new(),
+                    tags: Vec::new(),
+                })
+            }
+            (b"relation", _) => {
+                open = Some(XmlElement::Relation {
+                    id: attribute(&tag, "relation", "id")?,
+                    members: Vec::new(),
+                    tags: Vec::new(),
+                })
+            }
+            (b"nd", Some(XmlElement::Way { refs, .. })) => {
+                refs.push(attribute(&tag, "nd", "ref")?);
+            }
+            (b"member", Some(XmlElement::Relation { members, .. })) => {
+                members.push(member(&tag)?);
+            }
+            (b"tag", Some(XmlElement::Way { tags, .. } | XmlElement::Relation { tags, .. })) => {
+                tags.push((attribute(&tag, "tag", "k")?, attribute(&tag, "tag", "v")?));
+            }
+            _ => {}
+        }
+
+        // A self-closing element has no children, and no end tag.
+        if empty
+            && matches!(tag.local_name().as_ref(), b"node" | b"way" | b"relation")
+            && let Some(element) = open.take()
+        {
+            visit(action, element);
+        }
+    }
+
+    Ok(())
+}
+
+/// Tags as ingest reads them, borrowed from their listing.
+pub(crate) fn tags(list: &[(String, String)]) -> Tags<'_> {
+    Tags::new(
+        list.iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+            .collect::<HashMap<_, _>>(),
+    )
+}
+
+/// An attribute of `tag`, parsed as a `T`.
+fn attribute<T: core::str::FromStr>(
+    tag: &BytesStart,
+    element: &'static str,
+    name: &'static str,
+) -> Result<T, XmlError> {
+    let value = tag
+        .try_get_attribute(name)
+        .map_err(quick_xml::Error::from)?
+        .ok_or(XmlError::MissingAttribute {
+            element,
+            attribute: name,
+        })?;
+
+    let value = value.normalized_value(XmlVersion::default())?;
+    value.parse().map_err(|_| XmlError::Invalid {
+        attribute: name,
+        value: value.into_owned(),
+    })
+}
+
+/// Whether an element is marked as deleted, by its `action` or `visible`
+/// attribute.
+fn deleted(tag: &BytesStart) -> Result<bool, XmlError> {
+    let is = |name: &'static str, value: &str| match attribute::<String>(tag, "element", name) {
+        Err(XmlError::MissingAttribute { .. }) => Ok(false),
+        listed => listed.map(|listed| listed == value),
+    };
+
+    Ok(is("action", "delete")? || is("visible", "false")?)
+}
+
+/// A `<node>`'s position, if it carries both `lat` and `lon`.
+fn position(tag: &BytesStart) -> Result<Option<Point>, XmlError> {
+    let has = |name: &str| matches!(tag.try_get_attribute(name), Ok(Some(_)));
+    if !(has("lat") && has("lon")) {
+        return Ok(None);
+    }
+
+    Ok(Some(Point::new(
+        attribute(tag, "node", "lon")?,
+        attribute(tag, "node", "lat")?,
+    )))
+}
+
+/// A relation `<member>`, whose role may be left out when empty.
+fn member(tag: &BytesStart) -> Result<Member, XmlError> {
+    let kind = match attribute::<String>(tag, "member", "type")?.as_str() {
+        "node" => MemberType::NODE,
+        "way" => MemberType::WAY,
+        "relation" => MemberType::RELATION,
+        other => {
+            return Err(XmlError::Invalid {
+                attribute: "type",
+                value: other.to_string(),
+            });
+        }
+    };
+
+    let role = match attribute(tag, "member", "role") {
+        Err(XmlError::MissingAttribute { .. }) => String::new(),
+        role => role?,
+    };
+
+    Ok(Member {
+        kind,
+        id: attribute(tag, "member", "ref")?,
+        role,
+    })
+}
+
+/// A reader over the file at `path`, decompressing it first if its name
+/// ends in `.gz`.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn open(path: &std::path::Path) -> Result<Box<dyn BufRead>, XmlError> {
+    use std::io::BufReader;
+
+    let file = std::fs::File::open(path)?;
+    Ok(
+        if path.extension().is_some_and(|extension| extension == "gz") {
+            Box::new(BufReader::new(flate2::read::GzDecoder::new(file)))
+        } else {
+            Box::new(BufReader::new(file))
+        },
+    )
+}
@@ -7,6 +7,8 @@ pub struct TruckCosting {
     pub vehicle_costing: VehicleCosting,
 
     pub length: f32,
+    /// The gross weight of the truck, in tonnes.
+    pub weight: f32,
     pub axle_load: f32,
     pub axle_count: u8,
 
@@ -1 +1,3 @@
+extern crate alloc;
+
 mod osm;
@@ -0,0 +1,195 @@
+//! Every supported blob compression decodes to the same elements.
+//!
+//! Each fixture is one `OSMData` blob holding the same primitive block,
+//! compressed a different way, so a compression which decodes at all must
+//! decode to exactly the elements of the others.
+
+use alloc::sync::Arc;
+use std::io::Write;
+
+use buffa::Message;
+use flate2::Compression;
+use flate2::write::ZlibEncoder;
+
+use routers_codec::osm::blob::iterator::BlobIterator;
+use routers_codec::osm::element::item::Element;
+use routers_codec::osm::model::blob::Data;
+use routers_codec::osm::model::{
+    Blob, BlobHeader, Node, PrimitiveBlock, PrimitiveGroup, StringTable, Way,
+};
+
+/// Three nodes, and a way through them.
+fn block() -> PrimitiveBlock {
+    let nodes = (1..=3)
+        .map(|id| Node {
+            id,
+            lat: 389_000_000 + id,
+            lon: -770_000_000 - id,
+            ..Default::default()
+        })
+        .collect();
+
+    let way = Way {
+        id: 10,
+        // Delta-coded: nodes 1, 2 and 3.
+        refs: vec![1, 1, 1],
+        ..Default::default()
+    };
+
+    PrimitiveBlock {
+        stringtable: StringTable {
+            s: vec![Vec::new()],
+            ..Default::default()
+        }
+        .into(),
+        primitivegroup: vec![
+            PrimitiveGroup {
+                nodes,
+                ..Default::default()
+            },
+            PrimitiveGroup {
+                ways: vec![way],
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    }
+}
+
+/// A one-blob `.osm.pbf` of [`block`], compressed by `compress`.
+fn fixture(compress: impl Fn(&[u8]) -> Data) -> Vec<u8> {
+    encode(compress, true)
+}
+
+/// As [`fixture`], but leaving out the blob's optional `raw_size`.
+#[cfg(any(feature = "zstd", feature = "lz4", feature = "lzma"))]
+fn unsized_fixture(compress: impl Fn(&[u8]) -> Data) -> Vec<u8> {
+    encode(compress, false)
+}
+
+fn encode(compress: impl Fn(&[u8]) -> Data, sized: bool) -> Vec<u8> {
+    let raw = block().encode_to_vec();
+    let blob = Blob {
+        raw_size: sized.then_some(raw.len() as i32),
+        data: Some(compress(&raw)),
+        ..Default::default()
+    }
+    .encode_to_vec();
+
+    let header = BlobHeader {
+        r#type: "OSMData".to_string(),
+        datasize: blob.len() as i32,
+        ..Default::default()
+    }
+    .encode_to_vec();
+
+    let mut file = (header.len() as u32).to_be_bytes().to_vec();
+    file.extend(header);
+    file.extend(blob);
+    file
+}
+
+/// The (nodes, ways) decoded from `file`.
+fn count(file: Vec<u8>) -> (usize, usize) {
+    let mut iterator = BlobIterator::with_existing(Arc::new(file)).expect("must index");
+    let blobs = iterator.by_ref().collect::<Vec<_>>();
+
+    blobs
+        .iter()
+        .filter_map(|blob| iterator.make_block(blob))
+        .flat_map(|block| {
+            block
+                .raw_element_iter()
+                .map(|element| match element {
+                    Element::Node(_) => (1, 0),
+                    Element::Way(_) => (0, 1),
+                    _ => (0, 0),
+                })
+                .collect::<Vec<_>>()
+        })
+        .fold((0, 0), |a, b| (a.0 + b.0, a.1 + b.1))
+}
+
+#[test]
+fn decodes_raw_blobs() {
+    assert_eq!(count(fixture(|raw| Data::Raw(raw.to_vec()))), (3, 1));
+}
+
+#[test]
+fn decodes_zlib_blobs() {
+    let file = fixture(|raw| {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(raw).expect("must compress");
+        Data::ZlibData(encoder.finish().expect("must compress"))
+    });
+
+    assert_eq!(count(file), (3, 1));
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn decodes_zstd_blobs() {
+    let file = fixture(|raw| Data::ZstdData(zstd::bulk::compress(raw, 3).expect("must compress")));
+    assert_eq!(count(file), (3, 1));
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn decodes_zstd_blobs_without_a_raw_size() {
+    let file =
+        unsized_fixture(|raw| Data::ZstdData(zstd::bulk::compress(raw, 3).expect("must compress")));
+    assert_eq!(count(file), (3, 1));
+}
+
+#[cfg(feature = "lz4")]
+#[test]
+fn decodes_lz4_blobs() {
+    let file = fixture(|raw| {
+        Data::Lz4Data(lz4::block::compress(raw, None, false).expect("must compress"))
+    });
+    assert_eq!(count(file), (3, 1));
+}
+
+#[cfg(feature = "lz4")]
+#[test]
+fn skips_lz4_blobs_without_a_raw_size() {
+    let file = unsized_fixture(|raw| {
+        Data::Lz4Data(lz4::block::compress(raw, None, false).expect("must compress"))
+    });
+    assert_eq!(count(file), (0, 0));
+}
+
+#[cfg(feature = "lzma")]
+#[test]
+fn decodes_lzma_blobs() {
+    let file = fixture(|raw| {
+        let mut encoder = xz2::write::XzEncoder::new(Vec::new(), 6);
+        encoder.write_all(raw).expect("must compress");
+        Data::LzmaData(encoder.finish().expect("must compress"))
+    });
+    assert_eq!(count(file), (3, 1));
+}
+
+#[cfg(feature = "lzma")]
+#[test]
+fn decodes_lzma_blobs_without_a_raw_size() {
+    let file = unsized_fixture(|raw| {
+        let mut encoder = xz2::write::XzEncoder::new(Vec::new(), 6);
+        encoder.write_all(raw).expect("must compress");
+        Data::LzmaData(encoder.finish().expect("must compress"))
+    });
+    assert_eq!(count(file), (3, 1));
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn skips_blobs_which_fail_to_decode() {
+    let file = fixture(|raw| Data::ZstdData(raw.to_vec()));
+    assert_eq!(count(file), (0, 0));
+}
+
+#[test]
+fn skips_blobs_it_cannot_decode() {
+    let file = fixture(|raw| Data::OBSOLETEBzip2Data(raw.to_vec()));
+    assert_eq!(count(file), (0, 0));
+}
@@ -1,2 +1,3 @@
+mod compression;
 mod element;
 mod iter;
@@ -1,10 +1,13 @@
 extern crate alloc;
 
 pub mod primitive;
+pub mod routing;
 pub mod traits;
 
 pub use primitive::*;
 pub use traits::*;
 
+pub use routing::{Routing, RoutingGraph, RoutingProvider};
+
 #[cfg(any(test, feature = "testing"))]
 pub mod mock;
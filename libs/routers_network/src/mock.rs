@@ -30,11 +30,12 @@ use core::hash::BuildHasherDefault;
 
 use crate::{
     DataPlane, Direction, DirectionAwareEdgeId, Discovery, Edge, Entry, Metadata, Node, Route,
-    RowIndex, Scan, edge::Weight, envelope_of, network::GraphEdge,
+    Routing, RoutingGraph, RoutingProvider, RowIndex, Scan, Turn, edge::Weight, envelope_of,
+    network::GraphEdge,
 };
 use geo::{Point, Rect};
 use petgraph::prelude::DiGraphMap;
-use rustc_hash::{FxHashMap, FxHasher};
+use rustc_hash::{FxHashMap, FxHashSet, FxHasher};
 use serde::{Deserialize, Serialize};
 
 // ── Entry ────────────────────────────────────────────────────────────────────
@@ -98,6 +99,8 @@ pub struct MockNetwork {
     metadata: FxHashMap<MockEntryId, MockMetadata>,
     node_index: RowIndex<MockEntryId>,
     edge_index: RowIndex<(MockEntryId, MockEntryId)>,
+    /// Forbidden turns, as the `(origin, via, destination)` nodes they pass.
+    forbidden_turns: FxHashSet<(MockEntryId, MockEntryId, MockEntryId)>,
 }
 
 impl Debug for MockNetwork {
@@ -164,19 +167,16 @@ impl Scan for MockNetwork {
     }
 }
 
+impl RoutingGraph for MockNetwork {}
+
 impl Route for MockNetwork {
     fn route_nodes(
         &self,
         start_node: MockEntryId,
         finish_node: MockEntryId,
+        runtime: &(),
     ) -> Option<(Weight, Vec<Node<MockEntryId>>)> {
-        let (score, path) = petgraph::algo::astar(
-            &self.graph,
-            start_node,
-            |finish| finish == finish_node,
-            |(_, _, w)| w.0,
-            |_| 0,
-        )?;
+        let (score, path) = Routing::default().route(self, start_node, finish_node, runtime)?;
 
         let route = path
             .iter()
@@ -239,6 +239,17 @@ impl DataPlane for MockNetwork {
             weight: *weight,
         })
     }
+
+    fn turn_permitted(
+        &self,
+        turn: &Turn<MockEntryId>,
+        _approach: &mut dyn Iterator<Item = MockEntryId>,
+        _runtime: &(),
+    ) -> bool {
+        !self
+            .forbidden_turns
+            .contains(&(turn.origin, turn.via, turn.destination))
+    }
 }
 
 // ── Builder ───────────────────────────────────────────────────────────────────
@@ -288,6 +299,7 @@ struct EdgeDef {
 pub struct MockNetworkBuilder {
     nodes: Vec<NodeDef>,
     edges: Vec<EdgeDef>,
+    forbidden_turns: FxHashSet<(MockEntryId, MockEntryId, MockEntryId)>,
     /// Monotonically-increasing counter used to auto-assign edge IDs.
     next_edge_id: i64,
 }
@@ -304,6 +316,7 @@ impl MockNetworkBuilder {
         Self {
             nodes: Vec::new(),
             edges: Vec::new(),
+            forbidden_turns: FxHashSet::default(),
             next_edge_id: 1,
         }
     }
@@ -368,6 +381,17 @@ impl MockNetworkBuilder {
         self
     }
 
+    /// Forbid the turn which arrives at `via` from `origin` and departs
+    /// towards `destination`, as a turn restriction would.
+    pub fn forbid_turn(mut self, origin: i64, via: i64, destination: i64) -> Self {
+        self.forbidden_turns.insert((
+            MockEntryId(origin),
+            MockEntryId(via),
+            MockEntryId(destination),
+        ));
+        self
+    }
+
     /// Consume the builder and produce a [`MockNetwork`].
     pub fn build(self) -> MockNetwork {
         let mut graph = GraphStructure::new();
@@ -415,6 +439,7 @@ impl MockNetworkBuilder {
             metadata,
             node_index,
             edge_index,
+            forbidden_turns: self.forbidden_turns,
         }
     }
 }
@@ -491,6 +516,27 @@ mod tests {
         assert!(net.metadata(&MockEntryId(2)).is_some());
     }
 
+    #[test]
+    fn forbidden_turns_are_not_permitted() {
+        let net = MockNetworkBuilder::new()
+            .node(1, point!(x: -118.15, y: 34.15))
+            .node(2, point!(x: -118.16, y: 34.15))
+            .node(3, point!(x: -118.17, y: 34.15))
+            .node(4, point!(x: -118.16, y: 34.16))
+            .edge(1, 2)
+            .edge(2, 3)
+            .edge(2, 4)
+            .forbid_turn(1, 2, 4)
+            .build();
+
+        let entry = |a, b| net.edge(&MockEntryId(a), &MockEntryId(b)).unwrap();
+        let permitted =
+            |turn: Turn<MockEntryId>| net.turn_permitted(&turn, &mut core::iter::empty(), &());
+
+        assert!(permitted(Turn::between(&entry(1, 2), &entry(2, 3))));
+        assert!(!permitted(Turn::between(&entry(1, 2), &entry(2, 4))));
+    }
+
     #[test]
     fn mock_metadata_always_accessible() {
         let meta = MockMetadata;
@@ -504,7 +550,7 @@ mod tests {
     fn route_nodes_finds_direct_path() {
         let net = straight_road();
         let (_, path) = net
-            .route_nodes(MockEntryId(1), MockEntryId(3))
+            .route_nodes(MockEntryId(1), MockEntryId(3), &())
             .expect("route must exist");
         let ids: Vec<i64> = path.iter().map(|n| n.id.0).collect();
         assert_eq!(ids, vec![1, 2, 3]);
@@ -514,6 +560,9 @@ mod tests {
     fn route_nodes_returns_none_for_unreachable() {
         let net = straight_road();
         // Nodes 1→3 exist but 3→1 is unreachable in a one-way network.
-        assert!(net.route_nodes(MockEntryId(3), MockEntryId(1)).is_none());
+        assert!(
+            net.route_nodes(MockEntryId(3), MockEntryId(1), &())
+                .is_none()
+        );
     }
 }
@@ -2,8 +2,12 @@ pub mod direction;
 pub mod edge;
 pub mod index;
 pub mod node;
+pub mod shape;
+pub mod turn;
 
 pub use direction::Direction;
 pub use edge::{DirectionAwareEdgeId, Edge};
 pub use index::{RowIndex, envelope_of};
 pub use node::Node;
+pub use shape::{Projection, project};
+pub use turn::Turn;
@@ -0,0 +1,91 @@
+//! Locating points along edges which bend between their ends.
+//!
+//! Most edges are drawn straight from source to target, but a network may
+//! keep the [shape](crate::DataPlane::shape) of an edge which stands in for a
+//! whole run of road. Its [line](crate::DataPlane::edge_line) is then a
+//! polyline, and questions of where along the edge a point lies are answered
+//! against each of its segments in turn.
+
+use geo::{Distance, Haversine, InterpolatableLine, LineLocatePoint, LineString, Point};
+
+/// Where a point falls upon a line.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Projection {
+    /// The closest position to the point upon the line.
+    pub position: Point,
+    /// The segment holding [`position`](Self::position), which lies between
+    /// the line's `segment`th and `segment + 1`th points.
+    pub segment: usize,
+    /// The distance, in metres, along the line from its start to
+    /// [`position`](Self::position).
+    pub along: f64,
+}
+
+/// Project `point` onto the closest segment of `line`.
+///
+/// A line of a single segment projects exactly as a straight edge does.
+/// Returns `None` when no segment can locate the point, as for an empty
+/// line.
+pub fn project(line: &LineString, point: &Point) -> Option<Projection> {
+    let mut closest: Option<(f64, Projection)> = None;
+    let mut travelled = 0.0;
+
+    for (segment, part) in line.lines().enumerate() {
+        let start = part.start_point();
+        if let Some(position) = part
+            .line_locate_point(point)
+            .map(|frac| part.point_at_ratio_from_start(&Haversine, frac))
+        {
+            let distance = Haversine.distance(position, *point);
+            if closest.is_none_or(|(best, _)| distance < best) {
+                let along = travelled + Haversine.distance(start, position);
+                closest = Some((
+                    distance,
+                    Projection {
+                        position,
+                        segment,
+                        along,
+                    },
+                ));
+            }
+        }
+
+        travelled += Haversine.distance(start, part.end_point());
+    }
+
+    closest.map(|(_, projection)| projection)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo::{Line, line_string, point};
+
+    #[test]
+    fn projects_onto_the_closest_segment() {
+        // An L: east along the equator, then north.
+        let line = line_string![(x: 0.0, y: 0.0), (x: 0.001, y: 0.0), (x: 0.001, y: 0.001)];
+
+        let projection = project(&line, &point!(x: 0.0012, y: 0.0006)).expect("must project");
+        assert_eq!(projection.segment, 1);
+        assert!((projection.position.x() - 0.001).abs() < 1e-9);
+
+        let first = Haversine.distance(point!(x: 0.0, y: 0.0), point!(x: 0.001, y: 0.0));
+        let rest = Haversine.distance(point!(x: 0.001, y: 0.0), projection.position);
+        assert!((projection.along - (first + rest)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn projects_straight_lines_as_a_single_segment() {
+        let line = Line::new(point!(x: 0.0, y: 0.0), point!(x: 0.001, y: 0.001));
+        let point = point!(x: 0.0, y: 0.001);
+
+        let projection = project(&line.into(), &point).expect("must project");
+        let expected = line
+            .line_locate_point(&point)
+            .map(|frac| line.point_at_ratio_from_start(&Haversine, frac));
+
+        assert_eq!(projection.segment, 0);
+        assert_eq!(Some(projection.position), expected);
+    }
+}
@@ -0,0 +1,49 @@
+use crate::primitive::Edge;
+use crate::traits::Entry;
+
+/// A manoeuvre through a node: arriving along one way and leaving along
+/// another (or the same) way.
+///
+/// Turn restrictions are phrased in ways and the node they meet at, but the
+/// nodes either side of `via` are kept too, so that continuing straight along
+/// a way can be told apart from reversing along it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Turn<E>
+where
+    E: Entry,
+{
+    /// The node the turn is approached from.
+    pub origin: E,
+    /// The way the turn is approached along.
+    pub from: E,
+    /// The node the turn is taken at.
+    pub via: E,
+    /// The way the turn departs along.
+    pub to: E,
+    /// The node the turn departs towards.
+    pub destination: E,
+}
+
+impl<E> Turn<E>
+where
+    E: Entry,
+{
+    /// The turn taken between two consecutive edges, where `from` ends at the
+    /// node `to` starts from.
+    pub fn between(from: &Edge<E>, to: &Edge<E>) -> Self {
+        debug_assert!(from.target == to.source, "edges must be consecutive");
+
+        Self {
+            origin: from.source,
+            from: *from.id(),
+            via: from.target,
+            to: *to.id(),
+            destination: to.target,
+        }
+    }
+
+    /// Whether the turn doubles back along the way it arrived on.
+    pub fn is_reversal(&self) -> bool {
+        self.from == self.to && self.origin == self.destination
+    }
+}
@@ -0,0 +1,52 @@
+use geo::{Distance, Haversine};
+
+use crate::edge::Weight;
+use crate::routing::search::Frontier;
+use crate::routing::{Path, RoutingGraph, RoutingProvider};
+
+/// A* search, settling hops in order of their cost plus an estimate of the
+/// cost remaining: the straight-line distance to the finish, priced at the
+/// network's [`cost_per_metre`](RoutingGraph::cost_per_metre).
+///
+/// As that bound never overestimates, routes are as cheap as those
+/// [`Dijkstra`](super::Dijkstra) finds, while the search is drawn towards
+/// the finish rather than spreading evenly around the start.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AStar;
+
+impl RoutingProvider for AStar {
+    fn route<G: RoutingGraph>(
+        &self,
+        graph: &G,
+        start: G::Entry,
+        finish: G::Entry,
+        runtime: &G::Runtime,
+    ) -> Option<Path<G::Entry>> {
+        if start == finish {
+            return Some((0, vec![start]));
+        }
+
+        let target = graph.point(&finish)?;
+        let cost_per_metre = graph.cost_per_metre();
+
+        // Rounded down, so that the estimate stays a lower bound.
+        let estimate = |node: &G::Entry| match (cost_per_metre > 0.0, graph.point(node)) {
+            (true, Some(point)) => (Haversine.distance(point, target) * cost_per_metre) as Weight,
+            _ => 0,
+        };
+
+        let mut frontier = Frontier::new();
+        frontier.seed(graph, start, runtime, estimate);
+
+        while let Some(index) = frontier.settle() {
+            let label = *frontier.label(index);
+            if label.hop.target == finish {
+                return Some((label.cost, frontier.nodes(index)));
+            }
+
+            frontier.relax(graph, index, runtime, estimate);
+        }
+
+        None
+    }
+}
@@ -0,0 +1,208 @@
+use crate::edge::Weight;
+use crate::routing::search::{Frontier, Hop};
+use crate::routing::{Dijkstra, Path, RoutingGraph, RoutingProvider};
+use crate::{Entry, Turn};
+
+/// Dijkstra's algorithm run from both ends at once: forwards from the start
+/// and backwards from the finish, until the two searches meet along the
+/// cheapest route. Each settles roughly half the radius a one-sided search
+/// would, which pays off on long routes.
+///
+/// The backward search checks each turn against the network's restrictions
+/// without knowing the ways travelled before it, so it cannot see
+/// restrictions spanning more than one junction, such as via-way
+/// restrictions. The route found is therefore checked turn by turn once the
+/// searches meet, and should it break one, searched again with [`Dijkstra`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BidirectionalDijkstra;
+
+impl RoutingProvider for BidirectionalDijkstra {
+    fn route<G: RoutingGraph>(
+        &self,
+        graph: &G,
+        start: G::Entry,
+        finish: G::Entry,
+        runtime: &G::Runtime,
+    ) -> Option<Path<G::Entry>> {
+        if start == finish {
+            return Some((0, vec![start]));
+        }
+
+        // Forward labels cost the route up to and including their hop.
+        let mut forward = Frontier::new();
+        forward.seed(graph, start, runtime, |_| 0);
+
+        // Backward labels cost the route after their hop to the finish, so
+        // the two sum to the full cost wherever they share a hop.
+        let mut backward = Frontier::new();
+        let mut seeds = Vec::new();
+        for edge in graph.edges_into(finish) {
+            if !graph.traversable(&edge, runtime) {
+                continue;
+            }
+
+            let hop = Hop::of(&edge);
+            backward.offer(hop, 0, graph.cost(&edge), 0, None);
+            seeds.push(hop);
+        }
+
+        // The cheapest meeting so far, by the hop the two searches share.
+        let mut best = None;
+        for hop in seeds {
+            meet(&forward, &backward, hop, &mut best);
+        }
+
+        loop {
+            let (ahead, behind) = (forward.peek(), backward.peek());
+            let reach = ahead
+                .unwrap_or(Weight::MAX)
+                .saturating_add(behind.unwrap_or(Weight::MAX));
+
+            if ahead.is_none() && behind.is_none() {
+                break;
+            }
+            if best.is_some_and(|(cost, _)| reach >= cost) {
+                break;
+            }
+
+            let improved = match (ahead, behind) {
+                (Some(ahead), Some(behind)) if ahead > behind => {
+                    relax_backward(graph, &mut backward, runtime)
+                }
+                (Some(_), _) => match forward.settle() {
+                    Some(index) => forward
+                        .relax(graph, index, runtime, |_| 0)
+                        .into_iter()
+                        .map(|index| forward.label(index).hop)
+                        .collect(),
+                    None => Vec::new(),
+                },
+                (None, _) => relax_backward(graph, &mut backward, runtime),
+            };
+
+            for hop in improved {
+                meet(&forward, &backward, hop, &mut best);
+            }
+        }
+
+        let (cost, hop) = best?;
+
+        let mut nodes = forward.nodes(forward.index_of(&hop)?);
+        let mut cursor = backward.get(&hop)?.parent;
+        while let Some(at) = cursor {
+            let label = backward.label(at);
+            nodes.push(label.hop.target);
+            cursor = label.parent;
+        }
+
+        if !permitted(graph, &nodes, runtime) {
+            return Dijkstra.route(graph, start, finish, runtime);
+        }
+
+        Some((cost, nodes))
+    }
+}
+
+/// Whether the network permits every turn along the route through `nodes`,
+/// each checked with the ways travelled before it.
+fn permitted<G>(graph: &G, nodes: &[G::Entry], runtime: &G::Runtime) -> bool
+where
+    G: RoutingGraph,
+{
+    // The cheapest traversable edge between each pair of nodes, as the
+    // searches would have taken.
+    let hops = nodes
+        .windows(2)
+        .map(|pair| {
+            graph
+                .edges_outof(pair[0])
+                .filter(|edge| edge.1 == pair[1] && graph.traversable(edge, runtime))
+                .min_by_key(|edge| graph.cost(edge))
+                .map(|edge| Hop::of(&edge))
+        })
+        .collect::<Option<Vec<_>>>();
+
+    let Some(hops) = hops else {
+        return false;
+    };
+
+    (1..hops.len()).all(|index| {
+        let (from, to) = (hops[index - 1], hops[index]);
+        let turn = Turn {
+            origin: from.source,
+            from: from.way,
+            via: from.target,
+            to: to.way,
+            destination: to.target,
+        };
+
+        let mut approach = hops[..index - 1].iter().rev().map(|hop| hop.way);
+        graph.turn_permitted(&turn, &mut approach, runtime)
+    })
+}
+
+/// Record the route through `hop` in `best`, if both searches have reached
+/// it and it is cheaper than the best route so far.
+fn meet<E>(
+    forward: &Frontier<E>,
+    backward: &Frontier<E>,
+    hop: Hop<E>,
+    best: &mut Option<(Weight, Hop<E>)>,
+) where
+    E: Entry,
+{
+    if let (Some(ahead), Some(behind)) = (forward.get(&hop), backward.get(&hop)) {
+        let cost = ahead.cost.saturating_add(behind.cost);
+        if best.is_none_or(|(known, _)| cost < known) {
+            *best = Some((cost, hop));
+        }
+    }
+}
+
+/// Settle the cheapest backward label and offer each hop from which the
+/// network permits turning onto it, returning the hops improved.
+fn relax_backward<G>(
+    graph: &G,
+    backward: &mut Frontier<G::Entry>,
+    runtime: &G::Runtime,
+) -> Vec<Hop<G::Entry>>
+where
+    G: RoutingGraph,
+{
+    let Some(index) = backward.settle() else {
+        return Vec::new();
+    };
+
+    let label = *backward.label(index);
+    let cost = label.cost.saturating_add(label.step);
+    let mut improved = Vec::new();
+
+    for edge in graph.edges_into(label.hop.source) {
+        if !graph.traversable(&edge, runtime) {
+            continue;
+        }
+
+        let previous = Hop::of(&edge);
+        let turn = Turn {
+            origin: previous.source,
+            from: previous.way,
+            via: label.hop.source,
+            to: label.hop.way,
+            destination: label.hop.target,
+        };
+
+        if !graph.turn_permitted(&turn, &mut core::iter::empty(), runtime) {
+            continue;
+        }
+
+        let step = graph.cost(&edge);
+        if backward
+            .offer(previous, cost, step, cost, Some(index))
+            .is_some()
+        {
+            improved.push(previous);
+        }
+    }
+
+    improved
+}
@@ -0,0 +1,64 @@
+use rustc_hash::FxHashMap;
+
+use crate::routing::search::Frontier;
+use crate::routing::{Path, RoutingGraph, RoutingProvider};
+
+/// Dijkstra's algorithm, settling hops outwards from the start in order of
+/// cost.
+///
+/// A one-to-many query runs a single search, stopping once every finish has
+/// been settled, so it costs no more than routing to the furthest of them.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Dijkstra;
+
+impl RoutingProvider for Dijkstra {
+    fn route<G: RoutingGraph>(
+        &self,
+        graph: &G,
+        start: G::Entry,
+        finish: G::Entry,
+        runtime: &G::Runtime,
+    ) -> Option<Path<G::Entry>> {
+        self.route_many(graph, start, &[finish], runtime).pop()?
+    }
+
+    fn route_many<G: RoutingGraph>(
+        &self,
+        graph: &G,
+        start: G::Entry,
+        finishes: &[G::Entry],
+        runtime: &G::Runtime,
+    ) -> Vec<Option<Path<G::Entry>>> {
+        let mut routes = vec![None; finishes.len()];
+
+        // Where in `finishes` each node is asked for, as it may be repeated.
+        let mut remaining: FxHashMap<G::Entry, Vec<usize>> = FxHashMap::default();
+        for (position, &finish) in finishes.iter().enumerate() {
+            match finish == start {
+                true => routes[position] = Some((0, vec![start])),
+                false => remaining.entry(finish).or_default().push(position),
+            }
+        }
+
+        let mut frontier = Frontier::new();
+        frontier.seed(graph, start, runtime, |_| 0);
+
+        while !remaining.is_empty() {
+            let Some(index) = frontier.settle() else {
+                break;
+            };
+
+            let label = *frontier.label(index);
+            if let Some(positions) = remaining.remove(&label.hop.target) {
+                let nodes = frontier.nodes(index);
+                for position in positions {
+                    routes[position] = Some((label.cost, nodes.clone()));
+                }
+            }
+
+            frontier.relax(graph, index, runtime, |_| 0);
+        }
+
+        routes
+    }
+}
@@ -0,0 +1,168 @@
+use core::iter;
+
+use geo::{Distance, Haversine};
+
+use crate::edge::Weight;
+use crate::{DataPlane, Node};
+
+/// The cheapest route from an origin to a destination, in brief.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Leg {
+    /// The cost of the route, in the network's units.
+    pub cost: Weight,
+    /// The length of the route, in metres.
+    pub distance: f64,
+}
+
+impl Leg {
+    /// The leg of a route through `network` costing `cost`, which visits
+    /// `nodes` in order. Its length follows each edge's
+    /// [shape](DataPlane::shape) between the nodes.
+    pub fn along<N: DataPlane + ?Sized>(
+        network: &N,
+        cost: Weight,
+        nodes: &[Node<N::Entry>],
+    ) -> Self {
+        let distance = nodes
+            .windows(2)
+            .map(|pair| {
+                let shape = network.shape(&pair[0].id, &pair[1].id);
+                let points = iter::once(&pair[0].position)
+                    .chain(shape)
+                    .chain(iter::once(&pair[1].position));
+
+                points
+                    .clone()
+                    .zip(points.skip(1))
+                    .map(|(&a, &b)| Haversine.distance(a, b))
+                    .sum::<f64>()
+            })
+            .sum();
+
+        Leg { cost, distance }
+    }
+}
+
+/// The [`Leg`] from each of a set of origins to each of a set of
+/// destinations, or `None` where no route joins the two.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Matrix {
+    origins: usize,
+    destinations: usize,
+    /// Cells in row-major order: every destination of the first origin, then
+    /// of the second, and so on.
+    cells: Vec<Option<Leg>>,
+}
+
+impl Matrix {
+    /// Assemble a matrix from one row per origin, each holding a cell per
+    /// destination.
+    ///
+    /// ### Panics
+    /// If any row does not hold exactly `destinations` cells.
+    pub fn from_rows(
+        destinations: usize,
+        rows: impl IntoIterator<Item = Vec<Option<Leg>>>,
+    ) -> Self {
+        let mut matrix = Matrix {
+            origins: 0,
+            destinations,
+            cells: Vec::new(),
+        };
+
+        for row in rows {
+            assert_eq!(
+                row.len(),
+                destinations,
+                "matrix rows must be of equal length"
+            );
+            matrix.cells.extend(row);
+            matrix.origins += 1;
+        }
+
+        matrix
+    }
+
+    pub fn origins(&self) -> usize {
+        self.origins
+    }
+
+    pub fn destinations(&self) -> usize {
+        self.destinations
+    }
+
+    /// The leg from the `origin`-th origin to the `destination`-th
+    /// destination, or `None` if it is unreachable or out of bounds.
+    pub fn get(&self, origin: usize, destination: usize) -> Option<Leg> {
+        if origin >= self.origins || destination >= self.destinations {
+            return None;
+        }
+
+        self.cells[origin * self.destinations + destination]
+    }
+
+    /// Each origin's row of cells, in order.
+    pub fn rows(&self) -> impl Iterator<Item = &[Option<Leg>]> + '_ {
+        (0..self.origins)
+            .map(|origin| &self.cells[origin * self.destinations..(origin + 1) * self.destinations])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Route;
+    use crate::mock::{MockEntryId, MockNetworkBuilder};
+    use geo::point;
+
+    #[test]
+    fn cells_match_routing_each_pair() {
+        let network = MockNetworkBuilder::new()
+            .node(1, point!(x: 0.000, y: 0.0))
+            .node(2, point!(x: 0.001, y: 0.0))
+            .node(3, point!(x: 0.002, y: 0.0))
+            .node(4, point!(x: 0.003, y: 0.0))
+            .bidirectional_edge(1, 2)
+            .bidirectional_edge(2, 3)
+            .edge(3, 4)
+            .build();
+
+        let origins = [1, 4].map(MockEntryId);
+        let destinations = [1, 3, 4].map(MockEntryId);
+        let matrix = network.matrix(&origins, &destinations, &());
+
+        assert_eq!((matrix.origins(), matrix.destinations()), (2, 3));
+        for (row, &origin) in matrix.rows().zip(&origins) {
+            for (cell, &destination) in row.iter().zip(&destinations) {
+                let routed = network
+                    .route_nodes(origin, destination, &())
+                    .map(|(cost, nodes)| Leg::along(&network, cost, &nodes));
+                assert_eq!(*cell, routed, "{origin:?} -> {destination:?}");
+            }
+        }
+
+        // One-way out of 4, so nothing is reachable from it but itself.
+        assert_eq!(matrix.get(1, 0), None);
+        assert_eq!(matrix.get(1, 2).map(|leg| leg.cost), Some(0));
+        assert!(matrix.get(0, 2).is_some_and(|leg| leg.distance > 300.0));
+    }
+
+    #[test]
+    fn unsnapped_points_are_unreachable() {
+        let network = MockNetworkBuilder::new()
+            .node(1, point!(x: 0.000, y: 0.0))
+            .node(2, point!(x: 0.001, y: 0.0))
+            .bidirectional_edge(1, 2)
+            .build();
+
+        let empty = MockNetworkBuilder::new().build();
+        let points = [point!(x: 0.0, y: 0.0), point!(x: 0.001, y: 0.0)];
+
+        let matrix = network.matrix_points(&points, &points, &());
+        assert!(matrix.rows().flatten().all(Option::is_some));
+
+        let matrix = empty.matrix_points(&points, &points, &());
+        assert_eq!((matrix.origins(), matrix.destinations()), (2, 2));
+        assert!(matrix.rows().flatten().all(Option::is_none));
+    }
+}
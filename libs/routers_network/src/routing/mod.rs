@@ -0,0 +1,254 @@
+//! Point-to-point search strategies behind [`Route`](crate::Route).
+//!
+//! A network describes what a route may use by implementing
+//! [`RoutingGraph`]: the cost of each edge and, optionally, a lower bound on
+//! the cost of a metre of travel. A [`RoutingProvider`] searches it. The
+//! providers here are
+//!
+//! - [`Dijkstra`], which answers one-to-many queries in a single search,
+//! - [`AStar`], guided by the straight-line distance to the finish, and
+//! - [`BidirectionalDijkstra`], which grows a search from either end.
+//!
+//! Networks pick one at construction through [`Routing`].
+//!
+//! Every provider searches *edges* rather than nodes, so that the turn
+//! between each pair of edges can be checked with
+//! [`DataPlane::turn_permitted`](crate::DataPlane::turn_permitted).
+//!
+//! Many-to-many queries, through [`Route::matrix`](crate::Route::matrix),
+//! are summarised as a [`Matrix`] of [`Leg`]s.
+
+mod astar;
+mod bidirectional;
+mod dijkstra;
+mod matrix;
+mod search;
+
+pub use astar::AStar;
+pub use bidirectional::BidirectionalDijkstra;
+pub use dijkstra::Dijkstra;
+pub use matrix::{Leg, Matrix};
+
+use crate::edge::Weight;
+use crate::network::GraphEdge;
+use crate::{DataPlane, Metadata};
+
+/// A route found by a [`RoutingProvider`]: its total cost, and the nodes it
+/// visits from start to finish inclusive.
+pub type Path<E> = (Weight, Vec<E>);
+
+/// A network a [`RoutingProvider`] can search.
+///
+/// The topology, positions and turn restrictions come from the
+/// [`DataPlane`]; this adds what a search needs to price a route.
+pub trait RoutingGraph: DataPlane {
+    /// Whether `edge` may be travelled, in its direction, under `runtime`.
+    ///
+    /// Defaults to asking the [`Metadata`] of the edge's way. Edges whose way
+    /// has no metadata are assumed open.
+    fn traversable(&self, edge: &GraphEdge<Self::Entry>, runtime: &Self::Runtime) -> bool {
+        let (_, _, (_, id)) = edge;
+        self.metadata(&id.index())
+            .is_none_or(|meta| meta.accessible(runtime, id.direction()))
+    }
+
+    /// The cost of travelling along `edge`. Defaults to the edge's weight.
+    fn cost(&self, edge: &GraphEdge<Self::Entry>) -> Weight {
+        let (_, _, (weight, _)) = edge;
+        *weight
+    }
+
+    /// A lower bound on the cost of travelling one metre anywhere in the
+    /// network, used by [`AStar`] to estimate the cost remaining to the
+    /// finish.
+    ///
+    /// It must never exceed the true cost, or routes are no longer
+    /// guaranteed optimal. Networks whose costs do not grow with length
+    /// should keep the default of zero, which reduces [`AStar`] to
+    /// [`Dijkstra`].
+    fn cost_per_metre(&self) -> f64 {
+        0.0
+    }
+}
+
+/// A strategy for finding the cheapest route through a [`RoutingGraph`].
+pub trait RoutingProvider {
+    /// The cheapest route from `start` to `finish` which travels only edges
+    /// [traversable](RoutingGraph::traversable) under `runtime`, and takes
+    /// only turns the network permits under it, or `None` if there is none.
+    fn route<G: RoutingGraph>(
+        &self,
+        graph: &G,
+        start: G::Entry,
+        finish: G::Entry,
+        runtime: &G::Runtime,
+    ) -> Option<Path<G::Entry>>;
+
+    /// The cheapest route from `start` to each of `finishes`, in order.
+    ///
+    /// By default each finish is searched for on its own.
+    fn route_many<G: RoutingGraph>(
+        &self,
+        graph: &G,
+        start: G::Entry,
+        finishes: &[G::Entry],
+        runtime: &G::Runtime,
+    ) -> Vec<Option<Path<G::Entry>>> {
+        finishes
+            .iter()
+            .map(|&finish| self.route(graph, start, finish, runtime))
+            .collect()
+    }
+}
+
+/// The [`RoutingProvider`] a network routes with, selected when it is
+/// constructed.
+///
+/// Whichever is chosen for point-to-point queries, one-to-many queries are
+/// answered by a single [`Dijkstra`] search, which settles every finish at
+/// once.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Routing {
+    /// See [`Dijkstra`].
+    Dijkstra,
+    /// See [`AStar`].
+    #[default]
+    AStar,
+    /// See [`BidirectionalDijkstra`].
+    Bidirectional,
+}
+
+impl RoutingProvider for Routing {
+    fn route<G: RoutingGraph>(
+        &self,
+        graph: &G,
+        start: G::Entry,
+        finish: G::Entry,
+        runtime: &G::Runtime,
+    ) -> Option<Path<G::Entry>> {
+        match self {
+            Routing::Dijkstra => Dijkstra.route(graph, start, finish, runtime),
+            Routing::AStar => AStar.route(graph, start, finish, runtime),
+            Routing::Bidirectional => BidirectionalDijkstra.route(graph, start, finish, runtime),
+        }
+    }
+
+    fn route_many<G: RoutingGraph>(
+        &self,
+        graph: &G,
+        start: G::Entry,
+        finishes: &[G::Entry],
+        runtime: &G::Runtime,
+    ) -> Vec<Option<Path<G::Entry>>> {
+        match finishes {
+            [finish] => vec![self.route(graph, start, *finish, runtime)],
+            _ => Dijkstra.route_many(graph, start, finishes, runtime),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockEntryId, MockNetwork, MockNetworkBuilder};
+    use geo::point;
+
+    /// A grid of three rows and columns, every edge two-way.
+    ///
+    /// ```text
+    ///   7 ── 8 ── 9
+    ///   │    │    │
+    ///   4 ── 5 ── 6
+    ///   │    │    │
+    ///   1 ── 2 ── 3
+    /// ```
+    fn grid() -> MockNetworkBuilder {
+        let mut builder = MockNetworkBuilder::new();
+        for id in 1..=9 {
+            let (x, y) = ((id - 1) % 3, (id - 1) / 3);
+            builder = builder.node(id, point!(x: x as f64 * 0.001, y: y as f64 * 0.001));
+        }
+
+        for (a, b) in [(1, 2), (2, 3), (4, 5), (5, 6), (7, 8), (8, 9)] {
+            builder = builder.bidirectional_edge(a, b);
+        }
+        for (a, b) in [(1, 4), (4, 7), (2, 5), (5, 8), (3, 6), (6, 9)] {
+            builder = builder.bidirectional_edge(a, b);
+        }
+
+        builder
+    }
+
+    const PROVIDERS: [Routing; 3] = [Routing::Dijkstra, Routing::AStar, Routing::Bidirectional];
+
+    fn cost(network: &MockNetwork, routing: Routing, start: i64, finish: i64) -> Option<Weight> {
+        routing
+            .route(network, MockEntryId(start), MockEntryId(finish), &())
+            .map(|(cost, _)| cost)
+    }
+
+    fn nodes(path: Path<MockEntryId>) -> Vec<i64> {
+        path.1.iter().map(|node| node.0).collect()
+    }
+
+    #[test]
+    fn providers_agree_on_cost() {
+        let network = grid().build();
+
+        for start in 1..=9 {
+            for finish in 1..=9 {
+                let costs = PROVIDERS.map(|routing| cost(&network, routing, start, finish));
+                assert!(
+                    costs.iter().all(|cost| *cost == costs[0]),
+                    "{start} -> {finish} costs differ: {costs:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn a_route_to_the_start_is_empty() {
+        let network = grid().build();
+
+        for routing in PROVIDERS {
+            let path = routing.route(&network, MockEntryId(5), MockEntryId(5), &());
+            assert_eq!(path.map(nodes), Some(vec![5]));
+        }
+    }
+
+    #[test]
+    fn providers_route_around_a_forbidden_turn() {
+        // Straight on from 1 through 2 to 3, then forbid turning north at 3.
+        let network = grid().forbid_turn(2, 3, 6).build();
+
+        for routing in PROVIDERS {
+            let path = routing
+                .route(&network, MockEntryId(1), MockEntryId(6), &())
+                .expect("a route exists");
+
+            assert_eq!(path.0, 3, "{routing:?} took a longer way round");
+            assert_ne!(nodes(path), vec![1, 2, 3, 6], "{routing:?} took the turn");
+        }
+    }
+
+    #[test]
+    fn unreachable_finishes_have_no_route() {
+        let network = grid().node(10, point!(x: 1.0, y: 1.0)).build();
+
+        for routing in PROVIDERS {
+            assert_eq!(cost(&network, routing, 1, 10), None, "{routing:?}");
+        }
+    }
+
+    #[test]
+    fn one_to_many_matches_one_to_one() {
+        let network = grid().forbid_turn(2, 3, 6).build();
+        let finishes = (1..=9).map(MockEntryId).collect::<Vec<_>>();
+
+        let many = Dijkstra.route_many(&network, MockEntryId(1), &finishes, &());
+        for (finish, path) in finishes.iter().zip(many) {
+            let single = AStar.route(&network, MockEntryId(1), *finish, &());
+            assert_eq!(path.map(|p| p.0), single.map(|p| p.0), "to {finish:?}");
+        }
+    }
+}
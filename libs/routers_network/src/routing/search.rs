@@ -0,0 +1,236 @@
+//! The edge-based search state shared by every provider.
+
+use alloc::collections::BinaryHeap;
+use core::cmp::Reverse;
+
+use rustc_hash::FxHashMap;
+
+use crate::edge::Weight;
+use crate::network::GraphEdge;
+use crate::routing::RoutingGraph;
+use crate::{Entry, Turn};
+
+/// A directed edge of the network, as the state of a search.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct Hop<E> {
+    pub source: E,
+    pub target: E,
+    /// The way the edge belongs to.
+    pub way: E,
+}
+
+impl<E> Hop<E>
+where
+    E: Entry,
+{
+    pub fn of(&(source, target, (_, id)): &GraphEdge<E>) -> Self {
+        Hop {
+            source,
+            target,
+            way: id.index(),
+        }
+    }
+}
+
+/// A hop reached by a search, and how.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Label<E> {
+    pub hop: Hop<E>,
+    /// The cost accumulated up to this hop.
+    pub cost: Weight,
+    /// The cost of the hop's own edge.
+    pub step: Weight,
+    /// The order labels leave the frontier in: the cost plus any estimate of
+    /// what remains.
+    priority: Weight,
+    /// The label this hop was reached from, if it was not a starting hop.
+    pub parent: Option<usize>,
+    settled: bool,
+}
+
+/// One direction of a search: every hop reached so far, and a queue of
+/// those yet to be settled, cheapest first.
+///
+/// Labels live in an arena so that parent pointers are plain indices.
+pub(crate) struct Frontier<E> {
+    labels: Vec<Label<E>>,
+    index: FxHashMap<Hop<E>, usize>,
+    queue: BinaryHeap<Reverse<(Weight, usize)>>,
+}
+
+impl<E> Frontier<E>
+where
+    E: Entry,
+{
+    pub fn new() -> Self {
+        Self {
+            labels: Vec::new(),
+            index: FxHashMap::default(),
+            queue: BinaryHeap::new(),
+        }
+    }
+
+    pub fn label(&self, index: usize) -> &Label<E> {
+        &self.labels[index]
+    }
+
+    /// The index of the label of `hop`, if it has been reached.
+    pub fn index_of(&self, hop: &Hop<E>) -> Option<usize> {
+        self.index.get(hop).copied()
+    }
+
+    /// The label of `hop`, if it has been reached.
+    pub fn get(&self, hop: &Hop<E>) -> Option<&Label<E>> {
+        self.index_of(hop).map(|index| &self.labels[index])
+    }
+
+    /// Reach `hop` at `cost`, unless it is already reached more cheaply.
+    /// Returns the index of its label when it was improved.
+    pub fn offer(
+        &mut self,
+        hop: Hop<E>,
+        cost: Weight,
+        step: Weight,
+        priority: Weight,
+        parent: Option<usize>,
+    ) -> Option<usize> {
+        let label = Label {
+            hop,
+            cost,
+            step,
+            priority,
+            parent,
+            settled: false,
+        };
+
+        let index = match self.index.get(&hop) {
+            Some(&index) if self.labels[index].settled || self.labels[index].cost <= cost => {
+                return None;
+            }
+            Some(&index) => {
+                self.labels[index] = label;
+                index
+            }
+            None => {
+                self.labels.push(label);
+                self.index.insert(hop, self.labels.len() - 1);
+                self.labels.len() - 1
+            }
+        };
+
+        self.queue.push(Reverse((priority, index)));
+        Some(index)
+    }
+
+    /// Settle the cheapest unsettled label, returning its index.
+    pub fn settle(&mut self) -> Option<usize> {
+        while let Some(Reverse((priority, index))) = self.queue.pop() {
+            let label = &mut self.labels[index];
+            if label.settled || label.priority != priority {
+                continue;
+            }
+
+            label.settled = true;
+            return Some(index);
+        }
+
+        None
+    }
+
+    /// The lowest priority left to settle, or `None` once exhausted. Entries
+    /// made stale by a later improvement may make this an underestimate.
+    pub fn peek(&self) -> Option<Weight> {
+        self.queue.peek().map(|Reverse((priority, _))| *priority)
+    }
+
+    /// The ways travelled before the hop of label `index`, most recent first.
+    pub fn approach(&self, index: usize) -> impl Iterator<Item = E> + '_ {
+        core::iter::successors(self.labels[index].parent, |&at| self.labels[at].parent)
+            .map(|at| self.labels[at].hop.way)
+    }
+
+    /// The nodes from the start of the search to the target of label
+    /// `index`, inclusive.
+    pub fn nodes(&self, index: usize) -> Vec<E> {
+        let mut nodes = vec![self.labels[index].hop.target];
+        let mut cursor = Some(index);
+
+        while let Some(at) = cursor {
+            nodes.push(self.labels[at].hop.source);
+            cursor = self.labels[at].parent;
+        }
+
+        nodes.reverse();
+        nodes
+    }
+
+    /// Offer each traversable hop leaving `start`, with the cost of its edge.
+    pub fn seed<G>(
+        &mut self,
+        graph: &G,
+        start: E,
+        runtime: &G::Runtime,
+        estimate: impl Fn(&E) -> Weight,
+    ) where
+        G: RoutingGraph<Entry = E>,
+    {
+        for edge in graph.edges_outof(start) {
+            if !graph.traversable(&edge, runtime) {
+                continue;
+            }
+
+            let hop = Hop::of(&edge);
+            let cost = graph.cost(&edge);
+            self.offer(
+                hop,
+                cost,
+                cost,
+                cost.saturating_add(estimate(&hop.target)),
+                None,
+            );
+        }
+    }
+
+    /// Offer each traversable hop the network permits turning onto from the
+    /// hop of label `index`, returning the indices of the labels improved.
+    pub fn relax<G>(
+        &mut self,
+        graph: &G,
+        index: usize,
+        runtime: &G::Runtime,
+        estimate: impl Fn(&E) -> Weight,
+    ) -> Vec<usize>
+    where
+        G: RoutingGraph<Entry = E>,
+    {
+        let Label { hop, cost, .. } = self.labels[index];
+        let mut improved = Vec::new();
+
+        for edge in graph.edges_outof(hop.target) {
+            if !graph.traversable(&edge, runtime) {
+                continue;
+            }
+
+            let next = Hop::of(&edge);
+            let turn = Turn {
+                origin: hop.source,
+                from: hop.way,
+                via: hop.target,
+                to: next.way,
+                destination: next.target,
+            };
+
+            if !graph.turn_permitted(&turn, &mut self.approach(index), runtime) {
+                continue;
+            }
+
+            let step = graph.cost(&edge);
+            let next_cost = cost.saturating_add(step);
+            let priority = next_cost.saturating_add(estimate(&next.target));
+
+            improved.extend(self.offer(next, next_cost, step, priority, Some(index)));
+        }
+
+        improved
+    }
+}
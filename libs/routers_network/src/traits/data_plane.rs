@@ -18,8 +18,8 @@
 use alloc::sync::Arc;
 use core::fmt::Debug;
 
-use crate::{DirectionAwareEdgeId, Edge, Entry, Metadata, Node, edge::Weight};
-use geo::Point;
+use crate::{DirectionAwareEdgeId, Edge, Entry, Metadata, Node, Turn, edge::Weight};
+use geo::{LineString, Point};
 
 pub type EdgeData<E> = (Weight, DirectionAwareEdgeId<E>);
 pub type GraphEdge<E> = (E, E, EdgeData<E>);
@@ -57,12 +57,57 @@ pub trait DataPlane: Debug + Send + Sync {
     /// Produces an iterator of points for a given input.
     ///
     /// All provided nodes that do not exist will not be returned, so the iterator's
-    /// length may be smaller than the input slice.
+    /// length may be smaller than the input slice. Only the nodes' own positions
+    /// are given; see [`edge_line`](Self::edge_line) for the shape between them.
     fn line(&self, nodes: &[Self::Entry]) -> Vec<Point> {
         nodes.iter().filter_map(|node| self.point(node)).collect()
     }
 
+    /// The positions the edge from `source` to `target` bends through,
+    /// excluding its ends, in the direction of travel.
+    ///
+    /// An edge may stand in for a run of road which is not straight, such as
+    /// one left by simplifying the network, and keeps its shape here. Networks
+    /// which draw every edge straight between its ends keep none, which is
+    /// the default.
+    fn shape(&self, _source: &Self::Entry, _target: &Self::Entry) -> &[Point] {
+        &[]
+    }
+
+    /// The edge from `source` to `target` as drawn: its ends, and its
+    /// [`shape`](Self::shape) between them. `None` when either end does not
+    /// exist.
+    fn edge_line(&self, source: &Self::Entry, target: &Self::Entry) -> Option<LineString> {
+        let shape = self.shape(source, target);
+
+        let mut points = Vec::with_capacity(shape.len() + 2);
+        points.push(self.point(source)?);
+        points.extend_from_slice(shape);
+        points.push(self.point(target)?);
+
+        Some(LineString::from(points))
+    }
+
     fn fatten(&self, edge: &Edge<Self::Entry>) -> Option<Edge<Node<Self::Entry>>>;
+
+    /// Whether `turn` may be taken under `runtime`, according to the
+    /// network's turn restrictions.
+    ///
+    /// `approach` yields the ways travelled before `turn.from`, most recent
+    /// first. It is only drawn from to match restrictions spanning more than
+    /// one junction, such as a via-way restriction, so callers may pass an
+    /// empty iterator where the history is unknown.
+    ///
+    /// Networks without turn restrictions permit every turn, which is the
+    /// default.
+    fn turn_permitted(
+        &self,
+        _turn: &Turn<Self::Entry>,
+        _approach: &mut dyn Iterator<Item = Self::Entry>,
+        _runtime: &Self::Runtime,
+    ) -> bool {
+        true
+    }
 }
 
 // Blanket forward through `Arc<T>` so consumers (e.g. a viewer that swaps
@@ -103,7 +148,20 @@ where
         (**self).line(nodes)
     }
 
+    fn shape(&self, source: &Self::Entry, target: &Self::Entry) -> &[Point] {
+        (**self).shape(source, target)
+    }
+
     fn fatten(&self, edge: &Edge<Self::Entry>) -> Option<Edge<Node<Self::Entry>>> {
         (**self).fatten(edge)
     }
+
+    fn turn_permitted(
+        &self,
+        turn: &Turn<Self::Entry>,
+        approach: &mut dyn Iterator<Item = Self::Entry>,
+        runtime: &Self::Runtime,
+    ) -> bool {
+        (**self).turn_permitted(turn, approach, runtime)
+    }
 }
@@ -25,6 +25,13 @@ pub trait Metadata: Clone + Debug + Serialize + Send + Sync {
     /// TODO: Describe
     fn accessible(&self, access: &Self::Runtime, direction: Direction) -> bool;
 
+    /// The highest speed, in metres per second, a vehicle described by
+    /// `runtime` may legally travel the edge at, where the edge is tagged
+    /// with one. Networks without speed limits report `None`.
+    fn permitted_speed(&self, _runtime: &Self::Runtime) -> Option<f64> {
+        None
+    }
+
     /// The default runtime for the specific metadata implementation
     fn default_runtime() -> Self::Runtime {
         Self::runtime(None)
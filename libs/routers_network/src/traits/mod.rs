@@ -13,5 +13,5 @@ pub use discovery::Discovery;
 pub use entry::Entry;
 pub use metadata::Metadata;
 pub use network::Network;
-pub use route::Route;
+pub use route::{Route, RoutedNodes};
 pub use scan::Scan;
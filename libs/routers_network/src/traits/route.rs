@@ -2,19 +2,112 @@ use alloc::sync::Arc;
 
 use geo::Point;
 
+use rayon::prelude::*;
+
+use crate::routing::{Leg, Matrix};
 use crate::{Node, Scan, edge::Weight};
 #[cfg(feature = "tracing")]
 use tracing::Level;
 
+/// A routed path: its cost, and the nodes it visits from start to finish.
+pub type RoutedNodes<E> = (Weight, Vec<Node<E>>);
+
+/// Point-to-point routing over a network.
+///
+/// Every query takes the network's [`Runtime`](crate::DataPlane::Runtime),
+/// describing the trip being routed: routes only travel edges accessible to
+/// it, in the direction travelled, and only take turns permitted to it.
+///
+/// Implementors typically delegate the search itself to a
+/// [`RoutingProvider`](crate::RoutingProvider), chosen at construction
+/// through [`Routing`](crate::Routing).
 pub trait Route: Scan {
-    // Note to self, allow changing the strategy by making this a provider not just an attachment trait
-    /// TODO: Routes ...
+    /// Finds the cheapest route between two nodes.
+    /// Returns the cost and routing node vector.
     fn route_nodes(
         &self,
         start_node: Self::Entry,
         finish_node: Self::Entry,
+        runtime: &Self::Runtime,
     ) -> Option<(Weight, Vec<Node<Self::Entry>>)>;
 
+    /// Finds the cheapest route from one node to each of many, in order.
+    ///
+    /// By default each is routed on its own, through
+    /// [`route_nodes`](Self::route_nodes).
+    fn route_many(
+        &self,
+        start_node: Self::Entry,
+        finish_nodes: &[Self::Entry],
+        runtime: &Self::Runtime,
+    ) -> Vec<Option<RoutedNodes<Self::Entry>>> {
+        finish_nodes
+            .iter()
+            .map(|&finish| self.route_nodes(start_node, finish, runtime))
+            .collect()
+    }
+
+    /// Finds the cheapest route from each of `origins` to each of
+    /// `destinations`, in brief.
+    ///
+    /// By default each origin's row is found by one
+    /// [`route_many`](Self::route_many) search, with rows searched in
+    /// parallel.
+    fn matrix(
+        &self,
+        origins: &[Self::Entry],
+        destinations: &[Self::Entry],
+        runtime: &Self::Runtime,
+    ) -> Matrix {
+        let rows: Vec<Vec<Option<Leg>>> = origins
+            .par_iter()
+            .map(|&origin| {
+                self.route_many(origin, destinations, runtime)
+                    .into_iter()
+                    .map(|route| route.map(|(cost, nodes)| Leg::along(self, cost, &nodes)))
+                    .collect()
+            })
+            .collect();
+
+        Matrix::from_rows(destinations.len(), rows)
+    }
+
+    /// Finds the cheapest route from each of `origins` to each of
+    /// `destinations`, each point routed from its nearest node. Points with
+    /// no nearest node can reach, and be reached by, nothing.
+    fn matrix_points(
+        &self,
+        origins: &[Point],
+        destinations: &[Point],
+        runtime: &Self::Runtime,
+    ) -> Matrix {
+        let snap = |points: &[Point]| -> Vec<Option<Self::Entry>> {
+            points
+                .iter()
+                .map(|point| self.nearest_node(point).map(|node| node.id))
+                .collect()
+        };
+
+        let (origins, destinations) = (snap(origins), snap(destinations));
+        let snapped = |nodes: &[Option<Self::Entry>]| nodes.iter().flatten().copied().collect();
+        let (from, to): (Vec<_>, Vec<_>) = (snapped(&origins), snapped(&destinations));
+        let inner = self.matrix(&from, &to, runtime);
+
+        // Spread the snapped points' cells back over every point.
+        let mut rows = inner.rows();
+        let rows = origins.iter().map(|origin| {
+            let row = origin.and_then(|_| rows.next());
+            let mut cells = row.unwrap_or_default().iter();
+
+            destinations
+                .iter()
+                .map(|destination| destination.and_then(|_| *cells.next()?))
+                .collect()
+        });
+
+        Matrix::from_rows(destinations.len(), rows.collect::<Vec<_>>())
+    }
+
     /// Finds the optimal route between a start and end point.
     /// Returns the weight and routing node vector.
     #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, level = Level::INFO))]
@@ -22,11 +115,12 @@ pub trait Route: Scan {
         &self,
         start: &Point,
         finish: &Point,
+        runtime: &Self::Runtime,
     ) -> Option<(Weight, Vec<Node<Self::Entry>>)> {
         let start_node = self.nearest_node(start)?;
         let finish_node = self.nearest_node(finish)?;
 
-        self.route_nodes(start_node.id, finish_node.id)
+        self.route_nodes(start_node.id, finish_node.id, runtime)
     }
 }
 
@@ -38,7 +132,26 @@ where
         &self,
         start: Self::Entry,
         finish: Self::Entry,
+        runtime: &Self::Runtime,
     ) -> Option<(Weight, Vec<Node<Self::Entry>>)> {
-        (**self).route_nodes(start, finish)
+        (**self).route_nodes(start, finish, runtime)
+    }
+
+    fn route_many(
+        &self,
+        start: Self::Entry,
+        finishes: &[Self::Entry],
+        runtime: &Self::Runtime,
+    ) -> Vec<Option<RoutedNodes<Self::Entry>>> {
+        (**self).route_many(start, finishes, runtime)
+    }
+
+    fn matrix(
+        &self,
+        origins: &[Self::Entry],
+        destinations: &[Self::Entry],
+        runtime: &Self::Runtime,
+    ) -> Matrix {
+        (**self).matrix(origins, destinations, runtime)
     }
 }
@@ -2,7 +2,7 @@ use alloc::sync::Arc;
 
 use geo::{Haversine, InterpolatableLine, Line, LineLocatePoint, Point};
 
-use crate::{Discovery, Edge, Node};
+use crate::{Discovery, Edge, Node, project};
 
 /// Trait containing utility functions to find nodes on a root structure.
 pub trait Scan: Discovery {
@@ -16,7 +16,8 @@ pub trait Scan: Discovery {
     /// ### Note
     /// This is achieved by creating a line from every edge in the iteration, and finding
     /// the closest point upon that line to the source [point](Point).
-    /// This is a bounded projection.
+    /// This is a bounded projection. An edge with a [shape](crate::DataPlane::shape)
+    /// is projected onto its closest segment instead.
     ///
     /// [`Projected`]: https://en.wikipedia.org/wiki/Projection_(linear_algebra)
     fn nearest_nodes_projected<'a>(
@@ -29,6 +30,12 @@ pub trait Scan: Discovery {
             self.edges_at_distance(point, distance)
                 .into_iter()
                 .filter_map(move |edge| {
+                    let shape = self.shape(&edge.source.id, &edge.target.id);
+                    if !shape.is_empty() {
+                        let line = self.edge_line(&edge.source.id, &edge.target.id)?;
+                        return project(&line, point).map(|at| (at.position, edge));
+                    }
+
                     let line = Line::new(edge.source.position, edge.target.position);
 
                     // We locate the point upon the linestring,
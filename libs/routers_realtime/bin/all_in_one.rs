@@ -0,0 +1,196 @@
+/// The whole realtime pipeline in one process: replays a recording through
+/// the orchestrator, a matcher per shard in the directory, and the
+/// reconciler — over the in-memory bus and stores, so neither NATS nor
+/// Valkey is needed. For laptops and end-to-end checks, not production.
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use routers_codec::osm::{OsmEdgeMetadata, OsmEntryId};
+use routers_realtime::{
+    bus::{Bus, MemoryBus, Wire},
+    event::RawEvent,
+    ingest,
+    matcher::{self, Matching},
+    orchestrator::{self, Settings},
+    partition::{self, PARTITIONS},
+    reconcile::{self, Reconciler},
+    replay,
+    store::{MemoryStore, MemoryTimelines},
+};
+use routers_shard::{FileFetcher, Geohash, ShardLoader};
+use routers_transition::Lag;
+
+use anyhow::Context;
+use clap::Parser;
+use log::{info, warn};
+use tokio::time::Instant;
+
+type E = OsmEntryId;
+type M = OsmEdgeMetadata;
+
+const SHARD_SUFFIX: &str = ".shard.rt";
+
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+struct Args {
+    /// The recording to replay
+    #[arg(short, env, long)]
+    file: PathBuf,
+
+    /// The directory of stored shard files. Every shard in it is loaded and
+    /// served.
+    #[arg(short, env, long)]
+    directory: PathBuf,
+
+    /// The replay speed, as a multiplier of the original event rate.
+    /// Any negative, or zero-value will default to FLOOD mode, where events are published as fast as possible.
+    #[arg(short, env, long, default_value_t = 1.0)]
+    speed: f64,
+
+    /// The search distance to use for matching
+    #[arg(long, env)]
+    search_distance: Option<f64>,
+
+    /// Commit a match this many layers behind its newest observation, even
+    /// before the candidate paths converge.
+    #[arg(long, env)]
+    lag_layers: Option<usize>,
+
+    /// Commit a match this many seconds behind its newest observation, even
+    /// before the candidate paths converge.
+    #[arg(long, env)]
+    lag_seconds: Option<u64>,
+
+    /// How many contexts each shard's matcher solves concurrently.
+    #[arg(long, env, default_value_t = 2)]
+    solvers: usize,
+
+    /// How many orchestrator workers to fan vehicles across.
+    #[arg(short, env, long, default_value_t = 16)]
+    workers: usize,
+
+    /// The number of history entries a vehicle's context draws from.
+    #[arg(short, long = "context-window", env, default_value = "10")]
+    context_window: usize,
+
+    /// The finalized layers kept per vehicle.
+    #[arg(long, env, default_value_t = 4096)]
+    timeline: usize,
+}
+
+/// Every shard stored in `directory`, by the geohash its file is named for.
+fn shards_in(directory: &Path) -> anyhow::Result<Vec<Geohash>> {
+    let mut shards = Vec::new();
+
+    for entry in std::fs::read_dir(directory).context("could not read shard directory")? {
+        let name = entry?.file_name();
+        let Some(stem) = name
+            .to_str()
+            .and_then(|name| name.strip_suffix(SHARD_SUFFIX))
+        else {
+            continue;
+        };
+
+        match stem.parse() {
+            Ok(shard) => shards.push(shard),
+            Err(err) => warn!("skipping {name:?}: {err}"),
+        }
+    }
+
+    Ok(shards)
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let _telemetry = routers_realtime::telemetry::init("routers-all-in-one");
+
+    let args = Args::parse();
+    info!("all-in-one started: {:?}", args);
+
+    let bus = MemoryBus::new();
+    let settings = Settings {
+        workers: args.workers,
+        context_window: args.context_window,
+        ..Settings::default()
+    };
+
+    // Consumers first: the memory bus retains nothing, so a publish with no
+    // one listening is gone.
+    let shards = shards_in(&args.directory)?;
+    anyhow::ensure!(!shards.is_empty(), "no shards in {:?}", args.directory);
+
+    let fetcher = FileFetcher::new(args.directory.clone());
+    let mut loader = ShardLoader::<E, M, Geohash, _, _>::new(fetcher, |key: &Geohash| {
+        format!("{key}{SHARD_SUFFIX}")
+    });
+
+    let lag = Lag {
+        layers: args.lag_layers,
+        delay: args.lag_seconds.map(Duration::from_secs),
+    };
+
+    for shard in shards {
+        let network = loader
+            .load(&shard)
+            .await
+            .with_context(|| format!("could not load shard {shard}"))?;
+
+        let requests = bus
+            .serve(
+                format!("{}.{shard}", settings.match_prefix),
+                "matchers".into(),
+            )
+            .await?;
+
+        let matching = Matching::new(network)
+            .with_search_distance(args.search_distance)
+            .with_lag(lag);
+        tokio::spawn(matcher::serve(requests, Arc::new(matching), args.solvers));
+        info!("serving shard {shard}");
+    }
+
+    let deliveries = bus.tail(reconcile::matched_filter()).await?;
+    tokio::spawn(reconcile::run(
+        deliveries,
+        MemoryTimelines::<E>::new(args.timeline)?,
+        Reconciler::<E>::new(65536),
+    ));
+
+    let pipeline = orchestrator::start(
+        bus.clone(),
+        MemoryStore::<RawEvent>::new(),
+        settings,
+        0..=PARTITIONS - 1,
+    )
+    .await?;
+
+    let df = replay::load(args.file)?;
+    let Some((min, _)) = replay::time_range(&df)? else {
+        info!("no events found.");
+        return Ok(());
+    };
+
+    let speed = if args.speed <= 0.0 {
+        f64::INFINITY
+    } else {
+        args.speed
+    };
+
+    let start = Instant::now();
+    for (time, payload) in replay::rows_of(&df).context("could not deserialize rows")? {
+        let offset = Duration::from_micros(time - min).div_f64(speed);
+        tokio::time::sleep_until(start + offset).await;
+
+        let subject = ingest::raw_subject(partition::partition_of(payload.vehicle_id));
+        bus.publish(subject, payload.encode()?).await?;
+    }
+
+    info!(
+        "replayed {} events; the pipeline runs on until interrupted",
+        df.height()
+    );
+    pipeline.join().await;
+
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use std::time::Duration;
+
+use routers_codec::osm::OsmEntryId;
+use routers_realtime::{
+    bus::{Bus, NATSBus},
+    ingest,
+    reconcile::{self, Reconciler},
+    store::RedisTimelines,
+};
+
+use anyhow::Context;
+use async_nats::{ConnectOptions, ServerAddr};
+use clap::Parser;
+use log::{error, info};
+use url::Url;
+
+type E = OsmEntryId;
+
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+struct Args {
+    /// URL of the NATS server
+    #[arg(short, env, long)]
+    nats: Url,
+
+    /// Valkey primaries, comma-separated. Give the same set as the
+    /// orchestrators: timelines are placed by the same rendezvous hash.
+    #[arg(short, env, long, value_delimiter = ',')]
+    redis: Vec<Url>,
+
+    /// How long the matched stream retains emissions, should this be the
+    /// first binary to create it. Must agree with the orchestrators.
+    #[arg(long, env, value_parser = humantime::parse_duration, default_value = "15m")]
+    matched_retention: Duration,
+
+    /// Vehicles whose timelines are held open before the least recently
+    /// heard from is evicted, and its pending layers finalized as they
+    /// stand. Size it above the vehicles in flight at once. Rounded up to a
+    /// power of two.
+    #[arg(long, env, default_value_t = 65536)]
+    vehicle_cache: usize,
+
+    /// The finalized layers kept per vehicle for readers.
+    #[arg(long, env, default_value_t = 4096)]
+    timeline: usize,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let _telemetry = routers_realtime::telemetry::init("routers-reconciler");
+
+    let args = Args::parse();
+    info!("reconciler started: {:?}", args);
+
+    let nats_url = ServerAddr::from_url(args.nats.clone()).context("could not create NATS url")?;
+
+    let client = ConnectOptions::new()
+        .name("ReconcilerService")
+        .connect(nats_url)
+        .await
+        .context("could not connect to NATS")?;
+    let bus = NATSBus::new(client);
+
+    ingest::matched_stream(bus.jetstream(), args.matched_retention).await?;
+
+    let store = RedisTimelines::<E>::new(&args.redis, args.timeline)
+        .await
+        .context("could not connect to redis store")?;
+
+    let deliveries = bus.tail(reconcile::matched_filter()).await?;
+    reconcile::run(deliveries, store, Reconciler::<E>::new(args.vehicle_cache)).await;
+
+    error!("matched stream terminated");
+    Ok(())
+}
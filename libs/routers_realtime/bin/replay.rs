@@ -5,18 +5,13 @@
 use anyhow::Context;
 use async_nats::{ConnectOptions, ServerAddr, jetstream};
 use clap::Parser;
-use fnv_rs::{Fnv64, FnvHasher};
 use futures::{StreamExt, stream::FuturesUnordered};
-use geo::Point;
 use indicatif::{ProgressBar, ProgressState, ProgressStyle};
 use indicatif_log_bridge::LogWrapper;
-use itertools::izip;
 use log::{debug, info};
-use polars::prelude::*;
 use routers_realtime::{
     bus::{self, Wire},
-    event::{Payload, VehicleId},
-    ingest, partition,
+    ingest, partition, replay,
 };
 use std::future::IntoFuture;
 use std::{fmt::Write, path::PathBuf, time::Duration};
@@ -51,36 +46,10 @@ struct Args {
     streams: u64,
 }
 
-// 2026-04-01 03:40:02 UTC, or 2026-04-01 03:40:02.123456 UTC
-const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S %Z";
-const TIME_FORMAT_FRACTIONAL: &str = "%Y-%m-%d %H:%M:%S%.f %Z";
-
 /// Publish acknowledgements kept in flight before the sender waits: enough
 /// to hide broker latency in flood mode without unbounded memory.
 const ACK_WINDOW: usize = 256;
 
-// Column names
-const VEHICLE_ID_COL: &str = "VehicleID";
-
-const PROVIDER_COL: &str = "Provider";
-const EVENT_TIME_COL: &str = "EventTime";
-
-const LATITUDE_COL: &str = "Latitude";
-const LONGITUDE_COL: &str = "Longitude";
-
-fn parse_datetime(fmt: &str) -> Expr {
-    col(EVENT_TIME_COL).str().to_datetime(
-        Some(TimeUnit::Microseconds),
-        None,
-        StrptimeOptions {
-            format: Some(fmt.into()),
-            strict: false,
-            ..Default::default()
-        },
-        lit("raise"),
-    )
-}
-
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let logger = env_logger::Builder::from_default_env().build();
@@ -103,28 +72,13 @@ async fn main() -> anyhow::Result<()> {
         ingest::raw_stream(&stream, index, args.streams).await?;
     }
 
-    let df = LazyCsvReader::new(args.file)
-        .with_has_header(true)
-        .finish()?
-        .sort([EVENT_TIME_COL], SortMultipleOptions::default())
-        .select([
-            col(VEHICLE_ID_COL),
-            col(PROVIDER_COL),
-            parse_datetime(TIME_FORMAT).fill_null(parse_datetime(TIME_FORMAT_FRACTIONAL)),
-            col(LATITUDE_COL),
-            col(LONGITUDE_COL),
-        ])
-        .collect()
-        .map_err(|e| anyhow::anyhow!("dataframe parse: {e}"))?;
+    let df = replay::load(args.file)?;
 
     let n = df.height();
-    if n == 0 {
+    let Some((min, max)) = replay::time_range(&df)? else {
         debug!("no events found.");
         return Ok(());
-    }
-
-    let times = df.column(EVENT_TIME_COL)?.datetime()?;
-    let (min, max) = (times.min().unwrap() as u64, times.max().unwrap() as u64);
+    };
     let timespan_s = Duration::from_micros(max - min).as_secs_f64();
 
     debug!("loaded {n:>7} events spanning {timespan_s:.1} s");
@@ -160,7 +114,7 @@ async fn main() -> anyhow::Result<()> {
         pg.reset();
 
         debug!("loop {iteration}/{0}", args.loops);
-        let rows = rows_of(&df).context("could not deserialize rows from dataframe")?;
+        let rows = replay::rows_of(&df).context("could not deserialize rows from dataframe")?;
 
         let start = Instant::now();
         for (time, payload) in rows {
@@ -197,35 +151,3 @@ async fn main() -> anyhow::Result<()> {
 
     Ok(())
 }
-
-fn rows_of(df: &DataFrame) -> PolarsResult<impl Iterator<Item = (u64, Payload)> + '_> {
-    let vehicle = df.column(VEHICLE_ID_COL)?.str()?;
-    let provider = df.column(PROVIDER_COL)?.str()?;
-    let etime = df.column(EVENT_TIME_COL)?.datetime()?;
-    let lat = df.column(LATITUDE_COL)?.f64()?;
-    let lon = df.column(LONGITUDE_COL)?.f64()?;
-
-    Ok(izip!(
-        vehicle.into_iter(),
-        provider.into_iter(),
-        etime.into_iter(),
-        lat.into_iter(),
-        lon.into_iter()
-    )
-    .filter_map(|(vehicle, _, etime, lat, lon)| {
-        // The id contract (schema: realtime/v1/event.proto): the FNV-1a
-        // 64-bit hash of the upstream string, as an integer. `as_bytes`
-        // yields it big-endian.
-        let vehicle_id: [u8; 8] = Fnv64::hash(vehicle?).as_bytes().try_into().ok()?;
-
-        let payload = Payload {
-            vehicle_id: VehicleId(u64::from_be_bytes(vehicle_id)),
-            // The column is parsed as microseconds since the Unix epoch.
-            timestamp: chrono::DateTime::from_timestamp_micros(etime.unwrap_or_default())
-                .unwrap_or_default(),
-            point: Point::new(lon.unwrap(), lat.unwrap()),
-        };
-
-        Some((etime.unwrap() as u64, payload))
-    }))
-}
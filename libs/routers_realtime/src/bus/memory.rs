@@ -0,0 +1,290 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, bail};
+use futures::stream::{self, BoxStream};
+use futures::{FutureExt, StreamExt};
+use tokio::sync::mpsc;
+
+use super::{Bus, Delivery, Request};
+
+/// A [`Bus`] over tokio channels, for running the whole pipeline inside one
+/// process. Cloning shares the bus.
+///
+/// Nothing outlives the process, so nothing is retained either: a publish
+/// reaches the subject's work queue and the tails matching it as they stand
+/// at that moment, and is dropped if there are none — start consumers before
+/// producers. Deliveries are never redelivered, so acknowledging one is a
+/// no-op.
+#[derive(Clone, Default)]
+pub struct MemoryBus {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Default)]
+struct State {
+    /// The last sequence handed out, shared by every subject.
+    sequence: u64,
+
+    /// Each consumed subject's work queue.
+    queues: HashMap<String, mpsc::UnboundedSender<Delivery>>,
+
+    /// Every tail, with its filter.
+    tails: Vec<(String, mpsc::UnboundedSender<Delivery>)>,
+
+    /// Servers by subject, then by group.
+    servers: HashMap<String, HashMap<String, Group>>,
+}
+
+/// The servers sharing one group's requests, taken in turn.
+#[derive(Default)]
+struct Group {
+    members: Vec<mpsc::UnboundedSender<Request>>,
+    next: usize,
+}
+
+impl Group {
+    /// Hand a request to the next live member, forgetting any that left.
+    /// Gives the request back if none remain.
+    fn dispatch(&mut self, mut request: Request) -> Option<Request> {
+        while !self.members.is_empty() {
+            let index = self.next % self.members.len();
+            match self.members[index].send(request) {
+                Ok(()) => {
+                    self.next = index + 1;
+                    return None;
+                }
+                Err(mpsc::error::SendError(returned)) => {
+                    self.members.swap_remove(index);
+                    request = returned;
+                }
+            }
+        }
+
+        Some(request)
+    }
+}
+
+impl MemoryBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().expect("bus lock poisoned")
+    }
+}
+
+/// Whether `subject` falls under `filter`, NATS-style: `*` stands for one
+/// token, and a trailing `>` for one or more.
+fn matches(filter: &str, subject: &str) -> bool {
+    let mut subject = subject.split('.');
+
+    for token in filter.split('.') {
+        match (token, subject.next()) {
+            (">", Some(_)) => return true,
+            ("*", Some(_)) => {}
+            (token, Some(next)) if token == next => {}
+            _ => return false,
+        }
+    }
+
+    subject.next().is_none()
+}
+
+fn receive<T: Send + 'static>(receiver: mpsc::UnboundedReceiver<T>) -> BoxStream<'static, T> {
+    stream::unfold(receiver, |mut receiver| async move {
+        receiver.recv().await.map(|item| (item, receiver))
+    })
+    .boxed()
+}
+
+fn unacked() -> futures::future::BoxFuture<'static, anyhow::Result<()>> {
+    async { Ok(()) }.boxed()
+}
+
+impl Bus for MemoryBus {
+    async fn publish(&self, subject: String, payload: Vec<u8>) -> anyhow::Result<()> {
+        let sent_at = Some(super::wallclock());
+
+        let mut state = self.state();
+        state.sequence += 1;
+        let sequence = state.sequence;
+
+        let delivery =
+            || Delivery::new(subject.clone(), payload.clone(), sequence, sent_at, unacked);
+
+        state
+            .tails
+            .retain(|(filter, tail)| !matches(filter, &subject) || tail.send(delivery()).is_ok());
+
+        if let Some(queue) = state.queues.get(&subject)
+            && queue.send(delivery()).is_err()
+        {
+            state.queues.remove(&subject);
+        }
+
+        Ok(())
+    }
+
+    async fn request(&self, subject: String, payload: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+        let (reply, mut replies) = mpsc::channel(1);
+        let mut asked = false;
+
+        if let Some(groups) = self.state().servers.get_mut(&subject) {
+            for group in groups.values_mut() {
+                let reply = reply.clone();
+                let request = Request::new(subject.clone(), payload.clone(), move |payload| {
+                    async move {
+                        // The asker may have taken another group's answer.
+                        let _ = reply.send(payload).await;
+                        Ok(())
+                    }
+                    .boxed()
+                });
+
+                asked |= group.dispatch(request).is_none();
+            }
+        }
+
+        if !asked {
+            bail!("no responders on {subject}");
+        }
+
+        drop(reply);
+        replies
+            .recv()
+            .await
+            .ok_or_else(|| anyhow!("request on {subject} went unanswered"))
+    }
+
+    async fn consume(
+        &self,
+        subject: String,
+        _durable: String,
+    ) -> anyhow::Result<BoxStream<'static, Delivery>> {
+        let mut state = self.state();
+        if state
+            .queues
+            .get(&subject)
+            .is_some_and(|queue| !queue.is_closed())
+        {
+            bail!("{subject} already has a consumer");
+        }
+
+        let (sender, receiver) = mpsc::unbounded_channel();
+        state.queues.insert(subject, sender);
+        Ok(receive(receiver))
+    }
+
+    async fn tail(&self, filter: String) -> anyhow::Result<BoxStream<'static, Delivery>> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        self.state().tails.push((filter, sender));
+        Ok(receive(receiver))
+    }
+
+    async fn serve(
+        &self,
+        subject: String,
+        group: String,
+    ) -> anyhow::Result<BoxStream<'static, Request>> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        self.state()
+            .servers
+            .entry(subject)
+            .or_default()
+            .entry(group)
+            .or_default()
+            .members
+            .push(sender);
+
+        Ok(receive(receiver))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+
+    fn subjects(deliveries: &[Delivery]) -> Vec<&str> {
+        deliveries
+            .iter()
+            .map(|delivery| delivery.subject.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn filters_match_like_nats() {
+        assert!(matches("events.matched.p.>", "events.matched.p.485"));
+        assert!(matches("events.*.p.1", "events.raw.p.1"));
+        assert!(matches("events.raw.p.1", "events.raw.p.1"));
+        assert!(!matches("events.matched.p.>", "events.matched.p"));
+        assert!(!matches("events.raw.p.1", "events.raw.p.10"));
+        assert!(!matches("events.*", "events.raw.p"));
+    }
+
+    /// A publish reaches its subject's queue and every matching tail, in
+    /// order and with rising sequences.
+    #[test]
+    fn publishes_reach_queues_and_tails() {
+        block_on(async {
+            let bus = MemoryBus::new();
+
+            let mut queue = bus.consume("a.1".into(), "a-1".into()).await.unwrap();
+            let tail = bus.tail("a.>".into()).await.unwrap();
+
+            for subject in ["a.1", "a.2", "b.1", "a.1"] {
+                bus.publish(subject.into(), subject.into()).await.unwrap();
+            }
+
+            let first = queue.next().await.unwrap();
+            let second = queue.next().await.unwrap();
+            assert_eq!(first.payload, b"a.1");
+            assert!(first.sequence < second.sequence);
+
+            drop(bus);
+            let tailed: Vec<_> = tail.collect().await;
+            assert_eq!(subjects(&tailed), vec!["a.1", "a.2", "a.1"]);
+        });
+    }
+
+    #[test]
+    fn a_subject_has_one_consumer() {
+        block_on(async {
+            let bus = MemoryBus::new();
+
+            let _queue = bus.consume("a.1".into(), "a-1".into()).await.unwrap();
+            assert!(bus.consume("a.1".into(), "a-1".into()).await.is_err());
+        });
+    }
+
+    /// Requests go round the members of a group, and are refused outright
+    /// when nobody serves the subject.
+    #[test]
+    fn requests_are_answered_by_one_server() {
+        block_on(async {
+            let bus = MemoryBus::new();
+            assert!(bus.request("solve".into(), Vec::new()).await.is_err());
+
+            for name in [b"first", b"other"] {
+                let mut requests = bus.serve("solve".into(), "group".into()).await.unwrap();
+                std::thread::spawn(move || {
+                    block_on(async {
+                        while let Some(request) = requests.next().await {
+                            request.respond(name.to_vec()).await.unwrap();
+                        }
+                    })
+                });
+            }
+
+            let mut answers = Vec::new();
+            for _ in 0..2 {
+                answers.push(bus.request("solve".into(), Vec::new()).await.unwrap());
+            }
+
+            answers.sort();
+            assert_eq!(answers, vec![b"first".to_vec(), b"other".to_vec()]);
+        });
+    }
+}
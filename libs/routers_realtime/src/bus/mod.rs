@@ -1,10 +1,17 @@
+mod memory;
 mod nats;
 mod trace;
 
+pub use memory::MemoryBus;
+pub use nats::NATSBus;
 pub use nats::NATSSink;
 pub use nats::NATSStream;
 pub use trace::{inbound, last_sent_at, outbound, span_between, wallclock};
 
+use futures::future::BoxFuture;
+use futures::stream::BoxStream;
+use web_time::SystemTime;
+
 /// How a message crosses the bus.
 ///
 /// Rust-internal messages (the match control plane) use postcard via
@@ -33,3 +40,121 @@ macro_rules! postcard_wire {
     };
 }
 pub(crate) use postcard_wire;
+
+type Ack = Box<dyn FnOnce() -> BoxFuture<'static, anyhow::Result<()>> + Send>;
+type Respond = Box<dyn FnOnce(Vec<u8>) -> BoxFuture<'static, anyhow::Result<()>> + Send>;
+
+/// One message taken from a durable subject. It stays owed to the consumer
+/// until [`Delivery::ack`]; what happens to an unacknowledged one is the
+/// backend's call (NATS redelivers it after its ack wait).
+pub struct Delivery {
+    pub subject: String,
+    pub payload: Vec<u8>,
+
+    /// The message's position in its stream: monotonic per subject, and
+    /// stable across redeliveries — the revision emissions carry.
+    pub sequence: u64,
+
+    /// When the publisher sent it, if it said.
+    pub sent_at: Option<SystemTime>,
+
+    ack: Ack,
+}
+
+impl Delivery {
+    pub fn new(
+        subject: String,
+        payload: Vec<u8>,
+        sequence: u64,
+        sent_at: Option<SystemTime>,
+        ack: impl FnOnce() -> BoxFuture<'static, anyhow::Result<()>> + Send + 'static,
+    ) -> Self {
+        Self {
+            subject,
+            payload,
+            sequence,
+            sent_at,
+            ack: Box::new(ack),
+        }
+    }
+
+    /// Mark the message handled, so it is never delivered again.
+    pub async fn ack(self) -> anyhow::Result<()> {
+        (self.ack)().await
+    }
+}
+
+/// One request a server took, answered exactly once through
+/// [`Request::respond`].
+pub struct Request {
+    pub subject: String,
+    pub payload: Vec<u8>,
+
+    respond: Respond,
+}
+
+impl Request {
+    pub fn new(
+        subject: String,
+        payload: Vec<u8>,
+        respond: impl FnOnce(Vec<u8>) -> BoxFuture<'static, anyhow::Result<()>> + Send + 'static,
+    ) -> Self {
+        Self {
+            subject,
+            payload,
+            respond: Box::new(respond),
+        }
+    }
+
+    pub async fn respond(self, payload: Vec<u8>) -> anyhow::Result<()> {
+        (self.respond)(payload).await
+    }
+}
+
+/// The transport the realtime pipeline runs over: durable publishes and
+/// work-queue consumption for events, retained tails for emissions, and
+/// request/reply for solves.
+///
+/// [`NATSBus`] is the deployed backend. [`MemoryBus`] runs the same
+/// pipeline inside one process, for tests and laptops.
+pub trait Bus: Clone + Send + Sync + 'static {
+    /// Publish durably: resolves once the bus has accepted the message.
+    /// Successive publishes from one caller are delivered in order.
+    fn publish(
+        &self,
+        subject: String,
+        payload: Vec<u8>,
+    ) -> impl Future<Output = anyhow::Result<()>> + Send;
+
+    /// Ask whichever server takes `subject`, resolving with its reply.
+    fn request(
+        &self,
+        subject: String,
+        payload: Vec<u8>,
+    ) -> impl Future<Output = anyhow::Result<Vec<u8>>> + Send;
+
+    /// Take the messages published to `subject` as a work queue, under the
+    /// durable consumer `durable`: each is handed to one consumer, and
+    /// resumes from the last acknowledged on restart.
+    fn consume(
+        &self,
+        subject: String,
+        durable: String,
+    ) -> impl Future<Output = anyhow::Result<BoxStream<'static, Delivery>>> + Send;
+
+    /// Read every retained message on subjects matching `filter` (which may
+    /// end in the `>` wildcard), without consuming them. Acknowledging a
+    /// tailed delivery does nothing.
+    fn tail(
+        &self,
+        filter: String,
+    ) -> impl Future<Output = anyhow::Result<BoxStream<'static, Delivery>>> + Send;
+
+    /// Serve requests on `subject`. Servers sharing a `group` divide the
+    /// requests between them instead of each answering every one.
+    fn serve(
+        &self,
+        subject: String,
+        group: String,
+    ) -> impl Future<Output = anyhow::Result<BoxStream<'static, Request>>> + Send;
+}
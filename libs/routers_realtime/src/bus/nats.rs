@@ -1,11 +1,15 @@
 use std::pin::Pin;
 use std::task::{Context as Ctx, Poll, ready};
 
-use anyhow::Context;
-use futures::future::BoxFuture;
+use std::time::Duration;
+
+use anyhow::{Context, anyhow};
+use async_nats::jetstream;
+use futures::future::{self, BoxFuture};
+use futures::stream::BoxStream;
 use futures::{FutureExt, Sink, Stream, StreamExt};
 
-use super::Wire;
+use super::{Bus, Delivery, Request, Wire};
 
 pub struct NATSSink<T: Wire> {
     client: async_nats::Client,
@@ -134,3 +138,225 @@ where
         }
     }
 }
+
+/// The deployed [`Bus`]: JetStream for everything durable, core NATS
+/// request/reply for solves.
+///
+/// Streams are the binaries' to create (see [`ingest`](crate::ingest)); the
+/// bus only binds to whichever stream holds a subject. Cloning shares the
+/// connection.
+#[derive(Clone)]
+pub struct NATSBus {
+    client: async_nats::Client,
+    stream: jetstream::Context,
+    max_ack_pending: i64,
+    ack_wait: Duration,
+}
+
+impl NATSBus {
+    pub fn new(client: async_nats::Client) -> Self {
+        Self {
+            stream: jetstream::new(client.clone()),
+            client,
+            max_ack_pending: 2048,
+            ack_wait: Duration::from_secs(60),
+        }
+    }
+
+    /// Unacknowledged messages each durable consumer may hold. Under
+    /// saturation the stream buffers and this throttles delivery.
+    pub fn with_max_ack_pending(mut self, max_ack_pending: i64) -> Self {
+        self.max_ack_pending = max_ack_pending;
+        self
+    }
+
+    /// How long the broker waits for an ack before redelivering. Redelivery
+    /// is the crash-recovery path.
+    pub fn with_ack_wait(mut self, ack_wait: Duration) -> Self {
+        self.ack_wait = ack_wait;
+        self
+    }
+
+    pub fn client(&self) -> &async_nats::Client {
+        &self.client
+    }
+
+    pub fn jetstream(&self) -> &jetstream::Context {
+        &self.stream
+    }
+
+    async fn stream_of(&self, subject: &str) -> anyhow::Result<jetstream::stream::Stream> {
+        let name = self
+            .stream
+            .stream_by_subject(subject)
+            .await
+            .with_context(|| format!("no stream holds {subject}"))?;
+
+        self.stream
+            .get_stream(&name)
+            .await
+            .with_context(|| format!("could not bind stream {name}"))
+    }
+}
+
+/// A JetStream message as a [`Delivery`], closing the publisher's timing loop.
+fn delivery(message: jetstream::Message) -> Delivery {
+    super::trace::inbound(message.subject.as_str(), message.headers.as_ref());
+    let sent_at = super::trace::last_sent_at();
+
+    let sequence = message
+        .info()
+        .map(|info| info.stream_sequence)
+        .unwrap_or_default();
+
+    Delivery::new(
+        message.subject.to_string(),
+        message.payload.to_vec(),
+        sequence,
+        sent_at,
+        move || {
+            async move {
+                message.ack().await.map_err(|err| anyhow!(err))?;
+                Ok(())
+            }
+            .boxed()
+        },
+    )
+}
+
+/// A pulled JetStream subscription as deliveries. Pull errors are the
+/// client's to recover from, so they are logged and skipped.
+fn deliveries<S, E>(subject: String, messages: S) -> BoxStream<'static, Delivery>
+where
+    S: Stream<Item = Result<jetstream::Message, E>> + Send + 'static,
+    E: core::fmt::Display,
+{
+    messages
+        .filter_map(move |next| {
+            future::ready(match next {
+                Ok(message) => Some(delivery(message)),
+                Err(err) => {
+                    log::error!("{subject}: pull error: {err}");
+                    None
+                }
+            })
+        })
+        .boxed()
+}
+
+impl Bus for NATSBus {
+    async fn publish(&self, subject: String, payload: Vec<u8>) -> anyhow::Result<()> {
+        self.stream
+            .publish_with_headers(subject, super::trace::outbound(), payload.into())
+            .await
+            .context("could not publish")?
+            .await
+            .context("publish unacknowledged")?;
+
+        Ok(())
+    }
+
+    async fn request(&self, subject: String, payload: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+        let message = self
+            .client
+            .request_with_headers(subject, super::trace::outbound(), payload.into())
+            .await?;
+
+        Ok(message.payload.to_vec())
+    }
+
+    async fn consume(
+        &self,
+        subject: String,
+        durable: String,
+    ) -> anyhow::Result<BoxStream<'static, Delivery>> {
+        let consumer = self
+            .stream_of(&subject)
+            .await?
+            .get_or_create_consumer(
+                &durable,
+                jetstream::consumer::pull::Config {
+                    durable_name: Some(durable.clone()),
+                    filter_subject: subject.clone(),
+                    ack_policy: jetstream::consumer::AckPolicy::Explicit,
+                    max_ack_pending: self.max_ack_pending,
+                    ack_wait: self.ack_wait,
+                    ..Default::default()
+                },
+            )
+            .await
+            .with_context(|| format!("could not create consumer {durable}"))?;
+
+        let messages = consumer
+            .messages()
+            .await
+            .with_context(|| format!("could not pull {subject}"))?;
+
+        Ok(deliveries(subject, messages))
+    }
+
+    async fn tail(&self, filter: String) -> anyhow::Result<BoxStream<'static, Delivery>> {
+        // Ordered consumers hold no durable position: each tail replays
+        // whatever the stream retains.
+        let consumer = self
+            .stream_of(&filter)
+            .await?
+            .create_consumer(jetstream::consumer::pull::OrderedConfig {
+                filter_subject: filter.clone(),
+                deliver_policy: jetstream::consumer::DeliverPolicy::All,
+                ..Default::default()
+            })
+            .await
+            .with_context(|| format!("could not tail {filter}"))?;
+
+        let messages = consumer
+            .messages()
+            .await
+            .with_context(|| format!("could not pull {filter}"))?;
+
+        Ok(deliveries(filter, messages))
+    }
+
+    async fn serve(
+        &self,
+        subject: String,
+        group: String,
+    ) -> anyhow::Result<BoxStream<'static, Request>> {
+        let subscriber = self
+            .client
+            .queue_subscribe(subject.clone(), group)
+            .await
+            .with_context(|| format!("could not subscribe to {subject}"))?;
+
+        let client = self.client.clone();
+        Ok(subscriber
+            .filter_map(move |message| {
+                super::trace::inbound(message.subject.as_str(), message.headers.as_ref());
+
+                let Some(inbox) = message.reply else {
+                    log::warn!("dropping request without a reply inbox — not a request?");
+                    return future::ready(None);
+                };
+
+                let client = client.clone();
+                future::ready(Some(Request::new(
+                    message.subject.to_string(),
+                    message.payload.to_vec(),
+                    move |payload| {
+                        async move {
+                            client
+                                .publish_with_headers(
+                                    inbox,
+                                    super::trace::outbound(),
+                                    payload.into(),
+                                )
+                                .await?;
+                            Ok(())
+                        }
+                        .boxed()
+                    },
+                )))
+            })
+            .boxed())
+    }
+}
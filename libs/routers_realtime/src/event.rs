@@ -97,9 +97,12 @@ pub struct MatchedLayer<E: Entry> {
 }
 
 /// Everything a solve could still change, emitted whole: one layer per trip
-/// observation since the last convergence cut. Consumers merge layers by
+/// observation since the last commit cut. Consumers merge layers by
 /// (vehicle, timestamp) and resolve competing solves by revision — highest
 /// wins, equal is a duplicate — so re-emission is convergence, not conflict.
+///
+/// The layers the solve committed ride apart from the provisional ones: a
+/// committed layer is final the moment it is emitted.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(bound(serialize = "E: Serialize", deserialize = "E: Deserialize<'de>"))]
 pub struct MatchedDiff<E: Entry> {
@@ -111,6 +114,11 @@ pub struct MatchedDiff<E: Entry> {
     /// history — the emitted region may rewrite more than usual.
     pub downgraded: bool,
 
+    /// Layers whose choice is final, oldest first, all older than `layers`.
+    #[serde(default)]
+    pub committed: Vec<MatchedLayer<E>>,
+
+    /// Layers a later solve may still rewrite, oldest first.
     pub layers: Vec<MatchedLayer<E>>,
 }
 
@@ -150,9 +158,23 @@ impl<E: Entry> MatchedDiff<E> {
         Self {
             revision,
             downgraded: false,
+            committed: Vec::new(),
             layers,
         }
     }
+
+    /// Move the first `count` provisional layers over to the committed.
+    pub fn with_committed(mut self, count: usize) -> Self {
+        let provisional = self.layers.split_off(count.min(self.layers.len()));
+        self.committed.append(&mut self.layers);
+        self.layers = provisional;
+        self
+    }
+
+    /// Every layer, committed then provisional: oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = &MatchedLayer<E>> {
+        self.committed.iter().chain(&self.layers)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -165,6 +187,18 @@ pub struct Payload {
     pub timestamp: DateTime<Utc>,
 
     pub point: Point,
+
+    /// The direction of travel, in degrees clockwise from true north.
+    #[serde(default)]
+    pub heading: Option<f64>,
+
+    /// The speed over ground, in metres per second.
+    #[serde(default)]
+    pub speed: Option<f64>,
+
+    /// The device's estimate of its horizontal error, in metres.
+    #[serde(default)]
+    pub accuracy: Option<f64>,
 }
 
 // The match control plane is Rust-internal: postcard on the wire.
@@ -200,6 +234,9 @@ impl From<&Payload> for proto::Payload {
                 latitude: payload.point.y(),
                 ..Default::default()
             }),
+            heading: payload.heading,
+            speed: payload.speed,
+            accuracy: payload.accuracy,
             ..Default::default()
         }
     }
@@ -215,6 +252,9 @@ impl From<proto::Payload> for Payload {
             timestamp: DateTime::from_timestamp(timestamp.seconds, timestamp.nanos as u32)
                 .unwrap_or_default(),
             point: Point::new(point.longitude, point.latitude),
+            heading: payload.heading,
+            speed: payload.speed,
+            accuracy: payload.accuracy,
         }
     }
 }
@@ -225,6 +265,9 @@ impl Payload {
             vehicle_id: self.vehicle_id,
             point: self.point,
             timestamp: self.timestamp,
+            heading: self.heading,
+            speed: self.speed,
+            accuracy: self.accuracy,
         }
     }
 }
@@ -238,6 +281,44 @@ pub struct RawEvent {
     /// Unix epoch on the wire.
     #[serde(with = "chrono::serde::ts_microseconds")]
     pub timestamp: DateTime<Utc>,
+
+    /// As reported with the observation; see [`Payload`].
+    pub heading: Option<f64>,
+    pub speed: Option<f64>,
+    pub accuracy: Option<f64>,
+}
+
+/// The layout [`RawEvent`] was stored in before it carried heading, speed
+/// and accuracy, which histories written then still hold.
+#[derive(Serialize, Deserialize)]
+struct LegacyRawEvent {
+    vehicle_id: VehicleId,
+    point: Point,
+    #[serde(with = "chrono::serde::ts_microseconds")]
+    timestamp: DateTime<Utc>,
+}
+
+impl From<LegacyRawEvent> for RawEvent {
+    fn from(legacy: LegacyRawEvent) -> Self {
+        RawEvent {
+            vehicle_id: legacy.vehicle_id,
+            point: legacy.point,
+            timestamp: legacy.timestamp,
+            heading: None,
+            speed: None,
+            accuracy: None,
+        }
+    }
+}
+
+impl RawEvent {
+    /// The observation as the matcher takes it.
+    pub fn as_origin(&self) -> Origin {
+        Origin::new(self.point, self.timestamp.timestamp_micros())
+            .with_heading(self.heading)
+            .with_speed(self.speed)
+            .with_accuracy(self.accuracy)
+    }
 }
 
 /// The fleet's geographic shard precision. One source of truth: matcher
@@ -262,6 +343,14 @@ impl Storable for RawEvent {
     fn key(&self) -> Self::Key {
         self.vehicle_id
     }
+
+    /// An event in the legacy layout ends where the current one reads on
+    /// into its heading, so it fails to decode as current and is read as
+    /// legacy instead.
+    fn decode(bytes: &[u8]) -> Result<Self, postcard::Error> {
+        postcard::from_bytes::<RawEvent>(bytes)
+            .or_else(|_| postcard::from_bytes::<LegacyRawEvent>(bytes).map(RawEvent::from))
+    }
 }
 
 #[cfg(test)]
@@ -278,6 +367,9 @@ mod tests {
             vehicle_id: VehicleId(0xdead_beef_cafe_f00d),
             timestamp: DateTime::from_timestamp_micros(1_775_000_000_123_456).unwrap(),
             point: Point::new(150.871294, -33.938879),
+            heading: Some(271.5),
+            speed: None,
+            accuracy: Some(4.2),
         };
 
         let bytes = payload.encode().expect("payload must encode");
@@ -286,5 +378,36 @@ mod tests {
         assert_eq!(decoded.vehicle_id, payload.vehicle_id);
         assert_eq!(decoded.timestamp, payload.timestamp);
         assert_eq!(decoded.point, payload.point);
+        assert_eq!(decoded.heading, payload.heading);
+        assert_eq!(decoded.speed, payload.speed);
+        assert_eq!(decoded.accuracy, payload.accuracy);
+    }
+
+    /// Histories written before observations carried heading, speed and
+    /// accuracy must still decode, without them.
+    #[test]
+    fn raw_events_decode_from_the_legacy_layout() {
+        let timestamp = DateTime::from_timestamp_micros(1_775_000_000_123_456).unwrap();
+        let point = Point::new(150.871294, -33.938879);
+        let bytes = postcard::to_allocvec(&LegacyRawEvent {
+            vehicle_id: VehicleId(7),
+            point,
+            timestamp,
+        })
+        .expect("legacy event must encode");
+
+        let decoded = RawEvent::decode(&bytes).expect("legacy event must decode");
+        assert_eq!(decoded.vehicle_id, VehicleId(7));
+        assert_eq!(decoded.point, point);
+        assert_eq!(decoded.timestamp, timestamp);
+        assert_eq!(decoded.heading, None);
+
+        let current = RawEvent {
+            heading: Some(271.5),
+            ..decoded
+        };
+        let bytes = postcard::to_allocvec(&current).expect("event must encode");
+        let decoded = RawEvent::decode(&bytes).expect("event must decode");
+        assert_eq!(decoded.heading, Some(271.5));
     }
 }
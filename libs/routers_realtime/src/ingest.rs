@@ -92,33 +92,6 @@ pub async fn matched_stream(
         .context("could not create matched stream")
 }
 
-/// The durable consumer owning one partition's raw events. Redelivery after
-/// `ack_wait` is the crash-recovery path; `max_ack_pending` bounds how far a
-/// slow owner lets its unacked window grow — the backlog knob.
-pub async fn partition_consumer(
-    stream: &jetstream::stream::Stream,
-    partition: u64,
-    max_ack_pending: i64,
-    ack_wait: Duration,
-) -> anyhow::Result<jetstream::consumer::PullConsumer> {
-    let name = format!("orchestrator-p{partition}");
-
-    stream
-        .get_or_create_consumer(
-            &name,
-            jetstream::consumer::pull::Config {
-                durable_name: Some(name.clone()),
-                filter_subject: raw_subject(partition),
-                ack_policy: jetstream::consumer::AckPolicy::Explicit,
-                max_ack_pending,
-                ack_wait,
-                ..Default::default()
-            },
-        )
-        .await
-        .with_context(|| format!("could not create consumer for partition {partition}"))
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
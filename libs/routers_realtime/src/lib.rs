@@ -1,6 +1,10 @@
 pub mod bus;
 pub mod event;
 pub mod ingest;
+pub mod matcher;
+pub mod orchestrator;
 pub mod partition;
+pub mod reconcile;
+pub mod replay;
 pub mod store;
 pub mod telemetry;
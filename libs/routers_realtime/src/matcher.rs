@@ -0,0 +1,259 @@
+//! The matcher's half of the control plane: solves match contexts against
+//! one loaded shard, answering each request over any
+//! [`Bus`](crate::bus::Bus).
+
+use std::sync::Arc;
+
+use routers_codec::osm::{OsmEdgeMetadata, OsmEntryId};
+use routers_network::Metadata;
+use routers_shard::{Geohash, ShardedNetwork};
+use routers_transition::{
+    Continuation, Lag, MatchError, Matcher,
+    costing::{CostingStrategies, DefaultTransitionCost, ObservedEmissionCost},
+    layer::generation::StandardGenerator,
+    primitives::PredicateCache,
+    weigh::AllCompute,
+};
+
+use futures::StreamExt;
+use futures::stream::BoxStream;
+use log::{debug, error, warn};
+use tracing::{field, info_span};
+
+use crate::bus::{Request, Wire};
+use crate::event::{MatchContext, MatchReply, MatchedDiff};
+
+type E = OsmEntryId;
+type M = OsmEdgeMetadata;
+type Net = ShardedNetwork<E, M, Geohash>;
+
+/// Everything a solve needs, owned so the service can be shared (`Arc`) across
+/// the concurrent solves without leaking or juggling `'static` borrows. The
+/// network's spatial index and the predicate cache are the only heavy state,
+/// and both are shared; a per-solve [`Matcher`] is just a bundle of borrows
+/// into this and is free to build.
+pub struct Matching {
+    network: Arc<Net>,
+    runtime: <M as Metadata>::Runtime,
+    /// Observations carry what the device reported about them (heading,
+    /// speed, accuracy); those without it cost as the default would.
+    costing: CostingStrategies<ObservedEmissionCost, DefaultTransitionCost, E>,
+    cache: Arc<PredicateCache<Net>>,
+    search_distance: Option<f64>,
+    lag: Lag,
+}
+
+impl Matching {
+    pub fn new(network: Arc<Net>) -> Self {
+        Self {
+            network,
+            runtime: OsmEdgeMetadata::runtime(None),
+            costing: CostingStrategies::new(ObservedEmissionCost::default(), DefaultTransitionCost),
+            cache: Arc::new(PredicateCache::default()),
+            search_distance: None,
+            lag: Lag::default(),
+        }
+    }
+
+    /// The distance around each observation candidates are searched for.
+    pub fn with_search_distance(mut self, distance: Option<f64>) -> Self {
+        self.search_distance = distance;
+        self
+    }
+
+    /// How far behind a vehicle's newest observation its layers are
+    /// committed, converged or not: the longest a consumer waits for a final
+    /// answer. By default only convergence commits.
+    pub fn with_lag(mut self, lag: Lag) -> Self {
+        self.lag = lag;
+        self
+    }
+
+    /// Solve one context, recording its outcome onto a fresh `match_event`
+    /// span. Returns the reply to send: the emission and resume state, or
+    /// [`MatchReply::NoMatch`] when there is nothing to emit (no anchor, or a
+    /// nominal/fatal solve failure).
+    pub fn solve(
+        &self,
+        MatchContext {
+            vehicle_id,
+            continuation,
+        }: MatchContext<E>,
+    ) -> MatchReply<E> {
+        let mut generator = StandardGenerator::new(self.network.as_ref(), &self.costing.emission);
+        if let Some(distance) = self.search_distance {
+            generator = generator.with_search_distance(distance);
+        }
+
+        let weigher = AllCompute::default().use_cache(self.cache.clone());
+        let matcher = Matcher::new(
+            self.network.as_ref(),
+            &self.costing,
+            generator,
+            weigher,
+            &self.runtime,
+        )
+        .with_lag(self.lag);
+
+        let span = info_span!(
+            "match_event",
+            outcome = field::Empty,
+            severity = field::Empty,
+            continuation = field::Empty,
+            committed = field::Empty,
+            emitted = field::Empty,
+        );
+        let _entered = span.enter();
+
+        let (mut trip, fresh, downgraded) = match continuation {
+            // A resume solved on another shard references edges this shard's
+            // padding may not cover: adopting it would route through nodes
+            // that do not exist here. Degrade to a restart over the trip's
+            // own observations — the emission re-covers them under a higher
+            // revision, so the reconciled history heals the seam.
+            Continuation::Resume { trip, fresh } if !matcher.supports(&trip) => {
+                span.record("continuation", "downgrade");
+                warn!("{vehicle_id}: resume references a foreign shard; restarting");
+
+                let fresh = trip.origins().iter().copied().chain(fresh).collect();
+                (matcher.begin(), fresh, true)
+            }
+            Continuation::Resume { trip, fresh } => {
+                span.record("continuation", "resume");
+                (trip, fresh, false)
+            }
+            Continuation::Restart { fresh } => {
+                span.record("continuation", "restart");
+                (matcher.begin(), fresh, false)
+            }
+        };
+
+        info_span!("push", points = fresh.len()).in_scope(|| {
+            for origin in fresh {
+                match matcher.push(&mut trip, origin) {
+                    Ok(_) => {}
+                    Err(MatchError::Unanchored(err)) => {
+                        info_span!("point_drop", reason = "unanchored")
+                            .in_scope(|| debug!("{vehicle_id}: dropped off-network point ({err})"));
+                    }
+                    Err(err) => {
+                        info_span!("point_drop", reason = "push_error")
+                            .in_scope(|| error!("{vehicle_id}: could not push point: {err}"));
+                    }
+                }
+            }
+        });
+
+        if trip.is_empty() {
+            span.record("outcome", "no_anchor");
+            span.record("severity", "nominal");
+            warn!("{vehicle_id}: no anchored layers to solve");
+            return MatchReply::NoMatch;
+        }
+
+        if let Err(err) = info_span!("solve").in_scope(|| matcher.solve(&mut trip)) {
+            let (outcome, severity) = classify(err);
+            span.record("outcome", outcome);
+            span.record("severity", severity);
+            error!("{vehicle_id}: unable to solve trip");
+            return MatchReply::NoMatch;
+        }
+
+        // Copied out: the snapshot's borrow spans the whole trip mutably.
+        let origins = trip.origins().to_vec();
+
+        let solution = match info_span!("snapshot").in_scope(|| matcher.snapshot(&mut trip)) {
+            Ok(solution) => solution,
+            Err(err) => {
+                let (outcome, severity) = classify(err);
+                span.record("outcome", outcome);
+                span.record("severity", severity);
+                return MatchReply::NoMatch;
+            }
+        };
+
+        // Emit everything a future solve could still change — the whole trip
+        // since its last cut. The owner stamps the real revision (the ingest
+        // stream sequence) before publishing.
+        let mut diff = info_span!("emit")
+            .in_scope(|| MatchedDiff::new(&solution, &origins, self.network.as_ref(), 0));
+        diff.downgraded = downgraded;
+        drop(solution);
+        span.record("emitted", diff.layers.len());
+
+        // Commit through the convergence point, or the lag if it reaches
+        // further, and cut behind it: those layers are final, emitted as
+        // such, and only cost wire from here on. The commit point itself
+        // stays as the resume anchor. An uncommitted trip stays whole — the
+        // orchestrator's context window bounds its growth.
+        match matcher.commit(&mut trip) {
+            Ok(Some(layer)) => {
+                span.record("committed", layer.index() as u64);
+                diff = diff.with_committed(trip.committed());
+                trip.tail(trip.layers() - layer.index());
+            }
+            Ok(None) => {}
+            Err(err) => error!("{vehicle_id}: commit failed: {err}"),
+        }
+
+        span.record("outcome", "success");
+        span.record("severity", "ok");
+
+        MatchReply::Solved { diff, trip }
+    }
+}
+
+/// A match attempt's `outcome`/`severity` labels for the success-ratio series.
+/// Nominal failures are the data's fault (a point off every road, a trace the
+/// network cannot bridge) and expected in healthy operation; fatal ones are ours.
+fn classify(err: MatchError) -> (&'static str, &'static str) {
+    match err {
+        MatchError::Unanchored(_) => ("unanchored", "nominal"),
+        MatchError::Disconnected(_) => ("disconnected", "nominal"),
+        MatchError::TrellisError(_) | MatchError::SolveError(_) => ("internal", "fatal"),
+    }
+}
+
+/// Answer every request until the bus ends the stream (see [`Bus::serve`]),
+/// `workers` solves at a time.
+///
+/// Each context is solved on the blocking pool (solving is synchronous and
+/// CPU-bound). Every request is answered — a NoMatch is still an answer, so
+/// the asking orchestrator never waits out a timeout for an event that
+/// solved to nothing.
+pub async fn serve(requests: BoxStream<'static, Request>, matching: Arc<Matching>, workers: usize) {
+    requests
+        .for_each_concurrent(workers, |request| {
+            let matching = Arc::clone(&matching);
+
+            async move {
+                let context = match MatchContext::<E>::decode(&request.payload) {
+                    Ok(context) => context,
+                    Err(err) => {
+                        warn!("skipping undecodable context: {err}");
+                        return;
+                    }
+                };
+
+                let reply = tokio::task::spawn_blocking(move || matching.solve(context))
+                    .await
+                    .unwrap_or_else(|err| {
+                        error!("solve task panicked: {err}");
+                        MatchReply::NoMatch
+                    });
+
+                let payload = match reply.encode() {
+                    Ok(payload) => payload,
+                    Err(err) => {
+                        error!("could not encode reply: {err:#}");
+                        return;
+                    }
+                };
+
+                if let Err(err) = request.respond(payload).await {
+                    error!("could not send reply: {err:#}");
+                }
+            }
+        })
+        .await;
+}
@@ -0,0 +1,613 @@
+//! The orchestrator's pipeline: owns a slice of the partition space, gates
+//! and warms each vehicle's history lane, asks a matcher to solve, durably
+//! publishes the emission and archives the raw tail — over any [`Bus`] and
+//! [`HistoryStore`].
+
+use core::ops::RangeInclusive;
+use std::time::Duration;
+
+use scc::HashCache;
+use scc::hash_cache::Entry;
+
+use routers_codec::osm::OsmEntryId;
+use routers_transition::Continuation;
+use routers_transition::matcher::Trip;
+
+use anyhow::{Context, Result, anyhow};
+use futures::StreamExt;
+use geo::{Distance, Haversine};
+use log::{debug, error, info};
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+use tokio::time::{Instant, timeout_at};
+use tracing::{Instrument, field, info_span, warn};
+
+use crate::bus::{self, Bus, Delivery, Wire};
+use crate::event::{MatchContext, MatchReply, MatchedEvent, Payload, RawEvent, VehicleId};
+use crate::ingest;
+use crate::partition;
+use crate::store::HistoryStore;
+
+type E = OsmEntryId;
+
+/// How the pipeline behaves, whatever it runs over.
+#[derive(Clone, Debug)]
+pub struct Settings {
+    /// The subject prefix matchers serve requests on. Each request routes to
+    /// `<prefix>.<geohash>`, the geographic shard of the event's position.
+    pub match_prefix: String,
+
+    /// How long to wait for a matcher's reply before re-driving the request.
+    pub solve_timeout: Duration,
+
+    /// Re-drives after the first attempt before the pipeline backs off and
+    /// starts over.
+    pub solve_retries: usize,
+
+    /// The number of history entries a vehicle's context draws from.
+    pub context_window: usize,
+
+    /// Points older than this are discarded from history.
+    pub gap: Duration,
+
+    /// Consecutive points further apart than this, in metres, are a
+    /// "teleport", and everything older is dropped.
+    pub jump_distance: f64,
+
+    /// How many workers to fan vehicles across: the in-flight solve bound.
+    pub workers: usize,
+
+    /// Vehicles each worker keeps lanes for before evicting the least
+    /// recently used. Rounded up to a power of two.
+    pub vehicle_cache: usize,
+
+    /// The number of events kept in each vehicle's durable tail.
+    pub history: usize,
+
+    /// Batch size for tail writes.
+    pub batch_size: usize,
+
+    /// Batch timeout for tail writes.
+    pub batch_timeout: Duration,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            match_prefix: "events.match".into(),
+            solve_timeout: Duration::from_secs(5),
+            solve_retries: 3,
+            context_window: 10,
+            gap: Duration::from_secs(120),
+            jump_distance: 2000.0,
+            workers: 64,
+            vehicle_cache: 1024,
+            history: 25,
+            batch_size: 128,
+            batch_timeout: Duration::from_millis(20),
+        }
+    }
+}
+
+/// One durable raw event handed to a worker, with the wall-clock stamp the
+/// partition forwarder captured when it was queued (for channel-residency
+/// timing).
+struct Dispatch {
+    queued_at: web_time::SystemTime,
+    payload: Payload,
+    delivery: Delivery,
+}
+
+/// How one event left the pipeline: fully processed, or deliberately
+/// dropped. Either way it is acknowledged — transient failures never reach
+/// this type, they retry inside the pipeline.
+enum Processed {
+    Done,
+    Dropped(&'static str),
+}
+
+/// Everything one worker owns: its vehicles' trip and history lanes, plus
+/// handles to the stores and the bus. Workers share nothing, so a vehicle's
+/// events serialize on its worker with no locks anywhere.
+struct Worker<B, S> {
+    kv: S,
+    bus: B,
+    archive: mpsc::Sender<(RawEvent, oneshot::Sender<()>)>,
+    match_prefix: String,
+
+    gap: chrono::TimeDelta,
+    jump_distance: f64,
+    context_window: usize,
+    solve_timeout: Duration,
+    solve_retries: usize,
+
+    trips: HashCache<VehicleId, Trip<E>>,
+    histories: HashCache<VehicleId, Vec<RawEvent>>,
+}
+
+/// A running pipeline: the partition forwarders feeding its workers.
+pub struct Orchestrator {
+    forwarders: Vec<JoinHandle<()>>,
+    workers: Vec<JoinHandle<()>>,
+    txs: Vec<mpsc::Sender<Dispatch>>,
+}
+
+/// Run the pipeline over the `owned` partitions until their consumers end,
+/// then drain the workers.
+pub async fn run<B, S>(
+    bus: B,
+    store: S,
+    settings: Settings,
+    owned: RangeInclusive<u64>,
+) -> Result<()>
+where
+    B: Bus,
+    S: HistoryStore<RawEvent> + Clone + Send + Sync + 'static,
+{
+    start(bus, store, settings, owned).await?.join().await;
+    Ok(())
+}
+
+/// Consume the `owned` partitions and start the workers. Everything
+/// published to them from the moment this returns is processed.
+pub async fn start<B, S>(
+    bus: B,
+    store: S,
+    settings: Settings,
+    owned: RangeInclusive<u64>,
+) -> Result<Orchestrator>
+where
+    B: Bus,
+    S: HistoryStore<RawEvent> + Clone + Send + Sync + 'static,
+{
+    let gap = chrono::Duration::from_std(settings.gap).context("gap out of range")?;
+
+    // The sole durable writer of raw tails, batched like the historian this
+    // pipeline absorbed. A failed flush retries until the store returns —
+    // the tail is the failover recovery source, so acks wait on it.
+    let (archive_tx, mut archive_rx) = mpsc::channel::<(RawEvent, oneshot::Sender<()>)>(8192);
+    {
+        let mut kv = store.clone();
+        let (history, batch_size, batch_timeout) = (
+            settings.history,
+            settings.batch_size,
+            settings.batch_timeout,
+        );
+
+        tokio::spawn(async move {
+            let mut batch: Vec<RawEvent> = Vec::with_capacity(batch_size);
+            let mut completions: Vec<oneshot::Sender<()>> = Vec::with_capacity(batch_size);
+
+            while let Some((event, done)) = archive_rx.recv().await {
+                batch.clear();
+                completions.clear();
+                batch.push(event);
+                completions.push(done);
+
+                let deadline = Instant::now() + batch_timeout;
+                while batch.len() < batch_size {
+                    match timeout_at(deadline, archive_rx.recv()).await {
+                        Ok(Some((event, done))) => {
+                            batch.push(event);
+                            completions.push(done);
+                        }
+                        Ok(None) | Err(_) => break,
+                    }
+                }
+
+                let mut attempt: u32 = 0;
+                while let Err(err) = kv
+                    .write_many(&batch, history)
+                    .instrument(info_span!("archive", events = batch.len()))
+                    .await
+                {
+                    attempt += 1;
+                    error!("archive write failed (attempt {attempt}): {err}");
+                    tokio::time::sleep(
+                        (Duration::from_millis(250) * attempt).min(Duration::from_secs(5)),
+                    )
+                    .await;
+                }
+
+                for done in completions.drain(..) {
+                    let _ = done.send(());
+                }
+            }
+        });
+    }
+
+    let mut handles = Vec::with_capacity(settings.workers);
+    let mut txs = Vec::with_capacity(settings.workers);
+
+    for _ in 0..settings.workers {
+        let (tx, mut rx) = mpsc::channel::<Dispatch>(1024);
+        txs.push(tx);
+
+        let mut worker = Worker {
+            kv: store.clone(),
+            bus: bus.clone(),
+            archive: archive_tx.clone(),
+            match_prefix: settings.match_prefix.clone(),
+            gap,
+            jump_distance: settings.jump_distance,
+            context_window: settings.context_window,
+            solve_timeout: settings.solve_timeout,
+            solve_retries: settings.solve_retries,
+            trips: HashCache::with_capacity(0, settings.vehicle_cache),
+            histories: HashCache::with_capacity(0, settings.vehicle_cache),
+        };
+
+        handles.push(tokio::spawn(async move {
+            while let Some(Dispatch {
+                queued_at,
+                payload,
+                delivery,
+            }) = rx.recv().await
+            {
+                bus::span_between("worker_wait", queued_at, bus::wallclock());
+
+                let vehicle_id = payload.vehicle_id;
+                let span = info_span!(
+                    "orchestrate",
+                    continuation = field::Empty,
+                    fresh = field::Empty,
+                    cut = field::Empty,
+                    attempts = field::Empty,
+                );
+
+                // Never drop, never reorder: a transient failure backs off
+                // and starts the event over, holding this vehicle's lane
+                // (and, via the bus's unacked window, eventually the
+                // partition) — saturation builds a backlog in the stream
+                // instead.
+                let mut attempt: u32 = 0;
+                let outcome = loop {
+                    match worker
+                        .process(&payload, delivery.sequence, delivery.sent_at)
+                        .instrument(span.clone())
+                        .await
+                    {
+                        Ok(outcome) => break outcome,
+                        Err(err) => {
+                            attempt += 1;
+                            warn!("{vehicle_id}: pipeline attempt {attempt} failed: {err:#}");
+                            tokio::time::sleep(
+                                (Duration::from_millis(250) * attempt).min(Duration::from_secs(5)),
+                            )
+                            .await;
+                        }
+                    }
+                };
+
+                if let Processed::Dropped(reason) = outcome {
+                    debug!("{vehicle_id}: event dropped ({reason})");
+                }
+
+                if let Err(err) = delivery.ack().await {
+                    error!("{vehicle_id}: could not ack event: {err}");
+                }
+            }
+        }));
+    }
+
+    // One forwarder per owned partition: consume it, decode, and pin to the
+    // vehicle's worker. Poison messages (undecodable) are acked away —
+    // redelivering them can never succeed.
+    let mut forwarders = Vec::new();
+    for partition in owned.clone() {
+        let mut deliveries = bus
+            .consume(
+                ingest::raw_subject(partition),
+                format!("orchestrator-p{partition}"),
+            )
+            .await?;
+
+        let txs = txs.clone();
+        forwarders.push(tokio::spawn(async move {
+            while let Some(delivery) = deliveries.next().await {
+                let payload = match Payload::decode(&delivery.payload) {
+                    Ok(payload) => payload,
+                    Err(err) => {
+                        warn!("partition {partition}: acking poison event: {err}");
+                        let _ = delivery.ack().await;
+                        continue;
+                    }
+                };
+
+                // The stable path (not `DefaultHasher`): worker pinning is
+                // the same per-vehicle spread the partition scheme derives,
+                // so the two never disagree on a Rust release boundary.
+                let worker = partition::mix(payload.vehicle_id.0) as usize % txs.len();
+
+                let dispatch = Dispatch {
+                    queued_at: bus::wallclock(),
+                    payload,
+                    delivery,
+                };
+                if txs[worker].send(dispatch).await.is_err() {
+                    return;
+                }
+            }
+        }));
+    }
+
+    info!("consuming partitions {owned:?}");
+
+    Ok(Orchestrator {
+        forwarders,
+        workers: handles,
+        txs,
+    })
+}
+
+impl Orchestrator {
+    /// Wait for every partition's consumer to end, then drain the workers.
+    pub async fn join(self) {
+        for forwarder in self.forwarders {
+            forwarder.await.ok();
+        }
+
+        // Dropping the senders drains each worker before returning.
+        drop(self.txs);
+        for handle in self.workers {
+            handle.await.ok();
+        }
+    }
+}
+
+impl<B, S> Worker<B, S>
+where
+    B: Bus,
+    S: HistoryStore<RawEvent>,
+{
+    /// Run one event through the pipeline: warm and gate the history lane,
+    /// build the context, solve over req/res, durably publish the emission,
+    /// commit the resume state, durably archive the raw tail — then the
+    /// caller acks. Errors are transients: the caller retries the whole
+    /// pipeline, and every step tolerates being re-run (the publish carries
+    /// the same revision, the archive tolerates a duplicate append).
+    async fn process(
+        &mut self,
+        payload: &Payload,
+        revision: u64,
+        sent_at: Option<web_time::SystemTime>,
+    ) -> Result<Processed> {
+        let vehicle_id = payload.vehicle_id;
+
+        // Warm the lane once per vehicle per ownership — the only store
+        // read, off the per-event hot path.
+        if self.histories.get(&vehicle_id).is_none() {
+            let mut warmed = self
+                .kv
+                .get_many(&vehicle_id, self.context_window * 3)
+                .instrument(info_span!("lane_warm"))
+                .await
+                .context("could not warm history lane")?;
+            warmed.sort_by_key(|event| event.timestamp);
+
+            match self.histories.entry(vehicle_id) {
+                Entry::Occupied(mut entry) => {
+                    entry.put(warmed);
+                }
+                Entry::Vacant(entry) => {
+                    entry.put_entry(warmed);
+                }
+            }
+        }
+
+        // The lane gate: supplier timestamps are per-vehicle monotonic, so a
+        // regression is stale data — and a redelivery of an event whose ack
+        // was lost lands here too, making re-processing idempotent.
+        let history = {
+            let guard = self.histories.get(&vehicle_id).expect("lane warmed above");
+            guard.get().clone()
+        };
+        if let Some(last) = history.last()
+            && payload.timestamp <= last.timestamp
+        {
+            return Ok(Processed::Dropped("stale_or_duplicate"));
+        }
+
+        let context = self.create_context(history, payload);
+
+        // Route to the ground beneath the event: the matcher owning the
+        // head's shard solves it, degrading a foreign resume itself.
+        let subject = format!(
+            "{}.{}",
+            self.match_prefix,
+            crate::event::shard_of(payload.point)
+        );
+
+        // The vehicle's lane holds through the round trip: its next event
+        // cannot overtake this one, so a stale solve can never overwrite a
+        // fresh trip. Other vehicles overlap on other workers.
+        let Some(reply) = solve(
+            &self.bus,
+            &subject,
+            &context,
+            self.solve_timeout,
+            self.solve_retries,
+        )
+        .await
+        else {
+            anyhow::bail!("no matcher reply after retries");
+        };
+
+        if let MatchReply::Solved { mut diff, trip } = reply {
+            // The revision is the ingest stream sequence: broker-assigned,
+            // monotonic per vehicle, and identical across re-drives — the
+            // total order competing solves resolve by.
+            diff.revision = revision;
+
+            let matched = MatchedEvent { vehicle_id, diff };
+            let bytes = matched.encode().context("could not encode emission")?;
+
+            let subject = ingest::matched_subject(partition::partition_of(vehicle_id));
+            self.bus
+                .publish(subject, bytes)
+                .instrument(info_span!("publish_matched"))
+                .await
+                .context("could not publish emission")?;
+
+            if let Some(sent_at) = sent_at {
+                bus::span_between("event_to_match", sent_at, bus::wallclock());
+            }
+
+            // Commit after the durable publish: a crash in between re-drives
+            // the whole event, never strands a trip ahead of its emissions.
+            match self.trips.entry(vehicle_id) {
+                Entry::Occupied(mut entry) => {
+                    entry.put(trip);
+                }
+                Entry::Vacant(entry) => {
+                    entry.put_entry(trip);
+                }
+            }
+        }
+
+        // The raw tail is the failover recovery source: durably written
+        // before the ack, batched with everyone else's events.
+        let event = payload.as_event();
+
+        let (done, flushed) = oneshot::channel();
+        self.archive
+            .send((event.clone(), done))
+            .await
+            .map_err(|_| anyhow!("archive writer gone"))?;
+        flushed
+            .instrument(info_span!("archive_wait"))
+            .await
+            .map_err(|_| anyhow!("archive writer dropped the batch"))?;
+
+        // Only now does the event enter the lane: everything behind the gate
+        // is durably recorded, so a redelivery can trust the drop.
+        if let Some(mut guard) = self.histories.get(&vehicle_id) {
+            let lane = guard.get_mut();
+            lane.push(event);
+
+            let bound = self.context_window * 3;
+            if lane.len() > bound {
+                let excess = lane.len() - bound;
+                lane.drain(..excess);
+            }
+        }
+
+        Ok(Processed::Done)
+    }
+
+    /// Assemble the vehicle's match context from its (oldest-first) history
+    /// lane and the live event: gap/teleport cut, then reconcile against the
+    /// committed trip.
+    fn create_context(&self, mut entries: Vec<RawEvent>, payload: &Payload) -> MatchContext<E> {
+        let Payload {
+            vehicle_id,
+            timestamp,
+            point,
+            ..
+        } = *payload;
+
+        entries.retain(|event| event.timestamp <= timestamp);
+        entries.sort_by_key(|event| std::cmp::Reverse(event.timestamp));
+        entries.truncate(self.context_window);
+
+        let fetched = entries.len();
+        let context = entries
+            .into_iter()
+            .inspect(|v| debug!("event: {:?}", v))
+            .scan((point, timestamp), |(prev_p, prev_ts), event: RawEvent| {
+                let duration = (*prev_ts - event.timestamp).abs();
+                let distance = Haversine.distance(*prev_p, event.point);
+
+                if duration <= self.gap && distance <= self.jump_distance {
+                    *prev_p = event.point;
+                    *prev_ts = event.timestamp;
+                    Some(event)
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let cut = fetched - context.len();
+        if cut > 0 {
+            info_span!("history_cut", reason = "gap_or_teleport").in_scope(|| {});
+        }
+
+        let mut history: Vec<RawEvent> =
+            std::iter::once(payload.as_event()).chain(context).collect();
+
+        history.sort_by_key(|event| event.timestamp);
+        history.dedup_by_key(|event| event.timestamp);
+
+        let origins = history.iter().map(RawEvent::as_origin).collect::<Vec<_>>();
+
+        let previous = self.trips.get(&vehicle_id).map(|trip| trip.get().clone());
+        let continuation =
+            info_span!("reconcile").in_scope(|| Continuation::reconcile(previous, &origins));
+
+        let span = tracing::Span::current();
+        span.record("cut", cut);
+        match &continuation {
+            Continuation::Resume { fresh, .. } => {
+                span.record("continuation", "resume");
+                span.record("fresh", fresh.len());
+            }
+            Continuation::Restart { fresh } => {
+                span.record("continuation", "restart");
+                span.record("fresh", fresh.len());
+            }
+        }
+
+        MatchContext {
+            vehicle_id,
+            continuation,
+        }
+    }
+}
+
+/// Ask a matcher for one context's solve, re-driving on timeout or transport
+/// error. `None` when every attempt failed; the caller treats that as a
+/// transient and starts the event over — nothing is dropped.
+async fn solve<B: Bus>(
+    bus: &B,
+    subject: &str,
+    context: &MatchContext<E>,
+    timeout: Duration,
+    retries: usize,
+) -> Option<MatchReply<E>> {
+    let payload = match context.encode() {
+        Ok(payload) => payload,
+        Err(err) => {
+            error!("could not encode match context: {err:#}");
+            return None;
+        }
+    };
+
+    for attempt in 0..=retries {
+        if attempt > 0 {
+            tokio::time::sleep(Duration::from_millis(250) * attempt as u32).await;
+        }
+
+        let request = bus.request(subject.to_string(), payload.clone());
+
+        match tokio::time::timeout(timeout, request).await {
+            Ok(Ok(reply)) => {
+                tracing::Span::current().record("attempts", attempt as u64 + 1);
+
+                match MatchReply::<E>::decode(&reply) {
+                    Ok(reply) => return Some(reply),
+                    // A decode failure is a version skew, not a transient:
+                    // re-driving it would only re-fail.
+                    Err(err) => {
+                        error!("undecodable reply: {err:#}");
+                        return None;
+                    }
+                }
+            }
+            Ok(Err(err)) => warn!("solve request failed (attempt {attempt}): {err:#}"),
+            Err(_) => warn!("solve request timed out (attempt {attempt})"),
+        }
+    }
+
+    None
+}
@@ -0,0 +1,402 @@
+//! Folds the matched stream into one converged timeline per vehicle.
+//!
+//! Every [`MatchedDiff`] re-emits the whole region a solve could still
+//! change, so consecutive emissions overlap. Layers merge by (vehicle,
+//! timestamp), and a layer from a higher revision replaces a lower one. A
+//! diff at or below the latest revision applied is a late reply or a
+//! redelivered duplicate, and changes nothing.
+//!
+//! A diff also says what can no longer change: its committed layers, and
+//! everything before its first layer, which was cut behind a commit point.
+//! Those layers, and pending layers older than the newest diff's first, are
+//! therefore final, and leave the reconciler as a [`Finalized`] batch for a
+//! [`TimelineStore`](crate::store::TimelineStore) to record. The one exception is a `downgraded` diff, whose solve
+//! restarted from raw history: it may rewrite layers already finalized, so
+//! it rolls the vehicle's finalized watermark back to its first layer.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use futures::StreamExt;
+use futures::stream::BoxStream;
+use log::{error, warn};
+use routers_network::Entry;
+use scc::HashCache;
+use scc::hash_cache::Entry as CacheEntry;
+use serde::{Serialize, de::DeserializeOwned};
+use tracing::{Instrument, info_span};
+
+use crate::bus::{Delivery, Wire};
+use crate::event::{MatchedDiff, MatchedEvent, MatchedLayer, VehicleId};
+use crate::ingest;
+use crate::store::TimelineStore;
+
+/// Layers which can no longer change, oldest first. A store recording them
+/// replaces everything it holds for the vehicle from the first layer on.
+#[derive(Clone, Debug)]
+pub struct Finalized<E: Entry> {
+    pub vehicle_id: VehicleId,
+    pub layers: Vec<MatchedLayer<E>>,
+}
+
+/// One vehicle's reconciled state.
+#[derive(Clone, Debug)]
+struct Timeline<E: Entry> {
+    /// The highest revision applied so far.
+    revision: Option<u64>,
+
+    /// Layers a later solve may still rewrite, by timestamp, each with the
+    /// revision it came from.
+    pending: BTreeMap<i64, (u64, MatchedLayer<E>)>,
+
+    /// The timestamp of the latest finalized layer. Nothing at or before
+    /// it is pending.
+    finalized: Option<i64>,
+}
+
+impl<E: Entry> Default for Timeline<E> {
+    fn default() -> Self {
+        Self {
+            revision: None,
+            pending: BTreeMap::new(),
+            finalized: None,
+        }
+    }
+}
+
+impl<E: Entry> Timeline<E> {
+    /// Merge a diff, returning the layers it made final.
+    fn merge(&mut self, diff: MatchedDiff<E>) -> Vec<MatchedLayer<E>> {
+        let (Some(first), Some(last)) = (diff.iter().next(), diff.iter().last()) else {
+            return Vec::new();
+        };
+        let (first, last) = (first.timestamp, last.timestamp);
+
+        // An older solve cannot overrule the latest, not even for layers the
+        // latest never carried: it may have dropped them deliberately.
+        if self
+            .revision
+            .is_some_and(|revision| diff.revision <= revision)
+        {
+            return Vec::new();
+        }
+
+        if diff.downgraded && self.finalized.is_some_and(|at| at >= first) {
+            self.finalized = Some(first - 1);
+        }
+
+        // Everything before the diff's first layer was cut behind its
+        // commit point.
+        let mut finalized = Vec::new();
+        let still_pending = self.pending.split_off(&first);
+        for (timestamp, (_, layer)) in core::mem::replace(&mut self.pending, still_pending) {
+            self.finalized = Some(timestamp);
+            finalized.push(layer);
+        }
+
+        // The diff is the whole of what its solve could change: pending
+        // layers within its span that it no longer carries were dropped.
+        let beyond = self.pending.split_off(&(last + 1));
+        self.pending = beyond;
+
+        // Committed layers are final as they arrive.
+        for layer in diff.committed {
+            if self.finalized.is_none_or(|at| layer.timestamp > at) {
+                self.finalized = Some(layer.timestamp);
+                finalized.push(layer);
+            }
+        }
+
+        for layer in diff.layers {
+            if self.finalized.is_none_or(|at| layer.timestamp > at) {
+                self.pending.insert(layer.timestamp, (diff.revision, layer));
+            }
+        }
+
+        self.revision = Some(diff.revision);
+        finalized
+    }
+
+    /// Every pending layer, made final.
+    fn drain(self) -> Vec<MatchedLayer<E>> {
+        self.pending.into_values().map(|(_, layer)| layer).collect()
+    }
+}
+
+/// The converged matched timeline of every vehicle the reconciler has heard
+/// from recently.
+///
+/// Bounded like the orchestrator's caches: vehicles finish their trips and
+/// never return, so the least recently heard-from vehicle is evicted once
+/// `capacity` is reached. Its pending layers are finalized as they stand,
+/// since a vehicle gone that long has nothing left to re-solve.
+pub struct Reconciler<E: Entry> {
+    timelines: HashCache<VehicleId, Timeline<E>>,
+}
+
+impl<E: Entry> Reconciler<E> {
+    /// `capacity` is rounded up to a power of two. Size it above the
+    /// vehicles in flight at once, or live timelines are finalized early.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            timelines: HashCache::with_capacity(0, capacity),
+        }
+    }
+
+    /// Merge one emission, returning whatever it made final: the vehicle's
+    /// own settled layers, and those of any vehicle evicted to make room.
+    pub fn apply(&self, MatchedEvent { vehicle_id, diff }: MatchedEvent<E>) -> Vec<Finalized<E>> {
+        let mut finalized = Vec::new();
+
+        let layers = match self.timelines.entry(vehicle_id) {
+            CacheEntry::Occupied(mut occupied) => occupied.get_mut().merge(diff),
+            CacheEntry::Vacant(vacant) => {
+                let mut timeline = Timeline::default();
+                let layers = timeline.merge(diff);
+
+                let (evicted, _) = vacant.put_entry(timeline);
+                if let Some((vehicle_id, timeline)) = evicted {
+                    finalized.push(Finalized {
+                        vehicle_id,
+                        layers: timeline.drain(),
+                    });
+                }
+
+                layers
+            }
+        };
+
+        finalized.push(Finalized { vehicle_id, layers });
+        finalized.retain(|batch| !batch.layers.is_empty());
+        finalized
+    }
+
+    /// The vehicle's layers a later solve may still rewrite, oldest first.
+    pub fn pending(&self, vehicle_id: &VehicleId) -> Vec<MatchedLayer<E>> {
+        self.timelines
+            .get(vehicle_id)
+            .map(|timeline| {
+                timeline
+                    .get()
+                    .pending
+                    .values()
+                    .map(|(_, layer)| layer.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// The filter tailing every matched emission, for [`run`].
+pub fn matched_filter() -> String {
+    format!("{}.>", ingest::MATCHED_PREFIX)
+}
+
+/// Fold tailed emissions (see [`matched_filter`]) into `store` until the
+/// bus ends the stream.
+///
+/// Nothing consumes the matched stream destructively, so this holds no
+/// durable position: each start replays whatever the bus retains.
+/// Finalizing replaces a region outright, so replaying emissions already
+/// reconciled converges on the same timelines.
+pub async fn run<E, S>(
+    mut deliveries: BoxStream<'static, Delivery>,
+    mut store: S,
+    reconciler: Reconciler<E>,
+) where
+    E: Entry + Serialize + DeserializeOwned,
+    S: TimelineStore<E>,
+{
+    while let Some(delivery) = deliveries.next().await {
+        let event = match MatchedEvent::<E>::decode(&delivery.payload) {
+            Ok(event) => event,
+            Err(err) => {
+                warn!("skipping undecodable emission: {err}");
+                continue;
+            }
+        };
+
+        for Finalized { vehicle_id, layers } in reconciler.apply(event) {
+            // Finalized layers exist nowhere else once their emissions age
+            // out of the stream, so a failed write retries until it lands.
+            let mut attempt: u32 = 0;
+            while let Err(err) = store
+                .finalize(vehicle_id, &layers)
+                .instrument(info_span!("finalize", layers = layers.len()))
+                .await
+            {
+                attempt += 1;
+                error!("{vehicle_id}: finalize failed (attempt {attempt}): {err}");
+                tokio::time::sleep(
+                    (Duration::from_millis(250) * attempt).min(Duration::from_secs(5)),
+                )
+                .await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo::Point;
+    use routers_network::mock::MockEntryId;
+    use routers_network::{DirectionAwareEdgeId, Edge};
+
+    type E = MockEntryId;
+
+    const VEHICLE: VehicleId = VehicleId(7);
+
+    /// A layer whose edge records the revision that produced it.
+    fn layer(timestamp: i64, revision: u64) -> MatchedLayer<E> {
+        MatchedLayer {
+            timestamp,
+            edge: Edge {
+                source: MockEntryId(revision as i64),
+                target: MockEntryId(revision as i64 + 1),
+                weight: 1,
+                id: DirectionAwareEdgeId::new(MockEntryId(revision as i64)),
+            },
+            position: Point::new(timestamp as f64, 0.0),
+            path: Vec::new(),
+        }
+    }
+
+    fn event(revision: u64, downgraded: bool, timestamps: &[i64]) -> MatchedEvent<E> {
+        MatchedEvent {
+            vehicle_id: VEHICLE,
+            diff: MatchedDiff {
+                revision,
+                downgraded,
+                committed: Vec::new(),
+                layers: timestamps.iter().map(|&at| layer(at, revision)).collect(),
+            },
+        }
+    }
+
+    fn timestamps(layers: &[MatchedLayer<E>]) -> Vec<i64> {
+        layers.iter().map(|layer| layer.timestamp).collect()
+    }
+
+    fn finalized(batches: Vec<Finalized<E>>) -> Vec<i64> {
+        batches
+            .iter()
+            .flat_map(|batch| timestamps(&batch.layers))
+            .collect()
+    }
+
+    #[test]
+    fn overlapping_emissions_converge_on_the_highest_revision() {
+        let reconciler = Reconciler::new(16);
+
+        assert!(reconciler.apply(event(1, false, &[10, 20])).is_empty());
+        assert!(reconciler.apply(event(2, false, &[10, 20, 30])).is_empty());
+
+        let pending = reconciler.pending(&VEHICLE);
+        assert_eq!(timestamps(&pending), vec![10, 20, 30]);
+        assert!(
+            pending
+                .iter()
+                .all(|layer| layer.edge.source == MockEntryId(2))
+        );
+
+        // A late reply from an older solve does not overrule the newer one.
+        assert!(reconciler.apply(event(1, false, &[10, 20])).is_empty());
+        assert!(
+            reconciler
+                .pending(&VEHICLE)
+                .iter()
+                .all(|layer| layer.edge.source == MockEntryId(2))
+        );
+    }
+
+    #[test]
+    fn layers_behind_a_cut_are_finalized() {
+        let reconciler = Reconciler::new(16);
+
+        reconciler.apply(event(1, false, &[10, 20, 30]));
+        let done = reconciler.apply(event(2, false, &[30, 40]));
+
+        assert_eq!(finalized(done), vec![10, 20]);
+        assert_eq!(timestamps(&reconciler.pending(&VEHICLE)), vec![30, 40]);
+
+        // Finalized layers stay final: a stale re-emission cannot reopen them.
+        reconciler.apply(event(1, false, &[10, 20, 30]));
+        assert_eq!(timestamps(&reconciler.pending(&VEHICLE)), vec![30, 40]);
+    }
+
+    #[test]
+    fn committed_layers_are_final_on_arrival() {
+        let reconciler = Reconciler::new(16);
+
+        let mut commit = event(1, false, &[10, 20, 30]);
+        commit.diff = commit.diff.with_committed(2);
+
+        let done = reconciler.apply(commit);
+
+        assert_eq!(finalized(done), vec![10, 20]);
+        assert_eq!(timestamps(&reconciler.pending(&VEHICLE)), vec![30]);
+    }
+
+    #[test]
+    fn a_newer_solve_drops_layers_it_no_longer_carries() {
+        let reconciler = Reconciler::new(16);
+
+        reconciler.apply(event(1, false, &[10, 20, 30]));
+        reconciler.apply(event(2, false, &[10, 30]));
+
+        assert_eq!(timestamps(&reconciler.pending(&VEHICLE)), vec![10, 30]);
+    }
+
+    #[test]
+    fn a_stale_solve_cannot_restore_dropped_layers() {
+        let reconciler = Reconciler::new(16);
+
+        reconciler.apply(event(1, false, &[10, 20, 30]));
+        reconciler.apply(event(2, false, &[10, 30]));
+
+        // A replay of the first solve must not bring 20 back.
+        assert!(reconciler.apply(event(1, false, &[10, 20, 30])).is_empty());
+        assert_eq!(timestamps(&reconciler.pending(&VEHICLE)), vec![10, 30]);
+        assert!(
+            reconciler
+                .pending(&VEHICLE)
+                .iter()
+                .all(|layer| layer.edge.source == MockEntryId(2))
+        );
+    }
+
+    #[test]
+    fn a_downgraded_solve_rewrites_finalized_layers() {
+        let reconciler = Reconciler::new(16);
+
+        reconciler.apply(event(1, false, &[10, 20, 30]));
+        reconciler.apply(event(2, false, &[30, 40]));
+
+        // The restart re-covers the trip from its first observation.
+        assert!(reconciler.apply(event(3, true, &[20, 30, 40])).is_empty());
+        assert_eq!(timestamps(&reconciler.pending(&VEHICLE)), vec![20, 30, 40]);
+
+        let done = reconciler.apply(event(4, false, &[40]));
+        assert_eq!(finalized(done), vec![20, 30]);
+    }
+
+    #[test]
+    fn evicted_vehicles_are_finalized_as_they_stand() {
+        let reconciler = Reconciler::<E>::new(1);
+
+        let mut done = Vec::new();
+        for vehicle in 0..1024 {
+            done.extend(reconciler.apply(MatchedEvent {
+                vehicle_id: VehicleId(vehicle),
+                ..event(1, false, &[10])
+            }));
+        }
+
+        assert!(!done.is_empty());
+        assert!(
+            done.iter()
+                .all(|batch| timestamps(&batch.layers) == vec![10])
+        );
+    }
+}
@@ -0,0 +1,103 @@
+//! Recorded position traces, replayed as raw events: the reference producer
+//! for the ingest contract (`partition` + `ingest`).
+//!
+//! A recording is a CSV of `VehicleID`, `Provider`, `EventTime`, `Latitude`
+//! and `Longitude` columns, loaded whole and sorted by event time.
+
+use std::path::PathBuf;
+
+use fnv_rs::{Fnv64, FnvHasher};
+use geo::Point;
+use itertools::izip;
+use polars::prelude::*;
+
+use crate::event::{Payload, VehicleId};
+
+// 2026-04-01 03:40:02 UTC, or 2026-04-01 03:40:02.123456 UTC
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S %Z";
+const TIME_FORMAT_FRACTIONAL: &str = "%Y-%m-%d %H:%M:%S%.f %Z";
+
+// Column names
+const VEHICLE_ID_COL: &str = "VehicleID";
+
+const PROVIDER_COL: &str = "Provider";
+const EVENT_TIME_COL: &str = "EventTime";
+
+const LATITUDE_COL: &str = "Latitude";
+const LONGITUDE_COL: &str = "Longitude";
+
+fn parse_datetime(fmt: &str) -> Expr {
+    col(EVENT_TIME_COL).str().to_datetime(
+        Some(TimeUnit::Microseconds),
+        None,
+        StrptimeOptions {
+            format: Some(fmt.into()),
+            strict: false,
+            ..Default::default()
+        },
+        lit("raise"),
+    )
+}
+
+/// Load a recording, sorted by event time.
+pub fn load(file: PathBuf) -> anyhow::Result<DataFrame> {
+    LazyCsvReader::new(file)
+        .with_has_header(true)
+        .finish()?
+        .sort([EVENT_TIME_COL], SortMultipleOptions::default())
+        .select([
+            col(VEHICLE_ID_COL),
+            col(PROVIDER_COL),
+            parse_datetime(TIME_FORMAT).fill_null(parse_datetime(TIME_FORMAT_FRACTIONAL)),
+            col(LATITUDE_COL),
+            col(LONGITUDE_COL),
+        ])
+        .collect()
+        .map_err(|e| anyhow::anyhow!("dataframe parse: {e}"))
+}
+
+/// The first and last event times of a loaded recording, in microseconds
+/// since the Unix epoch. `None` when it holds no events.
+pub fn time_range(df: &DataFrame) -> PolarsResult<Option<(u64, u64)>> {
+    let times = df.column(EVENT_TIME_COL)?.datetime()?;
+    Ok(times
+        .min()
+        .zip(times.max())
+        .map(|(min, max)| (min as u64, max as u64)))
+}
+
+/// Every event of a loaded recording with its event time, in order.
+pub fn rows_of(df: &DataFrame) -> PolarsResult<impl Iterator<Item = (u64, Payload)> + '_> {
+    let vehicle = df.column(VEHICLE_ID_COL)?.str()?;
+    let provider = df.column(PROVIDER_COL)?.str()?;
+    let etime = df.column(EVENT_TIME_COL)?.datetime()?;
+    let lat = df.column(LATITUDE_COL)?.f64()?;
+    let lon = df.column(LONGITUDE_COL)?.f64()?;
+
+    Ok(izip!(
+        vehicle.into_iter(),
+        provider.into_iter(),
+        etime.into_iter(),
+        lat.into_iter(),
+        lon.into_iter()
+    )
+    .filter_map(|(vehicle, _, etime, lat, lon)| {
+        // The id contract (schema: realtime/v1/event.proto): the FNV-1a
+        // 64-bit hash of the upstream string, as an integer. `as_bytes`
+        // yields it big-endian.
+        let vehicle_id: [u8; 8] = Fnv64::hash(vehicle?).as_bytes().try_into().ok()?;
+
+        let payload = Payload {
+            vehicle_id: VehicleId(u64::from_be_bytes(vehicle_id)),
+            // The column is parsed as microseconds since the Unix epoch.
+            timestamp: chrono::DateTime::from_timestamp_micros(etime.unwrap_or_default())
+                .unwrap_or_default(),
+            point: Point::new(lon.unwrap(), lat.unwrap()),
+            heading: None,
+            speed: None,
+            accuracy: None,
+        };
+
+        Some((etime.unwrap() as u64, payload))
+    }))
+}
@@ -0,0 +1,230 @@
+use core::convert::Infallible;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use routers_network::Entry;
+
+use crate::event::{MatchedLayer, VehicleId};
+use crate::store::{HistoryStore, Storable, StoreError, TimelineStore};
+
+/// Vehicle histories held in process memory, the counterpart of
+/// [`RedisStore`](crate::store::RedisStore) for tests and single-process
+/// deployments. Cloning shares the histories.
+///
+/// Like [`MemoryTimelines`], each vehicle is bounded but the vehicles are
+/// not: they accumulate for the life of the process.
+#[derive(Clone)]
+pub struct MemoryStore<T: Storable> {
+    histories: Arc<Mutex<HashMap<String, VecDeque<T>>>>,
+}
+
+impl<T: Storable> MemoryStore<T> {
+    pub fn new() -> Self {
+        Self {
+            histories: Arc::default(),
+        }
+    }
+}
+
+impl<T: Storable> Default for MemoryStore<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Storable + Send + Sync> HistoryStore<T> for MemoryStore<T> {
+    type Error = Infallible;
+
+    async fn get_many(&mut self, vehicle_id: &VehicleId, len: usize) -> Result<Vec<T>, Infallible> {
+        let histories = self.histories.lock().expect("histories lock poisoned");
+
+        Ok(histories
+            .get(&vehicle_id.to_string())
+            .map(|history| history.iter().rev().take(len).cloned().collect())
+            .unwrap_or_default())
+    }
+
+    async fn write_many(&mut self, batch: &[T], limit: usize) -> Result<(), Infallible> {
+        let mut histories = self.histories.lock().expect("histories lock poisoned");
+
+        for item in batch {
+            let history = histories.entry(item.key().to_string()).or_default();
+            history.push_back(item.clone());
+
+            while history.len() > limit {
+                history.pop_front();
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// One vehicle's finalized layers, by timestamp.
+type Timeline<E> = BTreeMap<i64, MatchedLayer<E>>;
+
+/// Finalized timelines held in process memory, for tests and single-process
+/// deployments. Cloning shares the timelines, so a reader may hold one clone
+/// while the reconciler writes through another.
+///
+/// Nothing is ever evicted: each vehicle keeps at most `limit` layers, but
+/// the vehicles themselves accumulate for the life of the process.
+#[derive(Clone)]
+pub struct MemoryTimelines<E: Entry> {
+    timelines: Arc<Mutex<HashMap<VehicleId, Timeline<E>>>>,
+    limit: usize,
+}
+
+impl<E: Entry> MemoryTimelines<E> {
+    /// `limit` is the layers kept per vehicle, the oldest dropped first. It
+    /// must be at least one, as for [`RedisTimelines`](crate::store::RedisTimelines).
+    pub fn new(limit: usize) -> Result<Self, StoreError> {
+        if limit == 0 {
+            return Err(StoreError::ZeroLimit);
+        }
+
+        Ok(Self {
+            timelines: Arc::default(),
+            limit,
+        })
+    }
+}
+
+impl<E: Entry> TimelineStore<E> for MemoryTimelines<E> {
+    type Error = Infallible;
+
+    async fn finalize(
+        &mut self,
+        vehicle_id: VehicleId,
+        layers: &[MatchedLayer<E>],
+    ) -> Result<(), Infallible> {
+        let Some(first) = layers.first() else {
+            return Ok(());
+        };
+
+        let mut timelines = self.timelines.lock().expect("timelines lock poisoned");
+        let timeline = timelines.entry(vehicle_id).or_default();
+
+        timeline.split_off(&first.timestamp);
+        timeline.extend(layers.iter().map(|layer| (layer.timestamp, layer.clone())));
+
+        while timeline.len() > self.limit {
+            timeline.pop_first();
+        }
+
+        Ok(())
+    }
+
+    async fn read(
+        &mut self,
+        vehicle_id: VehicleId,
+        since: i64,
+    ) -> Result<Vec<MatchedLayer<E>>, Infallible> {
+        let timelines = self.timelines.lock().expect("timelines lock poisoned");
+
+        Ok(timelines
+            .get(&vehicle_id)
+            .map(|timeline| {
+                timeline
+                    .range(since..)
+                    .map(|(_, layer)| layer.clone())
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use geo::Point;
+    use routers_network::mock::MockEntryId;
+    use routers_network::{DirectionAwareEdgeId, Edge};
+
+    fn layer(timestamp: i64) -> MatchedLayer<MockEntryId> {
+        MatchedLayer {
+            timestamp,
+            edge: Edge {
+                source: MockEntryId(1),
+                target: MockEntryId(2),
+                weight: 1,
+                id: DirectionAwareEdgeId::new(MockEntryId(1)),
+            },
+            position: Point::new(timestamp as f64, 0.0),
+            path: Vec::new(),
+        }
+    }
+
+    fn timestamps(layers: Vec<MatchedLayer<MockEntryId>>) -> Vec<i64> {
+        layers.iter().map(|layer| layer.timestamp).collect()
+    }
+
+    #[derive(Clone, serde::Serialize, serde::Deserialize)]
+    struct Sample {
+        vehicle: u64,
+        at: i64,
+    }
+
+    impl Storable for Sample {
+        type ShardId = u64;
+        type Key = VehicleId;
+
+        fn shard_id(&self) -> u64 {
+            0
+        }
+
+        fn key(&self) -> VehicleId {
+            VehicleId(self.vehicle)
+        }
+    }
+
+    /// Histories read back newest first, each vehicle trimmed to the limit.
+    #[test]
+    fn histories_keep_the_newest_entries() {
+        let mut store = MemoryStore::new();
+        let batch: Vec<_> = (1..=4)
+            .flat_map(|at| [Sample { vehicle: 1, at }, Sample { vehicle: 2, at }])
+            .collect();
+
+        block_on(store.write_many(&batch, 3)).unwrap();
+
+        let read = |vehicle, len| {
+            block_on(store.clone().get_many(&VehicleId(vehicle), len))
+                .unwrap()
+                .iter()
+                .map(|sample| sample.at)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(read(1, 10), vec![4, 3, 2]);
+        assert_eq!(read(2, 2), vec![4, 3]);
+        assert!(read(3, 10).is_empty());
+    }
+
+    /// A re-finalized region replaces what was recorded from its start,
+    /// including layers the rewrite no longer carries.
+    #[test]
+    fn finalizing_replaces_from_the_first_layer() {
+        let mut store = MemoryTimelines::new(3).unwrap();
+        let vehicle = VehicleId(1);
+
+        block_on(store.finalize(vehicle, &[layer(10), layer(20), layer(30)])).unwrap();
+        block_on(store.finalize(vehicle, &[layer(20), layer(25)])).unwrap();
+
+        let read = |since| timestamps(block_on(store.clone().read(vehicle, since)).unwrap());
+        assert_eq!(read(0), vec![10, 20, 25]);
+        assert_eq!(read(20), vec![20, 25]);
+
+        block_on(store.finalize(vehicle, &[layer(40)])).unwrap();
+        assert_eq!(
+            timestamps(block_on(store.read(vehicle, 0)).unwrap()),
+            vec![20, 25, 40]
+        );
+    }
+
+    #[test]
+    fn timelines_refuse_a_zero_limit() {
+        let result = MemoryTimelines::<MockEntryId>::new(0);
+        assert!(matches!(result, Err(StoreError::ZeroLimit)));
+    }
+}
@@ -1,8 +1,17 @@
 use serde::{Serialize, de::DeserializeOwned};
 
+use routers_network::Entry;
+
+use crate::event::{MatchedLayer, VehicleId};
+
+mod memory;
 mod redis;
+pub use memory::MemoryStore;
+pub use memory::MemoryTimelines;
 pub use redis::CachedRedisStore;
 pub use redis::RedisStore;
+pub use redis::RedisTimelines;
+pub use redis::StoreError;
 
 pub trait Storable: Serialize + DeserializeOwned + Clone {
     type ShardId: std::fmt::Display;
@@ -10,4 +19,59 @@ pub trait Storable: Serialize + DeserializeOwned + Clone {
 
     fn shard_id(&self) -> Self::ShardId;
     fn key(&self) -> Self::Key;
+
+    /// Decode an entry as a store wrote it. Types whose stored layout has
+    /// changed override this to read the layouts older histories still hold.
+    fn decode(bytes: &[u8]) -> Result<Self, postcard::Error> {
+        postcard::from_bytes(bytes)
+    }
+}
+
+/// Where each vehicle's recent [`Storable`] history lives: the tail a trip
+/// resumes from after its owner changes.
+///
+/// [`RedisStore`] is the deployed backend. [`MemoryStore`] keeps the same
+/// tails in process, for tests and single-process runs.
+pub trait HistoryStore<T: Storable> {
+    type Error: core::error::Error + Send + Sync + 'static;
+
+    /// Up to `len` of the vehicle's entries, newest first.
+    fn get_many(
+        &mut self,
+        vehicle_id: &VehicleId,
+        len: usize,
+    ) -> impl Future<Output = Result<Vec<T>, Self::Error>> + Send;
+
+    /// Append a batch, which may span vehicles, keeping about `limit`
+    /// entries per vehicle.
+    fn write_many(
+        &mut self,
+        batch: &[T],
+        limit: usize,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send;
+}
+
+/// Where the reconciler records each vehicle's finalized matched timeline,
+/// and where readers find it.
+///
+/// Finalized layers arrive oldest first, and a batch replaces everything
+/// held for the vehicle from its first layer on: a downgraded solve may
+/// rewrite a region already recorded, and re-finalizes it from the start.
+pub trait TimelineStore<E: Entry> {
+    type Error: core::error::Error + Send + Sync + 'static;
+
+    /// Record a vehicle's newly finalized layers.
+    fn finalize(
+        &mut self,
+        vehicle_id: VehicleId,
+        layers: &[MatchedLayer<E>],
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+    /// The vehicle's finalized layers observed at or after `since`
+    /// (microseconds since the Unix epoch), oldest first.
+    fn read(
+        &mut self,
+        vehicle_id: VehicleId,
+        since: i64,
+    ) -> impl Future<Output = Result<Vec<MatchedLayer<E>>, Self::Error>> + Send;
 }
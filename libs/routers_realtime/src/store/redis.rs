@@ -8,9 +8,12 @@ use scc::hash_cache::Entry;
 use thiserror::Error;
 use url::Url;
 
-use crate::event::VehicleId;
+use routers_network::Entry as NetworkEntry;
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::event::{MatchedLayer, VehicleId};
 use crate::partition::{fnv1a, mix};
-use crate::store::Storable;
+use crate::store::{HistoryStore, Storable, TimelineStore};
 
 #[derive(Debug, Error)]
 pub enum StoreError {
@@ -20,6 +23,8 @@ pub enum StoreError {
     Serialisation(#[from] postcard::Error),
     #[error("no redis endpoints supplied")]
     NoEndpoints,
+    #[error("a timeline must keep at least one layer")]
+    ZeroLimit,
 }
 
 type Result<T> = std::result::Result<T, StoreError>;
@@ -56,8 +61,24 @@ impl Placement {
             // The seed breaks ties, so the winner never depends on list order.
             .max_by_key(|(_, seed)| (mix(hash ^ **seed), **seed))
             .map(|(index, _)| index)
-            .expect("fleet is non-empty, checked in RedisStore::new")
+            .expect("fleet is non-empty, checked in connect")
+    }
+}
+
+/// Open one connection per primary, alongside the placement addressing them.
+async fn connect(urls: &[Url]) -> Result<(Vec<MultiplexedConnection>, Placement)> {
+    if urls.is_empty() {
+        return Err(StoreError::NoEndpoints);
     }
+
+    let conns = try_join_all(urls.iter().map(|url| async move {
+        redis::Client::open(url.clone())?
+            .get_multiplexed_async_connection()
+            .await
+    }))
+    .await?;
+
+    Ok((conns, Placement::new(urls)))
 }
 
 /// A fleet of independent Valkey primaries, addressed by vehicle.
@@ -79,20 +100,11 @@ pub struct RedisStore<T: Storable> {
 
 impl<T: Storable> RedisStore<T> {
     pub async fn new(urls: &[Url]) -> Result<Self> {
-        if urls.is_empty() {
-            return Err(StoreError::NoEndpoints);
-        }
-
-        let conns = try_join_all(urls.iter().map(|url| async move {
-            redis::Client::open(url.clone())?
-                .get_multiplexed_async_connection()
-                .await
-        }))
-        .await?;
+        let (conns, placement) = connect(urls).await?;
 
         Ok(Self {
             conns,
-            placement: Placement::new(urls),
+            placement,
             _phantom: std::marker::PhantomData,
         })
     }
@@ -118,7 +130,7 @@ impl<T: Storable> RedisStore<T> {
                 _ => continue,
             };
 
-            let entry: T = postcard::from_bytes(value)?;
+            let entry = T::decode(value)?;
             entries.push(entry);
         }
 
@@ -166,6 +178,22 @@ impl<T: Storable> RedisStore<T> {
     }
 }
 
+impl<T> HistoryStore<T> for RedisStore<T>
+where
+    T: Storable + Send + Sync,
+    T::Key: Send + Sync,
+{
+    type Error = StoreError;
+
+    async fn get_many(&mut self, vehicle_id: &VehicleId, len: usize) -> Result<Vec<T>> {
+        RedisStore::get_many(self, vehicle_id, len).await
+    }
+
+    async fn write_many(&mut self, batch: &[T], limit: usize) -> Result<()> {
+        RedisStore::write_many(self, batch, limit).await
+    }
+}
+
 /// A [`RedisStore`] with a local window per vehicle.
 ///
 /// Bounded, and it has to be: entries are keyed by vehicle, vehicles leave a
@@ -238,9 +266,109 @@ impl<T: Storable> CachedRedisStore<T> {
     }
 }
 
+/// Finalized matched timelines on the same fleet of Valkey primaries as the
+/// raw history, placed by the same rendezvous hash.
+///
+/// The keyspace is `vehicle:<id>:matched`: a sorted set of layers scored by
+/// their timestamp, trimmed to the newest `limit`. Replacing a region is one
+/// transaction, so a reader never sees it half rewritten.
+#[derive(Clone)]
+pub struct RedisTimelines<E: NetworkEntry> {
+    conns: Vec<MultiplexedConnection>,
+    placement: Placement,
+    limit: usize,
+    _phantom: std::marker::PhantomData<E>,
+}
+
+impl<E: NetworkEntry> RedisTimelines<E> {
+    /// `limit` is the layers kept per vehicle, the oldest dropped first. It
+    /// must be at least one: trimming to zero would delete the timeline.
+    pub async fn new(urls: &[Url], limit: usize) -> Result<Self> {
+        if limit == 0 {
+            return Err(StoreError::ZeroLimit);
+        }
+
+        let (conns, placement) = connect(urls).await?;
+
+        Ok(Self {
+            conns,
+            placement,
+            limit,
+            _phantom: std::marker::PhantomData,
+        })
+    }
+
+    fn key(vehicle_id: VehicleId) -> String {
+        format!("vehicle:{vehicle_id}:matched")
+    }
+}
+
+impl<E> TimelineStore<E> for RedisTimelines<E>
+where
+    E: NetworkEntry + Serialize + DeserializeOwned,
+{
+    type Error = StoreError;
+
+    async fn finalize(&mut self, vehicle_id: VehicleId, layers: &[MatchedLayer<E>]) -> Result<()> {
+        let Some(first) = layers.first() else {
+            return Ok(());
+        };
+
+        let key = Self::key(vehicle_id);
+        let node = self.placement.index_for(&key);
+
+        let mut pipeline = redis::pipe();
+        pipeline
+            .atomic()
+            .cmd("ZREMRANGEBYSCORE")
+            .arg(&key)
+            .arg(first.timestamp)
+            .arg("+inf")
+            .ignore();
+
+        for layer in layers {
+            pipeline
+                .cmd("ZADD")
+                .arg(&key)
+                .arg(layer.timestamp)
+                .arg(postcard::to_allocvec(layer)?)
+                .ignore();
+        }
+
+        // Keep the newest `limit`: ranks run oldest first.
+        pipeline
+            .cmd("ZREMRANGEBYRANK")
+            .arg(&key)
+            .arg(0)
+            .arg(-(self.limit as i64) - 1)
+            .ignore();
+
+        pipeline.query_async::<()>(&mut self.conns[node]).await?;
+        Ok(())
+    }
+
+    async fn read(&mut self, vehicle_id: VehicleId, since: i64) -> Result<Vec<MatchedLayer<E>>> {
+        let key = Self::key(vehicle_id);
+        let node = self.placement.index_for(&key);
+
+        let members: Vec<Vec<u8>> = redis::cmd("ZRANGEBYSCORE")
+            .arg(&key)
+            .arg(since)
+            .arg("+inf")
+            .query_async(&mut self.conns[node])
+            .await?;
+
+        Ok(members
+            .iter()
+            .map(|member| postcard::from_bytes(member))
+            .collect::<core::result::Result<_, _>>()?)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::Placement;
+    use super::{Placement, RedisTimelines, StoreError};
+    use routers_network::mock::MockEntryId;
     use url::Url;
 
     fn fleet(n: usize) -> Vec<Url> {
@@ -333,4 +461,10 @@ mod tests {
             );
         }
     }
+
+    #[tokio::test]
+    async fn timelines_refuse_a_zero_limit() {
+        let result = RedisTimelines::<MockEntryId>::new(&fleet(1), 0).await;
+        assert!(matches!(result, Err(StoreError::ZeroLimit)));
+    }
 }
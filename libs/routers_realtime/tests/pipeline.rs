@@ -0,0 +1,115 @@
+//! The orchestrator end to end over the in-memory bus and store: raw events
+//! in, match contexts out to a stand-in matcher, raw tails archived.
+
+use std::time::Duration;
+
+use chrono::DateTime;
+use futures::StreamExt;
+use geo::point;
+use routers_codec::osm::OsmEntryId;
+use routers_realtime::bus::{Bus, MemoryBus, Wire};
+use routers_realtime::event::{MatchContext, MatchReply, Payload, RawEvent, VehicleId, shard_of};
+use routers_realtime::ingest;
+use routers_realtime::orchestrator::{self, Settings};
+use routers_realtime::partition::{PARTITIONS, partition_of};
+use routers_realtime::store::{HistoryStore, MemoryStore};
+use routers_transition::Continuation;
+use tokio::sync::mpsc;
+
+const VEHICLE: VehicleId = VehicleId(42);
+
+fn payload(seconds: i64, x: f64) -> Payload {
+    Payload {
+        vehicle_id: VEHICLE,
+        timestamp: DateTime::from_timestamp(1_775_000_000 + seconds, 0).unwrap(),
+        point: point!(x: x, y: 34.15),
+        heading: Some(270.0),
+        speed: Some(12.0),
+        accuracy: None,
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn events_flow_through_the_pipeline_in_memory() {
+    let bus = MemoryBus::new();
+    let store = MemoryStore::<RawEvent>::new();
+    let settings = Settings {
+        workers: 2,
+        batch_timeout: Duration::from_millis(1),
+        ..Settings::default()
+    };
+
+    // A matcher that solves nothing, but reports the origins it was asked
+    // to match.
+    let events = [
+        payload(0, -118.150),
+        payload(5, -118.151),
+        payload(10, -118.152),
+    ];
+    let subject = format!("{}.{}", settings.match_prefix, shard_of(events[0].point));
+    let mut requests = bus.serve(subject, "matchers".into()).await.unwrap();
+
+    let (asked, mut contexts) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Some(request) = requests.next().await {
+            let context = MatchContext::<OsmEntryId>::decode(&request.payload).unwrap();
+            asked.send(context).unwrap();
+
+            let reply = MatchReply::<OsmEntryId>::NoMatch.encode().unwrap();
+            request.respond(reply).await.unwrap();
+        }
+    });
+
+    orchestrator::start(bus.clone(), store.clone(), settings, 0..=PARTITIONS - 1)
+        .await
+        .unwrap();
+
+    let raw = ingest::raw_subject(partition_of(VEHICLE));
+    for event in &events {
+        bus.publish(raw.clone(), event.encode().unwrap())
+            .await
+            .unwrap();
+    }
+
+    // Nothing was solved, so each context restarts over the whole history
+    // the lane has gathered so far.
+    for expected in 1..=events.len() {
+        let context = contexts.recv().await.unwrap();
+        assert_eq!(context.vehicle_id, VEHICLE);
+
+        let Continuation::Restart { fresh } = context.continuation else {
+            panic!("nothing was solved, so nothing can resume");
+        };
+        assert_eq!(fresh.len(), expected);
+    }
+
+    // A redelivered event is gated out before it reaches a matcher: the
+    // next context the matcher sees is the next fresh event's.
+    let next = payload(15, -118.153);
+    bus.publish(raw.clone(), events[1].encode().unwrap())
+        .await
+        .unwrap();
+    bus.publish(raw, next.encode().unwrap()).await.unwrap();
+
+    let context = contexts.recv().await.unwrap();
+    let Continuation::Restart { fresh } = context.continuation else {
+        panic!("nothing was solved, so nothing can resume");
+    };
+    assert_eq!(fresh.len(), events.len() + 1);
+
+    let archived = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            let mut kv = store.clone();
+            let history = kv.get_many(&VEHICLE, 10).await.unwrap();
+            if history.len() == events.len() + 1 {
+                break history;
+            }
+
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("every event is archived");
+
+    assert_eq!(archived[0].timestamp, next.timestamp);
+}
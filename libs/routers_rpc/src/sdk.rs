@@ -36,6 +36,7 @@ pub mod r#match {
                 width: model.width,
             },
             length: model.length,
+            weight: model.weight,
             axle_load: model.axle_load,
             axle_count: model.axle_count as u8,
             hazmat_load: model.hazardous_load,
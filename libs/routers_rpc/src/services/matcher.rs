@@ -17,7 +17,7 @@ use schema::proto::routers::model::v1::{
 use alloc::sync::Arc;
 use routers_network::{Entry, Metadata};
 use routers_transition::primitives::{DEFAULT_REACH_DISTANCE, PredicateCache};
-use routers_transition::{Match, MatchOptions};
+use routers_transition::{Breakage, Match, MatchOptions};
 #[cfg(feature = "telemetry")]
 use tracing::Level;
 use uom::si::f64::Length;
@@ -69,29 +69,38 @@ impl<Ctx> Util<Ctx> {
                         edge: MessageField::some(edge),
                         ..Default::default()
                     }),
+                    confidence: entry.confidence,
                     ..Default::default()
                 }
             })
             .collect::<Vec<_>>()
     }
 
-    fn process<E, M>(result: RoutedPath<E, M>, ctx: M::Runtime) -> Vec<MatchedRoute>
+    fn process<E, M>(result: RoutedPath<E, M>, ctx: &M::Runtime) -> MatchedRoute
     where
         E: Entry,
         M: MatchSdk<Runtime = Ctx>,
     {
-        let interpolated = Util::<Ctx>::route_from_path::<E, M>(result.interpolated, &ctx);
-        let discretized = Util::<Ctx>::route_from_path::<E, M>(result.discretized, &ctx);
+        let interpolated = Util::<Ctx>::route_from_path::<E, M>(result.interpolated, ctx);
+        let discretized = Util::<Ctx>::route_from_path::<E, M>(result.discretized, ctx);
 
-        vec![MatchedRoute {
+        MatchedRoute {
             interpolated,
             discretized,
-            cost: 0,
+            cost: result.cost,
             ..Default::default()
-        }]
+        }
     }
 }
 
+/// The requested breakage: a new route starts wherever consecutive
+/// coordinates are further apart than `distance` metres.
+fn breakage(distance: Option<f64>) -> Breakage {
+    distance
+        .map(|distance| Breakage::new().with_distance(Length::new::<meter>(distance)))
+        .unwrap_or_default()
+}
+
 #[allow(refining_impl_trait)]
 impl<T> MatchService for RPCAdapter<T>
 where
@@ -133,16 +142,34 @@ where
             .with_runtime(runtime.clone())
             .with_solver(solver)
             .with_search_distance(owned.search_distance)
-            .with_cache(Arc::new(PredicateCache::with_reach_distance(reach)));
+            .with_cache(Arc::new(PredicateCache::with_reach_distance(reach)))
+            .with_breakage(breakage(owned.breakage_distance))
+            .with_split(owned.split)
+            .with_confidence(true);
 
-        let result = self
+        let segments = self
             .inner
-            .r#match(coordinates, opts)
+            .r#match_segments(coordinates, opts)
+            .map_err(|e| e.to_string())
+            .map_err(ConnectError::internal)?;
+
+        // Any piece which could not be matched fails the request, so a break
+        // only yields fewer coordinates when the client asked to `split`.
+        let matches = segments
+            .into_iter()
+            .map(|segment| {
+                segment.path.map(|path| MatchedRoute {
+                    start: segment.layers.start as u32,
+                    end: segment.layers.end as u32,
+                    ..Util::<T::Runtime>::process::<T::Entry, T::Meta>(path, &runtime)
+                })
+            })
+            .collect::<Result<_, _>>()
             .map_err(|e| e.to_string())
             .map_err(ConnectError::internal)?;
 
         Ok(MatchResponse {
-            matches: Util::<T::Runtime>::process::<T::Entry, T::Meta>(result, runtime),
+            matches,
             ..Default::default()
         }
         .into())
@@ -175,16 +202,31 @@ where
         let opts = MatchOptions::new()
             .with_runtime(runtime.clone())
             .with_solver(solver)
-            .with_search_distance(owned.search_distance);
+            .with_search_distance(owned.search_distance)
+            .with_breakage(breakage(owned.breakage_distance))
+            .with_confidence(true);
 
-        let result = self
+        let segments = self
             .inner
-            .snap(coordinates, opts)
+            .snap_segments(coordinates, opts)
+            .map_err(|e| e.to_string())
+            .map_err(ConnectError::internal)?;
+
+        let matches = segments
+            .into_iter()
+            .map(|segment| {
+                segment.path.map(|path| MatchedRoute {
+                    start: segment.layers.start as u32,
+                    end: segment.layers.end as u32,
+                    ..Util::<T::Runtime>::process::<T::Entry, T::Meta>(path, &runtime)
+                })
+            })
+            .collect::<Result<_, _>>()
             .map_err(|e| e.to_string())
             .map_err(ConnectError::internal)?;
 
         Ok(SnapResponse {
-            matches: Util::<T::Runtime>::process::<T::Entry, T::Meta>(result, runtime),
+            matches,
             ..Default::default()
         }
         .into())
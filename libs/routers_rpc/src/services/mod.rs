@@ -2,13 +2,30 @@ use alloc::sync::Arc;
 use routers_codec::osm::OsmNetwork;
 use std::path::PathBuf;
 
+/// The most cells, origins times destinations, a matrix request may ask for
+/// unless the adapter is given another limit.
+pub const DEFAULT_MATRIX_LIMIT: usize = 10_000;
+
 pub struct RPCAdapter<T> {
     pub(crate) inner: Arc<T>,
+    pub(crate) matrix_limit: usize,
 }
 
 impl<T> RPCAdapter<T> {
     pub fn new(inner: Arc<T>) -> Self {
-        Self { inner }
+        Self {
+            inner,
+            matrix_limit: DEFAULT_MATRIX_LIMIT,
+        }
+    }
+
+    /// Refuse matrix requests of more than `cells` origin and destination
+    /// pairs, in place of the [`DEFAULT_MATRIX_LIMIT`].
+    pub fn with_matrix_limit(self, cells: usize) -> Self {
+        Self {
+            matrix_limit: cells,
+            ..self
+        }
     }
 }
 
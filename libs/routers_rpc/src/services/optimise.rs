@@ -1,19 +1,26 @@
 use buffa::view::OwnedView;
 use connectrpc::{ConnectError, RequestContext, ServiceResult};
 use geo::Point;
-use routers_network::Network;
+use routers_network::{Metadata, Network};
 use schema::connect::routers::api::optimise::v1::OptimiseService;
-use schema::proto::routers::api::optimise::v1::{__buffa::view::RouteRequestView, RouteResponse};
+use schema::proto::routers::api::optimise::v1::__buffa::view::{
+    MatrixRequestView, RouteRequestView,
+};
+use schema::proto::routers::api::optimise::v1::{
+    MatrixCell, MatrixResponse, MatrixRow, RouteResponse,
+};
+use schema::proto::routers::model::v1::Coordinate;
 #[cfg(feature = "telemetry")]
 use tracing::Level;
 
-use crate::sdk::r#match::coordinate;
+use crate::sdk::r#match::{MatchSdk, coordinate};
 use crate::services::RPCAdapter;
 
 #[allow(refining_impl_trait)]
 impl<T> OptimiseService for RPCAdapter<T>
 where
     T: Network + Send + Sync + 'static,
+    T::Meta: MatchSdk,
 {
     #[cfg_attr(feature="telemetry", tracing::instrument(skip_all, level = Level::INFO))]
     async fn route(
@@ -35,9 +42,15 @@ where
             .map(|c| Point::new(c.longitude, c.latitude))
             .ok_or_else(|| ConnectError::invalid_argument("Missing End Coordinate"))?;
 
+        let context = owned
+            .costing_method
+            .as_option()
+            .and_then(<T::Meta>::trip_context);
+        let runtime = <T::Meta>::runtime(context);
+
         let (cost, route) = self
             .inner
-            .route_points(&start, &end)
+            .route_points(&start, &end, &runtime)
             .ok_or_else(|| ConnectError::internal("Could not route"))?;
 
         let shape = route
@@ -52,4 +65,103 @@ where
         }
         .into())
     }
+
+    #[cfg_attr(feature="telemetry", tracing::instrument(skip_all, level = Level::INFO))]
+    async fn matrix(
+        &self,
+        _ctx: RequestContext,
+        request: OwnedView<MatrixRequestView<'static>>,
+    ) -> ServiceResult<MatrixResponse> {
+        let owned = request.to_owned_message();
+
+        matrix_size(
+            owned.origins.len(),
+            owned.destinations.len(),
+            self.matrix_limit,
+        )?;
+
+        let context = owned
+            .costing_method
+            .as_option()
+            .and_then(<T::Meta>::trip_context);
+        let runtime = <T::Meta>::runtime(context);
+
+        let points = |coordinates: &[Coordinate]| -> Vec<Point> {
+            coordinates
+                .iter()
+                .map(|c| Point::new(c.longitude, c.latitude))
+                .collect()
+        };
+
+        let matrix = self.inner.matrix_points(
+            &points(&owned.origins),
+            &points(&owned.destinations),
+            &runtime,
+        );
+
+        let rows = matrix
+            .rows()
+            .map(|row| MatrixRow {
+                cells: row
+                    .iter()
+                    .map(|leg| match leg {
+                        Some(leg) => MatrixCell {
+                            reachable: true,
+                            cost: leg.cost,
+                            distance: leg.distance,
+                            ..Default::default()
+                        },
+                        None => MatrixCell::default(),
+                    })
+                    .collect(),
+                ..Default::default()
+            })
+            .collect();
+
+        Ok(MatrixResponse {
+            rows,
+            ..Default::default()
+        }
+        .into())
+    }
+}
+
+/// Check a matrix of `origins` by `destinations` is neither empty nor larger
+/// than `limit` cells.
+fn matrix_size(origins: usize, destinations: usize, limit: usize) -> Result<(), ConnectError> {
+    if origins == 0 || destinations == 0 {
+        return Err(ConnectError::invalid_argument(
+            "Matrix requires at least one origin and one destination",
+        ));
+    }
+
+    let cells = origins.saturating_mul(destinations);
+    if cells > limit {
+        return Err(ConnectError::invalid_argument(format!(
+            "Matrix of {cells} cells exceeds the limit of {limit}"
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use connectrpc::ErrorCode;
+
+    #[test]
+    fn matrix_size_refuses_empty_and_oversized_requests() {
+        let code = |origins, destinations| {
+            matrix_size(origins, destinations, 100)
+                .err()
+                .map(|error| error.code)
+        };
+
+        assert_eq!(code(10, 10), None);
+        assert_eq!(code(0, 10), Some(ErrorCode::InvalidArgument));
+        assert_eq!(code(10, 0), Some(ErrorCode::InvalidArgument));
+        assert_eq!(code(10, 11), Some(ErrorCode::InvalidArgument));
+        assert_eq!(code(usize::MAX, 2), Some(ErrorCode::InvalidArgument));
+    }
 }
@@ -2,10 +2,13 @@ use clap::{Args as ClapArgs, Parser};
 use geo::Point;
 use itertools::Itertools;
 use log::{debug, error, info, trace};
-use std::{collections::HashSet, path::PathBuf};
+use std::{
+    collections::{BTreeSet, HashSet},
+    path::PathBuf,
+};
 
-use routers_codec::osm::{OsmEdgeMetadata, OsmEntryId, OsmNetwork};
-use routers_network::edge::Weight;
+use routers_codec::osm::{OsmChange, OsmEdgeMetadata, OsmEntryId, OsmNetwork};
+use routers_network::{DataPlane, RoutingGraph, edge::Weight};
 use routers_shard::{
     Geohash, GeohashStrategy, Selection, SelectionMode, ShardId, ShardSource, ShardedNetwork,
     ShardingStrategy,
@@ -31,6 +34,17 @@ struct Args {
     /// The name of the manifest file to write.
     #[arg(short, long, env = "MANIFEST_FILENAME", default_value = "manifest.txt")]
     manifest_filename: String,
+
+    /// OsmChange (`.osc` or `.osc.gz`) files to apply to the network, in
+    /// order. Only the shards they touch are rebuilt, and the manifest keeps
+    /// the shards already listed in it.
+    #[arg(long)]
+    change: Vec<PathBuf>,
+
+    /// Where to save the network once the changes are applied, so the next
+    /// update may start from it.
+    #[arg(long, requires = "change")]
+    save: Option<PathBuf>,
 }
 
 #[derive(ClapArgs, Debug)]
@@ -54,7 +68,7 @@ fn main() {
     let out_dir = args.output.join("../../target/shard_cache");
     std::fs::create_dir_all(&out_dir).expect("create shard_cache dir");
 
-    let network = match (args.file.pbf, args.file.rt) {
+    let mut network = match (args.file.pbf, args.file.rt) {
         (Some(pbf), None) => {
             info!("loading OsmNetwork from protobuf file...");
             OsmNetwork::from_pbf(&pbf).map_err(|v| v.to_string())
@@ -75,11 +89,49 @@ fn main() {
 
     let strategy = GeohashStrategy::with_precision(args.precision);
 
-    let mut cells: HashSet<Geohash> = HashSet::new();
-    for node in network.hash.values() {
-        cells.insert(strategy.locate(node.position));
+    let mut touched = Vec::new();
+    for path in &args.change {
+        let change = OsmChange::from_file(path).expect("must be able to parse the change file");
+        let report = network
+            .apply(&change)
+            .expect("must be able to apply changes to an unsimplified network");
+
+        if !report.unresolved.is_empty() {
+            error!(
+                "{path:?}: {} ways pass through nodes missing from the network, and were left out",
+                report.unresolved.len()
+            );
+        }
+
+        info!(
+            "applied {path:?}: {} nodes, {} ways",
+            change.nodes.len(),
+            change.ways.len()
+        );
+        touched.extend(report.touched);
+    }
+
+    if let Some(save) = &args.save {
+        network
+            .save_to_file(save)
+            .expect("must be able to save the updated network");
     }
 
+    let cells: HashSet<Geohash> = if args.change.is_empty() {
+        network
+            .hash
+            .values()
+            .map(|node| strategy.locate(node.position))
+            .collect()
+    } else {
+        let mode = SelectionMode::OwnedAndPadded {
+            padding_distance: PADDING_DISTANCE,
+        };
+        Selection::affected(&strategy, touched, mode)
+            .into_iter()
+            .collect()
+    };
+
     debug!("contains {} unique geohash cells", cells.len());
     trace!("contains cells={cells:?}");
 
@@ -98,13 +150,25 @@ fn main() {
         })
         .partition_result();
 
-    // Write manifest
+    // Write manifest, keeping the shards an update did not rebuild
     let manifest = out_dir.join(args.manifest_filename);
-    let names = built
+    let mut names = built
         .iter()
         .map(|shard| format!("{shard}.shard.rt"))
-        .collect::<Vec<_>>()
-        .join("\n");
+        .collect::<BTreeSet<_>>();
+
+    if !args.change.is_empty()
+        && let Ok(existing) = std::fs::read_to_string(&manifest)
+    {
+        names.extend(
+            existing
+                .lines()
+                .filter(|line| !line.is_empty())
+                .map(str::to_owned),
+        );
+    }
+
+    let names = names.into_iter().collect::<Vec<_>>().join("\n");
 
     std::fs::write(&manifest, names).expect("write manifest");
 
@@ -162,4 +226,19 @@ impl<'a> ShardSource<OsmEntryId, OsmEdgeMetadata> for OsmSource<'a> {
                 }),
         )
     }
+
+    fn shape(&self, source: &OsmEntryId, target: &OsmEntryId) -> &[Point] {
+        DataPlane::shape(self.0, source, target)
+    }
+
+    fn cost(&self, source: &OsmEntryId, target: &OsmEntryId, weight: Weight) -> Weight {
+        match self.0.graph.edge_weight(*source, *target) {
+            Some(&data) => RoutingGraph::cost(self.0, &(*source, *target, data)),
+            None => weight,
+        }
+    }
+
+    fn cost_per_metre(&self) -> f64 {
+        RoutingGraph::cost_per_metre(self.0)
+    }
 }
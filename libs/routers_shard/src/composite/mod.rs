@@ -11,7 +11,7 @@ use std::sync::Arc;
 use petgraph::prelude::DiGraphMap;
 use rustc_hash::FxHasher;
 
-use routers_network::{DirectionAwareEdgeId, Entry, Metadata, edge::Weight};
+use routers_network::{DirectionAwareEdgeId, Entry, Metadata, Routing, edge::Weight};
 
 use crate::network::ShardedNetwork;
 use crate::strategy::ShardId;
@@ -27,6 +27,7 @@ where
 {
     shards: Vec<Arc<ShardedNetwork<E, M, S>>>,
     graph: GraphStructure<E>,
+    routing: Routing,
 }
 
 impl<E, M, S> MultiShardNetwork<E, M, S>
@@ -49,7 +50,16 @@ where
             }
         }
 
-        Self { shards, graph }
+        Self {
+            shards,
+            graph,
+            routing: Routing::default(),
+        }
+    }
+
+    /// Route with the given search, in place of the default [`Routing`].
+    pub fn with_routing(self, routing: Routing) -> Self {
+        Self { routing, ..self }
     }
 
     /// Number of shards composed into this network.
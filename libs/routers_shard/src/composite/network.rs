@@ -4,8 +4,8 @@ use geo::{Point, Rect};
 use rustc_hash::FxHashSet;
 
 use routers_network::{
-    DataPlane, DirectionAwareEdgeId, Discovery, Edge, Entry, Metadata, Node, Route, Scan,
-    edge::Weight, network::GraphEdge,
+    DataPlane, DirectionAwareEdgeId, Discovery, Edge, Entry, Metadata, Node, Route, RoutingGraph,
+    RoutingProvider, Scan, edge::Weight, network::GraphEdge,
 };
 
 use super::MultiShardNetwork;
@@ -50,6 +50,15 @@ where
         self.node(id).map(|n| n.position)
     }
 
+    fn shape(&self, source: &E, target: &E) -> &[Point] {
+        // Every shard holding the edge holds its shape, so the first will do.
+        self.shards
+            .iter()
+            .find_map(|s| s.shapes.get(&(*source, *target)))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
     fn edges_outof<'a>(&'a self, id: E) -> Box<dyn Iterator<Item = GraphEdge<E>> + 'a> {
         Box::new(
             self.graph
@@ -171,15 +180,56 @@ where
     M: Metadata,
     S: ShardId,
 {
-    fn route_nodes(&self, start: E, finish: E) -> Option<(Weight, Vec<Node<E>>)> {
-        let (cost, path) = petgraph::algo::astar(
-            &self.graph,
-            start,
-            |n| n == finish,
-            |(_, _, w)| w.0,
-            |_| 0 as Weight,
-        )?;
+    fn route_nodes(
+        &self,
+        start: E,
+        finish: E,
+        runtime: &M::Runtime,
+    ) -> Option<(Weight, Vec<Node<E>>)> {
+        let (cost, path) = self.routing.route(self, start, finish, runtime)?;
         let route = path.iter().filter_map(|v| self.node(v).copied()).collect();
         Some((cost, route))
     }
+
+    fn route_many(
+        &self,
+        start: E,
+        finishes: &[E],
+        runtime: &M::Runtime,
+    ) -> Vec<Option<(Weight, Vec<Node<E>>)>> {
+        self.routing
+            .route_many(self, start, finishes, runtime)
+            .into_iter()
+            .map(|path| {
+                let (cost, path) = path?;
+                let route = path.iter().filter_map(|v| self.node(v).copied()).collect();
+                Some((cost, route))
+            })
+            .collect()
+    }
+}
+
+impl<E, M, S> RoutingGraph for MultiShardNetwork<E, M, S>
+where
+    E: Entry,
+    M: Metadata,
+    S: ShardId,
+{
+    fn cost(&self, edge: &GraphEdge<E>) -> Weight {
+        // Every shard holding the edge prices it alike, so the first will do.
+        let &(source, target, (weight, _)) = edge;
+        self.shards
+            .iter()
+            .find(|s| s.graph.contains_edge(source, target))
+            .map_or(weight, |s| s.cost(edge))
+    }
+
+    fn cost_per_metre(&self) -> f64 {
+        // The bound must hold in every shard, so take the loosest.
+        self.shards
+            .iter()
+            .map(|s| s.cost_per_metre)
+            .reduce(f64::min)
+            .unwrap_or_default()
+    }
 }
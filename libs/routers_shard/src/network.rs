@@ -20,8 +20,8 @@ use std::path::Path;
 use web_time::Instant;
 
 use routers_network::{
-    DirectionAwareEdgeId, Discovery, Edge, Entry, Metadata, Node, Route, RowIndex, Scan,
-    edge::Weight, envelope_of, network::GraphEdge,
+    DirectionAwareEdgeId, Discovery, Edge, Entry, Metadata, Node, Route, Routing, RoutingGraph,
+    RoutingProvider, RowIndex, Scan, edge::Weight, envelope_of, network::GraphEdge,
 };
 
 use crate::selection::Selection;
@@ -39,6 +39,27 @@ pub type GraphStructure<E> =
 pub trait ShardSource<E: Entry, M: Metadata> {
     fn nodes<'a>(&'a self) -> Box<dyn Iterator<Item = (E, Point)> + 'a>;
     fn edges<'a>(&'a self) -> Box<dyn Iterator<Item = (E, E, Weight, M)> + 'a>;
+
+    /// The positions the edge from `source` to `target` bends through, as
+    /// [`DataPlane::shape`](routers_network::DataPlane::shape) gives them.
+    /// Sources which draw every edge straight keep none, which is the
+    /// default.
+    fn shape(&self, _source: &E, _target: &E) -> &[Point] {
+        &[]
+    }
+
+    /// The cost of routing along the edge from `source` to `target`, of
+    /// `weight`, as [`RoutingGraph::cost`] gives it. Sources which route by
+    /// the weight alone keep the default.
+    fn cost(&self, _source: &E, _target: &E, weight: Weight) -> Weight {
+        weight
+    }
+
+    /// A lower bound on the cost of a metre of travel anywhere in the
+    /// source, as [`RoutingGraph::cost_per_metre`] gives it.
+    fn cost_per_metre(&self) -> f64 {
+        0.0
+    }
 }
 
 /// Magic header + format fingerprint prepended to every shard cache file.
@@ -74,6 +95,18 @@ where
     pub hash: FxHashMap<E, Node<E>>,
     pub meta: FxHashMap<E, M>,
 
+    /// The shape of each edge which bends between its ends, carried over
+    /// from the source.
+    pub shapes: FxHashMap<(E, E), Vec<Point>>,
+
+    /// The routing cost of each edge the source prices other than by its
+    /// weight, carried over from the source.
+    pub costs: FxHashMap<(E, E), Weight>,
+
+    /// A lower bound on the cost of a metre of travel, carried over from the
+    /// source.
+    pub cost_per_metre: f64,
+
     /// Spatial index over node ids.
     #[serde(skip)]
     pub index: RowIndex<E>,
@@ -86,6 +119,10 @@ where
     pub owned: S,
     /// All shards whose data is materialised in `graph`.
     pub loaded: FxHashSet<S>,
+
+    /// The search point-to-point routes are found with.
+    #[serde(skip)]
+    pub routing: Routing,
 }
 
 impl<E, M, S> ShardedNetwork<E, M, S>
@@ -98,6 +135,11 @@ where
         self.graph.node_count()
     }
 
+    /// Route with the given search, in place of the default [`Routing`].
+    pub fn with_routing(self, routing: Routing) -> Self {
+        Self { routing, ..self }
+    }
+
     pub fn num_edges(&self) -> usize {
         self.graph.edge_count()
     }
@@ -119,6 +161,8 @@ where
         let mut graph: GraphStructure<E> = GraphStructure::new();
         let mut hash: FxHashMap<E, Node<E>> = FxHashMap::default();
         let mut meta: FxHashMap<E, M> = FxHashMap::default();
+        let mut shapes: FxHashMap<(E, E), Vec<Point>> = FxHashMap::default();
+        let mut costs: FxHashMap<(E, E), Weight> = FxHashMap::default();
 
         let all_nodes: FxHashMap<E, Point> = source.nodes().collect();
 
@@ -152,16 +196,30 @@ where
 
             graph.add_edge(from, to, (weight, DirectionAwareEdgeId::new(from)));
             meta.entry(from).or_insert(m);
+
+            let shape = source.shape(&from, &to);
+            if !shape.is_empty() {
+                shapes.insert((from, to), shape.to_vec());
+            }
+
+            let cost = source.cost(&from, &to, weight);
+            if cost != weight {
+                costs.insert((from, to), cost);
+            }
         }
 
         let mut net = Self {
             graph,
             hash,
             meta,
+            shapes,
+            costs,
+            cost_per_metre: source.cost_per_metre(),
             index: RowIndex::default(),
             index_edge: RowIndex::default(),
             owned: selection.owned,
             loaded: selection.loaded.clone(),
+            routing: Routing::default(),
         };
 
         net.rebuild_indices();
@@ -179,6 +237,7 @@ where
             .collect();
 
         let hash = &self.hash;
+        let shapes = &self.shapes;
         let (node_index, edge_index) = rayon::join(
             || {
                 RowIndex::build(nodes, |id| {
@@ -188,7 +247,14 @@ where
             },
             || {
                 RowIndex::build(edges, |&(s, t)| {
-                    envelope_of(hash[&s].position, hash[&t].position)
+                    let ends = envelope_of(hash[&s].position, hash[&t].position);
+                    shapes
+                        .get(&(s, t))
+                        .into_iter()
+                        .flatten()
+                        .fold(ends, |(min, max), &p| {
+                            (envelope_of(min, p).0, envelope_of(max, p).1)
+                        })
                 })
             },
         );
@@ -382,20 +448,54 @@ where
     M: Metadata,
     S: ShardId,
 {
-    fn route_nodes(&self, start: E, finish: E) -> Option<(Weight, Vec<Node<E>>)> {
-        let (score, path) = petgraph::algo::astar(
-            &self.graph,
-            start,
-            |n| n == finish,
-            |(_, _, w)| w.0,
-            |_| 0 as Weight,
-        )?;
+    fn route_nodes(
+        &self,
+        start: E,
+        finish: E,
+        runtime: &M::Runtime,
+    ) -> Option<(Weight, Vec<Node<E>>)> {
+        let (score, path) = self.routing.route(self, start, finish, runtime)?;
         let route = path
             .iter()
             .filter_map(|v| self.hash.get(v).copied())
             .collect();
         Some((score, route))
     }
+
+    fn route_many(
+        &self,
+        start: E,
+        finishes: &[E],
+        runtime: &M::Runtime,
+    ) -> Vec<Option<(Weight, Vec<Node<E>>)>> {
+        self.routing
+            .route_many(self, start, finishes, runtime)
+            .into_iter()
+            .map(|path| {
+                let (score, path) = path?;
+                let route = path
+                    .iter()
+                    .filter_map(|v| self.hash.get(v).copied())
+                    .collect();
+                Some((score, route))
+            })
+            .collect()
+    }
+}
+
+impl<E, M, S> RoutingGraph for ShardedNetwork<E, M, S>
+where
+    E: Entry,
+    M: Metadata,
+    S: ShardId,
+{
+    fn cost(&self, &(source, target, (weight, _)): &GraphEdge<E>) -> Weight {
+        self.costs.get(&(source, target)).copied().unwrap_or(weight)
+    }
+
+    fn cost_per_metre(&self) -> f64 {
+        self.cost_per_metre
+    }
 }
 
 impl<E, M, S> routers_network::DataPlane for ShardedNetwork<E, M, S>
@@ -416,6 +516,13 @@ where
         self.hash.get(id).map(|v| v.position)
     }
 
+    fn shape(&self, source: &E, target: &E) -> &[Point] {
+        self.shapes
+            .get(&(*source, *target))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
     fn edges_into<'a>(&'a self, id: E) -> Box<dyn Iterator<Item = GraphEdge<E>> + 'a> {
         Box::new(
             self.graph
@@ -74,6 +74,48 @@ impl<S: ShardId> Selection<S> {
         }
     }
 
+    /// The owned shards whose selections, under `mode`, hold any of
+    /// `points`: the shards the points fall in, and whichever neighbours load
+    /// them as context. These are the shards to rebuild when the data at
+    /// `points` changes.
+    ///
+    /// Only adjacent shards are considered, so a padding distance wider
+    /// than a shard is not fully accounted for.
+    pub fn affected<St>(
+        strategy: &St,
+        points: impl IntoIterator<Item = Point>,
+        mode: SelectionMode,
+    ) -> FxHashSet<S>
+    where
+        St: ShardingStrategy<Id = S>,
+    {
+        let mut affected = FxHashSet::default();
+        for point in points {
+            let owned = strategy.locate(point);
+            affected.insert(owned);
+            if mode == SelectionMode::Owned {
+                continue;
+            }
+
+            for neighbour in strategy.neighbours(&owned) {
+                let holds = match mode {
+                    SelectionMode::Owned => false,
+                    SelectionMode::OwnedAndNeighbours => true,
+                    SelectionMode::OwnedAndPadded { padding_distance } => within(
+                        padded_bounds(strategy.bounds(&neighbour), padding_distance),
+                        point,
+                    ),
+                };
+
+                if holds {
+                    affected.insert(neighbour);
+                }
+            }
+        }
+
+        affected
+    }
+
     /// Returns `true` if the shard `id` is part of the loaded selection.
     #[inline]
     pub fn contains(&self, id: &S) -> bool {
@@ -86,16 +128,18 @@ impl<S: ShardId> Selection<S> {
     /// membership in that case is decided purely by shard id.
     #[inline]
     pub fn padding_contains(&self, point: Point) -> bool {
-        let Some(rect) = self.padding.as_ref() else {
-            return false;
-        };
-        let (x, y) = point.x_y();
-        let min = rect.min();
-        let max = rect.max();
-        x >= min.x && x <= max.x && y >= min.y && y <= max.y
+        self.padding.is_some_and(|rect| within(rect, point))
     }
 }
 
+/// Returns `true` if `point` falls within `rect`, edges included.
+fn within(rect: Rect, point: Point) -> bool {
+    let (x, y) = point.x_y();
+    let min = rect.min();
+    let max = rect.max();
+    x >= min.x && x <= max.x && y >= min.y && y <= max.y
+}
+
 /// Expand `rect` by `padding_meters` in both axes, using a local
 /// equirectangular conversion centred on the rectangle's midpoint.
 fn padded_bounds(rect: Rect, padding_meters: f64) -> Rect {
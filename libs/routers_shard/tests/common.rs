@@ -2,6 +2,7 @@ use geo::Point;
 use routers_codec::osm::{OsmEdgeMetadata, OsmEntryId};
 use routers_network::edge::Weight;
 use routers_shard::ShardSource;
+use rustc_hash::FxHashMap;
 
 /// Synthetic grid data source for tests.
 ///
@@ -11,6 +12,9 @@ use routers_shard::ShardSource;
 pub struct MemSource {
     nodes: Vec<(OsmEntryId, Point)>,
     edges: Vec<(OsmEntryId, OsmEntryId, Weight, OsmEdgeMetadata)>,
+    shapes: FxHashMap<(OsmEntryId, OsmEntryId), Vec<Point>>,
+    costs: FxHashMap<(OsmEntryId, OsmEntryId), Weight>,
+    cost_per_metre: f64,
 }
 
 impl MemSource {
@@ -47,7 +51,35 @@ impl MemSource {
             }
         }
 
-        Self { nodes, edges }
+        Self {
+            nodes,
+            edges,
+            shapes: FxHashMap::default(),
+            costs: FxHashMap::default(),
+            cost_per_metre: 0.0,
+        }
+    }
+
+    /// Bend the edge from `source` to `target` through `shape`.
+    #[allow(dead_code)]
+    pub fn with_shape(mut self, source: OsmEntryId, target: OsmEntryId, shape: Vec<Point>) -> Self {
+        self.shapes.insert((source, target), shape);
+        self
+    }
+
+    /// Price the edge from `source` to `target` at `cost` rather than its
+    /// weight, with every metre costing at least `cost_per_metre`.
+    #[allow(dead_code)]
+    pub fn with_cost(
+        mut self,
+        source: OsmEntryId,
+        target: OsmEntryId,
+        cost: Weight,
+        cost_per_metre: f64,
+    ) -> Self {
+        self.costs.insert((source, target), cost);
+        self.cost_per_metre = cost_per_metre;
+        self
     }
 }
 
@@ -61,4 +93,22 @@ impl ShardSource<OsmEntryId, OsmEdgeMetadata> for MemSource {
     ) -> Box<dyn Iterator<Item = (OsmEntryId, OsmEntryId, Weight, OsmEdgeMetadata)> + 'a> {
         Box::new(self.edges.iter().cloned())
     }
+
+    fn shape(&self, source: &OsmEntryId, target: &OsmEntryId) -> &[Point] {
+        self.shapes
+            .get(&(*source, *target))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    fn cost(&self, source: &OsmEntryId, target: &OsmEntryId, weight: Weight) -> Weight {
+        self.costs
+            .get(&(*source, *target))
+            .copied()
+            .unwrap_or(weight)
+    }
+
+    fn cost_per_metre(&self) -> f64 {
+        self.cost_per_metre
+    }
 }
@@ -8,6 +8,8 @@
 //! - `Route::route_nodes` finds a path whose start and finish live in
 //!   different shards.
 //! - `metadata` lookups span every shard.
+//! - Edge shapes survive into the shards, their caches and the composite.
+//! - Edges are routed at the cost the source prices them.
 
 mod common;
 
@@ -15,8 +17,8 @@ use std::sync::Arc;
 
 use common::MemSource;
 use geo::Point;
-use routers_codec::osm::{OsmEdgeMetadata, OsmEntryId};
-use routers_network::{DataPlane, Discovery, Route, Scan};
+use routers_codec::osm::{OsmEdgeMetadata, OsmEntryId, OsmTripConfiguration};
+use routers_network::{DataPlane, Discovery, Route, RoutingGraph, Scan};
 use routers_shard::{
     MultiShardNetwork, QuadKey, QuadTreeStrategy, Selection, SelectionMode, ShardedNetwork,
     ShardingStrategy,
@@ -109,7 +111,7 @@ fn composite_routes_across_shard_boundary() {
     let sw_id = OsmEntryId::node(1); // corner of grid
     let ne_id = OsmEntryId::node(64); // opposite corner (8×8 grid)
     let route = composite
-        .route_nodes(sw_id, ne_id)
+        .route_nodes(sw_id, ne_id, &OsmTripConfiguration::default())
         .expect("cross-shard route should exist on a fully-connected grid");
     assert!(route.0 > 0, "non-zero weight expected");
     assert_eq!(route.1.first().map(|n| n.id), Some(sw_id));
@@ -134,3 +136,50 @@ fn composite_spatial_index_spans_both_shards() {
     let nearest = composite.nearest_node(&Point::new(-5.0, -5.0));
     assert!(nearest.is_some());
 }
+
+#[test]
+fn shapes_are_carried_into_shards() {
+    let (one, two) = (OsmEntryId::node(1), OsmEntryId::node(2));
+    let bend = vec![Point::new(0.25, 0.1)];
+    let source =
+        MemSource::grid(Point::new(0.0, 0.0), 4, 4, 0.5).with_shape(one, two, bend.clone());
+    let strategy = QuadTreeStrategy::with_depth(3);
+
+    let shard = build_shard(&source, &strategy, strategy.locate(Point::new(0.0, 0.0)));
+    assert_eq!(shard.shape(&one, &two), bend.as_slice());
+    assert!(shard.shape(&two, &one).is_empty());
+
+    let bytes = shard.to_cache_bytes().expect("must encode");
+    let shard = ShardedNetwork::<OsmEntryId, OsmEdgeMetadata, QuadKey>::from_cached_bytes(&bytes)
+        .expect("must decode");
+    assert_eq!(shard.shape(&one, &two), bend.as_slice());
+
+    let composite = MultiShardNetwork::new(vec![Arc::new(shard)]);
+    assert_eq!(composite.shape(&one, &two), bend.as_slice());
+}
+
+#[test]
+fn shards_route_at_the_source_cost() {
+    let (one, two) = (OsmEntryId::node(1), OsmEntryId::node(2));
+    let source =
+        MemSource::grid(Point::new(0.0, 0.0), 4, 4, 0.5).with_cost(one, two, 10_000, 0.001);
+    let strategy = QuadTreeStrategy::with_depth(3);
+
+    let shard = build_shard(&source, &strategy, strategy.locate(Point::new(0.0, 0.0)));
+    assert_eq!(RoutingGraph::cost_per_metre(&shard), 0.001);
+
+    let bytes = shard.to_cache_bytes().expect("must encode");
+    let shard = ShardedNetwork::<OsmEntryId, OsmEdgeMetadata, QuadKey>::from_cached_bytes(&bytes)
+        .expect("must decode");
+
+    let composite = MultiShardNetwork::new(vec![Arc::new(shard)]);
+    assert_eq!(RoutingGraph::cost_per_metre(&composite), 0.001);
+
+    // The direct edge now costs more than the detour through 5 and 6.
+    let (cost, nodes) = composite
+        .route_nodes(one, two, &OsmTripConfiguration::default())
+        .expect("must route");
+    let ids: Vec<i64> = nodes.iter().map(|node| node.id.identifier).collect();
+    assert_eq!(ids, vec![1, 5, 6, 2]);
+    assert_eq!(cost, 3_000);
+}
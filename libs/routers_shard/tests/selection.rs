@@ -118,3 +118,46 @@ fn selection_contains_only_loaded() {
     let neighbour = strategy.neighbours(&owned).into_iter().next().unwrap();
     assert!(!sel.contains(&neighbour));
 }
+
+#[test]
+fn affected_is_the_located_shard_when_owned() {
+    let strategy = GeohashStrategy::with_precision(4);
+    let point = Point::new(13.4, 52.5);
+    let affected = Selection::affected(&strategy, [point, point], SelectionMode::Owned);
+    assert_eq!(affected.len(), 1);
+    assert!(affected.contains(&strategy.locate(point)));
+}
+
+#[test]
+fn affected_includes_every_neighbour_loading_the_point() {
+    let strategy = QuadTreeStrategy::with_depth(8);
+    let point = Point::new(13.4, 52.5);
+    let owned = strategy.locate(point);
+    let affected = Selection::affected(&strategy, [point], SelectionMode::OwnedAndNeighbours);
+    assert_eq!(affected.len(), strategy.neighbours(&owned).len() + 1);
+}
+
+#[test]
+fn affected_includes_neighbours_padded_over_the_point() {
+    let strategy = GeohashStrategy::with_precision(6);
+    let owned = strategy.locate(Point::new(13.4, 52.5));
+    let cell = strategy.bounds(&owned);
+    let mode = SelectionMode::OwnedAndPadded {
+        padding_distance: 50.0,
+    };
+
+    // A point at the middle of the cell is beyond every neighbour's padding.
+    let centre = Point::new(
+        0.5 * (cell.min().x + cell.max().x),
+        0.5 * (cell.min().y + cell.max().y),
+    );
+    assert_eq!(Selection::affected(&strategy, [centre], mode).len(), 1);
+
+    // One just inside its western edge is within the padding of the
+    // neighbour to the west.
+    let edge = Point::new(cell.min().x + 1e-6, centre.y());
+    let west = strategy.locate(Point::new(cell.min().x - 1e-6, centre.y()));
+    let affected = Selection::affected(&strategy, [edge], mode);
+    assert_eq!(affected.len(), 2);
+    assert!(affected.contains(&west));
+}
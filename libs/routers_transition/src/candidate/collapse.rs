@@ -1,10 +1,11 @@
 use alloc::borrow::Cow;
+use core::slice::SliceIndex;
 
 use crate::candidate::*;
 use crate::primitives::{Reachable, ResolutionMethod};
 use geo::{LineString, Point};
-use routers_network::Entry;
 use routers_network::Network;
+use routers_network::{Edge, Entry};
 
 /// A solved map-match: the chosen candidate per input point, plus the routed
 /// path between them.
@@ -19,6 +20,12 @@ where
     /// [`matched`](Self::matched).
     pub route: Vec<CandidateRef>,
 
+    /// The posterior probability of each chosen candidate, in
+    /// [`route`](Self::route) order: near 1 where the match is solid, and
+    /// near `1 / n` where it is a coin-flip between `n` candidates. Empty
+    /// unless the matcher [scores it](crate::Matcher::with_confidence).
+    pub confidence: Vec<f64>,
+
     /// One [`Reachable`] per hop, each holding the routed path between consecutive
     /// chosen candidates. Render it with [`interpolated`](Self::interpolated).
     pub interpolated: Vec<Reachable<E>>,
@@ -40,6 +47,7 @@ where
         CollapsedPath {
             cost: self.cost,
             route: self.route,
+            confidence: self.confidence,
             interpolated: self.interpolated,
             candidates: Cow::Owned(self.candidates.into_owned()),
         }
@@ -63,36 +71,51 @@ where
 
     /// The road geometry driven across hop `hop` (between matched layers
     /// `hop` and `hop + 1`), exclusive of both endpoints' matched positions:
-    /// the current edge's exit node, any routed intermediate nodes, and the
-    /// next edge's entry node, with the shared seam nodes deduplicated.
+    /// the rest of the current edge's [shape](routers_network::DataPlane::shape)
+    /// and its exit node, each routed intermediate edge as drawn, and the next
+    /// edge's entry node and shape up to its matched position, with the shared
+    /// seam nodes deduplicated.
     ///
-    /// Empty for a same-edge hop — travel never leaves the edge, so the
-    /// endpoints alone describe it — and for a `hop` out of range.
+    /// For a same-edge hop, only the shape the edge bends through between the
+    /// two positions — none for a straight edge. Empty for a `hop` out of
+    /// range.
     pub fn hop_geometry(&self, hop: usize, map: &impl Network<Entry = E>) -> Vec<Point> {
         let Some(reachable) = self.interpolated.get(hop) else {
             return Vec::new();
         };
-        if !matches!(reachable.resolution_method, ResolutionMethod::Standard) {
-            return Vec::new();
+
+        let current = self.candidates.candidate(&reachable.source);
+        let next = self.candidates.candidate(&reachable.target);
+
+        let mut points = Vec::new();
+        match reachable.resolution_method {
+            ResolutionMethod::DistanceOnly => {
+                if let (Some(current), Some(next)) = (current, next) {
+                    let between = current.stretch(map)..next.stretch(map);
+                    points.extend(shape_points(&current.edge, between, map));
+                }
+            }
+            ResolutionMethod::Standard => {
+                if let Some(current) = current {
+                    points.extend(shape_points(&current.edge, current.stretch(map).., map));
+                    points.extend(map.point(&current.edge.target));
+                }
+
+                // Consecutive bridge edges share their endpoints with each
+                // other and with the exit/entry nodes, so the seams dedup away.
+                for edge in &reachable.path {
+                    if let Some(line) = map.edge_line(&edge.source, &edge.target) {
+                        points.extend(line.points());
+                    }
+                }
+
+                if let Some(next) = next {
+                    points.extend(map.point(&next.edge.source));
+                    points.extend(shape_points(&next.edge, ..next.stretch(map), map));
+                }
+            }
         }
 
-        let exit = self
-            .candidates
-            .candidate(&reachable.source)
-            .map(|candidate| candidate.edge.target);
-        let entry = self
-            .candidates
-            .candidate(&reachable.target)
-            .map(|candidate| candidate.edge.source);
-
-        // Consecutive bridge edges share their endpoints with each other and
-        // with the exit/entry nodes, so the seams dedup away.
-        let mut points: Vec<Point> = exit
-            .into_iter()
-            .chain(reachable.path_nodes())
-            .chain(entry)
-            .filter_map(|node| map.point(&node))
-            .collect();
         points.dedup();
         points
     }
@@ -115,3 +138,17 @@ where
         points.into_iter().collect::<LineString>()
     }
 }
+
+/// The points of `edge`'s [shape](routers_network::DataPlane::shape) within
+/// `range`, in the direction of travel. Empty for an edge without one, or a
+/// range which falls outside it.
+fn shape_points<E: Entry>(
+    edge: &Edge<E>,
+    range: impl SliceIndex<[Point], Output = [Point]>,
+    map: &impl Network<Entry = E>,
+) -> Vec<Point> {
+    map.shape(&edge.source, &edge.target)
+        .get(range)
+        .map(<[Point]>::to_vec)
+        .unwrap_or_default()
+}
@@ -1,8 +1,8 @@
 use crate::{candidate::CandidateRef, primitives::RoutingContext};
 
 use core::fmt::Debug;
-use geo::{Bearing, Distance, Haversine, LineLocatePoint, LineString, Point};
-use routers_network::{Edge, Entry, Network};
+use geo::{Bearing, Distance, Haversine, Length, LineLocatePoint, LineString, Point};
+use routers_network::{Edge, Entry, Network, Projection, project};
 use serde::{Deserialize, Serialize};
 
 /// One possible anchoring of a trajectory point: an edge of the network, the
@@ -76,12 +76,20 @@ where
     /// ```
     ///
     pub fn percentage<N: Network<Entry = E> + ?Sized>(&self, graph: &N) -> Option<f64> {
-        let edge = graph
-            .line(&[self.edge.source, self.edge.target])
-            .into_iter()
-            .collect::<LineString>();
+        graph
+            .edge_line(&self.edge.source, &self.edge.target)?
+            .line_locate_point(&self.position)
+    }
+
+    /// The candidate's edge as drawn, and where upon it the candidate lies.
+    fn located<N: Network<Entry = E> + ?Sized>(
+        &self,
+        graph: &N,
+    ) -> Option<(LineString, Projection)> {
+        let line = graph.edge_line(&self.edge.source, &self.edge.target)?;
+        let projection = project(&line, &self.position)?;
 
-        edge.line_locate_point(&self.position)
+        Some((line, projection))
     }
 
     /// Whether `other` is reachable from `self` by travelling along their shared
@@ -106,26 +114,49 @@ where
     }
 
     /// Get the bearing of the candidate's edge (source endpoint -> target endpoint).
+    ///
+    /// Where the edge bends along a [shape](routers_network::DataPlane::shape),
+    /// this is the bearing of the stretch the candidate lies on.
     pub fn edge_heading<N>(&self, ctx: &RoutingContext<N>) -> Option<f64>
     where
         N: Network<Entry = E> + ?Sized,
     {
-        let s = ctx.map.point(&self.edge.source)?;
-        let t = ctx.map.point(&self.edge.target)?;
+        if ctx
+            .map
+            .shape(&self.edge.source, &self.edge.target)
+            .is_empty()
+        {
+            let s = ctx.map.point(&self.edge.source)?;
+            let t = ctx.map.point(&self.edge.target)?;
 
-        // Consider degenerate case
-        if Haversine.distance(s, t) < 1.0 {
-            return None;
+            return bearing(s, t);
         }
 
-        Some(Haversine.bearing(s, t))
+        let (line, at) = self.located(ctx.map)?;
+        let stretch = line.lines().nth(at.segment)?;
+        bearing(stretch.start_point(), stretch.end_point())
     }
 
     /// Calculates the offset, in meters, of the candidate to it's edge by the [`VirtualTail`].
+    ///
+    /// The offset is measured along the edge, following its
+    /// [shape](routers_network::DataPlane::shape) where it has one.
     pub fn offset<N>(&self, ctx: &RoutingContext<N>, variant: VirtualTail) -> Option<f64>
     where
         N: Network<Entry = E> + ?Sized,
     {
+        if !ctx
+            .map
+            .shape(&self.edge.source, &self.edge.target)
+            .is_empty()
+        {
+            let (line, at) = self.located(ctx.map)?;
+            return match variant {
+                VirtualTail::ToSource => Some(at.along),
+                VirtualTail::ToTarget => Some(Haversine.length(&line) - at.along),
+            };
+        }
+
         match variant {
             VirtualTail::ToSource => {
                 let source = ctx.map.point(&self.edge.source)?;
@@ -138,6 +169,17 @@ where
         }
     }
 
+    /// The stretch of its edge's [shape](routers_network::DataPlane::shape)
+    /// the candidate lies on, counted from the edge's source. Always the
+    /// first where the edge is straight.
+    pub(crate) fn stretch<N: Network<Entry = E> + ?Sized>(&self, graph: &N) -> usize {
+        if graph.shape(&self.edge.source, &self.edge.target).is_empty() {
+            return 0;
+        }
+
+        self.located(graph).map_or(0, |(_, at)| at.segment)
+    }
+
     pub fn new(edge: Edge<E>, position: Point, emission: u32, location: CandidateRef) -> Self {
         Self {
             edge,
@@ -147,3 +189,14 @@ where
         }
     }
 }
+
+/// The bearing from `s` to `t`, or `None` where the two are too close to
+/// have a meaningful one.
+pub(crate) fn bearing(s: Point, t: Point) -> Option<f64> {
+    // Consider degenerate case
+    if Haversine.distance(s, t) < 1.0 {
+        return None;
+    }
+
+    Some(Haversine.bearing(s, t))
+}
@@ -0,0 +1,461 @@
+//! Map-friendly renderings of a match: GeoJSON, GPX and encoded polylines.
+//!
+//! [`RoutedPath`] and [`CollapsedPath`] carry the same information in the
+//! shapes the matcher works in. The exporters here turn either into the
+//! formats map tooling reads directly:
+//!
+//! - [`to_geojson`](RoutedPath::to_geojson): a `FeatureCollection` of the
+//!   interpolated line, with the ways and edges it drives, followed by one
+//!   Point per matched position carrying its way, edge, confidence and
+//!   metadata as properties.
+//! - [`to_gpx`](RoutedPath::to_gpx): a GPX 1.1 document holding the matched
+//!   positions as waypoints and the interpolated line as a track.
+//! - [`to_polyline`](RoutedPath::to_polyline): the interpolated line as a
+//!   Google encoded polyline, at either [`Precision`].
+//!
+//! A [`CollapsedPath`] has not been resolved against the network, so its
+//! exporters take the network to resolve against.
+//!
+//! Requires the `export` feature.
+use core::fmt::Write;
+
+use geo::Coord;
+use geojson::{Feature, FeatureCollection, Geometry, JsonObject, JsonValue, Value};
+use routers_network::{Entry, Metadata, Network};
+use serde::Serialize;
+use serde_json::json;
+
+use crate::candidate::{CollapsedPath, Path, RoutedPath};
+
+/// The number of decimal places an encoded polyline keeps.
+///
+/// Five is Google's own, and what most map tooling expects. Six is the
+/// "polyline6" OSRM and Valhalla use, worth the longer string when
+/// positions need to be accurate to a decimetre rather than a metre.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Precision {
+    #[default]
+    Five,
+    Six,
+}
+
+impl Precision {
+    fn factor(self) -> f64 {
+        match self {
+            Precision::Five => 1e5,
+            Precision::Six => 1e6,
+        }
+    }
+}
+
+/// Encode `coords` as a Google encoded polyline.
+///
+/// Each coordinate is written latitude first, as the format requires, and
+/// rounded to `precision` decimal places.
+pub fn encode_polyline(coords: impl IntoIterator<Item = Coord>, precision: Precision) -> String {
+    let factor = precision.factor();
+    let mut encoded = String::new();
+    let mut previous = (0, 0);
+
+    for coord in coords {
+        let current = (
+            (coord.y * factor).round() as i64,
+            (coord.x * factor).round() as i64,
+        );
+
+        encode_value(current.0 - previous.0, &mut encoded);
+        encode_value(current.1 - previous.1, &mut encoded);
+        previous = current;
+    }
+
+    encoded
+}
+
+fn encode_value(value: i64, encoded: &mut String) {
+    // Zig-zag the sign into the lowest bit, then emit five bits per
+    // character, low bits first, flagging every chunk but the last.
+    let mut value = if value < 0 { !(value << 1) } else { value << 1 };
+
+    while value >= 0x20 {
+        encoded.push(char::from((0x20 | (value & 0x1f)) as u8 + 63));
+        value >>= 5;
+    }
+
+    encoded.push(char::from(value as u8 + 63));
+}
+
+/// One matched position, as its exporters describe it.
+struct Position<'a, M> {
+    coord: Coord,
+    way: i64,
+    edge: (i64, i64),
+    confidence: Option<f64>,
+    metadata: Option<&'a M>,
+}
+
+impl<M: Serialize> Position<'_, M> {
+    fn feature(&self, index: usize) -> Feature {
+        let mut properties = JsonObject::new();
+        properties.insert("kind".into(), json!("matched"));
+        properties.insert("index".into(), json!(index));
+        properties.insert("way".into(), json!(self.way));
+        properties.insert("edge".into(), json!([self.edge.0, self.edge.1]));
+
+        if let Some(confidence) = self.confidence {
+            properties.insert("confidence".into(), json!(confidence));
+        }
+
+        if let Some(metadata) = self.metadata {
+            let metadata = serde_json::to_value(metadata).unwrap_or_else(|err| {
+                log::warn!("could not serialise edge metadata: {err}");
+                JsonValue::Null
+            });
+            properties.insert("metadata".into(), metadata);
+        }
+
+        feature(Value::Point(position(self.coord)), properties)
+    }
+}
+
+fn position(coord: Coord) -> Vec<f64> {
+    vec![coord.x, coord.y]
+}
+
+fn feature(value: Value, properties: JsonObject) -> Feature {
+    Feature {
+        geometry: Some(Geometry::new(value)),
+        properties: Some(properties),
+        ..Default::default()
+    }
+}
+
+/// The interpolated line, followed by each matched position.
+fn collection<M: Serialize>(
+    line: &[Coord],
+    ways: &[i64],
+    edges: &[(i64, i64)],
+    cost: u32,
+    positions: &[Position<'_, M>],
+) -> FeatureCollection {
+    let mut properties = JsonObject::new();
+    properties.insert("kind".into(), json!("interpolated"));
+    properties.insert("cost".into(), json!(cost));
+    properties.insert("ways".into(), json!(ways));
+    properties.insert("edges".into(), json!(edges));
+
+    let line = feature(
+        Value::LineString(line.iter().copied().map(position).collect()),
+        properties,
+    );
+
+    FeatureCollection {
+        bbox: None,
+        features: core::iter::once(line)
+            .chain(
+                positions
+                    .iter()
+                    .enumerate()
+                    .map(|(index, position)| position.feature(index)),
+            )
+            .collect(),
+        foreign_members: None,
+    }
+}
+
+/// A GPX 1.1 document of `waypoints` and a one-segment track of `track`.
+fn gpx(
+    waypoints: impl IntoIterator<Item = Coord>,
+    track: impl IntoIterator<Item = Coord>,
+) -> String {
+    // Writing into a `String` cannot fail.
+    let mut gpx = String::from(concat!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+        "\n",
+        r#"<gpx version="1.1" creator="routers" xmlns="http://www.topografix.com/GPX/1/1">"#,
+        "\n",
+    ));
+
+    for coord in waypoints {
+        let _ = writeln!(gpx, r#"  <wpt lat="{}" lon="{}"/>"#, coord.y, coord.x);
+    }
+
+    gpx.push_str("  <trk>\n    <trkseg>\n");
+    for coord in track {
+        let _ = writeln!(gpx, r#"      <trkpt lat="{}" lon="{}"/>"#, coord.y, coord.x);
+    }
+    gpx.push_str("    </trkseg>\n  </trk>\n</gpx>\n");
+
+    gpx
+}
+
+/// `values` with consecutive repeats collapsed.
+fn runs<T: PartialEq>(values: impl IntoIterator<Item = T>) -> Vec<T> {
+    let mut values = values.into_iter().collect::<Vec<_>>();
+    values.dedup();
+    values
+}
+
+impl<E, M> Path<E, M>
+where
+    E: Entry,
+    M: Metadata,
+{
+    fn coords(&self) -> impl Iterator<Item = Coord> + '_ {
+        self.iter().map(|element| element.point)
+    }
+
+    fn positions(&self) -> Vec<Position<'_, M>> {
+        self.iter()
+            .map(|element| Position {
+                coord: element.point,
+                way: element.edge.id().identifier(),
+                edge: (
+                    element.edge.source.identifier(),
+                    element.edge.target.identifier(),
+                ),
+                confidence: element.confidence,
+                metadata: Some(&element.metadata),
+            })
+            .collect()
+    }
+}
+
+impl<E, M> RoutedPath<E, M>
+where
+    E: Entry,
+    M: Metadata,
+{
+    /// The match as a GeoJSON `FeatureCollection`: first the
+    /// [`interpolated`](Self::interpolated) line, with its `cost` and the
+    /// `ways` and `edges` (as `[source, target]` node ids) it drives, then
+    /// one Point per [`discretized`](Self::discretized) position, with its
+    /// `index`, `way`, `edge`, `confidence` and `metadata`.
+    pub fn to_geojson(&self) -> FeatureCollection {
+        let interpolated = self.interpolated.positions();
+
+        collection(
+            &self.interpolated.coords().collect::<Vec<_>>(),
+            &runs(interpolated.iter().map(|position| position.way)),
+            &runs(interpolated.iter().map(|position| position.edge)),
+            self.cost,
+            &self.discretized.positions(),
+        )
+    }
+
+    /// The match as a GPX document: the [`discretized`](Self::discretized)
+    /// positions as waypoints and the [`interpolated`](Self::interpolated)
+    /// line as a track.
+    pub fn to_gpx(&self) -> String {
+        gpx(self.discretized.coords(), self.interpolated.coords())
+    }
+
+    /// The [`interpolated`](Self::interpolated) line as a Google encoded
+    /// polyline.
+    pub fn to_polyline(&self, precision: Precision) -> String {
+        encode_polyline(self.interpolated.coords(), precision)
+    }
+}
+
+impl<E> CollapsedPath<'_, E>
+where
+    E: Entry,
+{
+    /// The match as a GeoJSON `FeatureCollection`, in the same shape as
+    /// [`RoutedPath::to_geojson`], resolving metadata against `map`.
+    pub fn to_geojson<M: Metadata>(
+        &self,
+        map: &impl Network<Entry = E, Meta = M>,
+    ) -> FeatureCollection {
+        let matched = self.matched();
+
+        let positions = matched
+            .iter()
+            .enumerate()
+            .map(|(index, candidate)| Position {
+                coord: candidate.position.0,
+                way: candidate.edge.id().identifier(),
+                edge: (
+                    candidate.edge.source.identifier(),
+                    candidate.edge.target.identifier(),
+                ),
+                confidence: self.confidence.get(index).copied(),
+                metadata: map.metadata(candidate.edge.id()),
+            })
+            .collect::<Vec<_>>();
+
+        // Each matched edge, then the edges routed to the next one.
+        let driven = matched.iter().enumerate().flat_map(|(index, candidate)| {
+            let bridge = self
+                .interpolated
+                .get(index)
+                .map(|reachable| reachable.path.as_slice())
+                .unwrap_or_default();
+
+            core::iter::once(&candidate.edge).chain(bridge)
+        });
+        let driven = driven.collect::<Vec<_>>();
+
+        collection(
+            &self.interpolated(map).0,
+            &runs(driven.iter().map(|edge| edge.id().identifier())),
+            &runs(
+                driven
+                    .iter()
+                    .map(|edge| (edge.source.identifier(), edge.target.identifier())),
+            ),
+            self.cost,
+            &positions,
+        )
+    }
+
+    /// The match as a GPX document, in the same shape as
+    /// [`RoutedPath::to_gpx`].
+    pub fn to_gpx(&self, map: &impl Network<Entry = E>) -> String {
+        gpx(self.collapsed().0, self.interpolated(map).0)
+    }
+
+    /// The interpolated line as a Google encoded polyline.
+    pub fn to_polyline(&self, map: &impl Network<Entry = E>, precision: Precision) -> String {
+        encode_polyline(self.interpolated(map).0, precision)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo::{LineString, point, wkt};
+    use routers_network::mock::{MockNetwork, MockNetworkBuilder};
+
+    use crate::{Match, MatchOptions, MatchSimpleExt};
+
+    /// The coordinates an encoded polyline holds.
+    fn decode(encoded: &str, precision: Precision) -> Vec<Coord> {
+        let mut bytes = encoded.bytes().peekable();
+        let mut values = Vec::new();
+
+        while bytes.peek().is_some() {
+            let (mut value, mut shift) = (0i64, 0);
+            for byte in bytes.by_ref() {
+                let chunk = i64::from(byte - 63);
+                value |= (chunk & 0x1f) << shift;
+                shift += 5;
+                if chunk < 0x20 {
+                    break;
+                }
+            }
+
+            values.push(if value & 1 == 1 {
+                !(value >> 1)
+            } else {
+                value >> 1
+            });
+        }
+
+        let mut previous = (0, 0);
+        values
+            .chunks(2)
+            .map(|pair| {
+                previous = (previous.0 + pair[0], previous.1 + pair[1]);
+                Coord {
+                    x: previous.1 as f64 / precision.factor(),
+                    y: previous.0 as f64 / precision.factor(),
+                }
+            })
+            .collect()
+    }
+
+    fn straight_road() -> MockNetwork {
+        MockNetworkBuilder::new()
+            .node(1, point!(x: -118.15, y: 34.15))
+            .node(2, point!(x: -118.16, y: 34.15))
+            .node(3, point!(x: -118.17, y: 34.15))
+            .edge(1, 2)
+            .edge(2, 3)
+            .build()
+    }
+
+    #[test]
+    fn encodes_googles_example() {
+        let line: LineString = wkt! { LINESTRING(-120.2 38.5, -120.95 40.7, -126.453 43.252) };
+
+        assert_eq!(
+            encode_polyline(line.0, Precision::Five),
+            "_p~iF~ps|U_ulLnnqC_mqNvxq`@"
+        );
+    }
+
+    #[test]
+    fn six_places_survive_a_round_trip() {
+        let coords = vec![
+            Coord {
+                x: 151.209_296,
+                y: -33.868_821,
+            },
+            Coord {
+                x: 151.209_301,
+                y: -33.868_824,
+            },
+        ];
+
+        let decoded = decode(
+            &encode_polyline(coords.clone(), Precision::Six),
+            Precision::Six,
+        );
+        for (decoded, coord) in decoded.iter().zip(&coords) {
+            assert!((decoded.x - coord.x).abs() < 1e-9);
+            assert!((decoded.y - coord.y).abs() < 1e-9);
+        }
+
+        // At five places, the two collapse onto one position.
+        let decoded = decode(&encode_polyline(coords, Precision::Five), Precision::Five);
+        assert_eq!(decoded[0], decoded[1]);
+    }
+
+    #[test]
+    fn geojson_carries_the_line_and_matched_points() {
+        let network = straight_road();
+        let routed = network
+            .r#match(
+                wkt! { LINESTRING(-118.151 34.1503, -118.165 34.1503) },
+                MatchOptions::new().with_confidence(true),
+            )
+            .expect("must match");
+
+        let collection = routed.to_geojson();
+        assert_eq!(collection.features.len(), 1 + routed.discretized.len());
+
+        let line = collection.features[0].properties.as_ref().unwrap();
+        assert_eq!(line["kind"], "interpolated");
+        assert_eq!(line["edges"], json!([[1, 2], [2, 3]]));
+
+        let point = collection.features[1].properties.as_ref().unwrap();
+        assert_eq!(point["kind"], "matched");
+        assert_eq!(point["edge"], json!([1, 2]));
+        assert!(point.contains_key("way"));
+        assert!(point.contains_key("confidence"));
+        assert!(point.contains_key("metadata"));
+    }
+
+    #[test]
+    fn gpx_holds_waypoints_and_a_track() {
+        let network = straight_road();
+        let routed = network
+            .match_simple(wkt! { LINESTRING(-118.151 34.1503, -118.165 34.1503) })
+            .expect("must match");
+
+        let gpx = routed.to_gpx();
+        assert_eq!(gpx.matches("<wpt ").count(), routed.discretized.len());
+        assert_eq!(gpx.matches("<trkpt ").count(), routed.interpolated.len());
+    }
+
+    #[test]
+    fn polyline_follows_the_interpolated_line() {
+        let network = straight_road();
+        let routed = network
+            .match_simple(wkt! { LINESTRING(-118.151 34.1503, -118.165 34.1503) })
+            .expect("must match");
+
+        let decoded = decode(&routed.to_polyline(Precision::Six), Precision::Six);
+        assert_eq!(decoded.len(), routed.interpolated.len());
+    }
+}
@@ -10,12 +10,18 @@
 //! Results build upwards from there. A [`CollapsedPath`] is the matcher-level
 //! result: the chosen candidate per layer plus the routed hops between them.
 //! A [`RoutedPath`] is the facade-level result: the same information resolved
-//! against the network into render-ready, metadata-carrying [`Path`]s.
+//! against the network into render-ready, metadata-carrying [`Path`]s. A
+//! trajectory split at its breaks yields one [`Segment`] of either per stretch.
+//! With the `export` feature, either renders to GeoJSON, GPX or an encoded
+//! polyline.
 
 mod collapse;
 mod entry;
+#[cfg(feature = "export")]
+mod export;
 mod ident;
 mod route;
+mod segment;
 mod store;
 
 #[doc(inline)]
@@ -23,6 +29,10 @@ pub use collapse::CollapsedPath;
 #[doc(inline)]
 pub use route::{Path, PathElement, RoutedPath};
 
+pub(crate) use entry::bearing;
 pub use entry::{Candidate, VirtualTail};
+#[cfg(feature = "export")]
+pub use export::{Precision, encode_polyline};
 pub use ident::CandidateRef;
+pub use segment::{Segment, Segments};
 pub use store::CandidateStore;
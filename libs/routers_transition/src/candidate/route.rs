@@ -1,9 +1,11 @@
 use crate::{candidate::*, primitives::ResolutionMethod};
+use core::iter;
 use core::ops::Deref;
+use core::slice::SliceIndex;
 use routers_network::{Edge, Entry, Metadata, Network, Node};
 use serde::{Deserialize, Serialize};
 
-use geo::Coord;
+use geo::{Coord, Point};
 
 /// The result of a facade-level match: the trajectory resolved onto the
 /// network, ready to render or persist.
@@ -36,6 +38,16 @@ where
     /// a trip by "recovering" lost information, or understanding subtle details such as
     /// when the route left or joined a highway.
     pub interpolated: Path<E, M>,
+
+    /// The total cost of the match — comparable only against the costs of
+    /// its [`alternatives`](Self::alternatives).
+    pub cost: u32,
+
+    /// The next-cheapest distinct matches, cheapest first, when they were
+    /// asked for (see [`MatchOptions::alternatives`](crate::MatchOptions::alternatives)).
+    /// A runner-up costing barely more than this match makes it a coin-flip.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alternatives: Vec<RoutedPath<E, M>>,
 }
 
 impl<E, M> RoutedPath<E, M>
@@ -55,11 +67,8 @@ where
             .flat_map(|id| collapsed_path.candidates.candidate(id))
             .collect();
 
-        // One PathElement per GPS input point.
-        let discretized = matched
-            .iter()
-            .flat_map(|c| PathElement::new(*c, network))
-            .collect::<Path<E, M>>();
+        // One PathElement per GPS input point, with the match's confidence in it.
+        let discretized = collapsed_path.discretized(network);
 
         // The complete traversed path. Each candidate edge is interleaved
         // with the routing edges that bridge consecutive candidates.
@@ -81,6 +90,13 @@ where
                         elements.push(pe);
                     }
                 }
+
+                // Follow the edge's shape up to the candidate
+                elements.extend(shape_elements(
+                    &first.edge,
+                    ..first.stretch(network),
+                    network,
+                ));
             }
 
             for (i, reachable) in collapsed_path.interpolated.iter().enumerate() {
@@ -91,8 +107,21 @@ where
                     elements.push(pe);
                 }
 
+                if let ResolutionMethod::DistanceOnly = reachable.resolution_method
+                    && let Some(next) = matched.get(i + 1)
+                {
+                    // Follow the shared edge's shape between the two
+                    let between = current.stretch(network)..next.stretch(network);
+                    elements.extend(shape_elements(&current.edge, between, network));
+                }
+
                 if let ResolutionMethod::Standard = reachable.resolution_method {
-                    // Add target of current candidate edge
+                    // Add target of current candidate edge, following its shape
+                    elements.extend(shape_elements(
+                        &current.edge,
+                        current.stretch(network)..,
+                        network,
+                    ));
                     if let Some(fat) = network.fatten(&current.edge) {
                         if let Some(pe) = PathElement::from_edge_target(fat, network) {
                             elements.push(pe);
@@ -106,6 +135,7 @@ where
                                 elements.push(pe);
                             }
                         }
+                        elements.extend(shape_elements(edge, .., network));
                     }
 
                     // Add source of next candidate edge
@@ -115,6 +145,11 @@ where
                                 elements.push(pe);
                             }
                         }
+                        elements.extend(shape_elements(
+                            &next.edge,
+                            ..next.stretch(network),
+                            network,
+                        ));
                     }
                 }
             }
@@ -134,6 +169,54 @@ where
         RoutedPath {
             discretized,
             interpolated,
+            cost: collapsed_path.cost,
+            alternatives: Vec::new(),
+        }
+    }
+
+    /// Resolve a best match and its runners-up, cheapest first, into one
+    /// [`RoutedPath`] carrying the rest as its
+    /// [`alternatives`](Self::alternatives). `None` when there are none.
+    pub fn ranked<'c>(
+        collapsed_paths: impl IntoIterator<Item = CollapsedPath<'c, E>>,
+        network: &impl Network<Entry = E, Meta = M>,
+    ) -> Option<Self>
+    where
+        E: 'c,
+    {
+        let mut ranked = collapsed_paths
+            .into_iter()
+            .map(|collapsed| Self::new(collapsed, network));
+
+        let mut best = ranked.next()?;
+        best.alternatives = ranked.collect();
+        Some(best)
+    }
+
+    /// Resolve a snapped (unrouted) [`CollapsedPath`] against the network.
+    ///
+    /// Both views hold exactly one element per input point: with no routed
+    /// hops there is nothing to interpolate between the snapped positions.
+    /// As in [`new`](Self::new), only the discretized view carries
+    /// confidence.
+    pub fn snapped(
+        collapsed_path: CollapsedPath<'_, E>,
+        network: &impl Network<Entry = E, Meta = M>,
+    ) -> Self {
+        let cost = collapsed_path.cost;
+
+        let interpolated = collapsed_path
+            .discretized(network)
+            .elements
+            .into_iter()
+            .map(|element| element.with_confidence(None))
+            .collect();
+
+        RoutedPath {
+            discretized: collapsed_path.discretized(network),
+            interpolated,
+            cost,
+            alternatives: Vec::new(),
         }
     }
 }
@@ -151,6 +234,30 @@ where
     pub elements: Vec<PathElement<E, M>>,
 }
 
+impl<E> CollapsedPath<'_, E>
+where
+    E: Entry,
+{
+    /// One [`PathElement`] per matched point, each carrying its
+    /// [`confidence`](CollapsedPath::confidence).
+    fn discretized<M: Metadata>(&self, network: &impl Network<Entry = E, Meta = M>) -> Path<E, M> {
+        self.route
+            .iter()
+            .zip(
+                self.confidence
+                    .iter()
+                    .copied()
+                    .map(Some)
+                    .chain(iter::repeat(None)),
+            )
+            .filter_map(|(id, confidence)| {
+                let candidate = self.candidates.candidate(id)?;
+                Some(PathElement::new(candidate, network)?.with_confidence(confidence))
+            })
+            .collect()
+    }
+}
+
 impl<E, M> FromIterator<PathElement<E, M>> for Path<E, M>
 where
     E: Entry,
@@ -187,6 +294,12 @@ where
     pub edge: Edge<Node<E>>,
 
     pub metadata: M,
+
+    /// How sure the match is of this point, as a probability. Set on
+    /// [`discretized`](RoutedPath::discretized) elements only; see
+    /// [`CollapsedPath::confidence`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f64>,
 }
 
 impl<E, M> PathElement<E, M>
@@ -202,9 +315,14 @@ where
             point: candidate.position.0,
             edge: network.fatten(&candidate.edge)?,
             metadata: network.metadata(candidate.edge.id())?.clone(),
+            confidence: None,
         })
     }
 
+    pub fn with_confidence(self, confidence: Option<f64>) -> Self {
+        Self { confidence, ..self }
+    }
+
     pub fn from_edge_source(
         edge: Edge<Node<E>>,
         network: &impl Network<Entry = E, Meta = M>,
@@ -213,6 +331,7 @@ where
             point: edge.source.position.0,
             metadata: network.metadata(edge.id())?.clone(),
             edge,
+            confidence: None,
         })
     }
 
@@ -224,6 +343,39 @@ where
             point: edge.target.position.0,
             metadata: network.metadata(edge.id())?.clone(),
             edge,
+            confidence: None,
         })
     }
 }
+
+/// An element for each point of `edge`'s [shape](routers_network::DataPlane::shape)
+/// within `range`, in the direction of travel. Empty for an edge without one,
+/// or a range which falls outside it.
+fn shape_elements<E, M>(
+    edge: &Edge<E>,
+    range: impl SliceIndex<[Point], Output = [Point]>,
+    network: &impl Network<Entry = E, Meta = M>,
+) -> Vec<PathElement<E, M>>
+where
+    E: Entry,
+    M: Metadata,
+{
+    let shape = network.shape(&edge.source, &edge.target);
+    let Some(points) = shape.get(range).filter(|points| !points.is_empty()) else {
+        return Vec::new();
+    };
+
+    let (Some(fat), Some(metadata)) = (network.fatten(edge), network.metadata(edge.id())) else {
+        return Vec::new();
+    };
+
+    points
+        .iter()
+        .map(|point| PathElement {
+            point: point.0,
+            edge: fat,
+            metadata: metadata.clone(),
+            confidence: None,
+        })
+        .collect()
+}
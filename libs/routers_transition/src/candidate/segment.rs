@@ -0,0 +1,34 @@
+use core::ops::Range;
+use serde::{Deserialize, Serialize};
+
+use crate::primitives::MatchError;
+
+/// The segments a trajectory is matched in, in input order. A piece of the
+/// input which could not be matched is a segment holding its error, so the
+/// pieces around it are still reported.
+pub type Segments<T> = Vec<Segment<Result<T, MatchError>>>;
+
+/// One independently matched stretch of a trajectory which was split at its
+/// breaks, such as a [`CollapsedPath`](crate::candidate::CollapsedPath) or a
+/// [`RoutedPath`](crate::candidate::RoutedPath).
+///
+/// Each segment's path is expressed relative to its own first position;
+/// [`layers`](Self::layers) places it back within the input trajectory.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Segment<T> {
+    /// The input positions this segment covers, as indices into the trajectory.
+    pub layers: Range<usize>,
+
+    /// The match of those positions.
+    pub path: T,
+}
+
+impl<T> Segment<T> {
+    /// Transform the path, keeping the segment's placement.
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Segment<U> {
+        Segment {
+            layers: self.layers,
+            path: f(self.path),
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use routers_network::Entry;
-use routers_trellis::LayerId;
+use routers_trellis::{LayerId, NodeId};
 use serde::{Deserialize, Serialize};
 
 use crate::candidate::{Candidate, CandidateRef};
@@ -42,6 +42,33 @@ where
         }
     }
 
+    /// Narrow a layer to its one candidate `node`, re-stamped as node zero —
+    /// the mirror of [`Trellis::pin`](routers_trellis::Trellis::pin).
+    pub(crate) fn pin(&mut self, layer: LayerId, node: NodeId) {
+        let Some(candidates) = self.layers.get_mut(layer.index()) else {
+            return;
+        };
+        let Some(mut kept) = candidates.get(node.index()).copied() else {
+            return;
+        };
+
+        kept.location.node = NodeId(0);
+        *candidates = vec![kept];
+    }
+
+    /// An owned copy of the layers in `range`, re-stamped so the first kept
+    /// layer becomes layer zero.
+    pub(crate) fn partition(&self, range: core::ops::Range<usize>) -> Self {
+        let mut layers = self.layers[range].to_vec();
+        for (layer, candidates) in layers.iter_mut().enumerate() {
+            for candidate in candidates {
+                candidate.location.layer = LayerId(layer as u32);
+            }
+        }
+
+        Self { layers }
+    }
+
     /// The candidates of one layer, in node order.
     pub fn layer(&self, layer: LayerId) -> Option<&[Candidate<E>]> {
         self.layers.get(layer.index()).map(Vec::as_slice)
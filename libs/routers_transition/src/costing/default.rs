@@ -63,6 +63,193 @@ pub mod emission {
             Some(context.distance.div(self.emission_error).sqrt().neg().exp())
         }
     }
+
+    /// The smallest free radius an accuracy may narrow the emission to.
+    const DEFAULT_MIN_ERROR: f64 = 5.0;
+
+    /// Calculates the emission cost of a candidate using everything the
+    /// observation reported about itself, not only its position.
+    ///
+    /// ## Calculation
+    ///
+    /// The distance falloff is that of [`DefaultEmissionCost`], but the free
+    /// radius follows the observation's reported accuracy where it has one,
+    /// so a precise fix is held to its road and a poor one is given room.
+    ///
+    /// ```math
+    /// err = max(accuracy * accuracy_scale, min_error)
+    /// distance_cost = exp(-sqrt(distance / err))
+    /// ```
+    ///
+    /// Observations without an accuracy fall back to the
+    /// [`emission_error`](#field.emission_error).
+    ///
+    /// Where the observation reports a heading, and the candidate edge has
+    /// one, the candidate is discounted by how far the two disagree. A
+    /// vehicle travelling against an edge's direction, `δ = 180°`, pays the
+    /// full [`heading_weight`](#field.heading_weight).
+    ///
+    /// ```math
+    /// heading_cost = 1 - heading_weight * (1 - cos(δ)) / 2
+    /// cost = distance_cost * heading_cost
+    /// ```
+    ///
+    /// Headings reported while (nearly) stationary are noise, so no heading
+    /// cost applies below [`min_speed`](#field.min_speed).
+    pub struct ObservedEmissionCost {
+        /// The free radius used when the observation reports no accuracy.
+        ///
+        /// Default: 25 meters.
+        pub emission_error: f64,
+
+        /// The multiple of the reported accuracy taken as the free radius.
+        ///
+        /// Default: 2.
+        pub accuracy_scale: f64,
+
+        /// The narrowest free radius, however accurate the observation claims
+        /// to be.
+        ///
+        /// Default: 5 meters.
+        pub min_error: f64,
+
+        /// How much of the cost a fully opposed heading takes, in `[0, 1]`.
+        ///
+        /// Default: 0.9.
+        pub heading_weight: f64,
+
+        /// The speed, in meters per second, below which a reported heading is
+        /// ignored. Observations without a speed are trusted.
+        ///
+        /// Default: 2 m/s.
+        pub min_speed: f64,
+    }
+
+    impl Default for ObservedEmissionCost {
+        fn default() -> Self {
+            ObservedEmissionCost {
+                emission_error: DEFAULT_EMISSION_ERROR,
+                accuracy_scale: 2.0,
+                min_error: DEFAULT_MIN_ERROR,
+                heading_weight: 0.9,
+                min_speed: 2.0,
+            }
+        }
+    }
+
+    impl ObservedEmissionCost {
+        fn error(&self, context: &EmissionContext) -> f64 {
+            context
+                .accuracy
+                .filter(|accuracy| accuracy.is_finite() && *accuracy > 0.0)
+                .map_or(self.emission_error, |accuracy| {
+                    (accuracy * self.accuracy_scale).max(self.min_error)
+                })
+        }
+
+        fn heading(&self, context: &EmissionContext) -> f64 {
+            let moving = context.speed.is_none_or(|speed| speed >= self.min_speed);
+
+            match (context.heading, context.edge_heading) {
+                (Some(heading), Some(edge)) if moving => {
+                    let delta = (heading - edge).to_radians();
+                    1.0 - self.heading_weight * (1.0 - delta.cos()) / 2.0
+                }
+                _ => 1.0,
+            }
+        }
+    }
+
+    impl<'a> Strategy<EmissionContext<'a>> for ObservedEmissionCost {
+        type Cost = f64;
+
+        const ZETA: f64 = 1.;
+        const BETA: f64 = 1.;
+
+        #[inline(always)]
+        fn calculate(&self, context: EmissionContext<'a>) -> Option<Self::Cost> {
+            let distance = context
+                .distance
+                .div(self.error(&context))
+                .sqrt()
+                .neg()
+                .exp();
+            Some(distance * self.heading(&context))
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::Origin;
+        use geo::point;
+
+        fn context<'a>(position: &'a geo::Point, distance: f64) -> EmissionContext<'a> {
+            EmissionContext::new(position, position, distance, 1)
+        }
+
+        #[test]
+        fn bare_observations_cost_as_the_default() {
+            let position = point!(x: 0.0, y: 0.0);
+            let observed = ObservedEmissionCost::default();
+            let default = DefaultEmissionCost::default();
+
+            for distance in [0.0, 10.0, 50.0] {
+                assert_eq!(
+                    observed.calculate(context(&position, distance)),
+                    default.calculate(context(&position, distance)),
+                );
+            }
+        }
+
+        #[test]
+        fn accurate_observations_keep_close() {
+            let position = point!(x: 0.0, y: 0.0);
+            let strategy = ObservedEmissionCost::default();
+
+            let origin = |accuracy| Origin::new(position, 0).with_accuracy(Some(accuracy));
+            let cost = |accuracy| {
+                strategy
+                    .calculate(context(&position, 20.0).with_observation(&origin(accuracy)))
+                    .unwrap()
+            };
+
+            assert!(cost(3.0) < cost(30.0));
+
+            // However sure of itself, a fix is given the minimum radius.
+            assert_eq!(cost(0.5), cost(1.0));
+        }
+
+        #[test]
+        fn opposing_headings_are_discounted() {
+            let position = point!(x: 0.0, y: 0.0);
+            let strategy = ObservedEmissionCost::default();
+
+            let cost = |heading, edge, speed| {
+                let origin = Origin::new(position, 0)
+                    .with_heading(Some(heading))
+                    .with_speed(speed);
+
+                strategy
+                    .calculate(
+                        context(&position, 0.0)
+                            .with_observation(&origin)
+                            .with_edge_heading(Some(edge)),
+                    )
+                    .unwrap()
+            };
+
+            assert_eq!(cost(90.0, 90.0, None), 1.0);
+            assert!(cost(90.0, 0.0, None) < 1.0);
+            assert!(cost(90.0, 270.0, None) < cost(90.0, 0.0, None));
+
+            // Headings wrap: 359° and 1° barely disagree.
+            assert!(cost(359.0, 1.0, None) > 0.99);
+
+            // A stationary vehicle's heading says nothing.
+            assert_eq!(cost(90.0, 270.0, Some(0.5)), 1.0);
+        }
+    }
 }
 
 pub mod transition {
@@ -172,6 +359,95 @@ pub mod transition {
             Some((deviance * turn_cost * class_continuity).sqrt())
         }
     }
+
+    /// 200 km/h, in metres per second.
+    const DEFAULT_MAX_SPEED: f64 = 200.0 / 3.6;
+
+    /// Calculates the transition cost between two candidates with regard to
+    /// the time between their observations.
+    ///
+    /// # Calculation
+    ///
+    /// The [`DefaultTransitionCost`] prices the route's shape; this strategy
+    /// additionally prices how fast the vehicle must have travelled it. The
+    /// implied speed is the route length over the elapsed time, and is
+    /// allowed up to the fastest speed limit along the route, give or take
+    /// the [`speed_tolerance`](#field.speed_tolerance), but never beyond the
+    /// [`max_speed`](#field.max_speed).
+    ///
+    /// ```math
+    /// allowed = min(speed_limit * speed_tolerance, max_speed)
+    /// implied = route_length / elapsed
+    ///
+    /// speed_cost = min(1, allowed / implied)²
+    /// cost = default_cost * speed_cost
+    /// ```
+    ///
+    /// Routes with no limit along them are allowed the `max_speed`. A detour
+    /// or U-turn lengthens the route without lengthening the time, so its
+    /// implied speed — and its cost — grows with it.
+    ///
+    /// Transitions without a positive elapsed time are priced as the
+    /// default. Note that [`Matcher::r#match`](crate::Matcher::r#match)
+    /// numbers its points in place of real timestamps; use the streaming
+    /// lifecycle with real observation times alongside this strategy.
+    pub struct TimedTransitionCost {
+        /// The fastest any vehicle is taken to travel, in metres per second.
+        ///
+        /// Default: 200 km/h.
+        pub max_speed: f64,
+
+        /// The multiple of the speed limit a vehicle may travel at before it
+        /// is penalised, covering GPS error and drivers' habits alike.
+        ///
+        /// Default: 1.5.
+        pub speed_tolerance: f64,
+    }
+
+    impl Default for TimedTransitionCost {
+        fn default() -> Self {
+            TimedTransitionCost {
+                max_speed: DEFAULT_MAX_SPEED,
+                speed_tolerance: 1.5,
+            }
+        }
+    }
+
+    impl TimedTransitionCost {
+        /// The fastest the vehicle is allowed along the transition, in metres
+        /// per second.
+        pub fn allowed_speed<E: Entry>(&self, context: &TransitionContext<E>) -> f64 {
+            context.speed_limit.map_or(self.max_speed, |limit| {
+                (limit * self.speed_tolerance).min(self.max_speed)
+            })
+        }
+
+        fn speed_cost<E: Entry>(&self, context: &TransitionContext<E>) -> f64 {
+            let Some(implied) = context.implied_speed() else {
+                return 1.0;
+            };
+
+            (self.allowed_speed(context) / implied).min(1.0).powi(2)
+        }
+    }
+
+    impl<'a, E> Strategy<TransitionContext<'a, E>> for TimedTransitionCost
+    where
+        E: Entry,
+    {
+        type Cost = f64;
+
+        const ZETA: f64 = 1.;
+        const BETA: f64 = 1.;
+
+        #[inline]
+        fn calculate(&self, context: TransitionContext<'a, E>) -> Option<Self::Cost> {
+            let speed_cost = self.speed_cost(&context);
+            let cost = DefaultTransitionCost.calculate(context)?;
+
+            Some(cost * speed_cost)
+        }
+    }
 }
 
 pub mod costing {
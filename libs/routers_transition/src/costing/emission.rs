@@ -1,5 +1,6 @@
 use routers_network::edge::Weight;
 
+use crate::Origin;
 use crate::costing::Strategy;
 
 pub trait EmissionStrategy: for<'a> Strategy<EmissionContext<'a>> {}
@@ -33,6 +34,24 @@ pub struct EmissionContext<'a> {
     /// lower-quality roads relative to those on higher-quality roads at the
     /// same physical distance.
     pub weight: Weight,
+
+    /// The heading the source observation reported, in degrees clockwise
+    /// from true north, if any.
+    pub heading: Option<f64>,
+
+    /// The speed the source observation reported, in metres per second, if
+    /// any.
+    pub speed: Option<f64>,
+
+    /// The horizontal accuracy the source observation reported, in metres,
+    /// if any.
+    pub accuracy: Option<f64>,
+
+    /// The bearing of the candidate edge, source to target, in degrees.
+    ///
+    /// Only computed when the observation carries a [`heading`](Self::heading)
+    /// to compare it against, and `None` for degenerate edges.
+    pub edge_heading: Option<f64>,
 }
 
 impl<'a> EmissionContext<'a> {
@@ -47,6 +66,23 @@ impl<'a> EmissionContext<'a> {
             source_position: source,
             distance,
             weight,
+            heading: None,
+            speed: None,
+            accuracy: None,
+            edge_heading: None,
         }
     }
+
+    /// Carry the heading, speed and accuracy reported with `origin`.
+    pub fn with_observation(mut self, origin: &Origin) -> Self {
+        self.heading = origin.heading;
+        self.speed = origin.speed;
+        self.accuracy = origin.accuracy;
+        self
+    }
+
+    pub fn with_edge_heading(mut self, edge_heading: Option<f64>) -> Self {
+        self.edge_heading = edge_heading;
+        self
+    }
 }
@@ -18,7 +18,11 @@
 //!
 //! The defaults — [`DefaultEmissionCost`] and [`DefaultTransitionCost`] —
 //! suit road-vehicle GPS traces; their documentation details the exact
-//! calculations and tunable hyperparameters.
+//! calculations and tunable hyperparameters. Where devices report their
+//! accuracy and heading alongside each fix, [`ObservedEmissionCost`] puts
+//! those to use in place of [`DefaultEmissionCost`]; where observations
+//! carry real timestamps, [`TimedTransitionCost`] rejects routes no vehicle
+//! could have driven in the time between them.
 //!
 //! ## Bringing your own heuristic
 //!
@@ -50,10 +54,12 @@
 //! The context tells you which cost you are implementing, and carries
 //! everything there is to know at that point:
 //!
-//! - [`EmissionContext`] — the input point, the candidate position, and the
-//!   distance between them.
+//! - [`EmissionContext`] — the input point, the candidate position, the
+//!   distance between them, and whatever heading, speed and accuracy the
+//!   observation reported.
 //! - [`TransitionContext`] — the two candidates, the optimal road path
-//!   between them, and its geometry.
+//!   between them, its geometry and speed limits, and the time between the
+//!   two observations.
 //!
 //! The higher-order traits ([`EmissionStrategy`], [`TransitionStrategy`]) are
 //! blanket-implemented for anything implementing [`Strategy`] over the right
@@ -64,7 +70,10 @@ mod emission;
 mod transition;
 mod util;
 
-pub use default::{CostingStrategies, DefaultEmissionCost, DefaultTransitionCost};
+pub use default::{
+    CostingStrategies, DefaultEmissionCost, DefaultTransitionCost, ObservedEmissionCost,
+    TimedTransitionCost,
+};
 pub use emission::{EmissionContext, EmissionStrategy};
 pub use transition::{
     Headings, TransitionContext, TransitionLengths, TransitionStrategy, VirtualTails,
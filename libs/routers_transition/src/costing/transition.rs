@@ -4,7 +4,7 @@ use crate::costing::Strategy;
 use crate::map_path::MapPath;
 use crate::primitives::{ResolutionMethod, RoutingContext};
 use geo::{Distance, Haversine, Point};
-use routers_network::{Entry, Network};
+use routers_network::{Entry, Metadata, Network};
 
 pub trait TransitionStrategy<E>: for<'a> Strategy<TransitionContext<'a, E>> {}
 impl<T, E> TransitionStrategy<E> for T where T: for<'a> Strategy<TransitionContext<'a, E>> {}
@@ -81,6 +81,14 @@ where
 
     /// Per-candidate virtual-tail distances.
     pub virtual_tails: VirtualTails,
+
+    /// The time, in seconds, between the source and target observations,
+    /// when both are known.
+    pub elapsed: Option<f64>,
+
+    /// The highest speed limit, in metres per second, along any edge the
+    /// transition travels; `None` when no edge carries one.
+    pub speed_limit: Option<f64>,
 }
 
 pub struct TransitionLengths {
@@ -156,6 +164,22 @@ where
             target: target.offset(ctx, VirtualTail::ToSource),
         };
 
+        let elapsed = ctx.elapsed(src.layer, trg.layer);
+
+        // Limits only matter against the time taken, so untimed
+        // transitions skip the lookups.
+        let speed_limit = elapsed.and_then(|_| {
+            let interior = map_path
+                .windows(2)
+                .filter_map(|pair| ctx.edge(&pair[0], &pair[1]));
+
+            [source.edge, target.edge]
+                .into_iter()
+                .chain(interior)
+                .filter_map(|edge| ctx.map.metadata(edge.id())?.permitted_speed(ctx.runtime))
+                .max_by(f64::total_cmp)
+        });
+
         Some(Self {
             optimal_path: MapPath::new_with_map(ctx.map, map_path),
             candidates: ctx.candidates,
@@ -170,6 +194,8 @@ where
             map_path,
             headings,
             virtual_tails,
+            elapsed,
+            speed_limit,
         })
     }
 
@@ -223,6 +249,14 @@ where
         }
     }
 
+    /// The speed, in metres per second, the vehicle must have averaged to
+    /// travel the route in the time between its observations. `None` when
+    /// the transition is untimed, or no time passed.
+    pub fn implied_speed(&self) -> Option<f64> {
+        let elapsed = self.elapsed.filter(|elapsed| *elapsed > 0.0)?;
+        Some(self.lengths()?.route_length / elapsed)
+    }
+
     /// Returns the [`TransitionLengths`] of the context.
     pub fn lengths(&self) -> Option<TransitionLengths> {
         let (source, target) = self.candidates();
@@ -1,9 +1,10 @@
-use crate::candidate::CandidateRef;
+use crate::Origin;
+use crate::candidate::{CandidateRef, bearing};
 use crate::costing::{EmissionContext, EmissionStrategy};
 use crate::r#match::DEFAULT_SEARCH_DISTANCE;
 use crate::{candidate::Candidate, layer::generation::LayerGeneration};
 use geo::{Distance, Haversine, Point};
-use routers_network::Network;
+use routers_network::{Edge, Network, Node, project};
 use routers_trellis::{LayerId, NodeId};
 
 /// The default candidate generator: a radius search projected onto nearby
@@ -54,6 +55,18 @@ where
         self.search_distance = search_distance;
         self
     }
+
+    /// The bearing of `edge` where `position` lies upon it: the bearing of
+    /// the stretch holding it when the edge has a shape.
+    fn heading(&self, edge: &Edge<Node<N::Entry>>, position: &Point) -> Option<f64> {
+        if self.map.shape(&edge.source.id, &edge.target.id).is_empty() {
+            return bearing(edge.source.position, edge.target.position);
+        }
+
+        let line = self.map.edge_line(&edge.source.id, &edge.target.id)?;
+        let stretch = line.lines().nth(project(&line, position)?.segment)?;
+        bearing(stretch.start_point(), stretch.end_point())
+    }
 }
 
 impl<Emmis, N> LayerGeneration<N::Entry> for StandardGenerator<'_, N, Emmis>
@@ -61,19 +74,28 @@ where
     N: Network + ?Sized,
     Emmis: EmissionStrategy + Send + Sync,
 {
-    fn candidates(&self, origin: &Point, layer: LayerId) -> Vec<Candidate<N::Entry>> {
+    fn candidates(&self, point: &Point, layer: LayerId) -> Vec<Candidate<N::Entry>> {
+        self.candidates_for(&Origin::new(*point, 0), layer)
+    }
+
+    fn candidates_for(&self, origin: &Origin, layer: LayerId) -> Vec<Candidate<N::Entry>> {
+        let point = &origin.point;
+
         self.map
-            .nearest_nodes_projected(origin, self.search_distance)
+            .nearest_nodes_projected(point, self.search_distance)
             .enumerate()
             .map(|(node, (position, edge))| {
                 let location = CandidateRef::new(layer, NodeId(node as u32));
-                let distance = Haversine.distance(position, *origin);
-                let emission = self.emission.cost(EmissionContext::new(
-                    &position,
-                    origin,
-                    distance,
-                    edge.weight,
-                ));
+                let distance = Haversine.distance(position, *point);
+
+                // The edge's bearing only matters against a reported heading.
+                let heading = origin.heading.and_then(|_| self.heading(&edge, &position));
+
+                let emission = self.emission.cost(
+                    EmissionContext::new(&position, point, distance, edge.weight)
+                        .with_observation(origin)
+                        .with_edge_heading(heading),
+                );
 
                 Candidate::new(edge.thin(), position, emission, location)
             })
@@ -17,6 +17,7 @@ use rayon::prelude::*;
 use routers_network::Entry;
 use routers_trellis::LayerId;
 
+use crate::Origin;
 use crate::candidate::Candidate;
 
 /// Produces the candidates anchoring each trajectory point.
@@ -29,6 +30,14 @@ pub trait LayerGeneration<E: Entry>: Send + Sync {
     /// order.
     fn candidates(&self, point: &Point, layer: LayerId) -> Vec<Candidate<E>>;
 
+    /// The candidates anchoring a whole observation as layer `layer`.
+    ///
+    /// Generators that price the heading, speed or accuracy an [`Origin`]
+    /// may carry override this; by default only its point is considered.
+    fn candidates_for(&self, origin: &Origin, layer: LayerId) -> Vec<Candidate<E>> {
+        self.candidates(&origin.point, layer)
+    }
+
     /// Generates all candidates, one set per input point, starting from `first_layer`.
     fn generate(&self, input: &[Point], first_layer: LayerId) -> Vec<Vec<Candidate<E>>> {
         input
@@ -38,6 +47,18 @@ pub trait LayerGeneration<E: Entry>: Send + Sync {
             .collect()
     }
 
+    /// Generates all candidates, one set per observation, starting from
+    /// `first_layer`.
+    fn generate_for(&self, input: &[Origin], first_layer: LayerId) -> Vec<Vec<Candidate<E>>> {
+        input
+            .into_par_iter()
+            .enumerate()
+            .map(|(offset, origin)| {
+                self.candidates_for(origin, LayerId(first_layer.0 + offset as u32))
+            })
+            .collect()
+    }
+
     /// Generates the candidates for all input points, starting from the first layer.
     fn generate_all(&self, input: &[Point]) -> Vec<Vec<Candidate<E>>> {
         self.generate(input, LayerId::first())
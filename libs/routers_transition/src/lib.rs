@@ -37,9 +37,9 @@
 extern crate alloc;
 
 #[doc(inline)]
-pub use r#match::{MatchOptions, MatchSimpleExt};
+pub use r#match::{MatchOptions, MatchSimpleExt, RoutedSegments};
 #[doc(inline)]
-pub use matcher::{Continuation, Matcher, Origin};
+pub use matcher::{Breakage, Continuation, Lag, Matcher, Origin};
 #[doc(inline)]
 pub use primitives::MatchError;
 
@@ -51,6 +51,8 @@ pub mod costing;
 pub mod layer;
 pub mod matcher;
 pub mod primitives;
+#[cfg(feature = "trace")]
+pub mod trace;
 pub mod weigh;
 
 mod map_path;
@@ -45,15 +45,24 @@ where
 
     // TODO: This should be done lazily, since we may not need the points but possibly OK as is.
     /// Creates a new path from a slice of node ids, and a map to look up their locations.
+    ///
+    /// Where an edge between consecutive nodes has a
+    /// [shape](routers_network::DataPlane::shape), its points are included so
+    /// the path follows the road. They carry the id of the node they follow.
     pub fn new_with_map<N: Network<Entry = E> + ?Sized>(map: &N, nodes: &[E]) -> Self {
         let resolved = map.line(nodes);
 
-        let nodes = resolved
-            .into_iter()
-            .zip(nodes)
-            .map(|(point, id)| Node::new(point, *id));
+        let mut path = Vec::with_capacity(resolved.len());
+        for (index, (point, id)) in resolved.into_iter().zip(nodes).enumerate() {
+            path.push(Node::new(point, *id));
+
+            if let Some(next) = nodes.get(index + 1) {
+                let shape = map.shape(id, next);
+                path.extend(shape.iter().map(|&point| Node::new(point, *id)));
+            }
+        }
 
-        MapPath::new(nodes)
+        MapPath(path)
     }
 
     /// Computes the angle between each pair of nodes in the trip.
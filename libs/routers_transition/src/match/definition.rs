@@ -1,16 +1,21 @@
 use alloc::sync::Arc;
 
 use geo::LineString;
-use routers_network::{Metadata, Network};
+use routers_network::{DataPlane, Metadata, Network};
 
 use crate::{
-    candidate::RoutedPath,
+    candidate::{RoutedPath, Segments},
+    matcher::{Breakage, Origin},
     primitives::{MatchError, PredicateCache},
     weigh::SolverVariant,
 };
 
 pub const DEFAULT_SEARCH_DISTANCE: f64 = 50.0; // 50m
 
+/// One perfect emission's worth of cost: a candidate this much cheaper than
+/// its rival is `e` times likelier.
+pub const DEFAULT_TEMPERATURE: f64 = 100.0;
+
 /// Configuration for a facade [`Match`] call.
 ///
 /// Every option has a default suitable for road-vehicle GPS traces, so
@@ -69,6 +74,42 @@ pub struct MatchOptions<N: Network> {
     /// positions, so `PredicateCache::with_reach_distance` is how you change
     /// it.
     pub cache: Option<Arc<PredicateCache<N>>>,
+
+    /// Whether a trajectory which breaks — a boundary no route bridges — is
+    /// split into independently matched segments rather than failing with
+    /// [`MatchError::Disconnected`].
+    ///
+    /// Honoured by [`Match::r#match_segments`]; [`Match::r#match`] always
+    /// yields a single path, so it fails at a break regardless.
+    pub split: bool,
+
+    /// Where the trajectory is broken into independently matched pieces
+    /// before solving — by straight-line distance or time between
+    /// consecutive positions. See [`Breakage`].
+    ///
+    /// Honoured by [`Match::r#match_segments`] and [`Match::snap_segments`],
+    /// which report every piece. Nothing breaks by default.
+    pub breakage: Breakage,
+
+    /// How many runner-up matches [`Match::r#match`] reports alongside the
+    /// best, in [`RoutedPath::alternatives`]. Each costs another
+    /// collapse; none are found by default.
+    pub alternatives: usize,
+
+    /// Whether each point of [`RoutedPath::discretized`] carries its
+    /// confidence, at the cost of a forward-backward pass per match. See
+    /// [`Matcher::with_confidence`](crate::Matcher::with_confidence).
+    ///
+    /// Disabled by default.
+    pub confidence: bool,
+
+    /// The temperature at which path costs become the per-point confidence,
+    /// when [`confidence`](Self::confidence) is enabled. Lower values trust
+    /// the cheapest match more readily; see
+    /// [`Matcher::with_temperature`](crate::Matcher::with_temperature).
+    ///
+    /// The default value is [DEFAULT_TEMPERATURE].
+    pub temperature: f64,
 }
 
 impl<N: Network> Default for MatchOptions<N> {
@@ -78,6 +119,11 @@ impl<N: Network> Default for MatchOptions<N> {
             runtime: <N::Meta>::default_runtime(),
             solver: SolverVariant::default(),
             cache: None,
+            split: false,
+            breakage: Breakage::default(),
+            alternatives: 0,
+            confidence: false,
+            temperature: DEFAULT_TEMPERATURE,
         }
     }
 }
@@ -105,6 +151,32 @@ impl<N: Network> MatchOptions<N> {
         }
     }
 
+    pub fn with_split(self, split: bool) -> Self {
+        Self { split, ..self }
+    }
+
+    pub fn with_breakage(self, breakage: Breakage) -> Self {
+        Self { breakage, ..self }
+    }
+
+    pub fn with_alternatives(self, alternatives: usize) -> Self {
+        Self {
+            alternatives,
+            ..self
+        }
+    }
+
+    pub fn with_confidence(self, confidence: bool) -> Self {
+        Self { confidence, ..self }
+    }
+
+    pub fn with_temperature(self, temperature: f64) -> Self {
+        Self {
+            temperature,
+            ..self
+        }
+    }
+
     pub fn with_search_distance(self, search_distance: Option<f64>) -> Self {
         Self {
             search_distance: search_distance.unwrap_or(self.search_distance),
@@ -113,6 +185,10 @@ impl<N: Network> MatchOptions<N> {
     }
 }
 
+/// The [`Segments`] a trajectory is matched in, each resolved against the
+/// network, as the segment-reporting [`Match`] methods return them.
+pub type RoutedSegments<N> = Segments<RoutedPath<<N as DataPlane>::Entry, <N as DataPlane>::Meta>>;
+
 /// For matching a trajectory without assembling a
 /// [`Matcher`](crate::Matcher) yourself, use this facade — it is implemented
 /// for every [`Network`](routers_network::Network).
@@ -127,22 +203,69 @@ where
 {
     /// Matches a given [linestring](LineString) against the map, collapsing
     /// the input onto the network to find the most plausible match for every
-    /// input position.
+    /// input position. With [`MatchOptions::alternatives`] set, the
+    /// runners-up ride along in [`RoutedPath::alternatives`].
     fn r#match(
         &self,
         linestring: LineString,
         opts: MatchOptions<N>,
     ) -> Result<RoutedPath<N::Entry, N::Meta>, MatchError>;
 
+    /// Matches a given [linestring](LineString) against the map as
+    /// [`r#match`](Self::r#match) does, reporting one [`Segment`] per
+    /// independently matched stretch of the input.
+    ///
+    /// The input is first broken into pieces by [`MatchOptions::breakage`],
+    /// each matched on its own. With [`MatchOptions::split`] set, a piece is
+    /// further cut wherever the route breaks. Otherwise a break fails the
+    /// piece, and an unbroken input is a single segment.
+    ///
+    /// A piece which fails is reported as a segment holding its error, whose
+    /// layers count from the start of the input, so the pieces around it
+    /// are still matched. Only an empty input fails outright.
+    ///
+    /// [`Segment`]: crate::candidate::Segment
+    fn r#match_segments(
+        &self,
+        linestring: LineString,
+        opts: MatchOptions<N>,
+    ) -> Result<RoutedSegments<N>, MatchError>;
+
+    /// Matches a batch of timed observations as
+    /// [`r#match_segments`](Self::r#match_segments) does a linestring.
+    ///
+    /// Unlike a linestring, each [`Origin`] carries when it was observed
+    /// (and optionally its heading, speed and accuracy), so the costing sees
+    /// real elapsed times and a [`Breakage`] interval can break the input.
+    fn match_origins(
+        &self,
+        origins: &[Origin],
+        opts: MatchOptions<N>,
+    ) -> Result<RoutedSegments<N>, MatchError>;
+
     /// Snaps a given linestring against the map: each position moved to its
     /// most plausible road position, without routing between them.
     ///
-    /// Not yet implemented.
+    /// Candidates are priced by their emission cost plus a cheap continuity
+    /// term between consecutive positions, so a snap is far cheaper than a
+    /// match. Both views of the result hold one element per input position.
     fn snap(
         &self,
         linestring: LineString,
         opts: MatchOptions<N>,
     ) -> Result<RoutedPath<N::Entry, N::Meta>, MatchError>;
+
+    /// Snaps a given linestring against the map as [`snap`](Self::snap)
+    /// does, reporting one [`Segment`] per piece the input is broken into by
+    /// [`MatchOptions::breakage`]. A piece which fails is a segment holding
+    /// its error, as in [`r#match_segments`](Self::r#match_segments).
+    ///
+    /// [`Segment`]: crate::candidate::Segment
+    fn snap_segments(
+        &self,
+        linestring: LineString,
+        opts: MatchOptions<N>,
+    ) -> Result<RoutedSegments<N>, MatchError>;
 }
 
 /// Simplifies the interface to the `Match` trait, providing methods that uses appropriate options.
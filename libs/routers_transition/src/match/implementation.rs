@@ -1,13 +1,14 @@
 use crate::candidate::RoutedPath;
 use crate::costing::CostingStrategies;
 use crate::layer::generation::StandardGenerator;
-use crate::r#match::{Match, MatchOptions};
-use crate::matcher::Matcher;
+use crate::r#match::{Match, MatchOptions, RoutedSegments};
+use crate::matcher::{Matcher, Origin};
 use crate::primitives::MatchError;
 
 use geo::LineString;
 use log::info;
 use routers_network::Network;
+use routers_trellis::SolveError;
 
 #[cfg(feature = "tracing")]
 use tracing::Level;
@@ -30,17 +31,131 @@ where
 
         let weigher = opts.solver.instance(opts.cache.unwrap_or_default());
 
-        Matcher::new(self, &costing, generator, weigher, &opts.runtime)
-            .r#match(linestring)
-            .map(|collapsed| RoutedPath::new(collapsed, self))
+        let matcher = Matcher::new(self, &costing, generator, weigher, &opts.runtime)
+            .with_confidence(opts.confidence)
+            .with_temperature(opts.temperature);
+        if opts.alternatives == 0 {
+            return matcher
+                .r#match(linestring)
+                .map(|collapsed| RoutedPath::new(collapsed, self));
+        }
+
+        let ranked = matcher.r#match_alternatives(linestring, opts.alternatives + 1)?;
+        RoutedPath::ranked(ranked, self).ok_or(SolveError::Unreachable.into())
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, level = Level::INFO))]
+    fn r#match_segments(
+        &self,
+        linestring: LineString,
+        opts: MatchOptions<T>,
+    ) -> Result<RoutedSegments<T>, MatchError> {
+        info!(
+            "Finding matched segments for {} positions",
+            linestring.0.len()
+        );
+
+        let costing = CostingStrategies::default();
+        let generator = StandardGenerator::new(self, &costing.emission)
+            .with_search_distance(opts.search_distance);
+
+        let weigher = opts.solver.instance(opts.cache.unwrap_or_default());
+
+        let segments = Matcher::new(self, &costing, generator, weigher, &opts.runtime)
+            .with_confidence(opts.confidence)
+            .with_temperature(opts.temperature)
+            .with_split(opts.split)
+            .with_breakage(opts.breakage)
+            .r#match_segments(linestring)?;
+
+        Ok(segments
+            .into_iter()
+            .map(|segment| {
+                segment.map(|path| path.map(|collapsed| RoutedPath::new(collapsed, self)))
+            })
+            .collect())
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, level = Level::INFO))]
+    fn match_origins(
+        &self,
+        origins: &[Origin],
+        opts: MatchOptions<T>,
+    ) -> Result<RoutedSegments<T>, MatchError> {
+        info!(
+            "Finding matched segments for {} observations",
+            origins.len()
+        );
+
+        let costing = CostingStrategies::default();
+        let generator = StandardGenerator::new(self, &costing.emission)
+            .with_search_distance(opts.search_distance);
+
+        let weigher = opts.solver.instance(opts.cache.unwrap_or_default());
+
+        let segments = Matcher::new(self, &costing, generator, weigher, &opts.runtime)
+            .with_confidence(opts.confidence)
+            .with_temperature(opts.temperature)
+            .with_split(opts.split)
+            .with_breakage(opts.breakage)
+            .match_origins(origins)?;
+
+        Ok(segments
+            .into_iter()
+            .map(|segment| {
+                segment.map(|path| path.map(|collapsed| RoutedPath::new(collapsed, self)))
+            })
+            .collect())
     }
 
     #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, level = Level::INFO))]
     fn snap(
         &self,
-        _linestring: LineString,
-        _opts: MatchOptions<T>,
+        linestring: LineString,
+        opts: MatchOptions<T>,
     ) -> Result<RoutedPath<T::Entry, T::Meta>, MatchError> {
-        unimplemented!()
+        info!("Snapping {} positions", linestring.0.len());
+
+        let costing = CostingStrategies::default();
+        let generator = StandardGenerator::new(self, &costing.emission)
+            .with_search_distance(opts.search_distance);
+
+        // Snapping never routes, so the weigher is only carried to satisfy
+        // the matcher; its cache is never queried.
+        let weigher = opts.solver.instance(opts.cache.unwrap_or_default());
+
+        Matcher::new(self, &costing, generator, weigher, &opts.runtime)
+            .with_confidence(opts.confidence)
+            .with_temperature(opts.temperature)
+            .snap(linestring)
+            .map(|collapsed| RoutedPath::snapped(collapsed, self))
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, level = Level::INFO))]
+    fn snap_segments(
+        &self,
+        linestring: LineString,
+        opts: MatchOptions<T>,
+    ) -> Result<RoutedSegments<T>, MatchError> {
+        info!("Snapping segments of {} positions", linestring.0.len());
+
+        let costing = CostingStrategies::default();
+        let generator = StandardGenerator::new(self, &costing.emission)
+            .with_search_distance(opts.search_distance);
+
+        let weigher = opts.solver.instance(opts.cache.unwrap_or_default());
+
+        let segments = Matcher::new(self, &costing, generator, weigher, &opts.runtime)
+            .with_confidence(opts.confidence)
+            .with_temperature(opts.temperature)
+            .with_breakage(opts.breakage)
+            .snap_segments(linestring)?;
+
+        Ok(segments
+            .into_iter()
+            .map(|segment| {
+                segment.map(|path| path.map(|collapsed| RoutedPath::snapped(collapsed, self)))
+            })
+            .collect())
     }
 }
@@ -0,0 +1,135 @@
+use core::ops::Range;
+use core::time::Duration;
+
+use geo::{Distance, Haversine};
+use uom::si::f64::Length;
+use uom::si::length::meter;
+
+use crate::matcher::Origin;
+
+/// Where a trajectory is broken into independently matched pieces *before*
+/// solving, so one outlier jump cannot poison the solve for the whole trace.
+///
+/// Two consecutive [`Origin`]s further apart than [`distance`](Self::distance)
+/// (straight-line), or observed further apart than
+/// [`interval`](Self::interval), start a new piece. Either may be unset, and
+/// the default sets neither: the trajectory is never broken.
+///
+/// ```ignore
+/// let breakage = Breakage::new()
+///     .with_distance(Length::new::<meter>(500.0))
+///     .with_interval(Duration::from_secs(120));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Breakage {
+    /// The straight-line distance between consecutive positions above which
+    /// the trajectory breaks.
+    pub distance: Option<Length>,
+
+    /// The time between consecutive observations above which the trajectory
+    /// breaks, read from [`Origin::timestamp`].
+    pub interval: Option<Duration>,
+}
+
+impl Breakage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_distance(self, distance: Length) -> Self {
+        Self {
+            distance: Some(distance),
+            ..self
+        }
+    }
+
+    pub fn with_interval(self, interval: Duration) -> Self {
+        Self {
+            interval: Some(interval),
+            ..self
+        }
+    }
+
+    /// Whether the trajectory breaks between `from` and the next observation
+    /// `to`.
+    pub fn breaks(&self, from: &Origin, to: &Origin) -> bool {
+        let far = self.distance.is_some_and(|distance| {
+            Haversine.distance(from.point, to.point) > distance.get::<meter>()
+        });
+
+        let late = self.interval.is_some_and(|interval| {
+            let gap = to.timestamp.saturating_sub(from.timestamp).unsigned_abs();
+            gap > interval.as_micros() as u64
+        });
+
+        far || late
+    }
+
+    /// The pieces `origins` breaks into, as contiguous index ranges which
+    /// together cover every origin in order. Empty input has no pieces.
+    pub fn pieces(&self, origins: &[Origin]) -> Vec<Range<usize>> {
+        if origins.is_empty() {
+            return Vec::new();
+        }
+
+        let mut pieces = Vec::new();
+        let mut start = 0;
+        for (index, pair) in origins.windows(2).enumerate() {
+            if self.breaks(&pair[0], &pair[1]) {
+                pieces.push(start..index + 1);
+                start = index + 1;
+            }
+        }
+        pieces.push(start..origins.len());
+
+        pieces
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo::point;
+
+    fn origin(x: f64, seconds: i64) -> Origin {
+        Origin::new(point!(x: x, y: 0.0), seconds * 1_000_000)
+    }
+
+    #[test]
+    fn unset_breakage_keeps_one_piece() {
+        let origins = [origin(0.0, 0), origin(10.0, 1), origin(20.0, 2)];
+        assert_eq!(Breakage::new().pieces(&origins), vec![0..3]);
+    }
+
+    #[test]
+    fn breaks_on_distance() {
+        // 0.001° of longitude at the equator is ~111 m.
+        let origins = [
+            origin(0.0, 0),
+            origin(0.001, 1),
+            origin(0.1, 2),
+            origin(0.101, 3),
+        ];
+        let breakage = Breakage::new().with_distance(Length::new::<meter>(500.0));
+
+        assert_eq!(breakage.pieces(&origins), vec![0..2, 2..4]);
+    }
+
+    #[test]
+    fn breaks_on_interval() {
+        let origins = [
+            origin(0.0, 0),
+            origin(0.0, 5),
+            origin(0.0, 600),
+            origin(0.0, 605),
+        ];
+        let breakage = Breakage::new().with_interval(Duration::from_secs(60));
+
+        assert_eq!(breakage.pieces(&origins), vec![0..2, 2..4]);
+    }
+
+    #[test]
+    fn empty_input_has_no_pieces() {
+        assert!(Breakage::new().pieces(&[]).is_empty());
+    }
+}
@@ -1,14 +1,20 @@
 use alloc::borrow::Cow;
+use core::iter;
 
 use geo::LineString;
+use itertools::Itertools;
 use routers_network::{Entry, Network};
-use routers_trellis::{LayerId, Path, SolveError, TrellisError, ViterbiSolver};
+use routers_trellis::{
+    ForwardBackward, KBestSolver, LayerId, Path, Posteriors, SolveBest, SolveError, TrellisError,
+    ViterbiSolver,
+};
 
-use crate::candidate::{CandidateRef, CollapsedPath};
+use crate::candidate::{CandidateRef, CollapsedPath, Segment, Segments};
 use crate::costing::{CostingStrategies, EmissionStrategy, TransitionStrategy};
 use crate::layer::generation::LayerGeneration;
+use crate::r#match::DEFAULT_TEMPERATURE;
 use crate::matcher::trip::TripState;
-use crate::matcher::{Origin, Trip};
+use crate::matcher::{Breakage, Lag, Origin, Trip};
 use crate::primitives::{
     Disconnected, DisconnectedError, MatchError, Reachable, RoutingContext, Unanchored,
     UnanchoredError,
@@ -69,6 +75,25 @@ use crate::weigh::{Weigher, frontier_collapse};
 /// // given any solved point, you can obtain this information by using `snapshot(..)`.
 /// let collapsed = matcher.snapshot(&mut trip)?;
 /// ```
+///
+/// A stream which never ends needs its trip cut as it goes. [`commit`](Self::commit)
+/// fixes every layer a later solve can no longer change — or, with a
+/// [`Lag`](Self::with_lag), any layer old enough — after which the layers
+/// before the commit point may be dropped with [`Trip::tail`].
+///
+/// ```ignore
+/// let matcher = matcher.with_lag(Lag::new().with_layers(10));
+///
+/// for point in stream {
+///     matcher.push(&mut trip, point)?;
+///     let collapsed = matcher.snapshot(&mut trip)?;
+///
+///     if let Some(point) = matcher.commit(&mut trip)? {
+///         // Layers `..trip.committed()` are final; emit them, then cut.
+///         trip.tail(trip.layers() - point.index());
+///     }
+/// }
+/// ```
 pub struct Matcher<'a, Emmis, Trans, G, W, N>
 where
     N: Network,
@@ -82,6 +107,11 @@ where
     generator: G,
     weigher: W,
     runtime: &'a N::Runtime,
+    split: bool,
+    pub(super) score: bool,
+    pub(super) confidence: ForwardBackward,
+    pub(super) breakage: Breakage,
+    lag: Lag,
 }
 
 /// The store-independent parts of a [`CollapsedPath`], as derived by
@@ -92,6 +122,7 @@ where
 {
     cost: u32,
     route: Vec<CandidateRef>,
+    confidence: Vec<f64>,
     interpolated: Vec<Reachable<E>>,
 }
 
@@ -116,6 +147,58 @@ where
             generator,
             weigher,
             runtime,
+            split: false,
+            score: false,
+            confidence: ForwardBackward::new().with_temperature(DEFAULT_TEMPERATURE),
+            breakage: Breakage::default(),
+            lag: Lag::default(),
+        }
+    }
+
+    /// Break observations into independently matched pieces wherever
+    /// `breakage` says consecutive ones are too far apart. Applies to
+    /// [`match_origins`](Self::match_origins) and
+    /// [`snap_origins`](Self::snap_origins), before any solving; by default
+    /// nothing breaks.
+    pub fn with_breakage(self, breakage: Breakage) -> Self {
+        Self { breakage, ..self }
+    }
+
+    /// Commit streaming choices at most `lag` behind the newest observation,
+    /// whether or not the paths have converged. See [`commit`](Self::commit);
+    /// by default only convergence commits.
+    pub fn with_lag(self, lag: Lag) -> Self {
+        Self { lag, ..self }
+    }
+
+    /// Enable or disable split mode, in which [`r#match_segments`] cuts a
+    /// trajectory at its breaks rather than failing with
+    /// [`DisconnectedError`]. Disabled by default.
+    ///
+    /// [`r#match_segments`]: Self::r#match_segments
+    pub fn with_split(self, split: bool) -> Self {
+        Self { split, ..self }
+    }
+
+    /// Enable or disable scoring each matched point's
+    /// [`confidence`](CollapsedPath::confidence), which takes a
+    /// forward-backward pass over the trellis on top of the solve. Disabled
+    /// by default, leaving the confidence empty.
+    pub fn with_confidence(self, score: bool) -> Self {
+        Self { score, ..self }
+    }
+
+    /// The temperature at which path costs are turned into each matched
+    /// point's [`confidence`](CollapsedPath::confidence), once
+    /// [enabled](Self::with_confidence): the cost margin, in trellis weight
+    /// units, over which a candidate becomes `e` times likelier than its
+    /// rival. Defaults to 100, one perfect emission's worth of cost.
+    ///
+    /// Panics unless `temperature` is positive and finite.
+    pub fn with_temperature(self, temperature: f64) -> Self {
+        Self {
+            confidence: self.confidence.with_temperature(temperature),
+            ..self
         }
     }
 
@@ -128,6 +211,7 @@ where
     fn context<'b>(&'b self, trip: &'b Trip<N::Entry>) -> RoutingContext<'b, N> {
         RoutingContext {
             candidates: trip.candidates(),
+            origins: trip.origins(),
             map: self.map,
             runtime: self.runtime,
         }
@@ -142,7 +226,7 @@ where
     /// so the caller may drop or retry it.
     pub fn push(&self, trip: &mut Trip<N::Entry>, origin: Origin) -> Result<LayerId, MatchError> {
         let layer = trip.next_id();
-        let candidates = self.generator.candidates(&origin.point, layer);
+        let candidates = self.generator.candidates_for(&origin, layer);
 
         if candidates.is_empty() {
             return Err(UnanchoredError {
@@ -166,20 +250,16 @@ where
     pub fn extend(&self, trip: &mut Trip<N::Entry>, origins: &[Origin]) -> Result<(), MatchError> {
         let first_layer = trip.next_id();
 
-        let points = origins
-            .iter()
-            .map(|origin| origin.point)
-            .collect::<Vec<_>>();
-        let per_layer = self.generator.generate(&points, first_layer);
+        let per_layer = self.generator.generate_for(origins, first_layer);
 
         let unanchored = per_layer
             .iter()
-            .zip(&points)
+            .zip(origins)
             .enumerate()
             .filter(|(_, (candidates, _))| candidates.is_empty())
-            .map(|(offset, (_, &origin))| Unanchored {
+            .map(|(offset, (_, origin))| Unanchored {
                 layer: first_layer.index() + offset,
-                origin,
+                origin: origin.point,
             })
             .collect::<Vec<_>>();
         if !unanchored.is_empty() {
@@ -273,6 +353,47 @@ where
         Ok(ViterbiSolver::new().convergence(trellis)?)
     }
 
+    /// The latest layer whose choice is final: the later of the trip's
+    /// [convergence](Self::convergence) point and the matcher's
+    /// [lag](Self::with_lag) bound. `None` while neither reaches any layer.
+    pub fn commit_point(&self, trip: &Trip<N::Entry>) -> Result<Option<LayerId>, MatchError> {
+        let converged = self.convergence(trip)?;
+        Ok(converged.max(self.lag.bound(trip.origins())))
+    }
+
+    /// Solve (if pending) and commit every layer through the
+    /// [commit point](Self::commit_point) to its chosen candidate, returning
+    /// the point.
+    ///
+    /// Committed layers keep their choice whatever arrives later: the trip
+    /// narrows each to that one candidate, so every future path runs through
+    /// it. Layers `..trip.committed()` are final and the rest provisional,
+    /// so a consumer waits no longer than the lag for a final answer.
+    pub fn commit(&self, trip: &mut Trip<N::Entry>) -> Result<Option<LayerId>, MatchError> {
+        self.solve(trip)?;
+
+        let Some(point) = self.commit_point(trip)? else {
+            return Ok(None);
+        };
+
+        // Narrowing along the solved path leaves it optimal: the re-solve
+        // only re-certifies it, weighing nothing.
+        trip.commit(point)?;
+        self.solve(trip)?;
+
+        Ok(Some(point))
+    }
+
+    /// Solve (if pending) and find every candidate's posterior probability
+    /// of being the one matched, at the matcher's
+    /// [temperature](Self::with_temperature).
+    pub fn posteriors(&self, trip: &mut Trip<N::Entry>) -> Result<Posteriors, MatchError> {
+        self.solve(trip)?;
+
+        let trellis = trip.trellis().expect("solved trip has a trellis");
+        Ok(self.confidence.posteriors(trellis)?)
+    }
+
     /// Solve (if pending) and collapse the trip's current solution into a
     /// [`CollapsedPath`], re-deriving each chosen hop's routed geometry from
     /// the (warm) predicate cache — nothing is stored during weighing.
@@ -287,17 +408,78 @@ where
         let Collapse {
             cost,
             route,
+            confidence,
             interpolated,
         } = self.collapse(trip)?;
 
         Ok(CollapsedPath {
             cost,
             route,
+            confidence,
             interpolated,
             candidates: Cow::Borrowed(trip.candidates()),
         })
     }
 
+    /// Solve (if pending) and collapse up to `k` distinct matches of the
+    /// trip, cheapest first — the first is the one
+    /// [`snapshot`](Self::snapshot) gives. How close the runners-up come to
+    /// it is a measure of how sure the match is.
+    ///
+    /// Fewer than `k` are returned only when fewer routes exist. Each is
+    /// found by [`KBestSolver`], at `k` times the cost of a solve.
+    pub fn alternatives<'t>(
+        &self,
+        trip: &'t mut Trip<N::Entry>,
+        k: usize,
+    ) -> Result<Vec<CollapsedPath<'t, N::Entry>>, MatchError> {
+        let posteriors = self.scores(trip)?;
+
+        let trellis = trip.trellis().expect("solved trip has a trellis");
+        let paths = KBestSolver::new().solve_best(trellis, k)?;
+
+        let trip = &*trip;
+        Ok(paths
+            .iter()
+            .map(|path| {
+                let Collapse {
+                    cost,
+                    route,
+                    confidence,
+                    interpolated,
+                } = self.collapse_path(trip, path, posteriors.as_ref());
+
+                CollapsedPath {
+                    cost,
+                    route,
+                    confidence,
+                    interpolated,
+                    candidates: Cow::Borrowed(trip.candidates()),
+                }
+            })
+            .collect())
+    }
+
+    /// Match a whole trajectory in one call, as [`r#match`] does, returning
+    /// up to `k` distinct matches, cheapest first. See
+    /// [`alternatives`](Self::alternatives).
+    ///
+    /// [`r#match`]: Self::r#match
+    pub fn r#match_alternatives(
+        &self,
+        linestring: LineString,
+        k: usize,
+    ) -> Result<Vec<CollapsedPath<'a, N::Entry>>, MatchError> {
+        let mut trip = self.begin();
+        self.extend(&mut trip, &indexed(linestring))?;
+
+        Ok(self
+            .alternatives(&mut trip, k)?
+            .into_iter()
+            .map(CollapsedPath::into_owned)
+            .collect())
+    }
+
     /// Match a whole trajectory in one call: batch candidate generation,
     /// parallel weighing, solve, and collapse. The trip is internal here, so
     /// the result owns its candidates.
@@ -306,20 +488,112 @@ where
         linestring: LineString,
     ) -> Result<CollapsedPath<'a, N::Entry>, MatchError> {
         let mut trip = self.begin();
+        self.extend(&mut trip, &indexed(linestring))?;
 
-        // A bare linestring carries no observation times; indices stand in.
-        // Order is the only property the batch lifecycle reads from them.
-        let origins = linestring
-            .into_points()
-            .into_iter()
-            .enumerate()
-            .map(|(index, point)| Origin::new(point, index as i64))
-            .collect::<Vec<_>>();
-        self.extend(&mut trip, &origins)?;
+        self.finish(trip)
+    }
+
+    /// Match a whole trajectory in one call, as [`r#match`] does, reporting
+    /// the result as [`Segment`]s of the input.
+    ///
+    /// See [`match_origins`](Self::match_origins), which this forwards to;
+    /// a bare linestring carries no times, so only a [`Breakage`] distance
+    /// can break it.
+    ///
+    /// [`r#match`]: Self::r#match
+    pub fn r#match_segments(
+        &self,
+        linestring: LineString,
+    ) -> Result<Segments<CollapsedPath<'a, N::Entry>>, MatchError> {
+        self.match_origins(&indexed(linestring))
+    }
+
+    /// Match a batch of observations, reporting the result as [`Segment`]s of
+    /// the input.
+    ///
+    /// The observations are first broken into pieces by the matcher's
+    /// [`Breakage`] (see [`with_breakage`](Self::with_breakage)), and each
+    /// piece is matched independently. Then, in
+    /// [split mode](Self::with_split), a piece which breaks is cut at every
+    /// broken boundary and each stretch is solved on its own, so one gap no
+    /// longer discards the whole trace. The weighing done before the break was
+    /// found is kept: each stretch only re-runs the DP pass. Without split
+    /// mode a break fails the piece exactly as in [`r#match`].
+    ///
+    /// A piece which fails is reported as one segment holding its error, its
+    /// layers counted from the start of the input, so the pieces which
+    /// matched are kept. Only an empty input fails outright.
+    ///
+    /// [`r#match`]: Self::r#match
+    pub fn match_origins(
+        &self,
+        origins: &[Origin],
+    ) -> Result<Segments<CollapsedPath<'a, N::Entry>>, MatchError> {
+        if origins.is_empty() {
+            return Err(TrellisError::Empty.into());
+        }
+
+        let mut segments = Vec::new();
+        for piece in self.breakage.pieces(origins) {
+            let offset = piece.start;
+            match self.match_piece(&origins[piece.clone()]) {
+                Ok(matched) => segments.extend(matched.into_iter().map(|segment| Segment {
+                    layers: segment.layers.start + offset..segment.layers.end + offset,
+                    path: Ok(segment.path),
+                })),
+                Err(error) => segments.push(Segment {
+                    layers: piece,
+                    path: Err(error.offset(offset)),
+                }),
+            }
+        }
 
+        Ok(segments)
+    }
+
+    /// Match one unbroken piece, splitting it at its breaks in split mode.
+    fn match_piece(
+        &self,
+        origins: &[Origin],
+    ) -> Result<Vec<Segment<CollapsedPath<'a, N::Entry>>>, MatchError> {
+        let mut trip = self.begin();
+        self.extend(&mut trip, origins)?;
+
+        let breaks = match self.solve(&mut trip) {
+            Ok(_) => {
+                let layers = 0..trip.layers();
+                return Ok(vec![Segment {
+                    layers,
+                    path: self.finish(trip)?,
+                }]);
+            }
+            Err(MatchError::Disconnected(error)) if self.split => error.breaks,
+            Err(error) => return Err(error),
+        };
+
+        let bounds = iter::once(0)
+            .chain(breaks.iter().map(|gap| gap.to_layer))
+            .chain(iter::once(trip.layers()));
+
+        bounds
+            .tuple_windows()
+            .map(|(start, end)| {
+                let segment = trip.partition(LayerId(start as u32)..LayerId(end as u32))?;
+                Ok(Segment {
+                    layers: start..end,
+                    path: self.finish(segment)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Solve (if pending) and collapse a batch trip, consuming it so the
+    /// result owns its candidates.
+    fn finish(&self, mut trip: Trip<N::Entry>) -> Result<CollapsedPath<'a, N::Entry>, MatchError> {
         let Collapse {
             cost,
             route,
+            confidence,
             interpolated,
         } = self.collapse(&mut trip)?;
         let (candidates, _) = trip.into_parts();
@@ -327,18 +601,43 @@ where
         Ok(CollapsedPath {
             cost,
             route,
+            confidence,
             interpolated,
             candidates: Cow::Owned(candidates),
         })
     }
 
     /// Solve (if pending) and derive the collapse: total cost, the chosen
-    /// candidate per layer, and each hop's routed geometry.
+    /// candidate per layer and, if scored, its confidence, and each hop's
+    /// routed geometry.
     fn collapse(&self, trip: &mut Trip<N::Entry>) -> Result<Collapse<N::Entry>, MatchError> {
-        let path = self.solve(trip)?;
+        let posteriors = self.scores(trip)?;
+        let path = trip.path().expect("solved trip has a path").clone();
+        Ok(self.collapse_path(trip, &path, posteriors.as_ref()))
+    }
+
+    /// Solve (if pending), and find the [`posteriors`](Self::posteriors)
+    /// only if the matcher [scores confidence](Self::with_confidence).
+    fn scores(&self, trip: &mut Trip<N::Entry>) -> Result<Option<Posteriors>, MatchError> {
+        if !self.score {
+            self.solve(trip)?;
+            return Ok(None);
+        }
+
+        self.posteriors(trip).map(Some)
+    }
+
+    /// Derive the collapse of any one path through the trip's trellis.
+    fn collapse_path(
+        &self,
+        trip: &Trip<N::Entry>,
+        path: &Path,
+        posteriors: Option<&Posteriors>,
+    ) -> Collapse<N::Entry> {
         let cost = path.cost;
 
         let route = self.route_of(path);
+        let confidence = posteriors.map_or_else(Vec::new, |posteriors| posteriors.along(path));
         let interpolated = {
             let ctx = self.context(trip);
             route
@@ -350,11 +649,12 @@ where
                 .collect::<Vec<_>>()
         };
 
-        Ok(Collapse {
+        Collapse {
             cost,
             route,
+            confidence,
             interpolated,
-        })
+        }
     }
 
     /// Re-derive the routed geometry of a single hop — for realtime consumers
@@ -371,7 +671,7 @@ where
     }
 
     /// Map a solved node-path to the chosen candidate per layer.
-    fn route_of(&self, path: &Path) -> Vec<CandidateRef> {
+    pub(super) fn route_of(&self, path: &Path) -> Vec<CandidateRef> {
         path.nodes
             .iter()
             .enumerate()
@@ -398,3 +698,14 @@ where
         DisconnectedError { breaks }.into()
     }
 }
+
+/// Origins for a bare linestring. It carries no observation times, so indices
+/// stand in: order is the only property the batch lifecycle reads from them.
+pub(super) fn indexed(linestring: LineString) -> Vec<Origin> {
+    linestring
+        .into_points()
+        .into_iter()
+        .enumerate()
+        .map(|(index, point)| Origin::new(point, index as i64))
+        .collect()
+}
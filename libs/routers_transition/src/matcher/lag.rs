@@ -0,0 +1,115 @@
+use core::time::Duration;
+
+use routers_trellis::LayerId;
+
+use crate::matcher::Origin;
+
+/// How far behind the newest observation a streaming match commits its
+/// choices, whether or not the candidate paths have fused.
+///
+/// A layer [`layers`](Self::layers) or more behind the newest, or observed
+/// [`delay`](Self::delay) or more before it, is committed: its chosen
+/// candidate is fixed, and no later observation may rewrite it. Either may be
+/// unset, and the default sets neither: only convergence commits.
+///
+/// The lag bounds how long a consumer waits for a final answer, at the price
+/// of sometimes committing to a choice later evidence would have overturned.
+///
+/// ```ignore
+/// let lag = Lag::new()
+///     .with_layers(10)
+///     .with_delay(Duration::from_secs(30));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Lag {
+    /// How many layers behind the newest a layer is committed.
+    pub layers: Option<usize>,
+
+    /// How long before the newest observation a layer is committed, read
+    /// from [`Origin::timestamp`].
+    pub delay: Option<Duration>,
+}
+
+impl Lag {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_layers(self, layers: usize) -> Self {
+        Self {
+            layers: Some(layers),
+            ..self
+        }
+    }
+
+    pub fn with_delay(self, delay: Duration) -> Self {
+        Self {
+            delay: Some(delay),
+            ..self
+        }
+    }
+
+    /// The latest layer of `origins` the lag commits, if any.
+    pub fn bound(&self, origins: &[Origin]) -> Option<LayerId> {
+        let newest = origins.last()?;
+        let last = origins.len() - 1;
+
+        let by_layers = self.layers.and_then(|layers| last.checked_sub(layers));
+        let by_delay = self.delay.and_then(|delay| {
+            origins.iter().rposition(|origin| {
+                let age = newest.timestamp.saturating_sub(origin.timestamp);
+                age >= 0 && age.unsigned_abs() >= delay.as_micros() as u64
+            })
+        });
+
+        by_layers.max(by_delay).map(|layer| LayerId(layer as u32))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo::point;
+
+    fn origins(seconds: &[i64]) -> Vec<Origin> {
+        seconds
+            .iter()
+            .map(|&s| Origin::new(point!(x: 0.0, y: 0.0), s * 1_000_000))
+            .collect()
+    }
+
+    #[test]
+    fn unset_lag_commits_nothing() {
+        assert_eq!(Lag::new().bound(&origins(&[0, 1, 2, 3])), None);
+    }
+
+    #[test]
+    fn commits_by_layers() {
+        let lag = Lag::new().with_layers(2);
+
+        assert_eq!(lag.bound(&origins(&[0, 1])), None);
+        assert_eq!(lag.bound(&origins(&[0, 1, 2])), Some(LayerId(0)));
+        assert_eq!(lag.bound(&origins(&[0, 1, 2, 3, 4])), Some(LayerId(2)));
+    }
+
+    #[test]
+    fn commits_by_delay() {
+        let lag = Lag::new().with_delay(Duration::from_secs(30));
+
+        assert_eq!(lag.bound(&origins(&[0, 10, 20])), None);
+        assert_eq!(lag.bound(&origins(&[0, 10, 20, 40])), Some(LayerId(1)));
+    }
+
+    #[test]
+    fn the_later_bound_wins() {
+        let lag = Lag::new().with_layers(3).with_delay(Duration::from_secs(5));
+
+        assert_eq!(lag.bound(&origins(&[0, 1, 2, 3, 10])), Some(LayerId(3)));
+        assert_eq!(lag.bound(&origins(&[0, 1, 2, 3, 4])), Some(LayerId(1)));
+    }
+
+    #[test]
+    fn empty_input_commits_nothing() {
+        assert_eq!(Lag::new().with_layers(0).bound(&[]), None);
+    }
+}
@@ -7,12 +7,17 @@
 //! any matcher configured the same way. Start at [`Matcher`] for the full
 //! batch and streaming walkthroughs.
 
+mod breakage;
 mod continuation;
 mod entity;
+mod lag;
 mod origin;
+mod snap;
 mod trip;
 
+pub use breakage::Breakage;
 pub use continuation::Continuation;
 pub use entity::Matcher;
+pub use lag::Lag;
 pub use origin::Origin;
 pub use trip::{Trip, TripState};
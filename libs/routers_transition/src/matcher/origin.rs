@@ -5,11 +5,19 @@ use serde::{Deserialize, Serialize};
 ///
 /// The timestamp is microseconds since the Unix epoch, minted by the supplier
 /// at the ingest boundary — the per-vehicle ordering and identity key for
-/// everything derived from the observation. This crate never interprets it
-/// beyond equality; it rides along so every trip layer stays addressable by
-/// the observation that created it.
+/// everything derived from the observation, so every trip layer stays
+/// addressable by the observation that created it. The time between two
+/// observations also matters to the match: transition costing prices the
+/// speed a route between them implies, [`Breakage`](crate::Breakage) splits
+/// a trip where they are too far apart, and [`Lag`](crate::Lag) commits
+/// layers by their age.
 ///
-/// Equality is exact on both fields: two observations sharing a timestamp but
+/// Devices often report more than a position: the [`heading`](Self::heading),
+/// [`speed`](Self::speed) and [`accuracy`](Self::accuracy) are optional, and
+/// reach the emission strategy through the
+/// [`EmissionContext`](crate::costing::EmissionContext) when present.
+///
+/// Equality is exact on every field: two observations sharing a timestamp but
 /// not a position contradict each other, and a resume must refuse the pair.
 #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Origin {
@@ -17,10 +25,43 @@ pub struct Origin {
 
     /// Microseconds since the Unix epoch.
     pub timestamp: i64,
+
+    /// The direction of travel, in degrees clockwise from true north.
+    #[serde(default)]
+    pub heading: Option<f64>,
+
+    /// The speed over ground, in metres per second.
+    #[serde(default)]
+    pub speed: Option<f64>,
+
+    /// The device's estimate of its horizontal error, in metres.
+    #[serde(default)]
+    pub accuracy: Option<f64>,
 }
 
 impl Origin {
     pub fn new(point: Point, timestamp: i64) -> Self {
-        Self { point, timestamp }
+        Self {
+            point,
+            timestamp,
+            heading: None,
+            speed: None,
+            accuracy: None,
+        }
+    }
+
+    pub fn with_heading(mut self, heading: Option<f64>) -> Self {
+        self.heading = heading;
+        self
+    }
+
+    pub fn with_speed(mut self, speed: Option<f64>) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    pub fn with_accuracy(mut self, accuracy: Option<f64>) -> Self {
+        self.accuracy = accuracy;
+        self
     }
 }
@@ -0,0 +1,163 @@
+use alloc::borrow::Cow;
+
+use geo::{Distance, Haversine, LineString};
+use routers_network::{Entry, Network};
+use routers_trellis::{LayerId, MAX_WEIGHT, TrellisError, ViterbiSolver};
+
+use crate::candidate::{Candidate, CollapsedPath, Segment, Segments};
+use crate::costing::{EmissionStrategy, TransitionStrategy};
+use crate::layer::generation::LayerGeneration;
+use crate::matcher::entity::indexed;
+use crate::matcher::trip::TripState;
+use crate::matcher::{Matcher, Origin};
+use crate::primitives::MatchError;
+use crate::weigh::Weigher;
+
+/// The continuity cost of staying on the same directed edge between layers.
+const SAME_EDGE: u32 = 0;
+
+/// The continuity cost of stepping onto an edge which shares a node with the
+/// previous one, such as passing through an intersection.
+const ADJACENT_EDGE: u32 = 25;
+
+/// The continuity cost of jumping to an edge with no shared node.
+const DISJOINT_EDGE: u32 = 100;
+
+/// How many cost units each metre of deviance contributes. Comparable to the
+/// slope of the default emission cost inside its free radius.
+const DEVIANCE_PER_METRE: f64 = 4.0;
+
+impl<'a, Emmis, Trans, G, W, N> Matcher<'a, Emmis, Trans, G, W, N>
+where
+    N: Network,
+    Emmis: EmissionStrategy + Send + Sync,
+    Trans: TransitionStrategy<N::Entry> + Send + Sync,
+    G: LayerGeneration<N::Entry>,
+    W: Weigher<N> + Sync,
+{
+    /// Snap a whole trajectory in one call: every position is moved to its
+    /// most plausible road position without routing between them.
+    ///
+    /// Candidates are generated and priced exactly as in [`r#match`], but each
+    /// boundary is weighed by a cheap continuity term instead of
+    /// a network search, so a snap never consults the predicate cache. The
+    /// result carries no routed hops: its `interpolated` is empty.
+    ///
+    /// [`r#match`]: Self::r#match
+    pub fn snap(&self, linestring: LineString) -> Result<CollapsedPath<'a, N::Entry>, MatchError> {
+        self.snap_piece(&indexed(linestring))
+    }
+
+    /// Snap a whole trajectory as [`snap`](Self::snap) does, reporting the
+    /// result as [`Segment`]s of the input. See
+    /// [`snap_origins`](Self::snap_origins), which this forwards to.
+    pub fn snap_segments(
+        &self,
+        linestring: LineString,
+    ) -> Result<Segments<CollapsedPath<'a, N::Entry>>, MatchError> {
+        self.snap_origins(&indexed(linestring))
+    }
+
+    /// Snap a batch of observations as [`snap`](Self::snap) does, first
+    /// breaking them into independently snapped pieces by the matcher's
+    /// [`Breakage`](crate::Breakage). As in
+    /// [`match_origins`](Self::match_origins), a piece which fails is a
+    /// segment holding its error.
+    pub fn snap_origins(
+        &self,
+        origins: &[Origin],
+    ) -> Result<Segments<CollapsedPath<'a, N::Entry>>, MatchError> {
+        if origins.is_empty() {
+            return Err(TrellisError::Empty.into());
+        }
+
+        Ok(self
+            .breakage
+            .pieces(origins)
+            .into_iter()
+            .map(|piece| Segment {
+                path: self
+                    .snap_piece(&origins[piece.clone()])
+                    .map_err(|error| error.offset(piece.start)),
+                layers: piece,
+            })
+            .collect())
+    }
+
+    /// Snap one unbroken piece.
+    fn snap_piece(&self, origins: &[Origin]) -> Result<CollapsedPath<'a, N::Entry>, MatchError> {
+        let mut trip = self.begin();
+        self.extend(&mut trip, origins)?;
+
+        let mut trellis = match trip.take_state() {
+            TripState::Building(trellis) => trellis,
+            TripState::Solved(solved) => solved.reopen(),
+            TripState::Empty => return Err(TrellisError::Empty.into()),
+        };
+
+        for boundary in trellis.boundaries().collect::<Vec<_>>() {
+            let next = LayerId(boundary.0 + 1);
+            let (Some(from), Some(to)) = (trip.layer(boundary), trip.layer(next)) else {
+                continue;
+            };
+            let (Some(source), Some(target)) = (trip.point(boundary), trip.point(next)) else {
+                continue;
+            };
+            let observed = Haversine.distance(source, target);
+
+            let rows = from
+                .iter()
+                .flat_map(|a| to.iter().map(move |b| continuity(a, b, observed)))
+                .collect::<Vec<_>>();
+            trellis.fill_transition(boundary, &rows)?;
+        }
+
+        let solved = trellis
+            .solve(&ViterbiSolver::new())
+            .map_err(|(_, error)| error)?;
+        let cost = solved.cost();
+        let route = self.route_of(solved.path());
+        let confidence = if self.score {
+            solved.posteriors(&self.confidence).along(solved.path())
+        } else {
+            Vec::new()
+        };
+        let (candidates, _) = trip.into_parts();
+
+        Ok(CollapsedPath {
+            cost,
+            route,
+            confidence,
+            interpolated: Vec::new(),
+            candidates: Cow::Owned(candidates),
+        })
+    }
+}
+
+/// The snap-mode transition cost between two candidates of consecutive layers.
+///
+/// Staying on one edge is free and moving through a shared node is cheap,
+/// while jumping between unrelated edges costs the most. On top of that, the
+/// straight-line distance between the candidates is compared with that of
+/// their observations, so a candidate pair which stretches or shrinks the
+/// trajectory pays for the difference.
+fn continuity<E: Entry>(from: &Candidate<E>, to: &Candidate<E>, observed: f64) -> u32 {
+    let (a, b) = (&from.edge, &to.edge);
+
+    let topology = if a.source == b.source && a.target == b.target {
+        SAME_EDGE
+    } else if a.target == b.source
+        || a.source == b.target
+        || a.source == b.source
+        || a.target == b.target
+    {
+        ADJACENT_EDGE
+    } else {
+        DISJOINT_EDGE
+    };
+
+    let deviance = (Haversine.distance(from.position, to.position) - observed).abs();
+    let cost = topology as f64 + deviance * DEVIANCE_PER_METRE;
+
+    (cost as u32).min(MAX_WEIGHT)
+}
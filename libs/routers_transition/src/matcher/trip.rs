@@ -19,6 +19,10 @@ where
 {
     origins: Vec<Origin>,
     candidates: CandidateStore<E>,
+    /// How many leading layers are committed: each narrowed to its chosen
+    /// candidate, so no later solve can rewrite it.
+    #[serde(default)]
+    committed: usize,
     pub state: TripState,
 }
 
@@ -104,6 +108,14 @@ where
         }
     }
 
+    /// How many leading layers are committed, by
+    /// [`Matcher::commit`](crate::Matcher::commit): their choices are final.
+    /// The layers after them are provisional, and a later solve may still
+    /// rewrite them.
+    pub fn committed(&self) -> usize {
+        self.committed
+    }
+
     /// Whether the trip is currently solved (no pending data).
     pub fn is_solved(&self) -> bool {
         matches!(self.state, TripState::Solved(_))
@@ -131,6 +143,7 @@ where
 
         self.origins.drain(..len - n);
         self.candidates.tail(n);
+        self.committed = self.committed.saturating_sub(len - n);
         self.state = match core::mem::take(&mut self.state) {
             TripState::Empty => TripState::Empty,
             TripState::Building(trellis) => TripState::Building(
@@ -147,6 +160,78 @@ where
         };
     }
 
+    /// An owned copy of the layers in `range`, as a trip of its own.
+    ///
+    /// The trellis is cut with [`Trellis::partition`], so interior boundaries
+    /// keep their weights and only the transitions crossing the cut are
+    /// dropped. A solved certificate cannot describe the cut, so the copy is
+    /// always `Building`.
+    pub fn partition(&self, range: core::ops::Range<LayerId>) -> Result<Self, TrellisError> {
+        let trellis = self.trellis().ok_or(TrellisError::Empty)?;
+        let partitioned = trellis.partition(range.clone())?;
+        let layers = range.start.index()..range.end.index();
+        let committed = self
+            .committed
+            .saturating_sub(layers.start)
+            .min(layers.len());
+
+        Ok(Self {
+            origins: self.origins[layers.clone()].to_vec(),
+            candidates: self.candidates.partition(layers),
+            committed,
+            state: TripState::Building(partitioned),
+        })
+    }
+
+    /// Commit every layer through `through` to the candidate the solved path
+    /// chooses there, narrowing each to that one candidate with
+    /// [`Trellis::pin`]. The path is unchanged, but the certificate no longer
+    /// describes the narrowed trellis, so the trip reopens to `Building`.
+    ///
+    /// Fails with [`TrellisError::Empty`] unless the trip is solved. Every
+    /// choice is checked before any is pinned, so a commit which fails leaves
+    /// the trip as it was.
+    pub(crate) fn commit(&mut self, through: LayerId) -> Result<(), TrellisError> {
+        let solved = match core::mem::take(&mut self.state) {
+            TripState::Solved(solved) => solved,
+            state => {
+                self.state = state;
+                return Err(TrellisError::Empty);
+            }
+        };
+
+        let through = through.index().min(self.layers() - 1);
+        let chosen = solved
+            .path()
+            .nodes
+            .iter()
+            .enumerate()
+            .take(through + 1)
+            .skip(self.committed)
+            .map(|(layer, &node)| (LayerId(layer as u32), node))
+            .collect::<Vec<_>>();
+
+        let widths = solved.trellis().widths();
+        if let Some(&(layer, node)) = chosen.iter().find(|(layer, node)| {
+            widths
+                .get(layer.index())
+                .is_none_or(|&width| node.0 >= width)
+        }) {
+            self.state = TripState::Solved(solved);
+            return Err(TrellisError::NodeOutOfRange { layer, node });
+        }
+
+        let mut trellis = solved.reopen();
+        for (layer, node) in chosen {
+            trellis.pin(layer, node).expect("every choice was checked");
+            self.candidates.pin(layer, node);
+        }
+
+        self.committed = self.committed.max(through + 1);
+        self.state = TripState::Building(trellis);
+        Ok(())
+    }
+
     /// Append one layer: its origin, its candidates (identity is overwritten to
     /// be positionally true), and a trellis layer carrying the emission costs
     /// as node weights. A solved trip reopens through [`Solved::append`].
@@ -3,7 +3,6 @@ use core::cmp::Ordering;
 use core::hash::{BuildHasherDefault, Hash};
 use indexmap::IndexMap;
 use indexmap::map::Entry;
-use pathfinding::num_traits::Zero;
 use rustc_hash::{FxHashSet, FxHasher};
 
 use crate::primitives::WeightAndDistance;
@@ -44,7 +43,7 @@ impl Ord for SmallestHolder {
 /// Struct returned by [`dijkstra_reach`].
 pub struct DijkstraReachable<FN, E>
 where
-    E: routers_network::Entry,
+    E: Copy + Eq + Hash,
 {
     to_see: BinaryHeap<SmallestHolder>,
     seen: FxHashSet<usize>,
@@ -56,22 +55,43 @@ where
 #[derive(Debug, Hash, PartialEq, Eq, Clone)]
 pub struct DijkstraReachableItem<E>
 where
-    E: routers_network::Entry,
+    E: Copy + Eq + Hash,
 {
     /// The node that was reached by [`dijkstra_reach`].
     pub node: E,
     /// The previous node that the current node came from.
-    /// If the node is the first node, there will be no parent.
+    /// If the node is one of the starting nodes, there will be no parent.
     pub parent: Option<E>,
     /// The total cost from the starting node.
     pub total_cost: Cost,
 }
 
+/// The nodes a node was reached through, nearest first, back to the
+/// starting node it was reached from.
+#[derive(Clone)]
+pub struct Ancestors<'a, E> {
+    parents: &'a FxIndexMap<E, (usize, Cost)>,
+    index: usize,
+}
+
+impl<E> Iterator for Ancestors<'_, E>
+where
+    E: Copy,
+{
+    type Item = E;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (node, (parent, _)) = self.parents.get_index(self.index)?;
+        self.index = *parent;
+        Some(*node)
+    }
+}
+
 impl<FN, IN, E> Iterator for DijkstraReachable<FN, E>
 where
-    FN: FnMut(&E) -> IN,
+    FN: FnMut(&E, Ancestors<'_, E>) -> IN,
     IN: Iterator<Item = (E, Cost)>,
-    E: routers_network::Entry,
+    E: Copy + Eq + Hash,
 {
     type Item = DijkstraReachableItem<E>;
 
@@ -89,7 +109,12 @@ where
                     total_cost: *cost,
                 });
 
-                (item, (self.successors)(node))
+                let ancestors = Ancestors {
+                    parents: &self.parents,
+                    index: *parent_index,
+                };
+
+                (item, (self.successors)(node, ancestors))
             };
 
             for (successor, move_cost) in successors {
@@ -127,27 +152,32 @@ where
 pub struct Dijkstra;
 
 impl Dijkstra {
-    /// Visit all nodes that are reachable from a start node. The node
-    /// will be visited in order of cost, with the closest nodes first.
+    /// Visit all nodes that are reachable from a set of starting nodes, each
+    /// already at some cost. The nodes will be visited in order of cost, with
+    /// the closest nodes first.
     ///
-    /// The `successors` function receives the current node, and returns
-    /// an iterator of successors associated with their move cost.
-    pub fn reach<FN, IN, E>(&self, start: &E, successors: FN) -> DijkstraReachable<FN, E>
+    /// The `successors` function receives the current node and the nodes it
+    /// was reached through, and returns an iterator of successors associated
+    /// with their move cost.
+    pub fn reach<FN, IN, E>(
+        &self,
+        starts: impl IntoIterator<Item = (E, Cost)>,
+        successors: FN,
+    ) -> DijkstraReachable<FN, E>
     where
-        E: routers_network::Entry,
-        FN: FnMut(&E) -> IN,
+        E: Copy + Eq + Hash,
+        FN: FnMut(&E, Ancestors<'_, E>) -> IN,
         IN: Iterator<Item = (E, Cost)>,
     {
         let mut to_see: BinaryHeap<SmallestHolder> = BinaryHeap::with_capacity(256);
-        to_see.push(SmallestHolder {
-            cost: Zero::zero(),
-            index: 0,
-        });
-
         let mut parents: FxIndexMap<E, (usize, Cost)> =
             FxIndexMap::with_capacity_and_hasher(64, BuildHasherDefault::<FxHasher>::default());
 
-        parents.insert(*start, (usize::MAX, Zero::zero()));
+        for (start, cost) in starts {
+            let (index, _) = parents.insert_full(start, (usize::MAX, cost));
+            to_see.push(SmallestHolder { cost, index });
+        }
+
         let seen = FxHashSet::default();
 
         DijkstraReachable {
@@ -1,7 +1,9 @@
 use alloc::sync::Arc;
 use core::fmt::Debug;
+use core::hash::Hash;
+use core::marker::PhantomData;
 use geo::Distance;
-use routers_network::{DataPlane, Metadata, Network};
+use routers_network::{DataPlane, Edge, Entry, Metadata, Network};
 use rustc_hash::{FxBuildHasher, FxHashMap};
 use scc::HashCache;
 
@@ -24,7 +26,8 @@ const _: () = assert!(
     "DEFAULT_CACHE_CAPACITY must be a power of two, or HashCache rounds it up"
 );
 
-/// A generic read-through cache for a hashmap-backed data structure.
+/// A generic read-through cache for a hashmap-backed data structure, keyed by
+/// `K`.
 ///
 /// Backed by [`HashCache`], which is 32-way associative and evicts the least
 /// recently used entry of a bucket once that bucket is full. Eviction is
@@ -33,21 +36,25 @@ const _: () = assert!(
 ///
 /// Anything the value varies with beyond its key belongs in `Meta`, and caches
 /// whose metadata disagrees must stay separate maps.
-pub struct CacheMap<V, N, Meta>
+pub struct CacheMap<K, V, N, Meta>
 where
+    K: Eq + Hash,
     V: Debug,
     Meta: Debug,
     N: Network,
 {
-    pub(crate) map: HashCache<N::Entry, Arc<V>, FxBuildHasher>,
+    pub(crate) map: HashCache<K, Arc<V>, FxBuildHasher>,
     pub(crate) metadata: Meta,
+    /// The network the cached values are calculated against.
+    network: PhantomData<fn() -> N>,
 }
 
 // Hand-rolled so the cache's own `Debug` — and its stats accessors — do not
 // drag `V: Send + Sync + 'static` onto every use of `CacheMap`. The entries
 // themselves are elided.
-impl<V, N, Meta> Debug for CacheMap<V, N, Meta>
+impl<K, V, N, Meta> Debug for CacheMap<K, V, N, Meta>
 where
+    K: Eq + Hash,
     V: Debug,
     Meta: Debug,
     N: Network,
@@ -60,17 +67,19 @@ where
 }
 
 #[derive(Debug)]
-pub struct LockedMap<V, N, Meta>(Arc<CacheMap<V, N, Meta>>)
+pub struct LockedMap<K, V, N, Meta>(Arc<CacheMap<K, V, N, Meta>>)
 where
-    LockedMap<V, N, Meta>: Calculable<N, V>,
+    LockedMap<K, V, N, Meta>: Calculable<N, K, V>,
+    K: Eq + Hash,
     N: Network,
     V: Debug,
     Meta: Debug;
 
-impl<V, N, Meta> Default for LockedMap<V, N, Meta>
+impl<K, V, N, Meta> Default for LockedMap<K, V, N, Meta>
 where
-    LockedMap<V, N, Meta>: Calculable<N, V>,
-    CacheMap<V, N, Meta>: Default,
+    LockedMap<K, V, N, Meta>: Calculable<N, K, V>,
+    CacheMap<K, V, N, Meta>: Default,
+    K: Eq + Hash,
     V: Debug,
     N: Network,
     Meta: Debug,
@@ -80,10 +89,11 @@ where
     }
 }
 
-impl<V, N, Meta> Clone for LockedMap<V, N, Meta>
+impl<K, V, N, Meta> Clone for LockedMap<K, V, N, Meta>
 where
-    LockedMap<V, N, Meta>: Calculable<N, V>,
-    CacheMap<V, N, Meta>: Default,
+    LockedMap<K, V, N, Meta>: Calculable<N, K, V>,
+    CacheMap<K, V, N, Meta>: Default,
+    K: Eq + Hash,
     N: Network,
     V: Debug,
     Meta: Debug,
@@ -93,9 +103,10 @@ where
     }
 }
 
-impl<V, N, Meta> LockedMap<V, N, Meta>
+impl<K, V, N, Meta> LockedMap<K, V, N, Meta>
 where
-    LockedMap<V, N, Meta>: Calculable<N, V>,
+    LockedMap<K, V, N, Meta>: Calculable<N, K, V>,
+    K: Copy + Eq + Hash,
     N: Network,
     V: Debug + Send + Sync + 'static,
     Meta: Debug,
@@ -110,9 +121,9 @@ where
     ///
     /// The function returns the value, [`V`] wrapped in a reference counter.
     /// This, therefore does not require [`V`] to be `Clone`. However, it
-    /// consumes an owned value of the key, [`N::Entry`], which is required
+    /// consumes an owned value of the key, [`K`], which is required
     /// for the call to the [`Calculable::calculate`] function.
-    pub fn query(&self, ctx: &RoutingContext<N>, key: N::Entry) -> Arc<V> {
+    pub fn query(&self, ctx: &RoutingContext<N>, key: K) -> Arc<V> {
         if let Some(value) = self.0.map.read(&key, |_, v| Arc::clone(v)) {
             return value;
         }
@@ -124,8 +135,9 @@ where
     }
 }
 
-impl<V, N, Meta> CacheMap<V, N, Meta>
+impl<K, V, N, Meta> CacheMap<K, V, N, Meta>
 where
+    K: Eq + Hash,
     V: Debug,
     N: Network,
     Meta: Debug,
@@ -140,12 +152,14 @@ where
                 FxBuildHasher::default(),
             ),
             metadata,
+            network: PhantomData,
         }
     }
 }
 
-impl<V, N, Meta> Default for CacheMap<V, N, Meta>
+impl<K, V, N, Meta> Default for CacheMap<K, V, N, Meta>
 where
+    K: Eq + Hash + Send + Sync + 'static,
     V: Debug + Send + Sync + 'static,
     N: Network,
     Meta: Default + Debug,
@@ -157,8 +171,8 @@ where
 
 /// Implementation of a routing-domain calculable KV pair.
 ///
-/// Asserts that the value, [`V`] can be generated from the key
-/// (the network's [`Entry`]), given routing context, and the base structure.
+/// Asserts that the value, [`V`] can be generated from the key, [`K`],
+/// given routing context, and the base structure.
 ///
 /// ### Examples
 ///
@@ -168,20 +182,48 @@ where
 /// The [`SuccessorsCache`], given an underlying map key,
 /// can derive the successors using the routing map and an
 /// upper-bounded dijkstra algorithm.
-pub trait Calculable<N: Network, V> {
+pub trait Calculable<N: Network, K, V> {
     /// The concrete implementation of the function which derives the
     /// value, [`V`], from the key.
     ///
     /// The function parameters include relevant [`RoutingContext`] which
     /// may be required for the calculation.
-    fn calculate(&self, ctx: &RoutingContext<N>, key: N::Entry) -> V;
+    fn calculate(&self, ctx: &RoutingContext<N>, key: K) -> V;
+}
+
+/// A directed edge as the bounded Dijkstra walks it: the predicate cache
+/// searches edges rather than nodes, so each step knows the way it arrived
+/// along — which is what the network's turn restrictions are keyed on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Step<E>
+where
+    E: Entry,
+{
+    pub source: E,
+    pub target: E,
+    /// The way the edge belongs to.
+    pub way: E,
+}
+
+impl<E> From<&Edge<E>> for Step<E>
+where
+    E: Entry,
+{
+    fn from(edge: &Edge<E>) -> Self {
+        Step {
+            source: edge.source,
+            target: edge.target,
+            way: *edge.id(),
+        }
+    }
 }
 
 mod successor {
     use super::*;
     use crate::primitives::WeightAndDistance;
 
-    use geo::Haversine;
+    use core::iter;
+    use geo::{Haversine, Length as _, LineString};
     use routers_network::DirectionAwareEdgeId;
     use uom::si::f64::Length;
     use uom::si::length::meter;
@@ -194,9 +236,10 @@ mod successor {
     /// It accepts a node id as input, from which it will obtain all outgoing
     /// edges accessible to the runtime, and the distance to each one as a
     /// [`WeightAndDistance`].
-    pub type SuccessorsCache<N> = LockedMap<SuccessorWeights<<N as DataPlane>::Entry>, N, ()>;
+    pub type SuccessorsCache<N> =
+        LockedMap<<N as DataPlane>::Entry, SuccessorWeights<<N as DataPlane>::Entry>, N, ()>;
 
-    impl<N: Network> Calculable<N, SuccessorWeights<N::Entry>> for SuccessorsCache<N> {
+    impl<N: Network> Calculable<N, N::Entry, SuccessorWeights<N::Entry>> for SuccessorsCache<N> {
         #[inline]
         fn calculate(&self, ctx: &RoutingContext<N>, key: N::Entry) -> SuccessorWeights<N::Entry> {
             // Calc. once
@@ -215,9 +258,19 @@ mod successor {
                     #[allow(unsafe_code)]
                     let position = unsafe { ctx.map.point(&next).unwrap_unchecked() };
 
+                    // A shaped edge is as long as the road it follows.
+                    let metres = match ctx.map.shape(&key, &next) {
+                        [] => Haversine.distance(source, position),
+                        shape => Haversine.length(&LineString::from_iter(
+                            iter::once(source)
+                                .chain(shape.iter().copied())
+                                .chain(iter::once(position)),
+                        )),
+                    };
+
                     // `Haversine` answers in metres; the unit goes on here so
                     // nothing downstream has to remember that.
-                    let distance = Length::new::<meter>(Haversine.distance(source, position));
+                    let distance = Length::new::<meter>(metres);
                     (next, distance, w, edge)
                 })
                 .map(|(next, distance, weight, edge)| {
@@ -233,9 +286,10 @@ mod successor {
 mod predicate {
     use std::sync::OnceLock;
 
-    use crate::primitives::{Dijkstra, algorithms::DijkstraReachableItem};
-    use core::marker::PhantomData;
-    use routers_network::Network;
+    use crate::primitives::{Dijkstra, WeightAndDistance, algorithms::DijkstraReachableItem};
+    use core::iter;
+    use pathfinding::num_traits::Zero;
+    use routers_network::{Network, Turn};
     use uom::si::f64::Length;
 
     use super::*;
@@ -282,19 +336,20 @@ mod predicate {
         }
     }
 
-    /// Predicates represents a hashmap of the input [`NodeIx`] as the key,
-    /// mapped to the parent [`NodeIx`] it was reached from during an
+    /// Predicates represents a hashmap of each reached [`Step`] as the key,
+    /// mapped to the parent [`Step`] it was reached from during an
     /// upper-bounded dijkstra calculation. Following the parent pointers back
-    /// to the root reconstructs the path to any reachable node.
+    /// to the root reconstructs the path to any reachable edge.
     ///
     /// The output from the [`PredicateCache::calculate`] function.
-    type Predicates<E> = FxHashMap<E, E>;
+    type Predicates<E> = FxHashMap<Step<E>, Step<E>>;
 
     /// The reachability cache a weigher answers routing queries from.
     ///
-    /// Keyed by a root node, it holds the parent-pointer map of an
-    /// upper-bounded Dijkstra rooted there: every node reachable within the
-    /// cache's `reach_distance`, mapped to the node it was reached from.
+    /// Keyed by a root edge, it holds the parent-pointer map of an
+    /// upper-bounded Dijkstra leaving it: every edge reachable within the
+    /// cache's `reach_distance` by turns the network permits, mapped to the
+    /// edge it was reached from.
     /// Computed once on first query and read thereafter — and deterministic,
     /// which is what lets collapse re-derive hop geometry rather than store
     /// it.
@@ -304,8 +359,12 @@ mod predicate {
     /// other than [`DEFAULT_REACH_DISTANCE`], and pass it to
     /// [`MatchOptions::with_cache`](crate::MatchOptions::with_cache) — which
     /// also keeps it warm across matches.
-    pub type PredicateCache<N> =
-        LockedMap<Predicates<<N as DataPlane>::Entry>, N, PredicateMetadata<N>>;
+    pub type PredicateCache<N> = LockedMap<
+        Step<<N as DataPlane>::Entry>,
+        Predicates<<N as DataPlane>::Entry>,
+        N,
+        PredicateMetadata<N>,
+    >;
 
     impl<N: Network> PredicateCache<N> {
         /// An empty cache whose entries reach `reach_distance`.
@@ -327,9 +386,9 @@ mod predicate {
         }
     }
 
-    impl<N: Network> Calculable<N, Predicates<N::Entry>> for PredicateCache<N> {
+    impl<N: Network> Calculable<N, Step<N::Entry>, Predicates<N::Entry>> for PredicateCache<N> {
         #[inline]
-        fn calculate(&self, ctx: &RoutingContext<N>, key: N::Entry) -> Predicates<N::Entry> {
+        fn calculate(&self, ctx: &RoutingContext<N>, root: Step<N::Entry>) -> Predicates<N::Entry> {
             // Accessibility, and so every reachability map stored here, is a
             // function of the runtime; a second runtime needs a second cache.
             let bound = self
@@ -346,18 +405,73 @@ mod predicate {
 
             let reach = self.0.metadata.reach;
 
+            // The root is not itself a starting point, only the turns off it
+            // are, so that it can be reached again around a loop.
+            let mut costs = self
+                .turns(ctx, &root, iter::empty())
+                .into_iter()
+                .collect::<FxHashMap<_, _>>();
+            let starts = costs
+                .keys()
+                .map(|step| (*step, WeightAndDistance::zero()))
+                .collect::<Vec<_>>();
+
+            // A step is reached at the cost of arriving at its source, as the
+            // reach bounds how far from the root a route may *enter* an edge.
+            // Leaving a step therefore pays for the step itself.
             Dijkstra
-                .reach(&key, move |node| {
-                    ArcIter::new(self.0.metadata.successors.query(ctx, *node))
-                        .map(|(a, _, b)| (a, b))
+                .reach(starts, move |step, ancestors| {
+                    let cost = costs[step];
+
+                    // Nothing before the root is known, so via-way
+                    // restrictions are matched no further back than it.
+                    let approach = ancestors.map(|step| step.way).chain(iter::once(root.way));
+                    let turns = self.turns(ctx, step, approach);
+
+                    costs.extend(turns.iter().copied());
+                    turns.into_iter().map(move |(next, _)| (next, cost))
                 })
                 .take_while(|p| p.total_cost.distance() < reach)
-                .map(|DijkstraReachableItem { node, parent, .. }| {
-                    (node, parent.unwrap_or_default())
-                })
+                .map(|DijkstraReachableItem { node, parent, .. }| (node, parent.unwrap_or(root)))
                 .collect::<Predicates<N::Entry>>()
         }
     }
+
+    impl<N: Network> PredicateCache<N> {
+        /// The edges leaving `step`'s target which the network permits turning
+        /// onto from `step`, with the cost of each. `approach` is the ways
+        /// travelled before `step`, most recent first.
+        fn turns(
+            &self,
+            ctx: &RoutingContext<N>,
+            step: &Step<N::Entry>,
+            approach: impl Iterator<Item = N::Entry> + Clone,
+        ) -> Vec<(Step<N::Entry>, WeightAndDistance)> {
+            ArcIter::new(self.0.metadata.successors.query(ctx, step.target))
+                .filter(|(next, edge, _)| {
+                    let turn = Turn {
+                        origin: step.source,
+                        from: step.way,
+                        via: step.target,
+                        to: edge.index(),
+                        destination: *next,
+                    };
+
+                    ctx.map
+                        .turn_permitted(&turn, &mut approach.clone(), ctx.runtime)
+                })
+                .map(|(next, edge, cost)| {
+                    let next = Step {
+                        source: step.target,
+                        target: next,
+                        way: edge.index(),
+                    };
+
+                    (next, cost)
+                })
+                .collect()
+        }
+    }
 }
 
 /// Iterator wrapper that keeps the Arc alive while yielding `&T`
@@ -394,7 +508,7 @@ use crate::primitives::RoutingContext;
 
 #[cfg(test)]
 mod tests {
-    use super::{Arc, DEFAULT_CACHE_CAPACITY, DEFAULT_REACH_DISTANCE, PredicateCache};
+    use super::{Arc, DEFAULT_CACHE_CAPACITY, DEFAULT_REACH_DISTANCE, PredicateCache, Step};
     use routers_network::mock::{MockEntryId, MockNetwork};
     use uom::si::f64::Length;
     use uom::si::length::{centimeter, meter};
@@ -406,7 +520,12 @@ mod tests {
         let cache = PredicateCache::<MockNetwork>::default();
 
         for key in 0..(DEFAULT_CACHE_CAPACITY as i64 * 10) {
-            let _ = cache.0.map.put(MockEntryId(key), Arc::default());
+            let step = Step {
+                source: MockEntryId(key),
+                target: MockEntryId(key + 1),
+                way: MockEntryId(key),
+            };
+            let _ = cache.0.map.put(step, Arc::default());
         }
 
         assert_eq!(
@@ -26,6 +26,29 @@ pub enum MatchError {
     SolveError(#[from] SolveError),
 }
 
+impl MatchError {
+    /// The same error, its layers counted `by` layers further on: for an
+    /// error raised matching a piece of the input which starts at layer `by`.
+    pub(crate) fn offset(self, by: usize) -> Self {
+        match self {
+            MatchError::Unanchored(mut error) => {
+                for point in &mut error.points {
+                    point.layer += by;
+                }
+                MatchError::Unanchored(error)
+            }
+            MatchError::Disconnected(mut error) => {
+                for gap in &mut error.breaks {
+                    gap.from_layer += by;
+                    gap.to_layer += by;
+                }
+                MatchError::Disconnected(error)
+            }
+            error => error,
+        }
+    }
+}
+
 /// Every trajectory point that could not be placed on the road network,
 /// collected into one error so the caller can address them together.
 #[derive(Error, Debug, Clone, PartialEq)]
@@ -1,9 +1,11 @@
 use routers_network::{Edge, Network};
+use routers_trellis::LayerId;
 
+use crate::Origin;
 use crate::candidate::{Candidate, CandidateRef, CandidateStore};
 
 /// The read-only world a match is computed against: the map, its runtime,
-/// and every candidate considered so far.
+/// every candidate considered so far, and the observations they anchor.
 ///
 /// Weighers and costing strategies receive one of these rather than bare map
 /// references, so an extension point sees exactly what the built-in pipeline
@@ -14,6 +16,8 @@ where
     N: Network + ?Sized,
 {
     pub candidates: &'a CandidateStore<N::Entry>,
+    /// The observation behind each layer, in layer order.
+    pub origins: &'a [Origin],
     pub map: &'a N,
     pub runtime: &'a N::Runtime,
 }
@@ -27,6 +31,15 @@ where
         self.candidates.candidate(candidate)
     }
 
+    /// The time, in seconds, between the observations behind two layers, if
+    /// both are known. Negative when `to` was observed before `from`.
+    pub fn elapsed(&self, from: LayerId, to: LayerId) -> Option<f64> {
+        let from = self.origins.get(from.index())?;
+        let to = self.origins.get(to.index())?;
+
+        Some((to.timestamp - from.timestamp) as f64 / 1_000_000.0)
+    }
+
     /// Obtain the [edge](Edge), should it exist, between two nodes (specified as ids).
     pub fn edge(&self, a: &N::Entry, b: &N::Entry) -> Option<Edge<N::Entry>> {
         self.map.edge(a, b)
@@ -0,0 +1,128 @@
+use csv::{ReaderBuilder, StringRecord, Trim};
+use geo::Point;
+
+use crate::trace::{Observation, TraceError, Trip, group, number, timestamp};
+
+/// The header names each column is recognised by, compared ignoring case.
+const ID: [&str; 4] = ["id", "trip", "vehicle", "track"];
+const TIME: [&str; 2] = ["timestamp", "time"];
+const LONGITUDE: [&str; 4] = ["lon", "lng", "longitude", "x"];
+const LATITUDE: [&str; 3] = ["lat", "latitude", "y"];
+const HEADING: [&str; 3] = ["heading", "course", "bearing"];
+const SPEED: [&str; 1] = ["speed"];
+const ACCURACY: [&str; 1] = ["accuracy"];
+
+/// Every trip in a delimited file: a header row, then one observation per
+/// row, split on `delimiter`.
+///
+/// Columns are found by their header, ignoring case. Only a longitude
+/// (`lon`, `lng`, `longitude` or `x`) and a latitude (`lat`, `latitude` or
+/// `y`) are required. Rows are grouped into trips by an `id`, `trip`,
+/// `vehicle` or `track` column, or are all one trip without one; a
+/// `timestamp` or `time` column, in seconds since the Unix epoch or as
+/// RFC 3339, times them. A `heading` (or `course`, `bearing`), `speed` and
+/// `accuracy` column are read when present; empty cells are taken as
+/// unknown.
+pub fn parse_delimited(text: &str, delimiter: u8) -> Result<Vec<Trip>, TraceError> {
+    let mut reader = ReaderBuilder::new()
+        .delimiter(delimiter)
+        .trim(Trim::All)
+        .from_reader(text.as_bytes());
+
+    let headers = reader.headers()?.clone();
+    let column = |names: &[&str]| {
+        headers
+            .iter()
+            .position(|header| names.iter().any(|name| header.eq_ignore_ascii_case(name)))
+    };
+
+    let id = column(&ID);
+    let time = column(&TIME);
+    let longitude = column(&LONGITUDE).ok_or(TraceError::MissingField("lon"))?;
+    let latitude = column(&LATITUDE).ok_or(TraceError::MissingField("lat"))?;
+    let heading = column(&HEADING);
+    let speed = column(&SPEED);
+    let accuracy = column(&ACCURACY);
+
+    let mut observations = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+
+        let mut observation = Observation::new(Point::new(
+            number("lon", cell(&record, Some(longitude)).unwrap_or_default())?,
+            number("lat", cell(&record, Some(latitude)).unwrap_or_default())?,
+        ));
+        observation.time = cell(&record, time).map(timestamp).transpose()?;
+        observation.origin.heading = cell(&record, heading)
+            .map(|value| number("heading", value))
+            .transpose()?;
+        observation.origin.speed = cell(&record, speed)
+            .map(|value| number("speed", value))
+            .transpose()?;
+        observation.origin.accuracy = cell(&record, accuracy)
+            .map(|value| number("accuracy", value))
+            .transpose()?;
+
+        let id = cell(&record, id).unwrap_or("0").to_owned();
+        observations.push((id, observation));
+    }
+
+    Ok(group(observations))
+}
+
+/// The non-empty cell of `record` in `column`, if there is one.
+fn cell(record: &StringRecord, column: Option<usize>) -> Option<&str> {
+    record.get(column?).filter(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo::point;
+
+    #[test]
+    fn groups_rows_by_trip() {
+        let trips = parse_delimited(
+            "Vehicle,Timestamp,Lat,Lng,Speed\n\
+             a,20,1,2,\n\
+             b,10,5,5,3.5\n\
+             a,10,3,4,7\n",
+            b',',
+        )
+        .expect("must parse");
+
+        let ids: Vec<_> = trips.iter().map(|trip| trip.id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "b"]);
+
+        let a = &trips[0].origins;
+        assert_eq!(a[0].point, point!(x: 4.0, y: 3.0));
+        assert_eq!(a[0].timestamp, 10_000_000);
+        assert_eq!(a[0].speed, Some(7.0));
+        assert_eq!(a[1].speed, None);
+    }
+
+    #[test]
+    fn reads_tab_separated_rows_without_ids_or_times() {
+        let trips = parse_delimited("x\ty\n1\t2\n3\t4\n", b'\t').expect("must parse");
+
+        assert_eq!(trips.len(), 1);
+        let times: Vec<_> = trips[0].origins.iter().map(|o| o.timestamp).collect();
+        assert_eq!(times, vec![0, 1]);
+    }
+
+    #[test]
+    fn requires_coordinates() {
+        assert!(matches!(
+            parse_delimited("id,time,lon\na,1,2\n", b','),
+            Err(TraceError::MissingField("lat"))
+        ));
+    }
+
+    #[test]
+    fn rejects_unreadable_values() {
+        assert!(matches!(
+            parse_delimited("lon,lat\n1,north\n", b','),
+            Err(TraceError::Invalid { field: "lat", .. })
+        ));
+    }
+}
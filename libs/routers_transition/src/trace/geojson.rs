@@ -0,0 +1,292 @@
+use geo::Point;
+use geojson::{Feature, GeoJson, JsonValue, Value, feature::Id};
+
+use crate::trace::{Observation, TraceError, Trip, group, seconds_to_micros, timestamp};
+
+/// The properties a LineString's per-coordinate times are read from, in the
+/// order they are tried: the plain name, and the one `togeojson` writes for
+/// GPX and KML. Newer versions nest them in `coordinateProperties` instead.
+const TIMES: [&str; 2] = ["times", "coordTimes"];
+
+/// Every trip in a GeoJSON document.
+///
+/// A LineString (or MultiLineString, its lines joined) feature is a trip of
+/// its coordinates, timed by a `times` or `coordTimes` property — or by
+/// `coordinateProperties.times` — holding one time per coordinate, nested
+/// per line for a MultiLineString. A Point feature is one observation, timed
+/// by its `time` or `timestamp` property, carrying any `heading` (or
+/// `course`), `speed` and `accuracy` property along.
+///
+/// Features are grouped into trips by their id: the feature's own, or else
+/// its `id`, `trip` or `vehicle` property. An unnamed line is a trip of its
+/// own, named by its position; unnamed points share one trip. Times are
+/// seconds since the Unix epoch or RFC 3339 date-times. Other geometries are
+/// skipped.
+pub fn parse_geojson(text: &str) -> Result<Vec<Trip>, TraceError> {
+    let features = match text.parse::<GeoJson>().map_err(Box::new)? {
+        GeoJson::FeatureCollection(collection) => collection.features,
+        GeoJson::Feature(feature) => vec![feature],
+        GeoJson::Geometry(geometry) => vec![Feature::from(geometry)],
+    };
+
+    let mut observations = Vec::new();
+    let mut unnamed_points = None;
+
+    for (index, feature) in features.iter().enumerate() {
+        let Some(geometry) = &feature.geometry else {
+            continue;
+        };
+
+        match &geometry.value {
+            Value::Point(position) => {
+                let id = id(feature).unwrap_or_else(|| {
+                    unnamed_points
+                        .get_or_insert_with(|| index.to_string())
+                        .clone()
+                });
+                observations.push((id, point(feature, position)?));
+            }
+            Value::LineString(positions) => {
+                let id = id(feature).unwrap_or_else(|| index.to_string());
+                let times = times(feature)?;
+                line(&id, positions, times, &mut observations)?;
+            }
+            Value::MultiLineString(lines) => {
+                let id = id(feature).unwrap_or_else(|| index.to_string());
+                let positions = lines.concat();
+                let times = times(feature)?.map(flatten);
+                line(&id, &positions, times, &mut observations)?;
+            }
+            other => log::debug!("skipping a {} feature", other.type_name()),
+        }
+    }
+
+    Ok(group(observations))
+}
+
+/// A feature's trip id, if it names one.
+fn id(feature: &Feature) -> Option<String> {
+    match &feature.id {
+        Some(Id::String(id)) => return Some(id.clone()),
+        Some(Id::Number(id)) => return Some(id.to_string()),
+        None => {}
+    }
+
+    ["id", "trip", "vehicle"]
+        .iter()
+        .find_map(|key| match feature.property(key)? {
+            JsonValue::String(id) => Some(id.clone()),
+            JsonValue::Number(id) => Some(id.to_string()),
+            _ => None,
+        })
+}
+
+/// A line feature's per-coordinate times, if it has any.
+fn times(feature: &Feature) -> Result<Option<Vec<JsonValue>>, TraceError> {
+    let nested = feature
+        .property("coordinateProperties")
+        .and_then(|properties| properties.get("times"));
+
+    let Some(times) = TIMES
+        .iter()
+        .find_map(|key| feature.property(key))
+        .or(nested)
+    else {
+        return Ok(None);
+    };
+
+    match times {
+        JsonValue::Array(times) => Ok(Some(times.clone())),
+        other => Err(TraceError::invalid("times", other)),
+    }
+}
+
+/// A MultiLineString's times, nested per line, joined as its lines are.
+fn flatten(times: Vec<JsonValue>) -> Vec<JsonValue> {
+    times
+        .into_iter()
+        .flat_map(|time| match time {
+            JsonValue::Array(line) => line,
+            time => vec![time],
+        })
+        .collect()
+}
+
+/// The observations along a line, timed by `times` when given.
+fn line(
+    id: &str,
+    positions: &[Vec<f64>],
+    times: Option<Vec<JsonValue>>,
+    observations: &mut Vec<(String, Observation)>,
+) -> Result<(), TraceError> {
+    if let Some(times) = &times
+        && times.len() != positions.len()
+    {
+        return Err(TraceError::invalid(
+            "times",
+            format!("{} times for {} coordinates", times.len(), positions.len()),
+        ));
+    }
+
+    for (index, position) in positions.iter().enumerate() {
+        let mut observation = Observation::new(coordinate(position)?);
+        if let Some(times) = &times {
+            observation.time = Some(time(&times[index])?);
+        }
+
+        observations.push((id.to_owned(), observation));
+    }
+
+    Ok(())
+}
+
+/// A Point feature's observation, with whatever its properties say of it.
+fn point(feature: &Feature, position: &[f64]) -> Result<Observation, TraceError> {
+    let mut observation = Observation::new(coordinate(position)?);
+
+    if let Some(value) = ["time", "timestamp"]
+        .iter()
+        .find_map(|key| feature.property(key))
+    {
+        observation.time = Some(time(value)?);
+    }
+
+    let quantity = |field: &'static str, keys: &[&str]| -> Result<Option<f64>, TraceError> {
+        let Some(value) = keys.iter().find_map(|key| feature.property(key)) else {
+            return Ok(None);
+        };
+
+        value
+            .as_f64()
+            .map(Some)
+            .ok_or_else(|| TraceError::invalid(field, value))
+    };
+
+    observation.origin.heading = quantity("heading", &["heading", "course"])?;
+    observation.origin.speed = quantity("speed", &["speed"])?;
+    observation.origin.accuracy = quantity("accuracy", &["accuracy"])?;
+
+    Ok(observation)
+}
+
+fn coordinate(position: &[f64]) -> Result<Point, TraceError> {
+    match position {
+        [x, y, ..] => Ok(Point::new(*x, *y)),
+        _ => Err(TraceError::invalid("position", format!("{position:?}"))),
+    }
+}
+
+fn time(value: &JsonValue) -> Result<i64, TraceError> {
+    match value {
+        JsonValue::String(value) => timestamp(value),
+        JsonValue::Number(seconds) => seconds
+            .as_f64()
+            .and_then(seconds_to_micros)
+            .ok_or_else(|| TraceError::invalid("time", seconds)),
+        other => Err(TraceError::invalid("time", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo::point;
+
+    #[test]
+    fn reads_a_line_with_coordinate_times() {
+        let trips = parse_geojson(
+            r#"{
+                "type": "Feature",
+                "properties": {
+                    "id": "drive",
+                    "coordTimes": ["1970-01-01T00:00:02Z", "1970-01-01T00:00:01Z"]
+                },
+                "geometry": { "type": "LineString", "coordinates": [[1, 2], [3, 4]] }
+            }"#,
+        )
+        .expect("must parse");
+
+        assert_eq!(trips.len(), 1);
+        assert_eq!(trips[0].id, "drive");
+
+        let origins: Vec<_> = trips[0]
+            .origins
+            .iter()
+            .map(|origin| (origin.point, origin.timestamp))
+            .collect();
+        assert_eq!(
+            origins,
+            vec![
+                (point!(x: 3.0, y: 4.0), 1_000_000),
+                (point!(x: 1.0, y: 2.0), 2_000_000)
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_nested_coordinate_properties() {
+        let trips = parse_geojson(
+            r#"{
+                "type": "Feature",
+                "properties": { "coordinateProperties": { "times": [10, 20] } },
+                "geometry": { "type": "LineString", "coordinates": [[1, 2], [3, 4]] }
+            }"#,
+        )
+        .expect("must parse");
+
+        let times: Vec<_> = trips[0].origins.iter().map(|o| o.timestamp).collect();
+        assert_eq!(times, vec![10_000_000, 20_000_000]);
+    }
+
+    #[test]
+    fn groups_points_by_trip() {
+        let trips = parse_geojson(
+            r#"{
+                "type": "FeatureCollection",
+                "features": [
+                    { "type": "Feature", "properties": { "vehicle": "a", "time": 2, "speed": 9.5 },
+                      "geometry": { "type": "Point", "coordinates": [1, 1] } },
+                    { "type": "Feature", "properties": { "vehicle": "b", "time": 1 },
+                      "geometry": { "type": "Point", "coordinates": [5, 5] } },
+                    { "type": "Feature", "properties": { "vehicle": "a", "time": 1 },
+                      "geometry": { "type": "Point", "coordinates": [2, 2] } }
+                ]
+            }"#,
+        )
+        .expect("must parse");
+
+        let ids: Vec<_> = trips.iter().map(|trip| trip.id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "b"]);
+
+        let points: Vec<_> = trips[0].origins.iter().map(|o| o.point).collect();
+        assert_eq!(points, vec![point!(x: 2.0, y: 2.0), point!(x: 1.0, y: 1.0)]);
+        assert_eq!(trips[0].origins[1].speed, Some(9.5));
+    }
+
+    #[test]
+    fn untimed_lines_are_numbered() {
+        let trips =
+            parse_geojson(r#"{ "type": "LineString", "coordinates": [[1, 2], [3, 4], [5, 6]] }"#)
+                .expect("must parse");
+
+        assert_eq!(trips[0].id, "0");
+        let times: Vec<_> = trips[0].origins.iter().map(|o| o.timestamp).collect();
+        assert_eq!(times, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn rejects_mismatched_times() {
+        let result = parse_geojson(
+            r#"{
+                "type": "Feature",
+                "properties": { "times": [1] },
+                "geometry": { "type": "LineString", "coordinates": [[1, 2], [3, 4]] }
+            }"#,
+        );
+
+        assert!(matches!(
+            result,
+            Err(TraceError::Invalid { field: "times", .. })
+        ));
+    }
+}
@@ -0,0 +1,190 @@
+use geo::Point;
+use quick_xml::Reader;
+use quick_xml::events::{BytesStart, Event};
+
+use crate::trace::{Observation, TraceError, Trip, UERE, assemble, number, timestamp};
+
+/// The `<trkpt>` children read into an observation.
+#[derive(Clone, Copy)]
+enum Field {
+    Time,
+    Hdop,
+    Course,
+    Speed,
+}
+
+/// Every `<trk>` in a GPX document, as a [`Trip`] named by its `<name>` or
+/// else its position among the tracks. The segments of a track are joined.
+///
+/// Each `<trkpt>` becomes an observation: its `time`, its `course` as the
+/// heading, its `speed`, and its `hdop` scaled by [`UERE`] as the accuracy.
+/// Elements are matched by local name, so the GPX 1.0 and 1.1 schemas and
+/// namespaced extensions carrying these names are all read.
+pub fn parse_gpx(text: &str) -> Result<Vec<Trip>, TraceError> {
+    let mut reader = Reader::from_str(text);
+    reader.config_mut().trim_text(true);
+
+    let mut trips = Vec::new();
+    let mut track: Option<(Option<String>, Vec<Observation>)> = None;
+    let mut point: Option<Observation> = None;
+    let mut field: Option<Field> = None;
+    let mut naming = false;
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(tag) => match tag.local_name().as_ref() {
+                b"trk" => track = Some((None, Vec::new())),
+                b"trkpt" => point = Some(observation(&tag)?),
+                b"name" => naming = point.is_none(),
+                b"time" => field = Some(Field::Time),
+                b"hdop" => field = Some(Field::Hdop),
+                b"course" => field = Some(Field::Course),
+                b"speed" => field = Some(Field::Speed),
+                _ => {}
+            },
+            Event::Empty(tag) if tag.local_name().as_ref() == b"trkpt" => {
+                if let Some((_, observations)) = &mut track {
+                    observations.push(observation(&tag)?);
+                }
+            }
+            Event::Text(text) => {
+                let text = text.decode().map_err(quick_xml::Error::from)?;
+
+                if naming && let Some((name @ None, _)) = &mut track {
+                    *name = Some(text.into_owned());
+                } else if let (Some(observation), Some(field)) = (&mut point, field) {
+                    match field {
+                        Field::Time => observation.time = Some(timestamp(&text)?),
+                        Field::Hdop => {
+                            observation.origin.accuracy = Some(number("hdop", &text)? * UERE)
+                        }
+                        Field::Course => {
+                            observation.origin.heading = Some(number("course", &text)?)
+                        }
+                        Field::Speed => observation.origin.speed = Some(number("speed", &text)?),
+                    }
+                }
+            }
+            Event::End(tag) => match tag.local_name().as_ref() {
+                b"trkpt" => {
+                    if let (Some((_, observations)), Some(point)) = (&mut track, point.take()) {
+                        observations.push(point);
+                    }
+                }
+                b"trk" => {
+                    if let Some((name, observations)) = track.take() {
+                        let id = name.unwrap_or_else(|| trips.len().to_string());
+                        trips.push(assemble(id, observations));
+                    }
+                }
+                b"name" => naming = false,
+                _ => field = None,
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(trips)
+}
+
+/// A `<trkpt>`'s position, from its `lat` and `lon` attributes.
+fn observation(tag: &BytesStart) -> Result<Observation, TraceError> {
+    let coordinate = |name: &'static str| -> Result<f64, TraceError> {
+        let attribute = tag
+            .try_get_attribute(name)
+            .map_err(quick_xml::Error::from)?
+            .ok_or(TraceError::MissingField(name))?;
+
+        let value = core::str::from_utf8(&attribute.value)
+            .map_err(|_| TraceError::invalid(name, String::from_utf8_lossy(&attribute.value)))?;
+        number(name, value)
+    };
+
+    Ok(Observation::new(Point::new(
+        coordinate("lon")?,
+        coordinate("lat")?,
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo::point;
+
+    const GPX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<gpx version="1.1" creator="test" xmlns="http://www.topografix.com/GPX/1/1">
+  <trk>
+    <name>Morning</name>
+    <trkseg>
+      <trkpt lat="-33.8800" lon="151.1900">
+        <time>2024-05-01T08:00:05Z</time>
+        <hdop>2</hdop>
+        <name>ignored</name>
+      </trkpt>
+      <trkpt lat="-33.8810" lon="151.1910">
+        <time>2024-05-01T08:00:00Z</time>
+        <course>90.5</course>
+        <speed>12</speed>
+      </trkpt>
+    </trkseg>
+    <trkseg>
+      <trkpt lat="-33.8820" lon="151.1920"><time>2024-05-01T08:00:10Z</time></trkpt>
+    </trkseg>
+  </trk>
+  <trk>
+    <trkseg>
+      <trkpt lat="1" lon="2"/>
+      <trkpt lat="3" lon="4"/>
+    </trkseg>
+  </trk>
+</gpx>"#;
+
+    #[test]
+    fn reads_each_track_as_a_trip() {
+        let trips = parse_gpx(GPX).expect("must parse");
+
+        assert_eq!(trips.len(), 2);
+        assert_eq!(trips[0].id, "Morning");
+        assert_eq!(trips[1].id, "1");
+    }
+
+    #[test]
+    fn reads_point_times_and_quality() {
+        let trips = parse_gpx(GPX).expect("must parse");
+        let origins = &trips[0].origins;
+
+        // Ordered by time, across segments.
+        let points: Vec<_> = origins.iter().map(|origin| origin.point).collect();
+        assert_eq!(
+            points,
+            vec![
+                point!(x: 151.1910, y: -33.8810),
+                point!(x: 151.1900, y: -33.8800),
+                point!(x: 151.1920, y: -33.8820),
+            ]
+        );
+
+        assert_eq!(origins[0].timestamp, 1_714_550_400_000_000);
+        assert_eq!(origins[0].heading, Some(90.5));
+        assert_eq!(origins[0].speed, Some(12.0));
+        assert_eq!(origins[1].accuracy, Some(2.0 * UERE));
+    }
+
+    #[test]
+    fn untimed_tracks_are_numbered() {
+        let trips = parse_gpx(GPX).expect("must parse");
+
+        let times: Vec<_> = trips[1].origins.iter().map(|o| o.timestamp).collect();
+        assert_eq!(times, vec![0, 1]);
+    }
+
+    #[test]
+    fn rejects_a_point_without_coordinates() {
+        let gpx = r#"<gpx><trk><trkseg><trkpt lat="1"/></trkseg></trk></gpx>"#;
+        assert!(matches!(
+            parse_gpx(gpx),
+            Err(TraceError::MissingField("lon"))
+        ));
+    }
+}
@@ -0,0 +1,319 @@
+//! Reading recorded trajectories into [`Origin`]s.
+//!
+//! Traces arrive in whatever a device or pipeline happened to write: GPX
+//! tracks, GeoJSON, or delimited rows of one observation each. Every reader
+//! here yields [`Trip`]s, each an ordered run of observations ready for
+//! [`Matcher::extend`](crate::Matcher::extend) or
+//! [`Match::match_origins`](crate::Match::match_origins):
+//!
+//! - [`parse_gpx`] reads every `<trk>` as a trip, with each `<trkpt>`'s
+//!   `time`, `hdop`, `course` and `speed`.
+//! - [`parse_geojson`] reads LineString features, with per-coordinate times,
+//!   and Point features, grouped into trips by their id.
+//! - [`parse_delimited`] reads rows of observations, grouped into trips by
+//!   an id column.
+//!
+//! Recordings are rarely clean, so every trip is [cleaned](Trip::new) the
+//! same way: observations at implausible coordinates are dropped, the rest
+//! ordered by time, and repeated timestamps collapsed to their first
+//! observation. Observations recorded without a time are numbered in file
+//! order instead, as [`Matcher::r#match`](crate::Matcher::r#match) numbers a
+//! bare linestring.
+//!
+//! Requires the `trace` feature.
+use std::path::Path;
+
+use geo::Point;
+use indexmap::IndexMap;
+use thiserror::Error;
+
+use crate::Origin;
+
+mod delimited;
+mod geojson;
+mod gpx;
+
+pub use delimited::parse_delimited;
+pub use geojson::parse_geojson;
+pub use gpx::parse_gpx;
+
+/// The horizontal error, in metres, one unit of dilution of precision
+/// stands for: a typical GPS receiver's user-equivalent range error.
+pub const UERE: f64 = 5.0;
+
+/// A failure to read a trace.
+#[derive(Error, Debug)]
+pub enum TraceError {
+    #[error("malformed GPX: {0}")]
+    Gpx(#[from] quick_xml::Error),
+
+    #[error("malformed GeoJSON: {0}")]
+    GeoJson(#[from] Box<::geojson::Error>),
+
+    #[error("malformed delimited file: {0}")]
+    Delimited(#[from] csv::Error),
+
+    /// A field the reader cannot do without is absent: a column from the
+    /// header, or an attribute from an element.
+    #[error("missing `{0}`")]
+    MissingField(&'static str),
+
+    /// A value could not be read as the quantity it stands for.
+    #[error("invalid {field}: `{value}`")]
+    Invalid { field: &'static str, value: String },
+}
+
+impl TraceError {
+    fn invalid(field: &'static str, value: impl ToString) -> Self {
+        TraceError::Invalid {
+            field,
+            value: value.to_string(),
+        }
+    }
+}
+
+/// One recorded trajectory: its observations, cleaned and in time order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Trip {
+    /// The trip's name in its source, or its position there when unnamed.
+    pub id: String,
+
+    /// The observations, in strictly increasing time order.
+    pub origins: Vec<Origin>,
+
+    /// How many observations cleaning discarded.
+    pub dropped: usize,
+}
+
+impl Trip {
+    /// A trip of `origins`, cleaned: observations at implausible coordinates
+    /// are dropped, the remainder sorted by time (stably, so file order
+    /// breaks ties), and each run of equal timestamps collapsed to its first
+    /// observation.
+    ///
+    /// A coordinate is implausible when it is not finite, lies outside the
+    /// valid longitude and latitude ranges, or sits exactly at `(0, 0)` —
+    /// where receivers report a position they do not have.
+    pub fn new(id: impl Into<String>, origins: Vec<Origin>) -> Self {
+        let observed = origins.len();
+
+        let mut origins = origins
+            .into_iter()
+            .filter(|origin| plausible(origin.point))
+            .collect::<Vec<_>>();
+        origins.sort_by_key(|origin| origin.timestamp);
+        origins.dedup_by_key(|origin| origin.timestamp);
+
+        Trip {
+            id: id.into(),
+            dropped: observed - origins.len(),
+            origins,
+        }
+    }
+}
+
+fn plausible(point: Point) -> bool {
+    let (x, y) = point.x_y();
+
+    x.is_finite()
+        && y.is_finite()
+        && (-180.0..=180.0).contains(&x)
+        && (-90.0..=90.0).contains(&y)
+        && (x, y) != (0.0, 0.0)
+}
+
+/// The file formats a trace can be read from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TraceFormat {
+    Gpx,
+    GeoJson,
+    /// Delimited rows, split on the given byte.
+    Delimited(u8),
+}
+
+impl TraceFormat {
+    /// The format a file's extension names, if any: `.gpx`, `.geojson` or
+    /// `.json`, `.csv`, and `.tsv`.
+    pub fn detect(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "gpx" => Some(TraceFormat::Gpx),
+            "geojson" | "json" => Some(TraceFormat::GeoJson),
+            "csv" => Some(TraceFormat::Delimited(b',')),
+            "tsv" => Some(TraceFormat::Delimited(b'\t')),
+            _ => None,
+        }
+    }
+
+    /// Every trip in `text`, read as this format.
+    pub fn parse(self, text: &str) -> Result<Vec<Trip>, TraceError> {
+        match self {
+            TraceFormat::Gpx => parse_gpx(text),
+            TraceFormat::GeoJson => parse_geojson(text),
+            TraceFormat::Delimited(delimiter) => parse_delimited(text, delimiter),
+        }
+    }
+}
+
+/// An observation read from a source, before its trip is assembled.
+struct Observation {
+    origin: Origin,
+    time: Option<i64>,
+}
+
+impl Observation {
+    fn new(point: Point) -> Self {
+        Observation {
+            origin: Origin::new(point, 0),
+            time: None,
+        }
+    }
+}
+
+/// Assemble a trip from observations in file order. The trip keeps their
+/// times only if every observation has one; otherwise it is numbered in file
+/// order, since a partly-timed trip cannot be ordered by time.
+fn assemble(id: impl Into<String>, observations: Vec<Observation>) -> Trip {
+    let timed = observations
+        .iter()
+        .all(|observation| observation.time.is_some());
+
+    let origins = observations
+        .into_iter()
+        .enumerate()
+        .map(|(index, observation)| Origin {
+            timestamp: if timed {
+                observation.time.unwrap_or_default()
+            } else {
+                index as i64
+            },
+            ..observation.origin
+        })
+        .collect();
+
+    Trip::new(id, origins)
+}
+
+/// Assemble one trip per key, in order of each key's first appearance.
+fn group(observations: impl IntoIterator<Item = (String, Observation)>) -> Vec<Trip> {
+    let mut trips: IndexMap<String, Vec<Observation>> = IndexMap::new();
+    for (id, observation) in observations {
+        trips.entry(id).or_default().push(observation);
+    }
+
+    trips
+        .into_iter()
+        .map(|(id, observations)| assemble(id, observations))
+        .collect()
+}
+
+/// A timestamp in microseconds since the Unix epoch, from either a number
+/// of seconds since it or an RFC 3339 date-time.
+fn timestamp(value: &str) -> Result<i64, TraceError> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<f64>() {
+        return seconds_to_micros(seconds).ok_or_else(|| TraceError::invalid("time", value));
+    }
+
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|time| time.timestamp_micros())
+        .map_err(|_| TraceError::invalid("time", value))
+}
+
+fn seconds_to_micros(seconds: f64) -> Option<i64> {
+    seconds
+        .is_finite()
+        .then(|| (seconds * 1_000_000.0).round() as i64)
+}
+
+/// A number, named `field` should it not parse.
+fn number(field: &'static str, value: &str) -> Result<f64, TraceError> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| TraceError::invalid(field, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo::point;
+
+    fn at(x: f64, y: f64, timestamp: i64) -> Origin {
+        Origin::new(point!(x: x, y: y), timestamp)
+    }
+
+    #[test]
+    fn trips_are_ordered_by_time() {
+        let trip = Trip::new(
+            "t",
+            vec![at(1.0, 1.0, 30), at(2.0, 2.0, 10), at(3.0, 3.0, 20)],
+        );
+
+        let times: Vec<_> = trip.origins.iter().map(|o| o.timestamp).collect();
+        assert_eq!(times, vec![10, 20, 30]);
+        assert_eq!(trip.dropped, 0);
+    }
+
+    #[test]
+    fn repeated_timestamps_keep_the_first_observation() {
+        let trip = Trip::new(
+            "t",
+            vec![at(1.0, 1.0, 10), at(2.0, 2.0, 10), at(3.0, 3.0, 20)],
+        );
+
+        assert_eq!(trip.origins, vec![at(1.0, 1.0, 10), at(3.0, 3.0, 20)]);
+        assert_eq!(trip.dropped, 1);
+    }
+
+    #[test]
+    fn implausible_coordinates_are_dropped() {
+        let trip = Trip::new(
+            "t",
+            vec![
+                at(0.0, 0.0, 0),
+                at(181.0, 1.0, 1),
+                at(1.0, -91.0, 2),
+                at(f64::NAN, 1.0, 3),
+                at(151.2, -33.9, 4),
+            ],
+        );
+
+        assert_eq!(trip.origins, vec![at(151.2, -33.9, 4)]);
+        assert_eq!(trip.dropped, 4);
+    }
+
+    #[test]
+    fn partly_timed_trips_are_numbered_in_file_order() {
+        let observations = vec![
+            Observation {
+                time: Some(50),
+                ..Observation::new(point!(x: 1.0, y: 1.0))
+            },
+            Observation::new(point!(x: 2.0, y: 2.0)),
+        ];
+
+        let trip = assemble("t", observations);
+        assert_eq!(trip.origins, vec![at(1.0, 1.0, 0), at(2.0, 2.0, 1)]);
+    }
+
+    #[test]
+    fn timestamps_read_seconds_and_rfc3339() {
+        assert_eq!(timestamp("1.5").unwrap(), 1_500_000);
+        assert_eq!(timestamp("1970-01-01T00:00:02.25Z").unwrap(), 2_250_000);
+        assert!(timestamp("yesterday").is_err());
+    }
+
+    #[test]
+    fn formats_are_detected_by_extension() {
+        assert_eq!(
+            TraceFormat::detect(Path::new("a.GPX")),
+            Some(TraceFormat::Gpx)
+        );
+        assert_eq!(
+            TraceFormat::detect(Path::new("a.tsv")),
+            Some(TraceFormat::Delimited(b'\t'))
+        );
+        assert_eq!(TraceFormat::detect(Path::new("a.kml")), None);
+    }
+}
@@ -8,14 +8,15 @@ use rustc_hash::FxHashMap;
 
 use crate::{
     candidate::{Candidate, CandidateRef},
-    primitives::{PredicateCache, Reachable, RoutingContext},
+    primitives::{PredicateCache, Reachable, RoutingContext, Step},
 };
 
-/// A parent-pointer map — each node mapped to the parent it was reached from —
+/// A parent-pointer map — each step mapped to the parent it was reached from —
 /// as produced by the predicate cache's bounded Dijkstra.
 trait ParentPath<K> {
-    /// The nodes from `root` to `leaf` inclusive, followed via parent pointers,
-    /// or `None` if `leaf` is absent from the map.
+    /// The steps from `root` to `leaf` inclusive, followed via parent pointers,
+    /// or `None` if `leaf` is absent from the map. The root is never its own
+    /// parent, so a `leaf` equal to `root` is a loop back around to it.
     fn path(&self, root: &K, leaf: &K) -> Option<Vec<K>>;
 }
 
@@ -24,17 +25,17 @@ where
     K: Eq + Hash + Copy,
 {
     fn path(&self, root: &K, leaf: &K) -> Option<Vec<K>> {
-        let mut nodes = vec![*leaf];
-        let mut cursor = leaf;
+        let mut steps = vec![*leaf];
+        let mut cursor = self.get(leaf)?;
 
         while cursor != root {
-            let parent = self.get(cursor)?;
-            nodes.push(*parent);
-            cursor = parent;
+            steps.push(*cursor);
+            cursor = self.get(cursor)?;
         }
 
-        nodes.reverse();
-        Some(nodes)
+        steps.push(*root);
+        steps.reverse();
+        Some(steps)
     }
 }
 
@@ -76,22 +77,21 @@ where
     }
 
     /// The road edges linking `source`'s edge to `target`'s edge, walked from the
-    /// bounded-Dijkstra predicate map rooted at `source`'s edge target.
+    /// bounded-Dijkstra predicate map rooted at `source`'s edge. Both ends are
+    /// excluded, being the candidates' own edges.
     fn route(
         &self,
         source: &Candidate<N::Entry>,
         target: &Candidate<N::Entry>,
     ) -> Option<Vec<Edge<N::Entry>>> {
-        let parents = self.predicate.query(self.ctx, source.edge.target);
-        let nodes = parents.path(&source.edge.target, &target.edge.source)?;
+        let root = Step::from(&source.edge);
+        let parents = self.predicate.query(self.ctx, root);
+        let steps = parents.path(&root, &Step::from(&target.edge))?;
 
         Some(
-            nodes
-                .windows(2)
-                .filter_map(|pair| match pair {
-                    [a, b] => self.ctx.edge(a, b),
-                    _ => None,
-                })
+            steps[1..steps.len() - 1]
+                .iter()
+                .filter_map(|step| self.ctx.edge(&step.source, &step.target))
                 .collect(),
         )
     }
@@ -4,12 +4,14 @@
 extern crate alloc;
 
 use alloc::sync::Arc;
+use core::time::Duration;
 use geo::{LineString, point, wkt};
 use routers_network::mock::{MockMetadata, MockNetwork, MockNetworkBuilder};
 use routers_network::{DataPlane, Direction, Metadata};
+use routers_transition::candidate::{Segment, Segments};
 use routers_transition::primitives::PredicateCache;
 use routers_transition::weigh::SolverVariant;
-use routers_transition::{Match, MatchOptions, MatchSimpleExt};
+use routers_transition::{Breakage, Match, MatchError, MatchOptions, MatchSimpleExt, Origin};
 use uom::si::f64::Length;
 use uom::si::length::meter;
 
@@ -403,6 +405,133 @@ fn repeated_matches_are_deterministic() {
     );
 }
 
+/// Runners-up ride along with the best match, dearer and in order, without
+/// changing the best match itself.
+#[test]
+fn alternatives_rank_behind_the_best_match() {
+    let net = MockNetworkBuilder::new()
+        .node(1, point!(x: -118.10, y: 34.15))
+        .node(2, point!(x: -118.13, y: 34.15))
+        .node(3, point!(x: -118.16, y: 34.15))
+        .node(4, point!(x: -118.13, y: 34.12))
+        .bidirectional_edge(1, 2)
+        .bidirectional_edge(2, 3)
+        .bidirectional_edge(2, 4)
+        .build();
+    let ls: LineString = wkt! {
+        LINESTRING(-118.111 34.1503, -118.121 34.1503, -118.131 34.1503, -118.141 34.1503)
+    };
+
+    let best = net
+        .match_simple(ls.clone())
+        .expect("map match must succeed");
+    assert!(best.alternatives.is_empty(), "none were asked for");
+
+    let ranked = net
+        .r#match(ls, MatchOptions::new().with_alternatives(3))
+        .expect("map match must succeed");
+    assert_eq!(ranked.cost, best.cost);
+    assert_eq!(ranked.discretized.len(), best.discretized.len());
+    assert_eq!(ranked.alternatives.len(), 3);
+
+    let mut previous = ranked.cost;
+    for alternative in &ranked.alternatives {
+        assert!(alternative.cost >= previous, "alternatives must be ranked");
+        assert_eq!(alternative.discretized.len(), best.discretized.len());
+        previous = alternative.cost;
+    }
+}
+
+/// Once scored, every discretized point carries a probability; the
+/// interpolated filler between them, which was never a candidate, carries
+/// none.
+#[test]
+fn discretized_points_carry_their_confidence() {
+    let net = straight_road();
+    let linestring: LineString = wkt! {
+        LINESTRING(-118.151 34.1503, -118.155 34.1503, -118.158 34.1503, -118.165 34.1503)
+    };
+
+    let result = net
+        .r#match(linestring, MatchOptions::new().with_confidence(true))
+        .expect("map match must succeed");
+
+    for element in &result.discretized.elements {
+        let confidence = element
+            .confidence
+            .expect("discretized points carry confidence");
+        assert!(confidence > 0.5 && confidence <= 1.0, "got {confidence}");
+    }
+    assert!(
+        result.interpolated.iter().all(|e| e.confidence.is_none()),
+        "interpolated points carry no confidence"
+    );
+}
+
+/// Scoring confidence is opt-in, so by default no point carries any.
+#[test]
+fn confidence_is_only_scored_when_asked() {
+    let net = straight_road();
+    let linestring: LineString = wkt! {
+        LINESTRING(-118.151 34.1503, -118.155 34.1503, -118.165 34.1503)
+    };
+
+    let matched = net
+        .match_simple(linestring.clone())
+        .expect("map match must succeed");
+    let snapped = net.snap_simple(linestring).expect("snap must succeed");
+
+    for result in [matched, snapped] {
+        assert!(
+            result.discretized.iter().all(|e| e.confidence.is_none()),
+            "unscored points carry no confidence"
+        );
+    }
+}
+
+/// A trace midway between two identical parallel roads is a coin-flip, and
+/// is reported as one; the same trace hugging one road is not.
+#[test]
+fn confidence_falls_between_parallel_roads() {
+    let net = MockNetworkBuilder::new()
+        .node(1, point!(x: -118.15, y: 34.1500))
+        .node(2, point!(x: -118.16, y: 34.1500))
+        .node(3, point!(x: -118.17, y: 34.1500))
+        .node(4, point!(x: -118.15, y: 34.1504))
+        .node(5, point!(x: -118.16, y: 34.1504))
+        .node(6, point!(x: -118.17, y: 34.1504))
+        .edge(1, 2)
+        .edge(2, 3)
+        .edge(4, 5)
+        .edge(5, 6)
+        .build();
+
+    let confidence = |latitude: f64| {
+        let linestring = LineString::from(vec![
+            (-118.161, latitude),
+            (-118.164, latitude),
+            (-118.167, latitude),
+        ]);
+
+        net.r#match(linestring, MatchOptions::new().with_confidence(true))
+            .expect("map match must succeed")
+            .discretized
+            .iter()
+            .map(|e| e.confidence.expect("discretized points carry confidence"))
+            .collect::<Vec<_>>()
+    };
+
+    for c in confidence(34.1502) {
+        assert!(
+            (c - 0.5).abs() < 0.05,
+            "midway should be a coin-flip, got {c}"
+        );
+    }
+    for c in confidence(34.15002) {
+        assert!(c > 0.9, "hugging one road should be near-certain, got {c}");
+    }
+}
+
 /// Sanity: mock metadata is accessible in every direction (guards trait wiring).
 #[test]
 fn mock_metadata_accessible() {
@@ -508,3 +637,390 @@ fn shared_cache_is_reused_across_matches() {
     assert!(first.is_some(), "8km reach must bridge 5.5km");
     assert_eq!(first, second, "a warm cache changed the answer");
 }
+
+/// A snap moves every position onto the road and reports it once in each view:
+/// no routed geometry is interleaved between the snapped points, and only the
+/// discretized view carries confidence, once scored.
+#[test]
+fn snap_is_one_to_one_with_the_input() {
+    let net = straight_road();
+    let linestring: LineString = wkt! {
+        LINESTRING(-118.151 34.1503, -118.155 34.1503, -118.160 34.1503, -118.165 34.1503)
+    };
+
+    let result = net
+        .snap(linestring, MatchOptions::new().with_confidence(true))
+        .expect("snap must succeed");
+
+    assert_eq!(result.discretized.elements.len(), 4);
+    assert_eq!(result.interpolated.elements.len(), 4);
+    for (snapped, interpolated) in result.discretized.iter().zip(result.interpolated.iter()) {
+        assert_eq!(snapped.point, interpolated.point);
+        assert!(
+            (snapped.point.y - 34.15).abs() < 1e-6,
+            "snapped points must lie on the road"
+        );
+        assert!(
+            snapped.confidence.is_some(),
+            "snapped points carry confidence"
+        );
+        assert!(
+            interpolated.confidence.is_none(),
+            "interpolated points carry no confidence"
+        );
+    }
+}
+
+/// Snapping never routes, so components the matcher cannot bridge still snap.
+#[test]
+fn snap_ignores_disconnected_components() {
+    let net = MockNetworkBuilder::new()
+        .node(1, point!(x: -118.150, y: 34.150))
+        .node(2, point!(x: -118.151, y: 34.150))
+        .edge(1, 2)
+        .node(3, point!(x: -118.100, y: 34.150))
+        .node(4, point!(x: -118.101, y: 34.150))
+        .edge(3, 4)
+        .build();
+
+    let ls: LineString = wkt! { LINESTRING(-118.1505 34.1503, -118.1005 34.1503) };
+    let result = net.snap_simple(ls).expect("snap must succeed");
+
+    let edges: Vec<(i64, i64)> = result
+        .discretized
+        .iter()
+        .map(|e| (e.edge.source.id.0, e.edge.target.id.0))
+        .collect();
+    assert_eq!(edges, vec![(1, 2), (3, 4)]);
+}
+
+/// Snapping prefers staying on one edge over hopping to a parallel road that is
+/// only marginally closer for a single position.
+#[test]
+fn snap_prefers_continuity() {
+    let net = MockNetworkBuilder::new()
+        .node(1, point!(x: -118.150, y: 34.1500))
+        .node(2, point!(x: -118.160, y: 34.1500))
+        .node(3, point!(x: -118.150, y: 34.1502))
+        .node(4, point!(x: -118.160, y: 34.1502))
+        .edge(1, 2)
+        .edge(3, 4)
+        .build();
+
+    // Every position sits just south of the midline, except the middle one.
+    let ls: LineString = wkt! {
+        LINESTRING(-118.152 34.15008, -118.155 34.15011, -118.158 34.15008)
+    };
+    let result = net.snap_simple(ls).expect("snap must succeed");
+
+    let sources: Vec<i64> = result
+        .discretized
+        .iter()
+        .map(|e| e.edge.source.id.0)
+        .collect();
+    assert_eq!(sources, vec![1, 1, 1]);
+}
+
+/// An empty trajectory must error when snapped, as it does when matched.
+#[test]
+fn snap_empty_trajectory_errors() {
+    let net = straight_road();
+    assert!(net.snap_simple(LineString::new(vec![])).is_err());
+}
+
+/// Every segment's match, failing the test at a piece which did not match.
+fn matched<T>(segments: Segments<T>) -> Vec<Segment<T>> {
+    segments
+        .into_iter()
+        .map(|segment| segment.map(|path| path.expect("every piece must match")))
+        .collect()
+}
+
+/// Two components no route links, each carrying part of the trajectory.
+fn split_components() -> (MockNetwork, LineString) {
+    let net = MockNetworkBuilder::new()
+        .node(1, point!(x: -118.150, y: 34.150))
+        .node(2, point!(x: -118.155, y: 34.150))
+        .edge(1, 2)
+        .node(3, point!(x: -118.100, y: 34.150))
+        .node(4, point!(x: -118.105, y: 34.150))
+        .edge(3, 4)
+        .build();
+
+    let ls: LineString = wkt! {
+        LINESTRING(-118.151 34.1503, -118.153 34.1503, -118.101 34.1503, -118.103 34.1503)
+    };
+    (net, ls)
+}
+
+/// Split mode cuts the trajectory at the break and matches both sides.
+#[test]
+fn split_mode_matches_each_segment() {
+    let (net, ls) = split_components();
+
+    let segments = matched(
+        net.match_segments(ls, MatchOptions::new().with_split(true))
+            .expect("split match must succeed"),
+    );
+
+    assert_eq!(segments.len(), 2);
+    assert_eq!(segments[0].layers, 0..2);
+    assert_eq!(segments[1].layers, 2..4);
+
+    let edges = |index: usize| -> Vec<(i64, i64)> {
+        segments[index]
+            .path
+            .discretized
+            .iter()
+            .map(|e| (e.edge.source.id.0, e.edge.target.id.0))
+            .collect()
+    };
+    assert_eq!(edges(0), vec![(1, 2), (1, 2)]);
+    assert_eq!(edges(1), vec![(3, 4), (3, 4)]);
+}
+
+/// Without split mode a break still fails the piece it falls in.
+#[test]
+fn segments_without_split_fail_at_a_break() {
+    let (net, ls) = split_components();
+
+    let segments = net
+        .match_segments(ls, MatchOptions::new())
+        .expect("an unempty input must be reported");
+
+    assert_eq!(segments.len(), 1);
+    assert_eq!(segments[0].layers, 0..4);
+    assert!(matches!(segments[0].path, Err(MatchError::Disconnected(_))));
+}
+
+/// A piece which fails is reported by its own segment, counted from the start
+/// of the input, while the pieces around it still match.
+#[test]
+fn failed_pieces_keep_the_pieces_around_them() {
+    let (net, _) = split_components();
+    let ls: LineString = wkt! {
+        LINESTRING(-118.151 34.1503, -118.153 34.1503, -118.151 34.250, -118.101 34.1503, -118.103 34.1503)
+    };
+    let opts = MatchOptions::new().with_breakage(Breakage::new().with_distance(m(5_000.0)));
+
+    let segments = net
+        .match_segments(ls, opts)
+        .expect("an unempty input must be reported");
+
+    let layers: Vec<_> = segments.iter().map(|s| s.layers.clone()).collect();
+    assert_eq!(layers, vec![0..2, 2..3, 3..5]);
+    assert!(segments[0].path.is_ok());
+    assert!(segments[2].path.is_ok());
+
+    let Err(MatchError::Unanchored(error)) = &segments[1].path else {
+        panic!("the off-network piece must be unanchored");
+    };
+    let unanchored: Vec<_> = error.points.iter().map(|point| point.layer).collect();
+    assert_eq!(unanchored, vec![2]);
+}
+
+/// An unbroken trajectory is a single segment covering the whole input.
+#[test]
+fn split_mode_keeps_a_connected_trip_whole() {
+    let net = straight_road();
+    let ls: LineString = wkt! {
+        LINESTRING(-118.151 34.1503, -118.158 34.1503, -118.165 34.1503)
+    };
+
+    let segments = matched(
+        net.match_segments(ls, MatchOptions::new().with_split(true))
+            .expect("split match must succeed"),
+    );
+
+    assert_eq!(segments.len(), 1);
+    assert_eq!(segments[0].layers, 0..3);
+    assert_eq!(segments[0].path.discretized.len(), 3);
+}
+
+/// A breakage distance cuts the trajectory before solving, matching each piece
+/// on its own even where a route could bridge them.
+#[test]
+fn breakage_distance_matches_each_piece() {
+    let net = straight_road();
+    let ls: LineString = wkt! {
+        LINESTRING(-118.151 34.1503, -118.152 34.1503, -118.168 34.1503, -118.169 34.1503)
+    };
+    let opts = MatchOptions::new().with_breakage(Breakage::new().with_distance(m(500.0)));
+
+    let segments = matched(
+        net.match_segments(ls, opts)
+            .expect("broken match must succeed"),
+    );
+
+    assert_eq!(segments.len(), 2);
+    assert_eq!(segments[0].layers, 0..2);
+    assert_eq!(segments[1].layers, 2..4);
+    for segment in &segments {
+        assert_eq!(segment.path.discretized.len(), 2);
+    }
+}
+
+/// Timed observations break where too long passes between them, even where
+/// a route could bridge the gap.
+#[test]
+fn breakage_time_splits_timed_observations() {
+    let net = straight_road();
+    let origins = [
+        (-118.151, 0),
+        (-118.152, 10),
+        (-118.154, 600),
+        (-118.155, 610),
+    ]
+    .map(|(x, seconds)| Origin::new(point!(x: x, y: 34.1503), seconds * 1_000_000));
+    let opts =
+        MatchOptions::new().with_breakage(Breakage::new().with_interval(Duration::from_secs(60)));
+
+    let segments = matched(
+        net.match_origins(&origins, opts)
+            .expect("timed match must succeed"),
+    );
+
+    let layers: Vec<_> = segments.iter().map(|s| s.layers.clone()).collect();
+    assert_eq!(layers, vec![0..2, 2..4]);
+}
+
+/// Breakage also applies to snapping, with one snapped piece per segment.
+#[test]
+fn breakage_distance_splits_snaps() {
+    let net = straight_road();
+    let ls: LineString = wkt! {
+        LINESTRING(-118.151 34.1503, -118.152 34.1503, -118.168 34.1503)
+    };
+    let opts = MatchOptions::new().with_breakage(Breakage::new().with_distance(m(500.0)));
+
+    let segments = net.snap_segments(ls, opts).expect("snap must succeed");
+
+    let layers: Vec<_> = segments.iter().map(|s| s.layers.clone()).collect();
+    assert_eq!(layers, vec![0..2, 2..3]);
+}
+
+/// A junction whose left turn (1 -> 2 -> 3) has a block-sized detour around
+/// it through 4 and 5, optionally forbidding the direct turn.
+fn restricted_junction(forbid: bool) -> MockNetwork {
+    let builder = MockNetworkBuilder::new()
+        .node(1, point!(x: -118.100, y: 34.150))
+        .node(2, point!(x: -118.102, y: 34.150))
+        .node(3, point!(x: -118.102, y: 34.152))
+        .node(4, point!(x: -118.104, y: 34.150))
+        .node(5, point!(x: -118.104, y: 34.152))
+        .bidirectional_edge(1, 2)
+        .bidirectional_edge(2, 3)
+        .bidirectional_edge(2, 4)
+        .bidirectional_edge(4, 5)
+        .bidirectional_edge(5, 3);
+
+    match forbid {
+        true => builder.forbid_turn(1, 2, 3).build(),
+        false => builder.build(),
+    }
+}
+
+/// Whether the interpolated route of a trip from road 1-2 onto road 2-3
+/// takes the turn 1 -> 2 -> 3 directly.
+fn turns_directly(net: &MockNetwork) -> bool {
+    let ls: LineString = wkt! { LINESTRING(-118.1002 34.15003, -118.10203 34.1516) };
+    let result = net.match_simple(ls).expect("map match must succeed");
+
+    result.interpolated.elements.windows(2).any(|pair| {
+        let (a, b) = (&pair[0].edge, &pair[1].edge);
+        (a.source.id.0, a.target.id.0, b.target.id.0) == (1, 2, 3)
+    })
+}
+
+/// A forbidden turn is never routed through, even where it is the shortest
+/// way between two candidates.
+#[test]
+fn forbidden_turn_is_routed_around() {
+    assert!(turns_directly(&restricted_junction(false)));
+    assert!(!turns_directly(&restricted_junction(true)));
+}
+
+/// A residential street zig-zagging east from node 1 to node 6.
+const ZIGZAG: &str = r#"<osmChange version="0.6"><create>
+  <node id="1" lat="34.150" lon="-118.150"/>
+  <node id="2" lat="34.1505" lon="-118.151"/>
+  <node id="3" lat="34.150" lon="-118.152"/>
+  <node id="4" lat="34.1505" lon="-118.153"/>
+  <node id="5" lat="34.150" lon="-118.154"/>
+  <node id="6" lat="34.1505" lon="-118.155"/>
+  <way id="10">
+    <nd ref="1"/><nd ref="2"/><nd ref="3"/><nd ref="4"/><nd ref="5"/><nd ref="6"/>
+    <tag k="highway" v="residential"/>
+  </way>
+</create></osmChange>"#;
+
+/// Simplifying a network merges the street into one edge, but candidates
+/// still project onto its bends and the interpolated route still follows
+/// them.
+#[test]
+fn map_match_follows_simplified_shapes() {
+    use routers_codec::osm::{OsmChange, OsmNetwork};
+
+    let change = OsmChange::parse(ZIGZAG).expect("must parse");
+    let mut plain = OsmNetwork::default();
+    plain.apply(&change).expect("must apply");
+    let mut simple = OsmNetwork::default();
+    simple.apply(&change).expect("must apply");
+    assert_eq!(simple.simplify(), 4);
+
+    let trip: LineString = wkt! {
+        LINESTRING(-118.1505 34.15028, -118.1525 34.15022, -118.1545 34.15028)
+    };
+    let before = plain.match_simple(trip.clone()).expect("must match");
+    let after = simple.match_simple(trip).expect("must match");
+
+    let positions = |path: &routers_transition::candidate::Path<_, _>| {
+        path.elements.iter().map(|e| e.point).collect::<Vec<_>>()
+    };
+    assert_eq!(
+        positions(&before.discretized),
+        positions(&after.discretized)
+    );
+    assert_eq!(
+        positions(&before.interpolated),
+        positions(&after.interpolated)
+    );
+}
+
+/// The collapsed path's own geometry, which the realtime layers and the
+/// exporters draw from, follows the simplified street's bends too.
+#[test]
+fn collapsed_geometry_follows_simplified_shapes() {
+    use routers_codec::osm::{OsmChange, OsmEntryId, OsmNetwork};
+    use routers_transition::Matcher;
+    use routers_transition::costing::CostingStrategies;
+    use routers_transition::layer::generation::StandardGenerator;
+    use routers_transition::weigh::AllCompute;
+
+    let change = OsmChange::parse(ZIGZAG).expect("must parse");
+    let mut plain = OsmNetwork::default();
+    plain.apply(&change).expect("must apply");
+    let mut simple = OsmNetwork::default();
+    simple.apply(&change).expect("must apply");
+    assert_eq!(simple.simplify(), 4);
+
+    let trip: LineString = wkt! {
+        LINESTRING(-118.1505 34.15028, -118.1525 34.15022, -118.1545 34.15028)
+    };
+    let interpolated = |net: &OsmNetwork| {
+        let costing = CostingStrategies::<_, _, OsmEntryId>::default();
+        let generator = StandardGenerator::new(net, &costing.emission);
+        let runtime = <OsmNetwork as DataPlane>::Meta::default_runtime();
+        let matcher = Matcher::new(net, &costing, generator, AllCompute::default(), &runtime);
+
+        matcher
+            .r#match(trip.clone())
+            .expect("must match")
+            .interpolated(net)
+    };
+
+    let (before, after) = (interpolated(&plain), interpolated(&simple));
+    assert_eq!(before, after);
+    // Every bend between the first and last matched positions is kept.
+    assert!(before.0.len() >= 5);
+}
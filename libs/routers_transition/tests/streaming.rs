@@ -9,7 +9,7 @@ use routers_transition::costing::{CostingStrategies, DefaultEmissionCost, Defaul
 use routers_transition::layer::generation::StandardGenerator;
 use routers_transition::matcher::Trip;
 use routers_transition::weigh::AllCompute;
-use routers_transition::{Continuation, MatchError, Matcher, Origin};
+use routers_transition::{Continuation, Lag, MatchError, Matcher, Origin};
 
 type Costing = CostingStrategies<DefaultEmissionCost, DefaultTransitionCost, MockEntryId>;
 
@@ -541,3 +541,90 @@ fn trip_accessors_are_layer_indexed() {
     let trellis = trip.trellis().expect("trellis exists after first layer");
     assert_eq!(trellis.widths(), &[layer.len() as u32]);
 }
+
+/// With a fixed lag, no layer stays provisional further behind the newest
+/// than the lag allows, and a committed choice survives every later
+/// observation — including once the trip is cut behind it.
+#[test]
+fn lagged_commit_is_final() {
+    let net = bent_road();
+    let costing = Costing::default();
+    let generator = StandardGenerator::new(&net, &costing.emission);
+    let m = Matcher::new(&net, &costing, generator, AllCompute::default(), &())
+        .with_lag(Lag::new().with_layers(2));
+
+    let mut trip = m.begin();
+    let mut anchor = None;
+    for origin in origins_of(&trajectory().into_points()) {
+        m.push(&mut trip, origin).expect("push must anchor");
+        let matched = m
+            .snapshot(&mut trip)
+            .expect("snapshot must succeed")
+            .collapsed()
+            .into_points();
+
+        if let Some(anchor) = anchor {
+            assert_eq!(matched[0], anchor, "a committed choice must not move");
+        }
+
+        let Some(point) = m.commit(&mut trip).expect("commit must succeed") else {
+            assert!(trip.layers() <= 2, "only a short trip may commit nothing");
+            continue;
+        };
+        assert!(trip.committed() > point.index());
+        assert!(
+            trip.layers() - trip.committed() <= 2,
+            "no layer may stay provisional beyond the lag"
+        );
+
+        trip.tail(trip.layers() - point.index());
+        assert_eq!(trip.committed(), 1, "the commit point stays as the anchor");
+        anchor = Some(matched[point.index()]);
+    }
+}
+
+/// Two identical parallel roads: the paths along each never fuse, so
+/// convergence alone would never commit — the lag does.
+#[test]
+fn lag_commits_without_convergence() {
+    let net = MockNetworkBuilder::new()
+        .node(1, point!(x: -118.15, y: 34.1500))
+        .node(2, point!(x: -118.17, y: 34.1500))
+        .node(3, point!(x: -118.15, y: 34.1504))
+        .node(4, point!(x: -118.17, y: 34.1504))
+        .edge(1, 2)
+        .edge(3, 4)
+        .build();
+    let costing = Costing::default();
+    let generator = || StandardGenerator::new(&net, &costing.emission);
+
+    let origins = origins_of(&[
+        point!(x: -118.155, y: 34.1502),
+        point!(x: -118.160, y: 34.1502),
+        point!(x: -118.165, y: 34.1502),
+    ]);
+
+    let unlagged = Matcher::new(&net, &costing, generator(), AllCompute::default(), &());
+    let lagged = Matcher::new(&net, &costing, generator(), AllCompute::default(), &())
+        .with_lag(Lag::new().with_layers(1));
+
+    let mut trip = unlagged.begin();
+    for &origin in &origins {
+        unlagged.push(&mut trip, origin).expect("push must anchor");
+    }
+
+    assert_eq!(
+        unlagged.commit(&mut trip).expect("commit must succeed"),
+        None
+    );
+    assert_eq!(trip.committed(), 0);
+
+    let point = lagged.commit(&mut trip).expect("commit must succeed");
+    assert_eq!(point, Some(routers_transition::LayerId(1)));
+    assert_eq!(trip.committed(), 2);
+    let converged = lagged.convergence(&trip).expect("convergence must succeed");
+    assert!(
+        converged >= Some(routers_transition::LayerId(1)),
+        "every path now runs through the committed layer"
+    );
+}
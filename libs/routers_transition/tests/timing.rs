@@ -0,0 +1,79 @@
+//! Time-aware transition costing: a route is only as plausible as the speed
+//! it implies between the observations either side of it.
+
+use geo::{Point, point, wkt};
+use routers_network::mock::{MockEntryId, MockNetwork, MockNetworkBuilder};
+use routers_transition::costing::{
+    CostingStrategies, DefaultEmissionCost, DefaultTransitionCost, TimedTransitionCost,
+};
+use routers_transition::layer::generation::StandardGenerator;
+use routers_transition::weigh::AllCompute;
+use routers_transition::{Matcher, Origin};
+
+fn straight_road() -> MockNetwork {
+    MockNetworkBuilder::new()
+        .node(1, point!(x: -118.15, y: 34.15))
+        .node(2, point!(x: -118.16, y: 34.15))
+        .node(3, point!(x: -118.17, y: 34.15))
+        .edge(1, 2)
+        .edge(2, 3)
+        .build()
+}
+
+/// Points roughly 370 metres apart, `seconds` apart.
+fn observations(seconds: i64) -> Vec<Origin> {
+    let points: Vec<Point> = wkt! {
+        LINESTRING(-118.151 34.1503, -118.155 34.1503, -118.159 34.1503, -118.163 34.1503)
+    }
+    .into_points();
+
+    points
+        .into_iter()
+        .enumerate()
+        .map(|(index, point)| Origin::new(point, index as i64 * seconds * 1_000_000))
+        .collect()
+}
+
+fn matched_cost<T>(net: &MockNetwork, transition: T, origins: &[Origin]) -> u32
+where
+    T: routers_transition::costing::TransitionStrategy<MockEntryId> + Send + Sync,
+{
+    let costing = CostingStrategies::new(DefaultEmissionCost::default(), transition);
+    let generator = StandardGenerator::new(net, &costing.emission);
+    let m = Matcher::new(net, &costing, generator, AllCompute::default(), &());
+
+    let mut trip = m.begin();
+    m.extend(&mut trip, origins).expect("every point anchors");
+    m.solve(&mut trip).expect("the trip solves");
+    m.snapshot(&mut trip).expect("the trip collapses").cost
+}
+
+/// At a plausible pace the timed strategy agrees with the default exactly.
+#[test]
+fn plausible_speeds_cost_as_the_default() {
+    let net = straight_road();
+    let origins = observations(30);
+
+    assert_eq!(
+        matched_cost(&net, TimedTransitionCost::default(), &origins),
+        matched_cost(&net, DefaultTransitionCost, &origins),
+    );
+}
+
+/// The same route driven in a second between pings implies well over the
+/// maximum speed, and is priced accordingly.
+#[test]
+fn implausible_speeds_are_penalised() {
+    let net = straight_road();
+
+    let plausible = matched_cost(&net, TimedTransitionCost::default(), &observations(30));
+    let hurried = matched_cost(&net, TimedTransitionCost::default(), &observations(1));
+    assert!(hurried > plausible);
+
+    // Allowed the speed, the hurried trip is as cheap as the plausible one.
+    let lenient = TimedTransitionCost {
+        max_speed: 1_000.0,
+        ..TimedTransitionCost::default()
+    };
+    assert_eq!(matched_cost(&net, lenient, &observations(1)), plausible);
+}
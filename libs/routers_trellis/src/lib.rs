@@ -13,9 +13,17 @@
 //! # Solvers
 //!
 //! - [`ViterbiSolver`]: Viterbi with SIMD acceleration. Stateless; usable with any trellis.
+//! - [`KBestSolver`]: List-Viterbi, for the `k` cheapest paths ([`SolveBest`]).
 //! - [`BruteForceSolver`]: Correctness reference — never use in production.
+//!
+//! # Posteriors
+//!
+//! Where a solver answers *which path is cheapest*, [`ForwardBackward`] answers
+//! *how sure is that*: the probability of each node lying on the path taken,
+//! with every path weighed by its cost at a chosen temperature.
 
 mod path;
+mod posterior;
 mod solved;
 mod solver;
 mod transition;
@@ -23,7 +31,8 @@ mod trellis;
 pub mod types;
 
 pub use path::Path;
+pub use posterior::{ForwardBackward, Posteriors};
 pub use solved::Solved;
-pub use solver::{BruteForceSolver, Solve, SolveError, ViterbiSolver};
+pub use solver::{BruteForceSolver, KBestSolver, Solve, SolveBest, SolveError, ViterbiSolver};
 pub use trellis::{MAX_WEIGHT, NO_EDGE, Trellis, TrellisError};
 pub use types::{LayerId, NodeId};
@@ -0,0 +1,169 @@
+use crate::{LayerId, NodeId, Path, SolveError, Solved, Trellis, trellis::INF_W};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Forward–backward over a trellis: how much of the probability of every
+/// path passes through each node, where a path of cost `c` is weighed in
+/// proportion to `exp(-c / temperature)`.
+///
+/// The temperature sets how sharply cost becomes certainty. Toward zero every
+/// node on the cheapest path approaches probability 1; as it grows, paths
+/// converge on equal odds regardless of cost. It is measured in the same
+/// units as the trellis weights.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ForwardBackward {
+    temperature: f64,
+}
+
+impl Default for ForwardBackward {
+    fn default() -> Self {
+        ForwardBackward { temperature: 1.0 }
+    }
+}
+
+impl ForwardBackward {
+    /// Forward–backward at a temperature of 1.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Panics unless `temperature` is positive and finite.
+    pub fn with_temperature(self, temperature: f64) -> Self {
+        assert!(
+            temperature.is_finite() && temperature > 0.0,
+            "temperature must be positive and finite, got {temperature}"
+        );
+
+        ForwardBackward { temperature }
+    }
+
+    /// The temperature weights are divided by.
+    pub fn temperature(&self) -> f64 {
+        self.temperature
+    }
+
+    /// Every node's posterior probability of lying on the path taken.
+    ///
+    /// Each layer's posteriors sum to 1, with nodes no path passes through
+    /// at 0. Runs in the time of one Viterbi pass in each direction, working
+    /// in log space so that no weight underflows.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", name = "forward_backward", skip(self, t), fields(layers = t.layers()))
+    )]
+    pub fn posteriors(&self, t: &Trellis) -> Result<Posteriors, SolveError> {
+        if let Some(layer) = t.first_pending() {
+            return Err(SolveError::NotResolved(layer));
+        }
+
+        let ranges = t.layer_ranges().collect::<Vec<_>>();
+        let nodes = t.node_table();
+        let scale = |weight: u32| -f64::from(weight) / self.temperature;
+
+        // Forward: the log-weight of every partial path ending at the node.
+        let mut forward = vec![f64::NEG_INFINITY; nodes.len()];
+        for index in ranges[0].clone() {
+            forward[index] = scale(nodes[index]);
+        }
+
+        // Backward: the log-weight of every partial path leaving the node.
+        let mut backward = vec![f64::NEG_INFINITY; nodes.len()];
+        for index in ranges[ranges.len() - 1].clone() {
+            backward[index] = 0.0;
+        }
+
+        for (boundary, pair) in t.boundaries().zip(ranges.windows(2)) {
+            let (cur, next) = (&pair[0], &pair[1]);
+            let weights = t.layer(boundary).ok_or(SolveError::NotResolved(boundary))?;
+
+            for (to, index) in next.clone().enumerate() {
+                let into = cur.clone().enumerate().filter_map(|(from, source)| {
+                    let edge = weights[from * next.len() + to];
+                    (edge < INF_W).then(|| forward[source] + scale(edge))
+                });
+
+                forward[index] = log_sum_exp(into) + scale(nodes[index]);
+            }
+        }
+
+        for (boundary, pair) in t.boundaries().zip(ranges.windows(2)).rev() {
+            let (cur, next) = (&pair[0], &pair[1]);
+            let weights = t.layer(boundary).ok_or(SolveError::NotResolved(boundary))?;
+
+            for (from, index) in cur.clone().enumerate() {
+                let out = next.clone().enumerate().filter_map(|(to, target)| {
+                    let edge = weights[from * next.len() + to];
+                    (edge < INF_W).then(|| backward[target] + scale(edge) + scale(nodes[target]))
+                });
+
+                backward[index] = log_sum_exp(out);
+            }
+        }
+
+        let total = log_sum_exp(ranges[ranges.len() - 1].clone().map(|index| forward[index]));
+        if total == f64::NEG_INFINITY {
+            return Err(SolveError::Unreachable);
+        }
+
+        let values = forward
+            .iter()
+            .zip(&backward)
+            .map(|(forward, backward)| (forward + backward - total).exp())
+            .collect();
+
+        Ok(Posteriors { ranges, values })
+    }
+}
+
+/// `ln(Σ exp(x))`, shifted by the maximum so that no term overflows.
+/// Negative infinity for an empty sum.
+fn log_sum_exp(terms: impl Iterator<Item = f64> + Clone) -> f64 {
+    let max = terms.clone().fold(f64::NEG_INFINITY, f64::max);
+    if max == f64::NEG_INFINITY {
+        return max;
+    }
+
+    max + terms.map(|term| (term - max).exp()).sum::<f64>().ln()
+}
+
+/// Per-node posterior probabilities over a trellis, as found by
+/// [`ForwardBackward::posteriors`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Posteriors {
+    ranges: Vec<core::ops::Range<usize>>,
+    values: Vec<f64>,
+}
+
+impl Posteriors {
+    /// A layer's posteriors, one per node, summing to 1.
+    pub fn layer(&self, layer: LayerId) -> Option<&[f64]> {
+        let range = self.ranges.get(layer.index())?;
+        Some(&self.values[range.clone()])
+    }
+
+    /// One node's posterior, or `None` when out of range.
+    pub fn node(&self, layer: LayerId, node: NodeId) -> Option<f64> {
+        self.layer(layer)?.get(node.index()).copied()
+    }
+
+    /// The posterior of each node `path` chooses, in layer order: how sure
+    /// the trellis is of every step of it.
+    pub fn along(&self, path: &Path) -> Vec<f64> {
+        path.nodes
+            .iter()
+            .enumerate()
+            .map(|(layer, &node)| self.node(LayerId(layer as u32), node).unwrap_or(0.0))
+            .collect()
+    }
+}
+
+impl Solved {
+    /// Every node's posterior probability, found by `fb`. Infallible: a
+    /// solved trellis is resolved and reachable throughout.
+    pub fn posteriors(&self, fb: &ForwardBackward) -> Posteriors {
+        fb.posteriors(self.trellis())
+            .expect("a solved trellis is resolved and reachable")
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{Path, Solve, SolveError, Trellis, trellis::INF_W, types::NodeId};
+use crate::{Path, Solve, SolveBest, SolveError, Trellis, trellis::INF_W, types::NodeId};
 
 /// Correctness reference: enumerates every possible path and picks the cheapest.
 ///
@@ -84,3 +84,40 @@ impl Solve for BruteForceSolver {
         Ok(Path::new(best_nodes, best_cost))
     }
 }
+
+impl SolveBest for BruteForceSolver {
+    fn solve_best(&self, t: &Trellis, k: usize) -> Result<Vec<Path>, SolveError> {
+        if let Some(layer) = t.first_pending() {
+            return Err(SolveError::NotResolved(layer));
+        }
+
+        let widths = t.widths();
+        let mut paths = Vec::new();
+
+        // Enumerate all paths as a multi-digit counter over node indices.
+        let mut path = vec![NodeId(0); t.layers()];
+        'enumerate: loop {
+            let cost = t.path_cost(&path);
+            if cost < INF_W {
+                paths.push(Path::new(path.clone(), cost));
+            }
+
+            for layer in (0..path.len()).rev() {
+                path[layer].0 += 1;
+                if path[layer].0 < widths[layer] {
+                    continue 'enumerate;
+                }
+                path[layer] = NodeId(0);
+            }
+            break;
+        }
+
+        if paths.is_empty() {
+            return Err(SolveError::Unreachable);
+        }
+
+        paths.sort_by_key(|path| path.cost);
+        paths.truncate(k);
+        Ok(paths)
+    }
+}
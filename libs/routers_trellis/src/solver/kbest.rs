@@ -0,0 +1,145 @@
+use log::debug;
+
+use crate::{
+    Path, Solve, SolveBest, SolveError,
+    trellis::{INF_W, Trellis},
+    types::NodeId,
+};
+
+/// List-Viterbi: the `k` cheapest distinct paths through a trellis.
+///
+/// Where [`ViterbiSolver`](crate::ViterbiSolver) keeps the single cheapest
+/// way into every node, this keeps the `k` cheapest, each remembering which
+/// of its predecessor's ways it extends. Time and memory are `k` times
+/// Viterbi's, less the SIMD.
+///
+/// Ties break as Viterbi's do — to the lowest node — so the first path is
+/// always the one [`ViterbiSolver`](crate::ViterbiSolver) finds.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct KBestSolver;
+
+/// One way into a node: its cost so far, and the way into the previous
+/// layer it extends.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Way {
+    cost: u32,
+    from: u32,
+    rank: u32,
+}
+
+impl KBestSolver {
+    pub fn new() -> Self {
+        KBestSolver
+    }
+
+    /// Every node's `k` cheapest ways, cheapest first, laid out by
+    /// [`Trellis::layer_ranges`].
+    fn ways(t: &Trellis, k: usize) -> Result<Vec<Vec<Way>>, SolveError> {
+        let ranges = t.layer_ranges().collect::<Vec<_>>();
+        let nodes = t.node_table();
+
+        // The first layer is entered at its own node weights.
+        let mut ways = vec![Vec::new(); nodes.len()];
+        for index in ranges.first().cloned().unwrap_or(0..0) {
+            ways[index].push(Way {
+                cost: nodes[index],
+                from: 0,
+                rank: 0,
+            });
+        }
+
+        for (boundary, pair) in t.boundaries().zip(ranges.windows(2)) {
+            let (cur, next) = (&pair[0], &pair[1]);
+            let weights = t.layer(boundary).ok_or(SolveError::NotResolved(boundary))?;
+
+            for (to, index) in next.clone().enumerate() {
+                let mut into = cur
+                    .clone()
+                    .enumerate()
+                    .flat_map(|(from, source)| {
+                        let edge = weights[from * next.len() + to];
+                        ways[source]
+                            .iter()
+                            .enumerate()
+                            .map(move |(rank, way)| (way.cost.saturating_add(edge), from, rank))
+                    })
+                    .filter(|&(cost, _, _)| cost < INF_W)
+                    .map(|(cost, from, rank)| Way {
+                        cost: cost + nodes[index],
+                        from: from as u32,
+                        rank: rank as u32,
+                    })
+                    .collect::<Vec<_>>();
+
+                into.sort_unstable();
+                into.truncate(k);
+                ways[index] = into;
+            }
+        }
+
+        Ok(ways)
+    }
+}
+
+impl SolveBest for KBestSolver {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", name = "k_best", skip(self, t), fields(layers = t.layers()))
+    )]
+    fn solve_best(&self, t: &Trellis, k: usize) -> Result<Vec<Path>, SolveError> {
+        debug!("{} layers, widths={:?}, k={k}", t.layers(), t.widths());
+
+        let ways = Self::ways(t, k)?;
+        let ranges = t.layer_ranges().collect::<Vec<_>>();
+        let Some(last) = ranges.last() else {
+            return Err(SolveError::Unreachable);
+        };
+
+        // The cheapest ways out of the final layer, across all its nodes.
+        let mut ends = last
+            .clone()
+            .enumerate()
+            .flat_map(|(node, index)| {
+                ways[index]
+                    .iter()
+                    .enumerate()
+                    .map(move |(rank, way)| (way.cost, node, rank))
+            })
+            .collect::<Vec<_>>();
+        ends.sort_unstable();
+        ends.truncate(k);
+
+        if ends.is_empty() {
+            return Err(SolveError::Unreachable);
+        }
+
+        let paths = ends
+            .into_iter()
+            .map(|(cost, node, rank)| {
+                let mut nodes = vec![NodeId::from_index(node); ranges.len()];
+                let (mut node, mut rank) = (node, rank);
+
+                for layer in (1..ranges.len()).rev() {
+                    let way = ways[ranges[layer].start + node][rank];
+                    (node, rank) = (way.from as usize, way.rank as usize);
+                    nodes[layer - 1] = NodeId::from_index(node);
+                }
+
+                Path::new(nodes, cost)
+            })
+            .collect();
+
+        Ok(paths)
+    }
+}
+
+impl Solve for KBestSolver {
+    /// Minimum-cost path through `t`; prefer
+    /// [`ViterbiSolver`](crate::ViterbiSolver) when only one is wanted.
+    fn solve(&self, t: &Trellis) -> Result<Path, SolveError> {
+        self.solve_best(t, 1)?
+            .into_iter()
+            .next()
+            .ok_or(SolveError::Unreachable)
+    }
+}
@@ -2,9 +2,11 @@ use crate::{Path, Trellis, types::LayerId};
 use thiserror::Error;
 
 mod brute;
+mod kbest;
 mod viterbi;
 
 pub use brute::BruteForceSolver;
+pub use kbest::KBestSolver;
 pub use viterbi::ViterbiSolver;
 
 /// A strategy for finding the minimum-cost path through a [`Trellis`].
@@ -14,6 +16,15 @@ pub trait Solve {
     fn solve(&self, t: &Trellis) -> Result<Path, SolveError>;
 }
 
+/// A strategy for finding the `k` cheapest distinct paths through a
+/// [`Trellis`], for judging how far ahead of its alternatives the best path
+/// is.
+pub trait SolveBest {
+    /// Up to `k` distinct paths, cheapest first. Fewer are returned only when
+    /// fewer paths exist; none at all is [`SolveError::Unreachable`].
+    fn solve_best(&self, t: &Trellis, k: usize) -> Result<Vec<Path>, SolveError>;
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Error)]
 pub enum SolveError {
     #[error("transition at layer {0} is not yet resolved")]
@@ -352,6 +352,49 @@ impl Trellis {
         cost
     }
 
+    /// Narrow `layer` to the one node `node`, so every path must pass
+    /// through it. The node keeps its weight and its edges to either side;
+    /// its siblings and their edges are dropped, and it becomes node 0.
+    /// Boundary states carry over.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub fn pin(&mut self, layer: LayerId, node: NodeId) -> Result<()> {
+        let range = self.node_range(layer)?;
+        let width = range.len();
+        if node.index() >= width {
+            return Err(TrellisError::NodeOutOfRange { layer, node });
+        }
+
+        let weight = self.nodes[range.start + node.index()];
+        self.nodes.splice(range, [weight]);
+
+        // Into the layer: keep the node's column of the boundary below.
+        if let Some(below) = layer.index().checked_sub(1)
+            && let Some(weights) = self.transitions[below].weights_mut()
+        {
+            *weights = weights
+                .iter()
+                .skip(node.index())
+                .step_by(width)
+                .copied()
+                .collect();
+        }
+
+        // Out of the layer: keep the node's row of the boundary above.
+        if let Some(weights) = self
+            .transitions
+            .get_mut(layer.index())
+            .and_then(Transition::weights_mut)
+        {
+            let next_width = weights.len() / width;
+            weights.drain(..node.index() * next_width);
+            weights.truncate(next_width);
+        }
+
+        self.widths[layer.index()] = 1;
+        log::debug!("pin: L{layer} to {node}");
+        Ok(())
+    }
+
     /// An owned copy of the layers in `range`, keeping their node weights and
     /// interior transitions. Boundary states carry over; the transitions that
     /// crossed the cut are dropped.
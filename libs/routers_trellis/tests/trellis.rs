@@ -631,3 +631,273 @@ fn converged_prefix_is_stable_under_appends() {
         "no seed converged mid-trellis; the stability assertions never ran against a live tail"
     );
 }
+
+// ---- k-best ----
+
+/// The k-best solver against every path, enumerated: the same costs in the
+/// same order, each path distinct and priced as the trellis prices it.
+fn k_best_conformance(t: &Trellis, k: usize) {
+    let best = KBestSolver::new().solve_best(t, k);
+    let brute = BruteForceSolver::new().solve_best(t, k);
+
+    match (&best, &brute) {
+        (Ok(best), Ok(brute)) => {
+            let costs = |paths: &[Path]| paths.iter().map(|p| p.cost).collect::<Vec<_>>();
+            assert_eq!(costs(best), costs(brute), "k-best costs disagree");
+
+            for path in best {
+                assert_eq!(
+                    t.path_cost(&path.nodes),
+                    path.cost,
+                    "k-best path cost incorrect"
+                );
+            }
+
+            let mut distinct = best.iter().map(|p| &p.nodes).collect::<Vec<_>>();
+            distinct.sort();
+            distinct.dedup();
+            assert_eq!(distinct.len(), best.len(), "k-best paths repeat");
+        }
+        (Err(a), Err(b)) => assert_eq!(a, b, "error mismatch"),
+        _ => panic!("solver disagreement: k-best={best:?} brute={brute:?}"),
+    }
+}
+
+#[test]
+fn k_best_conformance_random_small() {
+    for seed in 0u64..20 {
+        k_best_conformance(&random_noded_trellis(5, 4, seed), 10);
+    }
+}
+
+#[test]
+fn k_best_conformance_partial_and_disconnected() {
+    let mut t = Trellis::new(vec![3u32, 3, 3]).unwrap();
+    t.set_edge(LayerId(0), NodeId(0), NodeId(1), 10).unwrap();
+    t.set_edge(LayerId(0), NodeId(2), NodeId(2), 5).unwrap();
+    t.set_edge(LayerId(1), NodeId(1), NodeId(0), 3).unwrap();
+    t.set_edge(LayerId(1), NodeId(2), NodeId(2), 1).unwrap();
+    k_best_conformance(&t, 5);
+
+    let mut t = Trellis::new(vec![2u32, 2, 2]).unwrap();
+    t.fill_transition(LayerId(0), &[NO_EDGE; 4]).unwrap();
+    t.fill_transition(LayerId(1), &[NO_EDGE; 4]).unwrap();
+    k_best_conformance(&t, 3);
+}
+
+/// The first of the k best is exactly the path Viterbi picks, ties and all.
+#[test]
+fn k_best_leads_with_the_viterbi_path() {
+    for seed in 0u64..20 {
+        let t = random_trellis(8, 5, seed);
+        let best = KBestSolver::new().solve_best(&t, 3).unwrap();
+        assert_eq!(best[0], ViterbiSolver::new().solve(&t).unwrap());
+        assert_eq!(KBestSolver::new().solve(&t), ViterbiSolver::new().solve(&t));
+    }
+}
+
+#[test]
+fn k_best_returns_every_path_when_fewer_exist() {
+    let paths = KBestSolver::new().solve_best(&line(&[2, 3]), 5).unwrap();
+    assert_eq!(paths, vec![Path::new(vec![NodeId(0); 3], 5)]);
+
+    let t = Trellis::new(vec![2u32, 2]).unwrap();
+    assert_eq!(
+        KBestSolver::new().solve_best(&t, 2),
+        Err(SolveError::NotResolved(LayerId(0)))
+    );
+}
+
+// ---- posteriors ----
+
+/// Forward–backward against every path, enumerated and weighed by hand: each
+/// node's posterior is the share of the total weight of paths through it.
+fn posterior_conformance(t: &Trellis, temperature: f64) {
+    let fb = ForwardBackward::new().with_temperature(temperature);
+    let posteriors = fb.posteriors(t).unwrap();
+
+    let paths = BruteForceSolver::new().solve_best(t, usize::MAX).unwrap();
+    let weight = |path: &Path| (-f64::from(path.cost) / temperature).exp();
+    let total = paths.iter().map(weight).sum::<f64>();
+
+    for (layer, &width) in t.widths().iter().enumerate() {
+        for node in 0..width {
+            let through = paths
+                .iter()
+                .filter(|path| path.nodes[layer] == NodeId(node))
+                .map(weight)
+                .sum::<f64>();
+
+            let found = posteriors
+                .node(LayerId(layer as u32), NodeId(node))
+                .unwrap();
+            assert!(
+                (found - through / total).abs() < 1e-9,
+                "posterior at L{layer} N{node}: {found} != {}",
+                through / total
+            );
+        }
+    }
+}
+
+#[test]
+fn posterior_conformance_random_small() {
+    for seed in 0u64..10 {
+        posterior_conformance(&random_noded_trellis(4, 3, seed), 25.0);
+    }
+}
+
+#[test]
+fn posterior_conformance_partial_edges() {
+    let mut t = Trellis::new(vec![3u32, 3, 3]).unwrap();
+    t.set_edge(LayerId(0), NodeId(0), NodeId(1), 10).unwrap();
+    t.set_edge(LayerId(0), NodeId(2), NodeId(2), 5).unwrap();
+    t.set_edge(LayerId(1), NodeId(1), NodeId(0), 3).unwrap();
+    t.set_edge(LayerId(1), NodeId(2), NodeId(2), 1).unwrap();
+    posterior_conformance(&t, 4.0);
+}
+
+#[test]
+fn posteriors_sum_to_one_per_layer() {
+    let t = random_noded_trellis(12, 6, 7);
+    let posteriors = ForwardBackward::new()
+        .with_temperature(10.0)
+        .posteriors(&t)
+        .unwrap();
+
+    for layer in 0..t.layers() {
+        let sum = posteriors
+            .layer(LayerId(layer as u32))
+            .unwrap()
+            .iter()
+            .sum::<f64>();
+        assert!((sum - 1.0).abs() < 1e-9, "layer {layer} sums to {sum}");
+    }
+}
+
+/// Cooling concentrates the posterior on the cheapest path; heating spreads
+/// it toward even odds.
+#[test]
+fn temperature_sharpens_and_flattens_the_posterior() {
+    let mut t = Trellis::new(vec![1u32, 2, 1]).unwrap();
+    t.fill_transition(LayerId(0), &[0, 0]).unwrap();
+    t.fill_transition(LayerId(1), &[0, 0]).unwrap();
+    t.fill_nodes(LayerId(1), &[100, 200]).unwrap();
+
+    let at = |temperature: f64| {
+        ForwardBackward::new()
+            .with_temperature(temperature)
+            .posteriors(&t)
+            .unwrap()
+            .node(LayerId(1), NodeId(0))
+            .unwrap()
+    };
+
+    assert!(at(1.0) > 0.999_999);
+    assert!((at(100.0) - 1.0 / (1.0 + (-1.0f64).exp())).abs() < 1e-12);
+    assert!((at(1e9) - 0.5).abs() < 1e-6);
+}
+
+#[test]
+fn posteriors_along_the_solved_path() {
+    let solved = random_trellis(6, 4, 3)
+        .solve(&ViterbiSolver::new())
+        .unwrap();
+    let confidence = solved
+        .posteriors(&ForwardBackward::new().with_temperature(1e-3))
+        .along(solved.path());
+
+    assert_eq!(confidence.len(), 6);
+    assert!(confidence.iter().all(|&p| p > 0.0 && p <= 1.0 + 1e-12));
+}
+
+#[test]
+fn posteriors_report_pending_and_unreachable() {
+    let t = Trellis::new(vec![2u32, 2]).unwrap();
+    assert_eq!(
+        ForwardBackward::new().posteriors(&t),
+        Err(SolveError::NotResolved(LayerId(0)))
+    );
+
+    let mut t = Trellis::new(vec![2u32, 2]).unwrap();
+    t.fill_transition(LayerId(0), &[NO_EDGE; 4]).unwrap();
+    assert_eq!(
+        ForwardBackward::new().posteriors(&t),
+        Err(SolveError::Unreachable)
+    );
+}
+
+#[test]
+#[should_panic(expected = "temperature must be positive")]
+fn zero_temperature_is_rejected() {
+    let _ = ForwardBackward::new().with_temperature(0.0);
+}
+
+// ---- pinning ----
+
+#[test]
+fn pin_forces_the_path_through_the_node() {
+    for seed in 0u64..10 {
+        let t = random_noded_trellis(5, 4, seed);
+        let free = ViterbiSolver::new().solve(&t).unwrap();
+
+        // Pin layer 2 to any node the free path avoids.
+        let avoided = NodeId((free.nodes[2].0 + 1) % 4);
+        let mut pinned = t.clone();
+        pinned.pin(LayerId(2), avoided).unwrap();
+        assert_eq!(pinned.widths(), &[4, 4, 1, 4, 4]);
+
+        let forced = ViterbiSolver::new().solve(&pinned).unwrap();
+        assert_eq!(forced.nodes[2], NodeId(0));
+
+        // The best path through the pinned node, priced on the original.
+        let mut through = forced.nodes.clone();
+        through[2] = avoided;
+        assert_eq!(t.path_cost(&through), forced.cost);
+        assert!(forced.cost >= free.cost);
+    }
+}
+
+#[test]
+fn pin_along_the_solved_path_keeps_it() {
+    let mut t = random_noded_trellis(6, 3, 11);
+    let free = ViterbiSolver::new().solve(&t).unwrap();
+
+    for layer in 0..3 {
+        t.pin(LayerId(layer), free.nodes[layer as usize]).unwrap();
+    }
+
+    let pinned = ViterbiSolver::new().solve(&t).unwrap();
+    assert_eq!(pinned.cost, free.cost);
+    assert_eq!(pinned.nodes[3..], free.nodes[3..]);
+}
+
+#[test]
+fn pin_keeps_pending_boundaries_pending() {
+    let mut t = Trellis::new(vec![3u32, 2]).unwrap();
+    t.fill_nodes(LayerId(0), &[7, 8, 9]).unwrap();
+    t.pin(LayerId(0), NodeId(1)).unwrap();
+
+    assert_eq!(t.widths(), &[1, 2]);
+    assert_eq!(t.node_weights(LayerId(0)), Some(&[8][..]));
+    assert!(!t.is_resolved(LayerId(0)));
+
+    t.fill_transition(LayerId(0), &[1, 2]).unwrap();
+    assert_eq!(ViterbiSolver::new().solve(&t).unwrap().cost, 9);
+}
+
+#[test]
+fn pin_rejects_bad_ids() {
+    let mut t = Trellis::new(vec![2u32, 2]).unwrap();
+    assert_eq!(
+        t.pin(LayerId(2), NodeId(0)),
+        Err(TrellisError::LayerOutOfRange(LayerId(2)))
+    );
+    assert_eq!(
+        t.pin(LayerId(1), NodeId(2)),
+        Err(TrellisError::NodeOutOfRange {
+            layer: LayerId(1),
+            node: NodeId(2)
+        })
+    );
+}
@@ -0,0 +1,108 @@
+//! Trace readers: every supported file format, read into [`Trip`]s.
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, bail};
+use clap::ValueEnum;
+use geo::LineString;
+use routers::Origin;
+use routers::trace::{TraceFormat, Trip};
+use wkt::TryFromWkt;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum InputFormat {
+    /// LineString features, timed per coordinate by a `times` or
+    /// `coordTimes` property, and Point features grouped by their id.
+    Geojson,
+    /// Rows with a header naming at least `lon` and `lat` columns, grouped
+    /// into one trace per `id`, `trip` or `vehicle`, and timed by a
+    /// `timestamp` column.
+    Csv,
+    /// As `csv`, tab-separated.
+    Tsv,
+    /// Every `<trk>` is one trace, timed by its points' `<time>`.
+    Gpx,
+    /// One untimed `LINESTRING` per line, each one trace.
+    Wkt,
+}
+
+impl InputFormat {
+    /// The format a file's extension names, if any.
+    pub fn detect(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "geojson" | "json" => Some(InputFormat::Geojson),
+            "csv" => Some(InputFormat::Csv),
+            "tsv" => Some(InputFormat::Tsv),
+            "gpx" => Some(InputFormat::Gpx),
+            "wkt" => Some(InputFormat::Wkt),
+            _ => None,
+        }
+    }
+}
+
+/// Every trace in `path`, in file order, read as `format` or else by the
+/// format its extension names. Each is named by the file's stem and its id
+/// within the file, so results from many files stay distinguishable.
+pub fn read(path: &PathBuf, format: Option<InputFormat>) -> anyhow::Result<Vec<Trip>> {
+    let Some(format) = format.or_else(|| InputFormat::detect(path)) else {
+        bail!(
+            "cannot tell the format of {} from its extension; pass --input-format",
+            path.display()
+        );
+    };
+
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("could not read {}", path.display()))?;
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let format = match format {
+        InputFormat::Geojson => TraceFormat::GeoJson,
+        InputFormat::Csv => TraceFormat::Delimited(b','),
+        InputFormat::Tsv => TraceFormat::Delimited(b'\t'),
+        InputFormat::Gpx => TraceFormat::Gpx,
+        InputFormat::Wkt => {
+            return wkt(&text, &stem)
+                .with_context(|| format!("could not parse {}", path.display()));
+        }
+    };
+
+    let trips = format
+        .parse(&text)
+        .with_context(|| format!("could not parse {}", path.display()))?;
+
+    Ok(trips
+        .into_iter()
+        .map(|trip| {
+            let id = format!("{stem}:{}", trip.id);
+            if trip.dropped > 0 {
+                log::warn!("{id}: dropped {} unusable observations", trip.dropped);
+            }
+
+            Trip { id, ..trip }
+        })
+        .collect())
+}
+
+fn wkt(text: &str, stem: &str) -> anyhow::Result<Vec<Trip>> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            let linestring = LineString::try_from_wkt_str(line.trim())
+                .map_err(|err| anyhow::anyhow!("line {}: {err}", index + 1))?;
+
+            // Untimed positions, their indices standing in for times.
+            let origins = linestring
+                .into_points()
+                .into_iter()
+                .enumerate()
+                .map(|(index, point)| Origin::new(point, index as i64))
+                .collect();
+
+            Ok(Trip::new(format!("{stem}:{}", index + 1), origins))
+        })
+        .collect()
+}
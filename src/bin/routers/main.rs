@@ -0,0 +1,33 @@
+//! The `routers` command line: offline tooling over a network file.
+//!
+//! ```sh
+//! routers match --pbf sydney.osm.pbf traces/*.gpx -o matched.geojson
+//! ```
+extern crate alloc;
+
+use clap::{Parser, Subcommand};
+
+mod input;
+mod r#match;
+mod output;
+
+#[derive(Parser, Debug)]
+#[command(name = "routers", version, about, long_about = None)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Map-match trace files against a network, in parallel.
+    Match(r#match::Args),
+}
+
+fn main() -> anyhow::Result<()> {
+    env_logger::init();
+
+    match Cli::parse().command {
+        Command::Match(args) => r#match::run(args),
+    }
+}
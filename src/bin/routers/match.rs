@@ -0,0 +1,192 @@
+//! `routers match`: map-match many trace files against one network.
+use alloc::sync::Arc;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+
+use anyhow::Context;
+use clap::Args as ClapArgs;
+use log::{info, warn};
+use rayon::prelude::*;
+use routers::candidate::Segment;
+use routers::codec::osm::OsmNetwork;
+use routers::primitives::PredicateCache;
+use routers::{Match, MatchOptions};
+
+use crate::input::{self, InputFormat};
+use crate::output::{self, OutputFormat};
+
+#[derive(ClapArgs, Debug)]
+pub struct Args {
+    #[command(flatten)]
+    network: NetworkInput,
+
+    /// The trace files to match. Each may hold many traces.
+    #[arg(required = true)]
+    traces: Vec<PathBuf>,
+
+    /// The format of every trace file, instead of detecting it from each
+    /// file's extension.
+    #[arg(long, value_enum)]
+    input_format: Option<InputFormat>,
+
+    /// The file to write matched traces to. Defaults to standard output.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// The format to write matched traces in. Defaults to the one the
+    /// output file's extension names, else NDJSON.
+    #[arg(short, long, value_enum)]
+    format: Option<OutputFormat>,
+
+    /// The file to write a report per failed trace, or piece of one, to, as
+    /// NDJSON. Defaults to standard error.
+    #[arg(long)]
+    errors: Option<PathBuf>,
+
+    /// The search distance to use for matching
+    #[arg(long)]
+    search_distance: Option<f64>,
+
+    /// Match each side of a break in the route on its own, instead of
+    /// failing the trace.
+    #[arg(long)]
+    split: bool,
+
+    /// How many traces to match at once. Defaults to one per core.
+    #[arg(short, long)]
+    jobs: Option<usize>,
+}
+
+#[derive(ClapArgs, Debug)]
+#[group(required = true, multiple = false)]
+struct NetworkInput {
+    /// The path to the PBF file to load.
+    #[arg(long)]
+    pbf: Option<PathBuf>,
+
+    /// The path to the OSM XML (`.osm`) file to load.
+    #[arg(long)]
+    osm: Option<PathBuf>,
+
+    /// The path to the RT file to load.
+    #[arg(long)]
+    rt: Option<PathBuf>,
+}
+
+pub fn run(args: Args) -> anyhow::Result<()> {
+    info!("match starting: {:?}", args);
+
+    if let Some(jobs) = args.jobs {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(jobs)
+            .build_global()
+            .context("could not size the worker pool")?;
+    }
+
+    let traces = args
+        .traces
+        .iter()
+        .map(|path| input::read(path, args.input_format))
+        .collect::<anyhow::Result<Vec<_>>>()?
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    info!("read {} traces", traces.len());
+
+    let network = match (args.network.pbf, args.network.osm, args.network.rt) {
+        (Some(pbf), None, None) => {
+            info!("loading OsmNetwork from protobuf file...");
+            OsmNetwork::from_pbf(&pbf).map_err(|v| anyhow::anyhow!(v.to_string()))
+        }
+        (None, Some(osm), None) => {
+            info!("loading OsmNetwork from XML file...");
+            OsmNetwork::from_xml(&osm).map_err(|v| anyhow::anyhow!(v.to_string()))
+        }
+        (None, None, Some(rt)) => {
+            info!("loading OsmNetwork from cached (.rt) file...");
+            OsmNetwork::from_saved(&rt).map_err(anyhow::Error::msg)
+        }
+        _ => unreachable!(),
+    }
+    .context("could not load the network")?;
+
+    // One cache for every trace, so reachability found for one is reused by
+    // the next to pass the same way.
+    let cache = Arc::new(PredicateCache::default());
+
+    let results = traces
+        .into_par_iter()
+        .map(|trace| {
+            let opts = MatchOptions::new()
+                .with_search_distance(args.search_distance)
+                .with_split(args.split)
+                .with_confidence(true)
+                .with_cache(cache.clone());
+
+            let result = network.match_origins(&trace.origins, opts);
+            (trace, result)
+        })
+        .collect::<Vec<_>>();
+
+    // A trace keeps the pieces which matched; each piece which did not is
+    // reported on its own.
+    let mut matched = Vec::new();
+    let mut failed = Vec::new();
+    for (trace, result) in results {
+        let segments = match result {
+            Ok(segments) => segments,
+            Err(error) => {
+                failed.push((trace.id, error));
+                continue;
+            }
+        };
+
+        let mut kept = Vec::new();
+        for segment in segments {
+            match segment.path {
+                Ok(path) => kept.push(Segment {
+                    layers: segment.layers,
+                    path,
+                }),
+                Err(error) => failed.push((trace.id.clone(), error)),
+            }
+        }
+
+        if !kept.is_empty() {
+            matched.push((trace, kept));
+        }
+    }
+    info!("matched {} traces, {} failed", matched.len(), failed.len());
+
+    let format = args
+        .format
+        .or_else(|| args.output.as_deref().and_then(OutputFormat::detect))
+        .unwrap_or(OutputFormat::Ndjson);
+
+    let mut out = match &args.output {
+        Some(path) => create(path)?,
+        None => Box::new(BufWriter::new(std::io::stdout())),
+    };
+    format.write(&matched, &mut out)?;
+    out.flush()?;
+
+    if !failed.is_empty() {
+        warn!("{} traces or pieces could not be matched", failed.len());
+
+        let mut errors = match &args.errors {
+            Some(path) => create(path)?,
+            None => Box::new(std::io::stderr()),
+        };
+        output::report(&failed, &mut errors)?;
+        errors.flush()?;
+    }
+
+    Ok(())
+}
+
+fn create(path: &PathBuf) -> anyhow::Result<Box<dyn Write>> {
+    let file =
+        File::create(path).with_context(|| format!("could not create {}", path.display()))?;
+    Ok(Box::new(BufWriter::new(file)))
+}
@@ -0,0 +1,234 @@
+//! Result writers: matched traces in every supported format, and a report
+//! per trace which failed.
+use std::io::Write;
+use std::path::Path;
+
+use clap::ValueEnum;
+use routers::candidate::{PathElement, Precision, RoutedPath, Segment};
+use routers::network::{Entry, Metadata};
+use routers::primitives::MatchError;
+use routers::trace::Trip;
+use serde_json::{Value, json};
+
+/// A trace's match: one segment per independently matched stretch.
+pub type Matched<E, M> = Vec<Segment<RoutedPath<E, M>>>;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum OutputFormat {
+    /// One FeatureCollection: each segment's routed line, and a point per
+    /// matched position, as [`RoutedPath::to_geojson`] draws them.
+    Geojson,
+    /// One row per matched position.
+    Csv,
+    /// One JSON object per trace, per line, each segment's routed line an
+    /// encoded polyline.
+    Ndjson,
+}
+
+impl OutputFormat {
+    /// The format a file's extension names, if any.
+    pub fn detect(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "geojson" | "json" => Some(OutputFormat::Geojson),
+            "csv" => Some(OutputFormat::Csv),
+            "ndjson" | "jsonl" => Some(OutputFormat::Ndjson),
+            _ => None,
+        }
+    }
+
+    /// Write every matched trace to `out`, in order.
+    pub fn write<E: Entry, M: Metadata>(
+        self,
+        matched: &[(Trip, Matched<E, M>)],
+        out: impl Write,
+    ) -> anyhow::Result<()> {
+        match self {
+            OutputFormat::Geojson => geojson(matched, out),
+            OutputFormat::Csv => csv(matched, out),
+            OutputFormat::Ndjson => ndjson(matched, out),
+        }
+    }
+}
+
+/// The positions of a trace each segment matched, with the index of the
+/// input position each answers.
+fn positions<E: Entry, M: Metadata>(
+    segment: &Segment<RoutedPath<E, M>>,
+) -> impl Iterator<Item = (usize, &PathElement<E, M>)> {
+    segment.layers.clone().zip(segment.path.discretized.iter())
+}
+
+fn position<E: Entry, M: Metadata>(index: usize, element: &PathElement<E, M>) -> Value {
+    json!({
+        "index": index,
+        "lon": element.point.x,
+        "lat": element.point.y,
+        "way": element.edge.id().identifier(),
+        "source": element.edge.source.identifier(),
+        "target": element.edge.target.identifier(),
+        "confidence": element.confidence,
+    })
+}
+
+fn geojson<E: Entry, M: Metadata>(
+    matched: &[(Trip, Matched<E, M>)],
+    mut out: impl Write,
+) -> anyhow::Result<()> {
+    let mut features = Vec::new();
+    for (trace, segments) in matched {
+        for (index, segment) in segments.iter().enumerate() {
+            for mut feature in segment.path.to_geojson().features {
+                let properties = feature.properties.get_or_insert_default();
+                properties.insert("trace".into(), json!(trace.id));
+                properties.insert("segment".into(), json!(index));
+
+                // Matched positions count from the segment's first layer, so
+                // renumber them by the input position each answers.
+                match properties.get("index").and_then(Value::as_u64) {
+                    Some(position) => {
+                        let layer = segment.layers.start + position as usize;
+                        properties.insert("index".into(), json!(layer));
+                    }
+                    None => {
+                        let layers = [segment.layers.start, segment.layers.end];
+                        properties.insert("layers".into(), json!(layers));
+                    }
+                }
+
+                features.push(feature);
+            }
+        }
+    }
+
+    let collection = geojson::FeatureCollection {
+        bbox: None,
+        features,
+        foreign_members: None,
+    };
+
+    serde_json::to_writer(&mut out, &collection)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+fn csv<E: Entry, M: Metadata>(
+    matched: &[(Trip, Matched<E, M>)],
+    out: impl Write,
+) -> anyhow::Result<()> {
+    let mut writer = ::csv::Writer::from_writer(out);
+    writer.write_record([
+        "trace",
+        "segment",
+        "index",
+        "lon",
+        "lat",
+        "way",
+        "source",
+        "target",
+        "confidence",
+    ])?;
+
+    for (trace, segments) in matched {
+        for (index, segment) in segments.iter().enumerate() {
+            for (layer, element) in positions(segment) {
+                writer.write_record([
+                    trace.id.clone(),
+                    index.to_string(),
+                    layer.to_string(),
+                    element.point.x.to_string(),
+                    element.point.y.to_string(),
+                    element.edge.id().identifier().to_string(),
+                    element.edge.source.identifier().to_string(),
+                    element.edge.target.identifier().to_string(),
+                    element
+                        .confidence
+                        .map(|confidence| confidence.to_string())
+                        .unwrap_or_default(),
+                ])?;
+            }
+        }
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+fn ndjson<E: Entry, M: Metadata>(
+    matched: &[(Trip, Matched<E, M>)],
+    mut out: impl Write,
+) -> anyhow::Result<()> {
+    for (trace, segments) in matched {
+        let segments = segments
+            .iter()
+            .map(|segment| {
+                json!({
+                    "layers": [segment.layers.start, segment.layers.end],
+                    "cost": segment.path.cost,
+                    "matched": positions(segment)
+                        .map(|(layer, element)| position(layer, element))
+                        .collect::<Vec<_>>(),
+                    "polyline": segment.path.to_polyline(Precision::Five),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        serde_json::to_writer(
+            &mut out,
+            &json!({ "trace": trace.id, "segments": segments }),
+        )?;
+        writeln!(out)?;
+    }
+
+    Ok(())
+}
+
+/// Write one NDJSON line per failed trace, or piece of one, naming the points
+/// which could not be placed on the network or the boundaries no route
+/// bridges. Both count from the start of the trace.
+pub fn report(failed: &[(String, MatchError)], mut out: impl Write) -> anyhow::Result<()> {
+    for (trace, error) in failed {
+        let mut line = json!({
+            "trace": trace,
+            "message": error.to_string(),
+        });
+
+        match error {
+            MatchError::Unanchored(error) => {
+                line["error"] = json!("unanchored");
+                line["points"] = error
+                    .points
+                    .iter()
+                    .map(|point| {
+                        json!({
+                            "index": point.layer,
+                            "lon": point.origin.x(),
+                            "lat": point.origin.y(),
+                        })
+                    })
+                    .collect();
+            }
+            MatchError::Disconnected(error) => {
+                line["error"] = json!("disconnected");
+                line["breaks"] = error
+                    .breaks
+                    .iter()
+                    .map(|gap| {
+                        json!({
+                            "from": gap.from_layer,
+                            "to": gap.to_layer,
+                            "from_point": [gap.from_origin.x(), gap.from_origin.y()],
+                            "to_point": [gap.to_origin.x(), gap.to_origin.y()],
+                        })
+                    })
+                    .collect();
+            }
+            _ => line["error"] = json!("other"),
+        }
+
+        serde_json::to_writer(&mut out, &line)?;
+        writeln!(out)?;
+    }
+
+    Ok(())
+}